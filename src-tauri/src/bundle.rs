@@ -0,0 +1,163 @@
+// Reading shared setup bundles: a zip with a profile XML plus optional notes and template
+use std::io::{Read, Seek};
+
+/// Contents extracted from a bundle
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub profile_file_name: String,
+    pub profile_xml: String,
+    pub notes_json: Option<String>,
+    pub template_file_name: Option<String>,
+    pub template_json: Option<String>,
+}
+
+/// Why a bundle could not be loaded
+#[derive(Debug, PartialEq)]
+pub enum BundleError {
+    InvalidArchive(String),
+    MissingProfile,
+    UnreadableEntry(String),
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::InvalidArchive(e) => write!(f, "Not a valid zip bundle: {}", e),
+            BundleError::MissingProfile => {
+                write!(
+                    f,
+                    "Bundle does not contain a keybindings profile (.xml with <ActionMaps>)"
+                )
+            }
+            BundleError::UnreadableEntry(e) => write!(f, "Failed to read bundle entry: {}", e),
+        }
+    }
+}
+
+/// Check if an XML document's root element is <ActionMaps>
+fn is_action_maps_xml(xml: &str) -> bool {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(quick_xml::events::Event::Start(e)) | Ok(quick_xml::events::Event::Empty(e)) => {
+                return e.name().as_ref() == b"ActionMaps";
+            }
+            Ok(quick_xml::events::Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Extract the profile (first .xml with an <ActionMaps> root), the notes
+/// ("*.notes.json") and a template (any other .json) from a bundle
+pub fn read_bundle<R: Read + Seek>(reader: R) -> Result<Bundle, BundleError> {
+    let mut archive =
+        zip::ZipArchive::new(reader).map_err(|e| BundleError::InvalidArchive(e.to_string()))?;
+
+    let mut profile: Option<(String, String)> = None;
+    let mut notes_json = None;
+    let mut template: Option<(String, String)> = None;
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| BundleError::UnreadableEntry(e.to_string()))?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        // Only the file name matters, bundles may nest files in a folder
+        let path = entry.name().replace('\\', "/");
+        let file_name = path.rsplit('/').next().unwrap_or(&path).to_string();
+        let lower = file_name.to_lowercase();
+
+        let wanted = (lower.ends_with(".xml") && profile.is_none())
+            || (lower.ends_with(".notes.json") && notes_json.is_none())
+            || (lower.ends_with(".json") && !lower.ends_with(".notes.json") && template.is_none());
+        if !wanted {
+            continue;
+        }
+
+        let mut contents = String::new();
+        entry
+            .read_to_string(&mut contents)
+            .map_err(|e| BundleError::UnreadableEntry(format!("{}: {}", path, e)))?;
+
+        if lower.ends_with(".xml") {
+            if is_action_maps_xml(&contents) {
+                profile = Some((file_name, contents));
+            }
+        } else if lower.ends_with(".notes.json") {
+            notes_json = Some(contents);
+        } else {
+            template = Some((file_name, contents));
+        }
+    }
+
+    let (profile_file_name, profile_xml) = profile.ok_or(BundleError::MissingProfile)?;
+    let (template_file_name, template_json) = template.unzip();
+
+    Ok(Bundle {
+        profile_file_name,
+        profile_xml,
+        notes_json,
+        template_file_name,
+        template_json,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn build_zip(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, contents) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        let mut cursor = writer.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    #[test]
+    fn test_read_bundle() {
+        let zip = build_zip(&[
+            ("setup/readme.xml", "<Readme/>"),
+            (
+                "setup/layout_mining.xml",
+                "<?xml version=\"1.0\"?>\n<ActionMaps version=\"1\" profileName=\"mining\"></ActionMaps>",
+            ),
+            ("setup/layout_mining.notes.json", "[]"),
+            ("setup/hosas.json", "{\"name\":\"HOSAS\"}"),
+        ]);
+
+        let bundle = read_bundle(zip).unwrap();
+        assert_eq!(bundle.profile_file_name, "layout_mining.xml");
+        assert!(bundle.profile_xml.contains("profileName=\"mining\""));
+        assert_eq!(bundle.notes_json.as_deref(), Some("[]"));
+        assert_eq!(bundle.template_file_name.as_deref(), Some("hosas.json"));
+        assert_eq!(
+            bundle.template_json.as_deref(),
+            Some("{\"name\":\"HOSAS\"}")
+        );
+    }
+
+    #[test]
+    fn test_read_bundle_without_profile() {
+        let zip = build_zip(&[("readme.xml", "<Readme/>"), ("notes.txt", "hello")]);
+        assert_eq!(read_bundle(zip).unwrap_err(), BundleError::MissingProfile);
+
+        let not_zip = Cursor::new(b"not a zip".to_vec());
+        assert!(matches!(
+            read_bundle(not_zip),
+            Err(BundleError::InvalidArchive(_))
+        ));
+    }
+}
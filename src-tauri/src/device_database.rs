@@ -1,3 +1,4 @@
+use log::error;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -13,12 +14,181 @@ pub struct DeviceEntry {
     pub axis_profile: String,
 }
 
+/// A database entry for a device that isn't in the database yet, with the raw names it
+/// reported so maintainers can check the suggested name. Serializes to the database schema
+/// (the extra fields are ignored when the database is loaded)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDbEntry {
+    #[serde(flatten)]
+    pub entry: DeviceEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gilrs_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hid_manufacturer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hid_product: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct DeviceDatabaseJson {
     devices: Vec<DeviceEntry>,
+    #[serde(default)] // User databases usually only add devices
+    axis_profiles: HashMap<String, HashMap<String, String>>,
+}
+
+/// Layout of the user's own database file, entries written by `append_user_entry`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UserDatabaseJson {
+    devices: Vec<DeviceDbEntry>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     axis_profiles: HashMap<String, HashMap<String, String>>,
 }
 
+/// Parse a "0x231d" style ID from the database
+fn parse_hex_id(id: &str) -> Result<u32, std::num::ParseIntError> {
+    u32::from_str_radix(id.trim_start_matches("0x"), 16)
+}
+
+/// A problem found by `validate_device_database`
+/// JSON syntax errors carry the line; problems with one device carry its index in "devices"
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DbValidationError {
+    pub line: Option<usize>,
+    pub entry: Option<usize>,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl DbValidationError {
+    fn entry(index: usize, field: &str, message: String) -> Self {
+        DbValidationError {
+            line: None,
+            entry: Some(index),
+            field: Some(field.to_string()),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for DbValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if let Some(entry) = self.entry {
+            write!(f, "devices[{}]: ", entry)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Check a device database file the way `DeviceDatabase::load` reads it, but report every
+/// problem instead of stopping at the first, plus entries that load but are unusable
+pub fn validate_device_database<P: AsRef<Path>>(path: P) -> Vec<DbValidationError> {
+    match std::fs::read_to_string(path) {
+        Ok(json_str) => validate_database_json(&json_str),
+        Err(e) => vec![DbValidationError {
+            line: None,
+            entry: None,
+            field: None,
+            message: format!("Failed to read database: {}", e),
+        }],
+    }
+}
+
+/// Validate database JSON: each device needs hex vendor/product IDs that fit in 16 bits,
+/// a non-empty name, and string type/axis_profile fields
+pub fn validate_database_json(json_str: &str) -> Vec<DbValidationError> {
+    let top_level = |message: &str| DbValidationError {
+        line: None,
+        entry: None,
+        field: None,
+        message: message.to_string(),
+    };
+
+    let root: serde_json::Value = match serde_json::from_str(json_str) {
+        Ok(root) => root,
+        Err(e) => {
+            return vec![DbValidationError {
+                line: Some(e.line()),
+                entry: None,
+                field: None,
+                message: format!("Invalid JSON: {}", e),
+            }]
+        }
+    };
+
+    let mut errors = Vec::new();
+
+    if let Some(axis_profiles) = root.get("axis_profiles") {
+        if serde_json::from_value::<HashMap<String, HashMap<String, String>>>(axis_profiles.clone())
+            .is_err()
+        {
+            errors.push(top_level(
+                "\"axis_profiles\" must map profile names to {axis index: axis name} objects",
+            ));
+        }
+    }
+
+    let Some(devices) = root.get("devices").and_then(|d| d.as_array()) else {
+        errors.push(top_level("Missing \"devices\" array"));
+        return errors;
+    };
+
+    for (index, device) in devices.iter().enumerate() {
+        if !device.is_object() {
+            errors.push(DbValidationError {
+                line: None,
+                entry: Some(index),
+                field: None,
+                message: "Device entry must be an object".to_string(),
+            });
+            continue;
+        }
+
+        for field in ["vendor_id", "product_id"] {
+            match device.get(field).and_then(|v| v.as_str()) {
+                None => errors.push(DbValidationError::entry(
+                    index,
+                    field,
+                    format!("Missing {}", field),
+                )),
+                Some(id) if parse_hex_id(id).map_or(true, |id| id > 0xFFFF) => {
+                    errors.push(DbValidationError::entry(
+                        index,
+                        field,
+                        format!("Invalid {} '{}', expected a hex ID like 0x231d", field, id),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        match device.get("name").and_then(|v| v.as_str()) {
+            Some(name) if !name.trim().is_empty() => {}
+            _ => errors.push(DbValidationError::entry(
+                index,
+                "name",
+                "Name is missing or empty".to_string(),
+            )),
+        }
+
+        for field in ["type", "axis_profile"] {
+            if device.get(field).and_then(|v| v.as_str()).is_none() {
+                errors.push(DbValidationError::entry(
+                    index,
+                    field,
+                    format!("Missing {}", field),
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
 pub struct DeviceDatabase {
     // Map of (vendor_id, product_id) -> DeviceEntry
     vid_pid_map: HashMap<(u32, u32), DeviceEntry>,
@@ -40,9 +210,9 @@ impl DeviceDatabase {
 
         // Parse VID/PID strings from hex format (e.g., "0x231d") to u32
         for device in db_json.devices {
-            let vid = u32::from_str_radix(device.vendor_id.trim_start_matches("0x"), 16)
+            let vid = parse_hex_id(&device.vendor_id)
                 .map_err(|e| format!("Invalid vendor_id format: {}", e))?;
-            let pid = u32::from_str_radix(device.product_id.trim_start_matches("0x"), 16)
+            let pid = parse_hex_id(&device.product_id)
                 .map_err(|e| format!("Invalid product_id format: {}", e))?;
 
             vid_pid_map.insert((vid, pid), device);
@@ -54,9 +224,35 @@ impl DeviceDatabase {
         })
     }
 
-    /// Initialize the global device database
-    pub fn init<P: AsRef<Path>>(path: P) -> Result<(), String> {
-        let db = Self::load(path)?;
+    /// Load the built-in database, then merge the user's database on top if it exists
+    /// User entries replace built-in entries with the same VID/PID; a user database that
+    /// can't be loaded is logged and skipped so the built-in one still works
+    pub fn load_with_user<P: AsRef<Path>, U: AsRef<Path>>(
+        path: P,
+        user_path: U,
+    ) -> Result<Self, String> {
+        let mut db = Self::load(path)?;
+
+        if user_path.as_ref().exists() {
+            match Self::load(&user_path) {
+                Ok(user_db) => {
+                    db.vid_pid_map.extend(user_db.vid_pid_map);
+                    db.axis_profiles.extend(user_db.axis_profiles);
+                }
+                Err(e) => error!(
+                    "Ignoring user database {}: {}",
+                    user_path.as_ref().display(),
+                    e
+                ),
+            }
+        }
+
+        Ok(db)
+    }
+
+    /// Initialize the global device database, merging the user's database when present
+    pub fn init<P: AsRef<Path>, U: AsRef<Path>>(path: P, user_path: U) -> Result<(), String> {
+        let db = Self::load_with_user(path, user_path)?;
         DEVICE_DATABASE.set(db).map_err(|_| {
             "Device database already initialized".to_string()
         })?;
@@ -113,3 +309,177 @@ impl DeviceDatabase {
         }
     }
 }
+
+/// Add an entry to the user's database file, creating it if needed
+/// An existing entry with the same VID/PID is replaced. Takes effect on the next start
+pub fn append_user_entry<P: AsRef<Path>>(
+    user_path: P,
+    entry: &DeviceDbEntry,
+) -> Result<(), String> {
+    let user_path = user_path.as_ref();
+
+    let mut user_db: UserDatabaseJson = if user_path.exists() {
+        let json_str = std::fs::read_to_string(user_path)
+            .map_err(|e| format!("Failed to read user database: {}", e))?;
+        serde_json::from_str(&json_str)
+            .map_err(|e| format!("Failed to parse user database: {}", e))?
+    } else {
+        UserDatabaseJson::default()
+    };
+
+    let same_device = |existing: &DeviceDbEntry| {
+        parse_hex_id(&existing.entry.vendor_id).ok() == parse_hex_id(&entry.entry.vendor_id).ok()
+            && parse_hex_id(&existing.entry.product_id).ok()
+                == parse_hex_id(&entry.entry.product_id).ok()
+    };
+    user_db.devices.retain(|existing| !same_device(existing));
+    user_db.devices.push(entry.clone());
+
+    if let Some(parent) = user_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&user_db)
+        .map_err(|e| format!("Failed to serialize user database: {}", e))?;
+    std::fs::write(user_path, json).map_err(|e| format!("Failed to write user database: {}", e))
+}
+
+/// Best name for a device missing from the database
+/// Prefers the HID product string (with the manufacturer when it isn't already part of it),
+/// then the name the OS reports
+pub fn suggested_device_name(
+    hid_manufacturer: Option<&str>,
+    hid_product: Option<&str>,
+    os_name: &str,
+) -> String {
+    let hid_manufacturer = hid_manufacturer.map(str::trim).filter(|m| !m.is_empty());
+    match hid_product.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(product) => match hid_manufacturer {
+            Some(manufacturer)
+                if !product
+                    .to_lowercase()
+                    .contains(&manufacturer.to_lowercase()) =>
+            {
+                format!("{} {}", manufacturer, product)
+            }
+            _ => product.to_string(),
+        },
+        None => os_name.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(vendor_id: &str, product_id: &str, name: &str) -> DeviceDbEntry {
+        DeviceDbEntry {
+            entry: DeviceEntry {
+                vendor_id: vendor_id.to_string(),
+                product_id: product_id.to_string(),
+                name: name.to_string(),
+                device_type: "joystick".to_string(),
+                axis_profile: "default".to_string(),
+            },
+            os_name: Some("HID-compliant game controller".to_string()),
+            gilrs_name: None,
+            hid_manufacturer: None,
+            hid_product: Some(name.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_user_database_overrides_and_extends() {
+        let dir = std::env::temp_dir().join(format!("sc_device_db_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let builtin = dir.join("device-database.json");
+        std::fs::write(
+            &builtin,
+            r#"{
+                "devices": [
+                    {"vendor_id": "0x231d", "product_id": "0x0200", "name": "VKB Gladiator NXT (Right)", "type": "joystick", "axis_profile": "default"}
+                ],
+                "axis_profiles": {"default": {"0": "x"}}
+            }"#,
+        )
+        .unwrap();
+        let user = dir.join("user").join("user-device-database.json");
+
+        // No user file yet: built-in only
+        let db = DeviceDatabase::load_with_user(&builtin, &user).unwrap();
+        assert_eq!(db.vid_pid_map.len(), 1);
+
+        append_user_entry(&user, &entry("0x231D", "0x0200", "My Renamed Gladiator")).unwrap();
+        append_user_entry(&user, &entry("0x3344", "0x4130", "VIRPIL Alpha")).unwrap();
+        // Same device again replaces the earlier user entry
+        append_user_entry(&user, &entry("0x3344", "0x4130", "VIRPIL Alpha Prime")).unwrap();
+
+        let db = DeviceDatabase::load_with_user(&builtin, &user).unwrap();
+        assert_eq!(db.vid_pid_map.len(), 2);
+        assert_eq!(
+            db.vid_pid_map[&(0x231d, 0x0200)].name,
+            "My Renamed Gladiator"
+        );
+        assert_eq!(db.vid_pid_map[&(0x3344, 0x4130)].name, "VIRPIL Alpha Prime");
+        // Built-in axis profiles survive a user file without any
+        assert!(db.axis_profiles.contains_key("default"));
+
+        // A corrupt user file falls back to the built-in database
+        std::fs::write(&user, "{ \"devices\": [").unwrap();
+        let db = DeviceDatabase::load_with_user(&builtin, &user).unwrap();
+        assert_eq!(db.vid_pid_map.len(), 1);
+        assert_eq!(
+            db.vid_pid_map[&(0x231d, 0x0200)].name,
+            "VKB Gladiator NXT (Right)"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validate_database_json() {
+        let valid = r#"{
+            "devices": [
+                {"vendor_id": "0x231d", "product_id": "0x0200", "name": "VKB Gladiator NXT (Right)", "type": "joystick", "axis_profile": "default"}
+            ],
+            "axis_profiles": {"default": {"0": "x"}}
+        }"#;
+        assert!(validate_database_json(valid).is_empty());
+
+        let errors = validate_database_json(
+            r#"{
+                "devices": [
+                    {"vendor_id": "0x231d", "product_id": "0x0200", "name": "VKB", "type": "joystick", "axis_profile": "default"},
+                    {"vendor_id": "0xVKB", "product_id": "0x0200", "name": "Bad VID", "type": "joystick", "axis_profile": "default"},
+                    {"vendor_id": "0x3344", "product_id": "0x4130", "name": "  ", "type": "joystick", "axis_profile": "default"}
+                ]
+            }"#,
+        );
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(errors[0].entry, Some(1));
+        assert_eq!(errors[0].field.as_deref(), Some("vendor_id"));
+        assert!(errors[0].message.contains("0xVKB"));
+        assert_eq!(errors[1].entry, Some(2));
+        assert_eq!(errors[1].field.as_deref(), Some("name"));
+
+        let errors = validate_database_json("{\n  \"devices\": [\n    {,\n  ]\n}");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_suggested_device_name() {
+        assert_eq!(
+            suggested_device_name(Some("VIRPIL Controls"), Some("Alpha Prime R"), "HID device"),
+            "VIRPIL Controls Alpha Prime R"
+        );
+        assert_eq!(
+            suggested_device_name(Some("VKB"), Some("VKB-Sim Gladiator NXT R"), "HID device"),
+            "VKB-Sim Gladiator NXT R"
+        );
+        assert_eq!(
+            suggested_device_name(None, Some("  "), "HID-compliant game controller"),
+            "HID-compliant game controller"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+// Shared similarity scoring for pairing device names, SC Product strings and connected devices
+
+/// Minimum score for a match to be suggested
+pub const MIN_MATCH_SCORE: f32 = 0.3;
+
+/// Split a device name into lowercase alphanumeric tokens
+/// GUIDs ("{...}") and trailing OS suffixes in parentheses are dropped
+/// e.g. "Thrustmaster T.16000M (Left)" -> ["thrustmaster", "t", "16000m"]
+pub fn name_tokens(name: &str) -> Vec<String> {
+    let name = name.split('{').next().unwrap_or("");
+    let name = name.split('(').next().unwrap_or("");
+
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// Similarity between two device names in 0.0..=1.0
+/// Averages token containment (one name's tokens all found in the other, e.g. a name
+/// missing its vendor prefix) with Jaccard overlap (penalizes extra unrelated tokens)
+pub fn name_similarity(a: &str, b: &str) -> f32 {
+    let a_tokens = name_tokens(a);
+    let b_tokens = name_tokens(b);
+
+    if a_tokens.is_empty() || b_tokens.is_empty() {
+        return 0.0;
+    }
+
+    let shared = a_tokens.iter().filter(|t| b_tokens.contains(t)).count() as f32;
+    let mut union: Vec<&String> = a_tokens.iter().chain(b_tokens.iter()).collect();
+    union.sort();
+    union.dedup();
+
+    let containment = shared / a_tokens.len().min(b_tokens.len()) as f32;
+    let jaccard = shared / union.len() as f32;
+
+    ((containment + jaccard) / 2.0).min(1.0)
+}
+
+/// Extract (vendor_id, product_id) from the GUID in an SC Product string
+/// SC writes DirectInput product GUIDs as {PPPPVVVV-0000-0000-0000-504944564944}
+pub fn product_guid_vid_pid(product: &str) -> Option<(u16, u16)> {
+    let start = product.find('{')?;
+    let guid = &product[start + 1..];
+    let first_group = guid.split('-').next()?;
+
+    if first_group.len() != 8 || !guid.to_uppercase().contains("504944564944") {
+        return None;
+    }
+
+    let pid = u16::from_str_radix(&first_group[..4], 16).ok()?;
+    let vid = u16::from_str_radix(&first_group[4..], 16).ok()?;
+    Some((vid, pid))
+}
+
+/// Build the SC Product string for a device: name, two spaces, then the DirectInput product GUID
+/// e.g. ("VKB-Sim Gladiator NXT R", 0x231D, 0x0200) -> "VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}"
+pub fn sc_product_string(name: &str, vendor_id: u16, product_id: u16) -> String {
+    format!(
+        "{}  {{{:04X}{:04X}-0000-0000-0000-504944564944}}",
+        name.trim(),
+        product_id,
+        vendor_id
+    )
+}
+
+/// Score how well a declared SC Product string matches a connected device
+/// An exact VID/PID match is a certain match; otherwise names are compared
+pub fn device_match_score(
+    declared_product: &str,
+    device_name: &str,
+    vendor_id: Option<u16>,
+    product_id: Option<u16>,
+) -> f32 {
+    if let (Some((vid, pid)), Some(dev_vid), Some(dev_pid)) = (
+        product_guid_vid_pid(declared_product),
+        vendor_id,
+        product_id,
+    ) {
+        if vid == dev_vid && pid == dev_pid {
+            return 1.0;
+        }
+    }
+
+    name_similarity(declared_product, device_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_name_match() {
+        let score = name_similarity("T.16000M", "Thrustmaster T.16000M");
+        assert!(score > 0.7, "score was {}", score);
+
+        let unrelated = name_similarity("T.16000M", "VKB Gladiator NXT");
+        assert_eq!(unrelated, 0.0);
+    }
+
+    #[test]
+    fn test_name_tokens_strip_guid_and_suffix() {
+        assert_eq!(
+            name_tokens(" VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}"),
+            vec!["vkb", "sim", "gladiator", "nxt", "r"]
+        );
+        assert_eq!(name_tokens("T.16000M (Left)"), vec!["t", "16000m"]);
+    }
+
+    #[test]
+    fn test_product_guid_vid_pid() {
+        assert_eq!(
+            product_guid_vid_pid(
+                " VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}"
+            ),
+            Some((0x231D, 0x0200))
+        );
+        assert_eq!(product_guid_vid_pid("Keyboard"), None);
+
+        let score = device_match_score(
+            "Joystick  {B10A044F-0000-0000-0000-504944564944}",
+            "Some Renamed Device",
+            Some(0x044F),
+            Some(0xB10A),
+        );
+        assert_eq!(score, 1.0);
+    }
+
+    #[test]
+    fn test_sc_product_string_round_trip() {
+        let product = sc_product_string("VKB-Sim Gladiator NXT R", 0x231D, 0x0200);
+        assert_eq!(
+            product,
+            "VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}"
+        );
+        assert_eq!(product_guid_vid_pid(&product), Some((0x231D, 0x0200)));
+    }
+}
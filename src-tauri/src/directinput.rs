@@ -1,17 +1,81 @@
 use gilrs::{Button, EventType, Gilrs};
+use log::{debug, error};
 use once_cell::sync::Lazy;
+#[cfg(windows)]
 use rusty_xinput::XInputHandle;
 use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tauri::Emitter;
 
 use crate::device_database;
+use crate::hid_reader;
 
 // Global Gilrs instance for axis detection to avoid recreating it on every poll
 static GILRS_INSTANCE: Lazy<Mutex<Option<Gilrs>>> = Lazy::new(|| Mutex::new(None));
 
+// Events read from the shared instance by the device lists, kept for axis detection
+// Only locked while GILRS_INSTANCE is held
+static GILRS_PENDING_EVENTS: Lazy<Mutex<VecDeque<gilrs::Event>>> =
+    Lazy::new(|| Mutex::new(VecDeque::new()));
+const MAX_PENDING_EVENTS: usize = 256;
+
+/// Keep an event for a later reader, dropping the oldest once the queue is full
+fn queue_pending<E>(queue: &mut VecDeque<E>, event: E) {
+    if queue.len() >= MAX_PENDING_EVENTS {
+        queue.pop_front();
+    }
+    queue.push_back(event);
+}
+
+/// Empty the queue, keeping only events that happened at or after `since`
+/// Older ones are movement from before detection started, e.g. an earlier session
+fn take_pending_since<E>(
+    queue: &mut VecDeque<E>,
+    since: SystemTime,
+    time: impl Fn(&E) -> SystemTime,
+) -> Vec<E> {
+    queue
+        .drain(..)
+        .filter(|event| time(event) >= since)
+        .collect()
+}
+
+/// Run `f` on the value in `slot`, creating it with `create` on first use
+fn with_shared<G, T>(
+    slot: &Mutex<Option<G>>,
+    create: impl FnOnce() -> Result<G, String>,
+    f: impl FnOnce(&mut G) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut guard = slot.lock().map_err(|e| e.to_string())?;
+    let value = match guard.as_mut() {
+        Some(value) => value,
+        None => guard.insert(create()?),
+    };
+    f(value)
+}
+
+/// Run `f` with the shared Gilrs instance so device lists see hot-plugged devices
+/// Pending events are read first so gilrs updates its internal cache, and queued so
+/// axis detection running on the same instance still sees them
+fn with_shared_gilrs<T>(f: impl FnOnce(&mut Gilrs) -> Result<T, String>) -> Result<T, String> {
+    with_shared(
+        &GILRS_INSTANCE,
+        || Gilrs::new().map_err(|e| e.to_string()),
+        |gilrs| {
+            let mut pending = GILRS_PENDING_EVENTS.lock().map_err(|e| e.to_string())?;
+            while let Some(event) = gilrs.next_event() {
+                queue_pending(&mut pending, event);
+            }
+            drop(pending);
+            f(gilrs)
+        },
+    )
+}
+
 /// Determine if a device is a gamepad (Xbox-style controller) or a joystick (HOTAS/flight stick)
 /// Based on the device name and button/axis count
 fn get_friendly_device_name(gamepad: &gilrs::Gamepad) -> String {
@@ -22,13 +86,20 @@ fn get_friendly_device_name(gamepad: &gilrs::Gamepad) -> String {
     
     // Try to look up device in the database by VID/PID
     if let (Some(vendor_id), Some(product_id)) = (gamepad.vendor_id(), gamepad.product_id()) {
-        eprintln!("[DirectInput] Looking up device: VID=0x{:04x}, PID=0x{:04x}", vendor_id, product_id);
+        debug!("Looking up device: VID=0x{:04x}, PID=0x{:04x}", vendor_id, product_id);
         if let Some(device_entry) = device_database::DeviceDatabase::lookup_device(vendor_id as u32, product_id as u32) {
-            eprintln!("[DirectInput] Device found in database: {}", device_entry.name);
+            debug!("Device found in database: {}", device_entry.name);
             return device_entry.name;
         }
-        eprintln!("[DirectInput] Device not found in database, using fallback vendor lookup");
+        debug!("Device not found in database, using fallback vendor lookup");
 
+        // Generic OS name: the HID product string usually says what the device really is
+        if gamepad.name().to_lowercase().contains("hid-compliant") {
+            if let Some(name) = cached_hid_product_name(vendor_id, product_id) {
+                debug!("Resolved generic HID device to: {}", name);
+                return name;
+            }
+        }
 
         // Fallback for unknown devices: generic name based on vendor
         match vendor_id {
@@ -75,6 +146,105 @@ fn get_friendly_device_name(gamepad: &gilrs::Gamepad) -> String {
     gamepad.name().to_string()
 }
 
+/// Display name for an axis: the descriptor's label when it has one for this index,
+/// otherwise Star Citizen's naming by index (more than 6 axes for advanced joysticks)
+fn axis_display_name(axis_index: u32, descriptor_names: &HashMap<u32, String>) -> String {
+    if let Some(name) = descriptor_names.get(&axis_index) {
+        if !name.trim().is_empty() {
+            return name.clone();
+        }
+    }
+
+    match axis_index {
+        1 => "X".to_string(),
+        2 => "Y".to_string(),
+        3 => "RotX".to_string(),
+        4 => "RotY".to_string(),
+        5 => "Z".to_string(),
+        6 => "RotZ".to_string(),
+        _ => format!("Axis{}", axis_index),
+    }
+}
+
+/// Axis names from the HID report descriptor of the device behind a gamepad, cached per
+/// device UUID so each descriptor is read once per detection session
+/// Empty when no HID device has the same VID/PID or the descriptor can't be read
+fn descriptor_axis_names<'a>(
+    cache: &'a mut HashMap<String, HashMap<u32, String>>,
+    device_uuid: &str,
+    gamepad: &gilrs::Gamepad,
+) -> &'a HashMap<u32, String> {
+    cache.entry(device_uuid.to_string()).or_insert_with(|| {
+        let (Some(vendor_id), Some(product_id)) = (gamepad.vendor_id(), gamepad.product_id())
+        else {
+            return HashMap::new();
+        };
+
+        hid_reader::list_hid_game_controllers()
+            .unwrap_or_default()
+            .iter()
+            .find(|d| d.vendor_id == vendor_id && d.product_id == product_id)
+            .and_then(|d| hid_reader::get_axis_names_from_descriptor(&d.path).ok())
+            .unwrap_or_default()
+    })
+}
+
+/// Button/axis/hat counts from the HID report descriptor of the device with this VID/PID
+/// None when no HID device matches or its descriptor can't be read; gilrs has no real counts
+pub fn hid_capabilities(vendor_id: u16, product_id: u16) -> Option<hid_reader::HidCapabilities> {
+    hid_reader::list_hid_game_controllers()
+        .ok()?
+        .iter()
+        .find(|d| d.vendor_id == vendor_id && d.product_id == product_id)
+        .and_then(|d| hid_reader::get_capabilities_from_descriptor(&d.path).ok())
+        .flatten()
+}
+
+// HID product names by VID/PID, so device lists don't enumerate HID devices on every call
+type HidProductNames = HashMap<(u16, u16), Option<String>>;
+static HID_PRODUCT_NAMES: Lazy<Mutex<HidProductNames>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// `hid_product_name` for a connected device, enumerating HID devices only the first time
+/// a VID/PID is seen; a failed enumeration isn't cached so the next call retries
+fn cached_hid_product_name(vendor_id: u16, product_id: u16) -> Option<String> {
+    let mut cache = HID_PRODUCT_NAMES.lock().ok()?;
+    if let Some(name) = cache.get(&(vendor_id, product_id)) {
+        return name.clone();
+    }
+
+    match hid_reader::list_hid_game_controllers() {
+        Ok(hid_devices) => {
+            let name = hid_product_name(vendor_id, product_id, &hid_devices);
+            cache.insert((vendor_id, product_id), name.clone());
+            name
+        }
+        Err(e) => {
+            error!("Failed to list HID devices: {}", e);
+            None
+        }
+    }
+}
+
+/// Friendly name from the HID product/manufacturer strings of the device with this VID/PID
+/// None when no HID device matches or it has no product string
+fn hid_product_name(
+    vendor_id: u16,
+    product_id: u16,
+    hid_devices: &[hid_reader::HidDeviceListItem],
+) -> Option<String> {
+    hid_devices
+        .iter()
+        .filter(|d| d.vendor_id == vendor_id && d.product_id == product_id)
+        .find(|d| d.product.as_deref().is_some_and(|p| !p.trim().is_empty()))
+        .map(|d| {
+            device_database::suggested_device_name(
+                d.manufacturer.as_deref(),
+                d.product.as_deref(),
+                "",
+            )
+        })
+}
+
 fn is_gamepad(name: &str, _gamepad: &gilrs::Gamepad) -> bool {
     let name_lower = name.to_lowercase();
 
@@ -172,18 +342,24 @@ fn get_active_modifiers() -> Vec<String> {
     modifiers
 }
 
+// Stub for non-Windows platforms: no global key state, so no modifiers are reported
+#[cfg(not(windows))]
+fn get_active_modifiers() -> Vec<String> {
+    Vec::new()
+}
+
+/// Modifiers held right now (LALT, RALT, LCTRL, RCTRL, LSHIFT, RSHIFT), outside of any
+/// detection session. Always empty where global key state isn't available
+pub fn poll_active_modifiers() -> Vec<String> {
+    get_active_modifiers()
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct AxisMovement {
     pub axis_id: u32,
     pub value: f32,
 }
 
-// Stub for non-Windows platforms
-#[cfg(not(windows))]
-fn get_active_modifiers() -> Vec<String> {
-    Vec::new()
-}
-
 #[derive(Serialize, Clone, Debug)]
 pub struct DetectedInput {
     pub input_string: String, // Star Citizen format like "js1_button3", "js1_hat1_up", or "js1_axis1_positive"
@@ -232,6 +408,8 @@ pub struct DeviceInfo {
     pub hat_count: usize,
     pub device_type: String,
     pub is_connected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>, // User-chosen name, filled in from the app settings
 }
 
 fn resolve_device_uuid(gamepad: &gilrs::Gamepad, fallback_id: usize) -> String {
@@ -247,6 +425,7 @@ fn resolve_device_uuid(gamepad: &gilrs::Gamepad, fallback_id: usize) -> String {
     encoded
 }
 
+#[cfg(windows)]
 fn resolve_xinput_uuid(controller_id: u32) -> String {
     // Create a consistent UUID for XInput controllers based on their slot
     format!("xinput_{}", controller_id)
@@ -291,6 +470,223 @@ struct AxisState {
     last_triggered_direction: Option<bool>, // true = positive, false = negative
 }
 
+// Axis detection thresholds shared by all detection loops
+const AXIS_TRIGGER_THRESHOLD: f32 = 0.5; // 50% deflection to trigger
+const AXIS_RESET_THRESHOLD: f32 = 0.3; // 30% to reset (hysteresis)
+const MOVEMENT_THRESHOLD: f32 = 0.3; // Require 30% movement from starting position
+
+/// Direction an axis was pushed when it triggered
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+impl AxisDirection {
+    /// Suffix used in Star Citizen input strings (e.g. "js1_axis2_positive")
+    fn as_str(&self) -> &'static str {
+        match self {
+            AxisDirection::Positive => "positive",
+            AxisDirection::Negative => "negative",
+        }
+    }
+
+    /// Short symbol used in display names
+    fn symbol(&self) -> &'static str {
+        match self {
+            AxisDirection::Positive => "+",
+            AxisDirection::Negative => "-",
+        }
+    }
+}
+
+/// Decide whether an axis sample should trigger a detection
+/// Only triggers when the axis is past the trigger threshold, has moved significantly
+/// from its initial/last position (so axes parked at an extreme, like a throttle at max,
+/// never trigger on their own), and that direction hasn't already fired.
+fn evaluate_axis(state: &mut AxisState, value: f32) -> Option<AxisDirection> {
+    let movement_delta = (value - state.last_value).abs();
+
+    let is_positive = value > AXIS_TRIGGER_THRESHOLD;
+    let is_negative = value < -AXIS_TRIGGER_THRESHOLD;
+    let is_centered = value.abs() < AXIS_RESET_THRESHOLD;
+    let has_moved_enough = movement_delta > MOVEMENT_THRESHOLD;
+
+    // Reset state if axis returns to center
+    if is_centered {
+        state.last_triggered_direction = None;
+        state.last_value = value;
+    }
+
+    let should_trigger_positive =
+        is_positive && has_moved_enough && state.last_triggered_direction != Some(true);
+    let should_trigger_negative =
+        is_negative && has_moved_enough && state.last_triggered_direction != Some(false);
+
+    if !should_trigger_positive && !should_trigger_negative {
+        return None;
+    }
+
+    state.last_triggered_direction = Some(should_trigger_positive);
+    state.last_value = value;
+
+    if should_trigger_positive {
+        Some(AxisDirection::Positive)
+    } else {
+        Some(AxisDirection::Negative)
+    }
+}
+
+/// One raw event captured while a detection session was being recorded
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RecordedEvent {
+    pub elapsed_ms: u64,   // Since the recording started
+    pub source: String,    // "gilrs" or "xinput"
+    pub device_id: usize,  // gilrs id or XInput slot
+    pub event: String,     // Debug form of the raw event
+    pub code: Option<u32>, // 1-based gilrs code index, or the XInput button mask
+    pub value: Option<f32>,
+}
+
+struct Recording {
+    started: Instant,
+    events: Vec<RecordedEvent>,
+}
+
+/// Keep a forgotten recording from growing without bound
+const MAX_RECORDED_EVENTS: usize = 10_000;
+
+// Recordings by detection session id; detection loops only record sessions listed here
+static RECORDINGS: Lazy<Mutex<HashMap<String, Recording>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Whether any session is being recorded, so detection loops skip the lock when none is
+static RECORDING_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Start capturing every event the detection loops see for `session_id`
+/// Starting again discards whatever was recorded so far
+pub fn start_recording(session_id: &str) {
+    if let Ok(mut recordings) = RECORDINGS.lock() {
+        recordings.insert(
+            session_id.to_string(),
+            Recording {
+                started: Instant::now(),
+                events: Vec::new(),
+            },
+        );
+        RECORDING_ACTIVE.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Stop recording `session_id` and hand back its events, oldest first
+/// Unknown sessions give an empty list
+pub fn stop_recording(session_id: &str) -> Vec<RecordedEvent> {
+    let Ok(mut recordings) = RECORDINGS.lock() else {
+        return Vec::new();
+    };
+    let recording = recordings.remove(session_id);
+    RECORDING_ACTIVE.store(!recordings.is_empty(), Ordering::Relaxed);
+    recording
+        .map(|recording| recording.events)
+        .unwrap_or_default()
+}
+
+/// Whether `session_id` is being recorded; cheap when nothing is
+fn is_recording(session_id: &str) -> bool {
+    RECORDING_ACTIVE.load(Ordering::Relaxed)
+        && RECORDINGS
+            .lock()
+            .is_ok_and(|recordings| recordings.contains_key(session_id))
+}
+
+fn record_event(
+    session_id: &str,
+    source: &str,
+    device_id: usize,
+    event: String,
+    code: Option<u32>,
+    value: Option<f32>,
+) {
+    let Ok(mut recordings) = RECORDINGS.lock() else {
+        return;
+    };
+    let Some(recording) = recordings.get_mut(session_id) else {
+        return;
+    };
+    if recording.events.len() >= MAX_RECORDED_EVENTS {
+        return;
+    }
+
+    recording.events.push(RecordedEvent {
+        elapsed_ms: recording.started.elapsed().as_millis() as u64,
+        source: source.to_string(),
+        device_id,
+        event,
+        code,
+        value,
+    });
+}
+
+/// Record a gilrs event if `session_id` is being recorded
+fn record_gilrs_event(session_id: &str, event: &gilrs::Event) {
+    if !is_recording(session_id) {
+        return;
+    }
+
+    let (code, value) = match event.event {
+        EventType::ButtonPressed(_, code)
+        | EventType::ButtonRepeated(_, code)
+        | EventType::ButtonReleased(_, code) => (extract_index_from_code(&code), None),
+        EventType::ButtonChanged(_, value, code) | EventType::AxisChanged(_, value, code) => {
+            (extract_index_from_code(&code), Some(value))
+        }
+        _ => (None, None),
+    };
+
+    record_event(
+        session_id,
+        "gilrs",
+        event.id.into(),
+        format!("{:?}", event.event),
+        code,
+        value,
+    );
+}
+
+/// Record an XInput controller state if `session_id` is being recorded and it changed
+#[cfg(windows)]
+fn record_xinput_state(
+    session_id: &str,
+    controller_id: u32,
+    state: &rusty_xinput::XInputState,
+    prev_state: Option<&rusty_xinput::XInputState>,
+) {
+    if !is_recording(session_id)
+        || prev_state.is_some_and(|prev| prev.raw.dwPacketNumber == state.raw.dwPacketNumber)
+    {
+        return;
+    }
+
+    let pad = &state.raw.Gamepad;
+    record_event(
+        session_id,
+        "xinput",
+        controller_id as usize,
+        format!(
+            "State {{ buttons: 0x{:04X}, lx: {}, ly: {}, rx: {}, ry: {}, lt: {}, rt: {} }}",
+            pad.wButtons,
+            pad.sThumbLX,
+            pad.sThumbLY,
+            pad.sThumbRX,
+            pad.sThumbRY,
+            pad.bLeftTrigger,
+            pad.bRightTrigger
+        ),
+        Some(pad.wButtons as u32),
+        None,
+    );
+}
+
 /// Wait for joystick input using gilrs with hat detection and axis direction support
 pub fn wait_for_input(
     session_id: String,
@@ -307,15 +703,19 @@ pub fn wait_for_input(
         gilrs.gamepads().count()
     );
 
-    // Initialize XInput for Xbox controller support
+    // Initialize XInput for Xbox controller support (Windows only, elsewhere gilrs covers gamepads)
+    #[cfg(windows)]
     let xinput =
         XInputHandle::load_default().map_err(|e| format!("Failed to load XInput: {:?}", e))?;
+    #[cfg(windows)]
     let mut xinput_prev_states = [None, None, None, None]; // Track previous state for 4 possible controllers
 
     // Track XInput axis states (controller_id, axis_index) -> last triggered direction
+    #[cfg(windows)]
     let mut xinput_axis_states: HashMap<(u32, u32), AxisState> = HashMap::new();
 
     // Initialize XInput states
+    #[cfg(windows)]
     for i in 0..4 {
         if let Ok(state) = xinput.get_state(i) {
             xinput_prev_states[i as usize] = Some(state);
@@ -378,18 +778,17 @@ pub fn wait_for_input(
     // Track axis states to prevent duplicate triggers
     // We'll initialize states dynamically as axes are moved to support any device
     let mut axis_states: HashMap<(usize, u32), AxisState> = HashMap::new();
+    // Descriptor axis names per device UUID, read on the first axis event from each device
+    let mut axis_name_cache: HashMap<String, HashMap<u32, String>> = HashMap::new();
 
     let start = Instant::now();
     let timeout = Duration::from_secs(timeout_secs);
 
-    // Axis detection thresholds
-    const AXIS_TRIGGER_THRESHOLD: f32 = 0.5; // 50% deflection to trigger
-    const AXIS_RESET_THRESHOLD: f32 = 0.3; // 30% to reset (hysteresis)
-
     while start.elapsed() < timeout {
         // Process all available gilrs events (non-blocking)
         while let Some(event) = gilrs.next_event() {
             eprintln!("wait_for_input: Received event: {:?}", event);
+            record_gilrs_event(&session_id, &event);
             match event.event {
                 EventType::ButtonPressed(button, code) => {
                     let joystick_id: usize = event.id.into();
@@ -534,62 +933,22 @@ pub fn wait_for_input(
                             last_triggered_direction: None,
                         });
 
-                        // Calculate how much the axis has moved from its initial/last value
-                        let movement_delta = (value - state.last_value).abs();
-
-                        // Require significant movement from initial position (prevents false triggers)
-                        const MOVEMENT_THRESHOLD: f32 = 0.3; // Require 30% movement from starting position
-
-                        // Determine if this is a significant movement
-                        let is_positive = value > AXIS_TRIGGER_THRESHOLD;
-                        let is_negative = value < -AXIS_TRIGGER_THRESHOLD;
-                        let is_centered = value.abs() < AXIS_RESET_THRESHOLD;
-                        let has_moved_enough = movement_delta > MOVEMENT_THRESHOLD;
-
-                        // Reset state if axis returns to center
-                        if is_centered {
-                            state.last_triggered_direction = None;
-                            state.last_value = value;
-                        }
-
-                        // Only trigger if:
-                        // 1. Axis moved beyond threshold
-                        // 2. Axis has moved significantly from its starting position
-                        // 3. This direction hasn't been triggered yet
-                        let should_trigger_positive = is_positive
-                            && has_moved_enough
-                            && state.last_triggered_direction != Some(true);
-                        let should_trigger_negative = is_negative
-                            && has_moved_enough
-                            && state.last_triggered_direction != Some(false);
-
-                        if should_trigger_positive || should_trigger_negative {
-                            let direction = if should_trigger_positive {
-                                "positive"
-                            } else {
-                                "negative"
-                            };
-                            let direction_symbol = if should_trigger_positive { "+" } else { "-" };
-
-                            // Update state
-                            state.last_triggered_direction = Some(should_trigger_positive);
-                            state.last_value = value;
-
-                            // Get friendly axis name (using Star Citizen naming convention)
-                            // Support more than 6 axes for advanced joysticks
-                            let axis_name = match axis_index {
-                                1 => "X",
-                                2 => "Y",
-                                3 => "RotX",
-                                4 => "RotY",
-                                5 => "Z",
-                                6 => "RotZ",
-                                _ => &format!("Axis{}", axis_index), // For axes beyond the standard 6
-                            };
+                        // Shared trigger logic: requires movement from the initial/last position
+                        if let Some(axis_direction) = evaluate_axis(state, value) {
+                            let direction = axis_direction.as_str();
+                            let direction_symbol = axis_direction.symbol();
 
                             // Get device UUID
                             let device_uuid = resolve_device_uuid(&gamepad, joystick_id);
 
+                            // Real axis label from the HID descriptor, SC's index naming otherwise
+                            let axis_names = descriptor_axis_names(
+                                &mut axis_name_cache,
+                                &device_uuid,
+                                &gamepad,
+                            );
+                            let axis_name = axis_display_name(axis_index, axis_names);
+
                             return Ok(Some(DetectedInput {
                                 input_string: format!(
                                     "{}{}_axis{}_{}",
@@ -628,8 +987,15 @@ pub fn wait_for_input(
         }
 
         // Poll XInput controllers for button presses and axis movements
+        #[cfg(windows)]
         for controller_id in 0..4 {
             if let Ok(state) = xinput.get_state(controller_id) {
+                record_xinput_state(
+                    &session_id,
+                    controller_id,
+                    &state,
+                    xinput_prev_states[controller_id as usize].as_ref(),
+                );
                 if let Some(prev_state) = &xinput_prev_states[controller_id as usize] {
                     // Check if any button was newly pressed
                     let buttons_pressed =
@@ -776,38 +1142,10 @@ pub fn wait_for_input(
                             last_triggered_direction: None,
                         });
 
-                        let movement_delta = (value - state_entry.last_value).abs();
-                        const MOVEMENT_THRESHOLD: f32 = 0.3;
-                        const AXIS_TRIGGER_THRESHOLD: f32 = 0.5;
-                        const AXIS_RESET_THRESHOLD: f32 = 0.3;
-
-                        let is_positive = *value > AXIS_TRIGGER_THRESHOLD;
-                        let is_negative = *value < -AXIS_TRIGGER_THRESHOLD;
-                        let is_centered = value.abs() < AXIS_RESET_THRESHOLD;
-                        let has_moved_enough = movement_delta > MOVEMENT_THRESHOLD;
-
-                        if is_centered {
-                            state_entry.last_triggered_direction = None;
-                            state_entry.last_value = *value;
-                        }
-
-                        let should_trigger_positive = is_positive
-                            && has_moved_enough
-                            && state_entry.last_triggered_direction != Some(true);
-                        let should_trigger_negative = is_negative
-                            && has_moved_enough
-                            && state_entry.last_triggered_direction != Some(false);
-
-                        if should_trigger_positive || should_trigger_negative {
-                            let direction = if should_trigger_positive {
-                                "positive"
-                            } else {
-                                "negative"
-                            };
-                            let direction_symbol = if should_trigger_positive { "+" } else { "-" };
-
-                            state_entry.last_triggered_direction = Some(should_trigger_positive);
-                            state_entry.last_value = *value;
+                        // Shared trigger logic: requires movement from the initial/last position
+                        if let Some(axis_direction) = evaluate_axis(state_entry, *value) {
+                            let direction = axis_direction.as_str();
+                            let direction_symbol = axis_direction.symbol();
 
                             let sc_instance = controller_id as usize + 1;
                             let device_uuid = resolve_xinput_uuid(controller_id);
@@ -864,6 +1202,8 @@ pub fn wait_for_multiple_inputs(
     // Track axis states to prevent duplicate triggers
     // Track axis states - dynamically initialized as axes are moved
     let mut axis_states: HashMap<(usize, u32), AxisState> = HashMap::new();
+    // Descriptor axis names per device UUID, read on the first axis event from each device
+    let mut axis_name_cache: HashMap<String, HashMap<u32, String>> = HashMap::new();
 
     let start = Instant::now();
     let initial_timeout = Duration::from_secs(initial_timeout_secs);
@@ -871,10 +1211,6 @@ pub fn wait_for_multiple_inputs(
     let mut first_input_time: Option<Instant> = None;
     let collect_duration = Duration::from_secs(collect_duration_secs);
 
-    // Axis detection thresholds
-    const AXIS_TRIGGER_THRESHOLD: f32 = 0.5;
-    const AXIS_RESET_THRESHOLD: f32 = 0.3;
-
     loop {
         // Check timeout conditions
         if first_input_time.is_none() {
@@ -890,6 +1226,7 @@ pub fn wait_for_multiple_inputs(
         }
 
         while let Some(event) = gilrs.next_event_blocking(Some(Duration::from_millis(50))) {
+            record_gilrs_event(&session_id, &event);
             let detected_input = match event.event {
                 EventType::ButtonPressed(button, code) => {
                     let joystick_id: usize = event.id.into();
@@ -1016,47 +1353,18 @@ pub fn wait_for_multiple_inputs(
                             last_triggered_direction: None,
                         });
 
-                        let movement_delta = (value - state.last_value).abs();
-                        const MOVEMENT_THRESHOLD: f32 = 0.3;
-
-                        let is_positive = value > AXIS_TRIGGER_THRESHOLD;
-                        let is_negative = value < -AXIS_TRIGGER_THRESHOLD;
-                        let is_centered = value.abs() < AXIS_RESET_THRESHOLD;
-                        let has_moved_enough = movement_delta > MOVEMENT_THRESHOLD;
+                        // Shared trigger logic: requires movement from the initial/last position
+                        if let Some(axis_direction) = evaluate_axis(state, value) {
+                            let direction = axis_direction.as_str();
+                            let direction_symbol = axis_direction.symbol();
 
-                        if is_centered {
-                            state.last_triggered_direction = None;
-                            state.last_value = value;
-                        }
-
-                        let should_trigger_positive = is_positive
-                            && has_moved_enough
-                            && state.last_triggered_direction != Some(true);
-                        let should_trigger_negative = is_negative
-                            && has_moved_enough
-                            && state.last_triggered_direction != Some(false);
-
-                        if should_trigger_positive || should_trigger_negative {
-                            let direction = if should_trigger_positive {
-                                "positive"
-                            } else {
-                                "negative"
-                            };
-                            let direction_symbol = if should_trigger_positive { "+" } else { "-" };
-
-                            state.last_triggered_direction = Some(should_trigger_positive);
-                            state.last_value = value;
-
-                            // Use index-based axis naming instead of gilrs::Axis enum
-                            let axis_name = match axis_index {
-                                1 => "X",
-                                2 => "Y",
-                                3 => "RotX",
-                                4 => "RotY",
-                                5 => "Z",
-                                6 => "RotZ",
-                                _ => &format!("Axis{}", axis_index),
-                            };
+                            // Real axis label from the HID descriptor, SC's index naming otherwise
+                            let axis_names = descriptor_axis_names(
+                                &mut axis_name_cache,
+                                &resolve_device_uuid(&gamepad, joystick_id),
+                                &gamepad,
+                            );
+                            let axis_name = axis_display_name(axis_index, axis_names);
 
                             // Collect extended debug info
                             let raw_axis_code = format!("{:?}", axis);
@@ -1138,15 +1446,19 @@ pub fn wait_for_inputs_with_events(
 
     let mut gilrs = Gilrs::new().map_err(|e| e.to_string())?;
 
-    // Initialize XInput for Xbox controller support
+    // Initialize XInput for Xbox controller support (Windows only, elsewhere gilrs covers gamepads)
+    #[cfg(windows)]
     let xinput =
         XInputHandle::load_default().map_err(|e| format!("Failed to load XInput: {:?}", e))?;
+    #[cfg(windows)]
     let mut xinput_prev_states = [None, None, None, None]; // Track previous state for 4 possible controllers
 
     // Track XInput axis states (controller_id, axis_index) -> last triggered direction
+    #[cfg(windows)]
     let mut xinput_axis_states: HashMap<(u32, u32), AxisState> = HashMap::new();
 
     // Initialize XInput states
+    #[cfg(windows)]
     for i in 0..4 {
         if let Ok(state) = xinput.get_state(i) {
             xinput_prev_states[i as usize] = Some(state);
@@ -1208,6 +1520,8 @@ pub fn wait_for_inputs_with_events(
     // Track axis states to prevent duplicate triggers
     // Track axis states - dynamically initialized as axes are moved
     let mut axis_states: HashMap<(usize, u32), AxisState> = HashMap::new();
+    // Descriptor axis names per device UUID, read on the first axis event from each device
+    let mut axis_name_cache: HashMap<String, HashMap<u32, String>> = HashMap::new();
 
     let start = Instant::now();
     let initial_timeout = Duration::from_secs(initial_timeout_secs);
@@ -1215,9 +1529,6 @@ pub fn wait_for_inputs_with_events(
     let mut first_input_time: Option<Instant> = None;
     let collect_duration = Duration::from_secs(collect_duration_secs);
 
-    const AXIS_TRIGGER_THRESHOLD: f32 = 0.5;
-    const AXIS_RESET_THRESHOLD: f32 = 0.3;
-
     loop {
         // Check timeout conditions
         if first_input_time.is_none() {
@@ -1245,6 +1556,7 @@ pub fn wait_for_inputs_with_events(
         }
 
         while let Some(event) = gilrs.next_event_blocking(Some(Duration::from_millis(50))) {
+            record_gilrs_event(&session_id, &event);
             let detected_input = match event.event {
                 EventType::ButtonPressed(button, code) => {
                     let joystick_id: usize = event.id.into();
@@ -1365,42 +1677,18 @@ pub fn wait_for_inputs_with_events(
                             last_triggered_direction: None,
                         });
 
-                        let abs_value = value.abs();
-                        let is_positive = value > 0.0;
-                        let should_trigger = match state.last_triggered_direction {
-                            None => abs_value >= AXIS_TRIGGER_THRESHOLD,
-                            Some(last_positive) if last_positive == is_positive => false,
-                            Some(_) => {
-                                if abs_value >= AXIS_TRIGGER_THRESHOLD {
-                                    true
-                                } else {
-                                    false
-                                }
-                            }
-                        };
-
-                        if abs_value < AXIS_RESET_THRESHOLD {
-                            state.last_triggered_direction = None;
-                        }
-
-                        state.last_value = value;
+                        // Shared trigger logic: requires movement from the initial/last position
+                        if let Some(axis_direction) = evaluate_axis(state, value) {
+                            let direction = axis_direction.as_str();
+                            let direction_symbol = axis_direction.symbol();
 
-                        if should_trigger {
-                            state.last_triggered_direction = Some(is_positive);
-
-                            let direction = if is_positive { "positive" } else { "negative" };
-                            let direction_symbol = if is_positive { "+" } else { "-" };
-
-                            // Use index-based axis naming
-                            let axis_name = match axis_index {
-                                1 => "X",
-                                2 => "Y",
-                                3 => "RotX",
-                                4 => "RotY",
-                                5 => "Z",
-                                6 => "RotZ",
-                                _ => &format!("Axis{}", axis_index),
-                            };
+                            // Real axis label from the HID descriptor, SC's index naming otherwise
+                            let axis_names = descriptor_axis_names(
+                                &mut axis_name_cache,
+                                &resolve_device_uuid(&gamepad, joystick_id),
+                                &gamepad,
+                            );
+                            let axis_name = axis_display_name(axis_index, axis_names);
 
                             // Collect extended debug info
                             let raw_axis_code = format!("{:?}", axis);
@@ -1462,8 +1750,15 @@ pub fn wait_for_inputs_with_events(
         }
 
         // Poll XInput controllers for button presses and axis movements
+        #[cfg(windows)]
         for controller_id in 0..4 {
             if let Ok(state) = xinput.get_state(controller_id) {
+                record_xinput_state(
+                    &session_id,
+                    controller_id,
+                    &state,
+                    xinput_prev_states[controller_id as usize].as_ref(),
+                );
                 if let Some(prev_state) = &xinput_prev_states[controller_id as usize] {
                     // Check if any button was newly pressed
                     let buttons_pressed =
@@ -1615,38 +1910,10 @@ pub fn wait_for_inputs_with_events(
                             last_triggered_direction: None,
                         });
 
-                        let movement_delta = (value - state_entry.last_value).abs();
-                        const MOVEMENT_THRESHOLD: f32 = 0.3;
-                        const AXIS_TRIGGER_THRESHOLD: f32 = 0.5;
-                        const AXIS_RESET_THRESHOLD: f32 = 0.3;
-
-                        let is_positive = *value > AXIS_TRIGGER_THRESHOLD;
-                        let is_negative = *value < -AXIS_TRIGGER_THRESHOLD;
-                        let is_centered = value.abs() < AXIS_RESET_THRESHOLD;
-                        let has_moved_enough = movement_delta > MOVEMENT_THRESHOLD;
-
-                        if is_centered {
-                            state_entry.last_triggered_direction = None;
-                            state_entry.last_value = *value;
-                        }
-
-                        let should_trigger_positive = is_positive
-                            && has_moved_enough
-                            && state_entry.last_triggered_direction != Some(true);
-                        let should_trigger_negative = is_negative
-                            && has_moved_enough
-                            && state_entry.last_triggered_direction != Some(false);
-
-                        if should_trigger_positive || should_trigger_negative {
-                            let direction = if should_trigger_positive {
-                                "positive"
-                            } else {
-                                "negative"
-                            };
-                            let direction_symbol = if should_trigger_positive { "+" } else { "-" };
-
-                            state_entry.last_triggered_direction = Some(should_trigger_positive);
-                            state_entry.last_value = *value;
+                        // Shared trigger logic: requires movement from the initial/last position
+                        if let Some(axis_direction) = evaluate_axis(state_entry, *value) {
+                            let direction = axis_direction.as_str();
+                            let direction_symbol = axis_direction.symbol();
 
                             let sc_instance = controller_id as usize + 1;
                             let device_uuid = resolve_xinput_uuid(controller_id);
@@ -1699,144 +1966,308 @@ pub fn wait_for_inputs_with_events(
 
 /// Get list of available joysticks
 pub fn detect_joysticks() -> Result<Vec<JoystickInfo>, String> {
-    let mut gilrs = Gilrs::new().map_err(|e| e.to_string())?;
-
-    let mut joysticks = Vec::new();
-
     eprintln!("=== Gilrs Gamepad Detection ===");
     eprintln!("Gilrs version: {}", env!("CARGO_PKG_VERSION"));
 
-    // Give gilrs a moment to initialize
+    // Give gilrs a moment to initialize, without holding the shared instance
     thread::sleep(Duration::from_millis(100));
 
-    // Process any pending events to ensure device list is up to date
-    while let Some(_event) = gilrs.next_event() {
-        // Just drain the event queue
-    }
-
-    let gamepad_count = gilrs.gamepads().count();
-    eprintln!(
-        "Gilrs reports {} gamepads after event processing",
-        gamepad_count
-    );
-
-    for (_id, gamepad) in gilrs.gamepads() {
-        let name = get_friendly_device_name(&gamepad);
-        let is_connected = gamepad.is_connected();
-        let id = usize::from(gamepad.id());
+    with_shared_gilrs(|gilrs| {
+        let mut joysticks = Vec::new();
 
+        let gamepad_count = gilrs.gamepads().count();
         eprintln!(
-            "Found gamepad ID {}: {} (connected: {})",
-            id, name, is_connected
+            "Gilrs reports {} gamepads after event processing",
+            gamepad_count
         );
-        eprintln!("  Power info: {:?}", gamepad.power_info());
-        eprintln!("  Is FF supported: {}", gamepad.is_ff_supported());
-        eprintln!("  UUID: {:?}", gamepad.uuid());
-        eprintln!("  Vendor ID: {:?}", gamepad.vendor_id());
-        eprintln!("  Product ID: {:?}", gamepad.product_id());
-
-        // Gilrs doesn't provide a way to query exact button/axis counts before they're used
-        // Use reasonable defaults based on device type
-        let is_gamepad_device = is_gamepad(&name, &gamepad);
-
-        let (button_count, axis_count, hat_count) = if is_gamepad_device {
-            // Standard gamepad: Xbox/PlayStation style
-            (15, 6, 1) // A/B/X/Y, LB/RB, LT/RT, Back/Start, LS/RS, D-pad (4 buttons) | Left stick X/Y, Right stick X/Y, Triggers | D-pad as hat
-        } else {
-            // Flight stick/HOTAS: More buttons, fewer axes
-            (32, 6, 1) // Many buttons typical on flight sticks | Fewer axes | Usually 1 hat switch
-        };
 
-        eprintln!(
-            "  Estimated: {} buttons, {} axes, {} hats (type: {})",
-            button_count,
-            axis_count,
-            hat_count,
-            if is_gamepad_device {
-                "gamepad"
+        for (_id, gamepad) in gilrs.gamepads() {
+            let name = get_friendly_device_name(&gamepad);
+            let is_connected = gamepad.is_connected();
+            let id = usize::from(gamepad.id());
+
+            eprintln!(
+                "Found gamepad ID {}: {} (connected: {})",
+                id, name, is_connected
+            );
+            eprintln!("  Power info: {:?}", gamepad.power_info());
+            eprintln!("  Is FF supported: {}", gamepad.is_ff_supported());
+            eprintln!("  UUID: {:?}", gamepad.uuid());
+            eprintln!("  Vendor ID: {:?}", gamepad.vendor_id());
+            eprintln!("  Product ID: {:?}", gamepad.product_id());
+
+            // Gilrs doesn't provide a way to query exact button/axis counts before they're used
+            // Use reasonable defaults based on device type
+            let is_gamepad_device = is_gamepad(&name, &gamepad);
+
+            let (button_count, axis_count, hat_count) = if is_gamepad_device {
+                // Standard gamepad: Xbox/PlayStation style
+                (15, 6, 1) // A/B/X/Y, LB/RB, LT/RT, Back/Start, LS/RS, D-pad (4 buttons) | Left stick X/Y, Right stick X/Y, Triggers | D-pad as hat
             } else {
-                "joystick"
-            }
-        );
+                // Flight stick/HOTAS: More buttons, fewer axes
+                (32, 6, 1) // Many buttons typical on flight sticks | Fewer axes | Usually 1 hat switch
+            };
 
-        let device_type_name = if is_gamepad_device {
-            "Gamepad"
-        } else {
-            "Joystick"
-        };
+            eprintln!(
+                "  Estimated: {} buttons, {} axes, {} hats (type: {})",
+                button_count,
+                axis_count,
+                hat_count,
+                if is_gamepad_device {
+                    "gamepad"
+                } else {
+                    "joystick"
+                }
+            );
 
-        joysticks.push(JoystickInfo {
-            id,
-            name: name.clone(),
-            is_connected,
-            button_count,
-            axis_count,
-            hat_count,
-            device_type: device_type_name.to_string(),
-        });
+            let device_type_name = if is_gamepad_device {
+                "Gamepad"
+            } else {
+                "Joystick"
+            };
+
+            joysticks.push(JoystickInfo {
+                id,
+                name: name.clone(),
+                is_connected,
+                button_count,
+                axis_count,
+                hat_count,
+                device_type: device_type_name.to_string(),
+            });
+        }
+        eprintln!("=== Total gamepads found: {} ===", joysticks.len());
+
+        Ok(joysticks)
+    })
+}
+
+/// Identity of a connected device, used to match it against devices declared in a profile
+#[derive(Serialize, Clone, Debug)]
+pub struct DeviceIdentity {
+    pub uuid: String,
+    pub name: String,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    pub sc_instance: usize, // Instance number this device currently gets in detection (gilrs id + 1)
+}
+
+/// List connected gilrs devices with their VID/PID for profile device matching
+pub fn list_device_identities() -> Result<Vec<DeviceIdentity>, String> {
+    with_shared_gilrs(|gilrs| {
+        Ok(gilrs
+            .gamepads()
+            .map(|(_id, gamepad)| {
+                let id = usize::from(gamepad.id());
+                DeviceIdentity {
+                    uuid: resolve_device_uuid(&gamepad, id),
+                    name: get_friendly_device_name(&gamepad),
+                    vendor_id: gamepad.vendor_id(),
+                    product_id: gamepad.product_id(),
+                    sc_instance: id + 1,
+                }
+            })
+            .collect())
+    })
+}
+
+/// Names and IDs a connected device reports, before any device database lookup
+#[derive(Serialize, Clone, Debug)]
+pub struct ReportedDeviceNames {
+    pub os_name: String,
+    pub gilrs_name: String, // SDL mapping name when gilrs has one, otherwise the OS name
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    pub device_type: String, // "gamepad" or "joystick"
+}
+
+/// Look up the raw names of the connected device with this UUID
+pub fn reported_device_names(uuid: &str) -> Result<Option<ReportedDeviceNames>, String> {
+    with_shared_gilrs(|gilrs| {
+        Ok(gilrs
+            .gamepads()
+            .find(|(_id, gamepad)| resolve_device_uuid(gamepad, usize::from(gamepad.id())) == uuid)
+            .map(|(_id, gamepad)| {
+                let device_type = if is_gamepad(&get_friendly_device_name(&gamepad), &gamepad) {
+                    "gamepad"
+                } else {
+                    "joystick"
+                };
+                ReportedDeviceNames {
+                    os_name: gamepad.os_name().to_string(),
+                    gilrs_name: gamepad.name().to_string(),
+                    vendor_id: gamepad.vendor_id(),
+                    product_id: gamepad.product_id(),
+                    device_type: device_type.to_string(),
+                }
+            }))
+    })
+}
+
+/// Longest rumble `rumble_device` will play
+const MAX_RUMBLE_MS: u64 = 3000;
+
+/// Error for a device that can't rumble, kept apart from gilrs so it can be tested without hardware
+fn require_ff_support(name: &str, ff_supported: bool) -> Result<(), String> {
+    if ff_supported {
+        Ok(())
+    } else {
+        Err(format!("{} does not support force feedback", name))
     }
-    eprintln!("=== Total gamepads found: {} ===", joysticks.len());
+}
 
-    Ok(joysticks)
+/// Rumble a connected device so the user can feel which physical device it is
+/// Blocks for the length of the rumble, since the effect stops as soon as it's dropped
+pub fn rumble_device(uuid: &str, duration_ms: u64) -> Result<(), String> {
+    use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Replay, Ticks};
+
+    let duration_ms = duration_ms.clamp(1, MAX_RUMBLE_MS);
+
+    let effect = with_shared_gilrs(|gilrs| {
+        let (id, name, ff_supported) = gilrs
+            .gamepads()
+            .find(|(_id, gamepad)| resolve_device_uuid(gamepad, usize::from(gamepad.id())) == uuid)
+            .map(|(id, gamepad)| {
+                (
+                    id,
+                    get_friendly_device_name(&gamepad),
+                    gamepad.is_ff_supported(),
+                )
+            })
+            .ok_or_else(|| format!("Device {} is not connected", uuid))?;
+        require_ff_support(&name, ff_supported)?;
+
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: u16::MAX / 2,
+                },
+                scheduling: Replay {
+                    play_for: Ticks::from_ms(duration_ms as u32),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .gamepads(&[id])
+            .finish(gilrs)
+            .map_err(|e| format!("Failed to create rumble effect for {}: {}", name, e))?;
+        effect
+            .play()
+            .map_err(|e| format!("Failed to play rumble effect on {}: {}", name, e))?;
+        Ok(effect)
+    })?;
+
+    // gilrs plays the effect on its own thread; the shared instance isn't locked meanwhile
+    thread::sleep(Duration::from_millis(duration_ms));
+    drop(effect);
+    Ok(())
 }
 
-/// Returns detailed information for all connected devices.
-pub fn list_connected_devices() -> Result<Vec<DeviceInfo>, String> {
-    let mut gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+/// Connected device with the VID/PID, power and force feedback details reported by gilrs
+#[derive(Serialize, Clone, Debug)]
+pub struct DetailedDeviceInfo {
+    #[serde(flatten)]
+    pub info: DeviceInfo,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    pub power_info: String,
+    pub ff_supported: bool,
+}
 
-    // Drain events so gilrs updates its internal cache
-    while let Some(_event) = gilrs.next_event() {
-        // no-op
+fn format_power_info(power: gilrs::PowerInfo) -> String {
+    match power {
+        gilrs::PowerInfo::Unknown => "Unknown".to_string(),
+        gilrs::PowerInfo::Wired => "Wired".to_string(),
+        gilrs::PowerInfo::Discharging(level) => format!("Discharging ({}%)", level),
+        gilrs::PowerInfo::Charging(level) => format!("Charging ({}%)", level),
+        gilrs::PowerInfo::Charged => "Charged".to_string(),
     }
+}
 
-    let mut devices = Vec::new();
+/// Returns basic information for all connected devices.
+pub fn list_connected_devices() -> Result<Vec<DeviceInfo>, String> {
+    Ok(list_connected_devices_detailed()?
+        .into_iter()
+        .map(|d| d.info)
+        .collect())
+}
 
-    for (_id, gamepad) in gilrs.gamepads() {
-        let name = get_friendly_device_name(&gamepad);
+/// Returns detailed information for all connected devices.
+pub fn list_connected_devices_detailed() -> Result<Vec<DetailedDeviceInfo>, String> {
+    with_shared_gilrs(|gilrs| {
+        let mut devices = Vec::new();
 
-        // Skip Xbox controllers in Gilrs if we're on Windows, as we'll add them via XInput
-        // This prevents duplicates and ensures we use the XInput UUIDs that match our input detection
-        if cfg!(windows) && (name.to_lowercase().contains("xbox") || name.to_lowercase().contains("xinput")) {
-            continue;
-        }
+        for (_id, gamepad) in gilrs.gamepads() {
+            let name = get_friendly_device_name(&gamepad);
 
-        let is_connected = gamepad.is_connected();
-        let id = usize::from(gamepad.id());
-        let uuid = resolve_device_uuid(&gamepad, id);
-        let is_gamepad_device = is_gamepad(&name, &gamepad);
+            if listed_via_xinput(&name) {
+                continue;
+            }
 
-        let (button_count, axis_count, hat_count) = if is_gamepad_device {
-            (15, 6, 1)
-        } else {
-            (32, 7, 1)
-        };
+            let is_connected = gamepad.is_connected();
+            let id = usize::from(gamepad.id());
+            let uuid = resolve_device_uuid(&gamepad, id);
+            let is_gamepad_device = is_gamepad(&name, &gamepad);
 
-        devices.push(DeviceInfo {
-            uuid,
-            name,
-            axis_count,
-            button_count,
-            hat_count,
-            device_type: if is_gamepad_device {
-                "gamepad"
+            let (button_count, axis_count, hat_count) = if is_gamepad_device {
+                (15, 6, 1)
             } else {
-                "joystick"
-            }
-            .to_string(),
-            is_connected,
-        });
-    }
+                (32, 7, 1)
+            };
 
-    // Add XInput devices explicitly
-    // This ensures that if wait_for_input detects via XInput fallback, we have a matching device in the list
-    if let Ok(xinput) = XInputHandle::load_default() {
-        for i in 0..4 {
-            if xinput.get_state(i).is_ok() {
-                let uuid = resolve_xinput_uuid(i);
-                
-                // Only add if not already present (though UUIDs will likely differ from Gilrs)
-                if !devices.iter().any(|d| d.uuid == uuid) {
-                    devices.push(DeviceInfo {
+            devices.push(DetailedDeviceInfo {
+                info: DeviceInfo {
+                    uuid,
+                    name,
+                    axis_count,
+                    button_count,
+                    hat_count,
+                    device_type: if is_gamepad_device {
+                        "gamepad"
+                    } else {
+                        "joystick"
+                    }
+                    .to_string(),
+                    is_connected,
+                    alias: None,
+                },
+                vendor_id: gamepad.vendor_id(),
+                product_id: gamepad.product_id(),
+                power_info: format_power_info(gamepad.power_info()),
+                ff_supported: gamepad.is_ff_supported(),
+            });
+        }
+
+        devices.extend(xinput_devices(&devices));
+
+        Ok(devices)
+    })
+}
+
+// Skip Xbox controllers in Gilrs if we're on Windows, as we'll add them via XInput
+// This prevents duplicates and ensures we use the XInput UUIDs that match our input detection
+fn listed_via_xinput(name: &str) -> bool {
+    let name = name.to_lowercase();
+    cfg!(windows) && (name.contains("xbox") || name.contains("xinput"))
+}
+
+// Add XInput devices explicitly
+// This ensures that if wait_for_input detects via XInput fallback, we have a matching device in the list
+#[cfg(windows)]
+fn xinput_devices(listed: &[DetailedDeviceInfo]) -> Vec<DetailedDeviceInfo> {
+    let mut devices = Vec::new();
+    let Ok(xinput) = XInputHandle::load_default() else {
+        return devices;
+    };
+
+    for i in 0..4 {
+        if xinput.get_state(i).is_ok() {
+            let uuid = resolve_xinput_uuid(i);
+
+            // Only add if not already present (though UUIDs will likely differ from Gilrs)
+            if !listed.iter().any(|d| d.info.uuid == uuid) {
+                devices.push(DetailedDeviceInfo {
+                    info: DeviceInfo {
                         uuid,
                         name: format!("Xbox Controller (XInput {})", i),
                         axis_count: 6,
@@ -1844,13 +2275,25 @@ pub fn list_connected_devices() -> Result<Vec<DeviceInfo>, String> {
                         hat_count: 1,
                         device_type: "Gamepad".to_string(),
                         is_connected: true,
-                    });
-                }
+                        alias: None,
+                    },
+                    // XInput does not expose these
+                    vendor_id: None,
+                    product_id: None,
+                    power_info: "Unknown".to_string(),
+                    ff_supported: false,
+                });
             }
         }
     }
 
-    Ok(devices)
+    devices
+}
+
+// Stub for non-Windows platforms: gamepads come from gilrs only
+#[cfg(not(windows))]
+fn xinput_devices(_listed: &[DetailedDeviceInfo]) -> Vec<DetailedDeviceInfo> {
+    Vec::new()
 }
 
 /// Waits for the user to move an axis on the specified device and returns the raw axis index.
@@ -1860,6 +2303,7 @@ pub fn detect_axis_movement_for_device(
 ) -> Result<Option<AxisMovement>, String> {
     let timeout = Duration::from_millis(timeout_millis);
     let start = Instant::now();
+    let started_at = SystemTime::now();
 
     // Process any pending events within the timeout window
     while start.elapsed() < timeout {
@@ -1873,8 +2317,20 @@ pub fn detect_axis_movement_for_device(
         let mut latest_movement: Option<AxisMovement> = None;
         
         if let Some(gilrs) = gilrs_lock.as_mut() {
-            // Process ALL pending events to clear the queue
+            // Process ALL pending events to clear the queue, starting with those the device lists read
+            // Anything from before detection started is stale movement and is dropped
+            let mut events: Vec<gilrs::Event> = take_pending_since(
+                &mut *GILRS_PENDING_EVENTS.lock().map_err(|e| e.to_string())?,
+                started_at,
+                |event| event.time,
+            );
             while let Some(event) = gilrs.next_event() {
+                if event.time >= started_at {
+                    events.push(event);
+                }
+            }
+
+            for event in events {
                 if let EventType::AxisChanged(_axis, value, code) = event.event {
                     let gamepad = gilrs.gamepad(event.id);
                     let uuid = resolve_device_uuid(&gamepad, usize::from(event.id));
@@ -1909,3 +2365,249 @@ pub fn detect_axis_movement_for_device(
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_instance_created_once() {
+        let slot: Mutex<Option<u32>> = Mutex::new(None);
+        let mut created = 0;
+
+        for expected in 1..=3 {
+            let value = with_shared(
+                &slot,
+                || {
+                    created += 1;
+                    Ok(0)
+                },
+                |value| {
+                    *value += 1;
+                    Ok(*value)
+                },
+            )
+            .unwrap();
+            // State carries over between calls
+            assert_eq!(value, expected);
+        }
+        assert_eq!(created, 1);
+    }
+
+    #[test]
+    fn test_pending_events_keep_the_newest() {
+        let mut queue = VecDeque::new();
+        for event in 0..MAX_PENDING_EVENTS + 2 {
+            queue_pending(&mut queue, event);
+        }
+
+        assert_eq!(queue.len(), MAX_PENDING_EVENTS);
+        assert_eq!(queue.front(), Some(&2));
+        assert_eq!(queue.back(), Some(&(MAX_PENDING_EVENTS + 1)));
+    }
+
+    #[test]
+    fn test_pending_events_before_detection_are_dropped() {
+        let started = SystemTime::now();
+        let mut queue = VecDeque::new();
+        queue_pending(
+            &mut queue,
+            (started - Duration::from_secs(30), "earlier session"),
+        );
+        queue_pending(&mut queue, (started, "at start"));
+        queue_pending(
+            &mut queue,
+            (started + Duration::from_millis(5), "after start"),
+        );
+
+        let events = take_pending_since(&mut queue, started, |(time, _)| *time);
+        let names: Vec<&str> = events.iter().map(|(_, name)| *name).collect();
+        assert_eq!(names, vec!["at start", "after start"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_detailed_device_info_serializes_flat() {
+        let device = DetailedDeviceInfo {
+            info: DeviceInfo {
+                uuid: "030000004f04000002b4000000000000".to_string(),
+                name: "T.16000M".to_string(),
+                axis_count: 7,
+                button_count: 32,
+                hat_count: 1,
+                device_type: "joystick".to_string(),
+                is_connected: true,
+                alias: None,
+            },
+            vendor_id: Some(0x044F),
+            product_id: Some(0xB10A),
+            power_info: format_power_info(gilrs::PowerInfo::Wired),
+            ff_supported: false,
+        };
+
+        let json = serde_json::to_value(&device).unwrap();
+        assert_eq!(json["uuid"], "030000004f04000002b4000000000000");
+        assert_eq!(json["vendor_id"], 0x044F);
+        assert_eq!(json["product_id"], 0xB10A);
+        assert_eq!(json["power_info"], "Wired");
+        assert_eq!(json["ff_supported"], false);
+        assert!(json.get("info").is_none());
+    }
+
+    #[test]
+    fn test_rumble_requires_ff_support() {
+        assert_eq!(
+            require_ff_support("T.16000M", false),
+            Err("T.16000M does not support force feedback".to_string())
+        );
+        assert!(require_ff_support("Xbox Controller", true).is_ok());
+    }
+
+    #[test]
+    fn test_hid_product_name_for_generic_device() {
+        let hid_device =
+            |vendor_id: u16, product_id: u16, manufacturer: Option<&str>, product: Option<&str>| {
+                hid_reader::HidDeviceListItem {
+                    vendor_id,
+                    product_id,
+                    serial_number: None,
+                    manufacturer: manufacturer.map(str::to_string),
+                    product: product.map(str::to_string),
+                    path: String::new(),
+                    interface_number: 0,
+                }
+            };
+        let hid_devices = vec![
+            hid_device(0x3344, 0x4130, Some("VIRPIL Controls"), None),
+            hid_device(
+                0x3344,
+                0x4130,
+                Some("VIRPIL Controls"),
+                Some("R-VPC Stick MT-50CM2"),
+            ),
+            hid_device(0x231d, 0x0200, Some("VKB"), Some("VKB-Sim Gladiator NXT R")),
+        ];
+
+        assert_eq!(
+            hid_product_name(0x3344, 0x4130, &hid_devices).as_deref(),
+            Some("VIRPIL Controls R-VPC Stick MT-50CM2")
+        );
+        assert_eq!(
+            hid_product_name(0x231d, 0x0200, &hid_devices).as_deref(),
+            Some("VKB-Sim Gladiator NXT R")
+        );
+        assert_eq!(hid_product_name(0x044f, 0xb10a, &hid_devices), None);
+    }
+
+    #[test]
+    fn test_axis_display_name_prefers_descriptor() {
+        // A HOTAS reporting its throttle as the third axis
+        let descriptor: HashMap<u32, String> = [
+            (1, "X".to_string()),
+            (2, "Y".to_string()),
+            (3, "Throttle".to_string()),
+            (4, " ".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(axis_display_name(3, &descriptor), "Throttle");
+        // Blank or missing descriptor names fall back to SC's index naming
+        assert_eq!(axis_display_name(4, &descriptor), "RotY");
+        assert_eq!(axis_display_name(6, &descriptor), "RotZ");
+        assert_eq!(axis_display_name(9, &HashMap::new()), "Axis9");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_poll_active_modifiers_stub() {
+        assert!(poll_active_modifiers().is_empty());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_xbox_controllers_listed_via_gilrs_without_xinput() {
+        // Without XInput the gilrs entry is the only one, so it must not be skipped
+        assert!(!listed_via_xinput("Xbox Wireless Controller"));
+        assert!(!listed_via_xinput("XInput Controller"));
+        assert!(xinput_devices(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_recorded_events_serialize_and_clear_on_stop() {
+        let session_id = "test-recording-session";
+        record_event(session_id, "gilrs", 0, "ignored".to_string(), None, None);
+        assert!(!is_recording(session_id));
+
+        start_recording(session_id);
+        assert!(is_recording(session_id));
+        record_event(
+            session_id,
+            "gilrs",
+            1,
+            "AxisChanged(LeftStickX, 0.75, Code(...))".to_string(),
+            Some(3),
+            Some(0.75),
+        );
+        record_event(
+            "some-other-session",
+            "gilrs",
+            0,
+            "ButtonPressed".to_string(),
+            None,
+            None,
+        );
+
+        let events = stop_recording(session_id);
+        assert!(!is_recording(session_id));
+        assert_eq!(events.len(), 1);
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["source"], "gilrs");
+        assert_eq!(json["device_id"], 1);
+        assert_eq!(json["event"], "AxisChanged(LeftStickX, 0.75, Code(...))");
+        assert_eq!(json["code"], 3);
+        assert_eq!(json["value"], 0.75);
+        assert!(json["elapsed_ms"].is_u64());
+
+        // Stopping removed the buffer, so nothing more is recorded for the session
+        record_event(session_id, "gilrs", 1, "late".to_string(), None, None);
+        assert!(stop_recording(session_id).is_empty());
+    }
+
+    fn axis_state(initial_value: f32) -> AxisState {
+        AxisState {
+            last_value: initial_value,
+            last_triggered_direction: None,
+        }
+    }
+
+    #[test]
+    fn test_parked_axis_does_not_trigger() {
+        // Throttle parked at max when detection starts
+        let mut state = axis_state(1.0);
+        assert_eq!(evaluate_axis(&mut state, 1.0), None);
+        assert_eq!(evaluate_axis(&mut state, 0.95), None);
+        assert_eq!(evaluate_axis(&mut state, 1.0), None);
+    }
+
+    #[test]
+    fn test_parked_axis_triggers_after_real_movement() {
+        let mut state = axis_state(1.0);
+        assert_eq!(evaluate_axis(&mut state, 1.0), None);
+        // Pulled all the way back: moved well past the threshold in the negative direction
+        assert_eq!(evaluate_axis(&mut state, -0.9), Some(AxisDirection::Negative));
+        // Holding the same direction does not re-trigger
+        assert_eq!(evaluate_axis(&mut state, -1.0), None);
+    }
+
+    #[test]
+    fn test_centered_axis_triggers_once_per_direction() {
+        let mut state = axis_state(0.0);
+        assert_eq!(evaluate_axis(&mut state, 0.2), None);
+        assert_eq!(evaluate_axis(&mut state, 0.8), Some(AxisDirection::Positive));
+        assert_eq!(evaluate_axis(&mut state, 0.9), None);
+        // Return to center resets, allowing the same direction again
+        assert_eq!(evaluate_axis(&mut state, 0.0), None);
+        assert_eq!(evaluate_axis(&mut state, 0.7), Some(AxisDirection::Positive));
+    }
+}
@@ -1,5 +1,5 @@
 use hidapi::HidApi;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ffi::CString;
 use hut::Usage;
@@ -25,6 +25,28 @@ pub struct HidAxisReport {
     pub is_16bit: bool, // Indicates if values are 16-bit (true) or 8-bit (false)
 }
 
+/// Location of the button bitmap inside an input report, derived from the report descriptor
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HidButtonLayout {
+    pub report_id: Option<u8>, // Reports start with this ID byte when present
+    pub bit_offset: usize,     // Bit offset of button 1, after the report ID byte
+    pub button_count: usize,
+}
+
+/// Button, axis and hat counts declared by a device's report descriptor
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HidCapabilities {
+    pub button_count: usize,
+    pub axis_count: usize, // Generic Desktop X through Wheel (0x30-0x38)
+    pub hat_count: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HidFullReport {
+    pub axes: HidAxisReport,
+    pub buttons: Vec<bool>, // buttons[0] is button 1
+}
+
 /// List all HID devices that appear to be game controllers
 pub fn list_hid_game_controllers() -> Result<Vec<HidDeviceListItem>, String> {
     let api = HidApi::new().map_err(|e| format!("Failed to initialize HID API: {}", e))?;
@@ -264,6 +286,194 @@ pub fn parse_hid_axes(report: &[u8], axis_names: &HashMap<u32, String>) -> Resul
     })
 }
 
+/// Extract the pressed state of each button from a HID report using the descriptor's button layout
+/// Returns an empty list if the report belongs to a different report ID
+pub fn parse_hid_buttons(report: &[u8], layout: &HidButtonLayout) -> Vec<bool> {
+    // Strip the report ID prefix byte when the device uses report IDs
+    let data = match layout.report_id {
+        Some(id) => match report.split_first() {
+            Some((&first, rest)) if first == id => rest,
+            _ => return Vec::new(),
+        },
+        None => report,
+    };
+
+    (0..layout.button_count)
+        .map(|i| {
+            let bit = layout.bit_offset + i;
+            data.get(bit / 8)
+                .map(|byte| byte & (1 << (bit % 8)) != 0)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Parse a report descriptor to find where the button bitmap (usage page 0x09) lives
+/// Walks the descriptor items tracking report size/count per report ID
+pub fn parse_hid_button_layout(descriptor: &[u8]) -> Option<HidButtonLayout> {
+    let mut usage_page: u32 = 0;
+    let mut report_size: usize = 0;
+    let mut report_count: usize = 0;
+    let mut report_id: Option<u8> = None;
+    let mut usage_min: Option<u32> = None;
+    let mut usage_max: Option<u32> = None;
+    // Current bit offset within each report (keyed by report ID)
+    let mut offsets: HashMap<Option<u8>, usize> = HashMap::new();
+
+    let mut i = 0;
+    while i < descriptor.len() {
+        let item = descriptor[i];
+        let size = match item & 0x03 {
+            3 => 4,
+            n => n as usize,
+        };
+        if i + size >= descriptor.len() {
+            break;
+        }
+
+        let mut data: u32 = 0;
+        for j in 0..size {
+            data |= (descriptor[i + 1 + j] as u32) << (j * 8);
+        }
+
+        let tag = (item >> 4) & 0x0F;
+        let item_type = (item >> 2) & 0x03;
+
+        match (item_type, tag) {
+            // Main: Input
+            (0, 0x08) => {
+                let offset = offsets.entry(report_id).or_insert(0);
+                let is_constant = data & 0x01 != 0;
+
+                if usage_page == 0x09 && !is_constant && report_size == 1 {
+                    let button_count = match (usage_min, usage_max) {
+                        (Some(min), Some(max)) if max >= min => {
+                            ((max - min + 1) as usize).min(report_count)
+                        }
+                        _ => report_count,
+                    };
+                    return Some(HidButtonLayout {
+                        report_id,
+                        bit_offset: *offset,
+                        button_count,
+                    });
+                }
+
+                *offset += report_size * report_count;
+                usage_min = None;
+                usage_max = None;
+            }
+            // Main: Output, Feature, Collection, End Collection reset local items
+            (0, _) => {
+                usage_min = None;
+                usage_max = None;
+            }
+            // Global items
+            (1, 0x00) => usage_page = data,
+            (1, 0x07) => report_size = data as usize,
+            (1, 0x08) => report_id = Some(data as u8),
+            (1, 0x09) => report_count = data as usize,
+            // Local items
+            (2, 0x01) => usage_min = Some(data),
+            (2, 0x02) => usage_max = Some(data),
+            _ => {}
+        }
+
+        i += 1 + size;
+    }
+
+    None
+}
+
+/// Count the buttons, axes and hat switches a report descriptor declares as inputs
+/// None when it declares none of them
+pub fn parse_hid_capabilities(descriptor: &[u8]) -> Option<HidCapabilities> {
+    let mut usage_page: u32 = 0;
+    let mut report_count: usize = 0;
+    let mut usages: Vec<(u32, u32)> = Vec::new(); // (usage page, usage)
+    let mut usage_min: Option<(u32, u32)> = None;
+    let mut usage_max: Option<u32> = None;
+    let mut capabilities = HidCapabilities {
+        button_count: 0,
+        axis_count: 0,
+        hat_count: 0,
+    };
+
+    let mut i = 0;
+    while i < descriptor.len() {
+        let item = descriptor[i];
+        let size = match item & 0x03 {
+            3 => 4,
+            n => n as usize,
+        };
+        if i + size >= descriptor.len() {
+            break;
+        }
+
+        let mut data: u32 = 0;
+        for j in 0..size {
+            data |= (descriptor[i + 1 + j] as u32) << (j * 8);
+        }
+        // A 4-byte usage carries its own usage page in the high half
+        let usage = |data: u32| {
+            if size == 4 {
+                (data >> 16, data & 0xFFFF)
+            } else {
+                (usage_page, data)
+            }
+        };
+
+        let tag = (item >> 4) & 0x0F;
+        let item_type = (item >> 2) & 0x03;
+
+        match (item_type, tag) {
+            // Main: Input
+            (0, 0x08) => {
+                let is_constant = data & 0x01 != 0;
+                if !is_constant {
+                    if let (Some((page, min)), Some(max)) = (usage_min, usage_max) {
+                        usages.extend((min..=max).map(|u| (page, u)));
+                    }
+                    // Buttons without usages are still one button per report field
+                    if usages.is_empty() && usage_page == 0x09 {
+                        capabilities.button_count += report_count;
+                    }
+                    for (page, usage) in usages.iter().take(report_count) {
+                        match (page, usage) {
+                            (0x09, _) => capabilities.button_count += 1,
+                            (0x01, 0x30..=0x38) => capabilities.axis_count += 1,
+                            (0x01, 0x39) => capabilities.hat_count += 1,
+                            _ => {}
+                        }
+                    }
+                }
+                usages.clear();
+                usage_min = None;
+                usage_max = None;
+            }
+            // Main: Output, Feature, Collection, End Collection reset local items
+            (0, _) => {
+                usages.clear();
+                usage_min = None;
+                usage_max = None;
+            }
+            // Global items
+            (1, 0x00) => usage_page = data,
+            (1, 0x09) => report_count = data as usize,
+            // Local items
+            (2, 0x00) => usages.push(usage(data)),
+            (2, 0x01) => usage_min = Some(usage(data)),
+            (2, 0x02) => usage_max = Some(usage(data).1),
+            _ => {}
+        }
+
+        i += 1 + size;
+    }
+
+    let declared = capabilities.button_count + capabilities.axis_count + capabilities.hat_count;
+    (declared > 0).then_some(capabilities)
+}
+
 /// Detect the effective bit depth of an axis value
 /// This determines how many bits are actually being used
 /// Examples: 
@@ -392,6 +602,43 @@ fn current_time_ms() -> u64 {
         .as_millis() as u64
 }
 
+/// Get the button bitmap layout from the device's report descriptor
+pub fn get_button_layout_from_descriptor(device_path: &str) -> Result<Option<HidButtonLayout>, String> {
+    let api = HidApi::new().map_err(|e| format!("Failed to initialize HID API: {}", e))?;
+    
+    let c_path = CString::new(device_path)
+        .map_err(|e| format!("Invalid device path: {}", e))?;
+    
+    let device = api.open_path(&c_path)
+        .map_err(|e| format!("Failed to open device: {}", e))?;
+    
+    let mut descriptor_buf = vec![0u8; 4096];
+    let descriptor_len = device.get_report_descriptor(&mut descriptor_buf)
+        .map_err(|e| format!("Failed to get report descriptor: {}", e))?;
+    
+    Ok(parse_hid_button_layout(&descriptor_buf[..descriptor_len]))
+}
+
+/// Get the button/axis/hat counts from the device's report descriptor
+pub fn get_capabilities_from_descriptor(
+    device_path: &str,
+) -> Result<Option<HidCapabilities>, String> {
+    let api = HidApi::new().map_err(|e| format!("Failed to initialize HID API: {}", e))?;
+
+    let c_path = CString::new(device_path).map_err(|e| format!("Invalid device path: {}", e))?;
+
+    let device = api
+        .open_path(&c_path)
+        .map_err(|e| format!("Failed to open device: {}", e))?;
+
+    let mut descriptor_buf = vec![0u8; 4096];
+    let descriptor_len = device
+        .get_report_descriptor(&mut descriptor_buf)
+        .map_err(|e| format!("Failed to get report descriptor: {}", e))?;
+
+    Ok(parse_hid_capabilities(&descriptor_buf[..descriptor_len]))
+}
+
 /// Get HID axis names from the device's report descriptor using proper HID parsing libraries
 /// Returns a mapping of axis index -> axis name (e.g., "X", "Y", "Rz", "Slider")
 pub fn get_axis_names_from_descriptor(device_path: &str) -> Result<HashMap<u32, String>, String> {
@@ -607,12 +854,114 @@ mod tests {
         // [ReportID, Buttons, X, Y, Z, Rx, Ry, Slider]
         let report = vec![0x01, 0x00, 128, 64, 255, 100, 200, 50];
         
-        let result = parse_hid_axes(&report).unwrap();
+        let result = parse_hid_axes(&report, &HashMap::new()).unwrap();
         
         // Should extract axes starting from byte 2 (after report ID and buttons)
         assert!(result.axis_values.len() > 0);
     }
     
+    #[test]
+    fn test_parse_hid_capabilities() {
+        let descriptor = [
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x04, // Usage (Joystick)
+            0xA1, 0x01, // Collection (Application)
+            0x85, 0x01, //   Report ID (1)
+            0x05, 0x09, //   Usage Page (Button)
+            0x19, 0x01, //   Usage Minimum (1)
+            0x29, 0x28, //   Usage Maximum (40)
+            0x75, 0x01, //   Report Size (1)
+            0x95, 0x28, //   Report Count (40)
+            0x81, 0x02, //   Input (Data, Var, Abs)
+            0x05, 0x01, //   Usage Page (Generic Desktop)
+            0x09, 0x39, //   Usage (Hat Switch)
+            0x09, 0x39, //   Usage (Hat Switch)
+            0x75, 0x04, //   Report Size (4)
+            0x95, 0x02, //   Report Count (2)
+            0x81, 0x42, //   Input (Data, Var, Abs, Null)
+            0x09, 0x30, //   Usage (X)
+            0x09, 0x31, //   Usage (Y)
+            0x09, 0x35, //   Usage (Rz)
+            0x09, 0x36, //   Usage (Slider)
+            0x75, 0x10, //   Report Size (16)
+            0x95, 0x04, //   Report Count (4)
+            0x81, 0x02, //   Input (Data, Var, Abs)
+            0x75, 0x08, //   Report Size (8)
+            0x95, 0x01, //   Report Count (1)
+            0x81, 0x03, //   Input (Const) padding
+            0xC0, // End Collection
+        ];
+
+        assert_eq!(
+            parse_hid_capabilities(&descriptor),
+            Some(HidCapabilities {
+                button_count: 40,
+                axis_count: 4,
+                hat_count: 2,
+            })
+        );
+        assert_eq!(
+            parse_hid_capabilities(&[0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, 0xC0]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_hid_buttons() {
+        let layout = HidButtonLayout {
+            report_id: Some(0x01),
+            bit_offset: 0,
+            button_count: 24,
+        };
+
+        // Buttons 1 and 3 in the first byte, button 17 in the third byte
+        let report = vec![0x01, 0b0000_0101, 0x00, 0b0000_0001, 0x80, 0x80];
+        let buttons = parse_hid_buttons(&report, &layout);
+
+        assert_eq!(buttons.len(), 24);
+        let pressed: Vec<usize> = buttons
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p)
+            .map(|(i, _)| i + 1)
+            .collect();
+        assert_eq!(pressed, vec![1, 3, 17]);
+
+        // A report with another ID is ignored
+        assert!(parse_hid_buttons(&[0x02, 0xFF, 0xFF, 0xFF], &layout).is_empty());
+    }
+
+    #[test]
+    fn test_parse_hid_button_layout() {
+        let descriptor = vec![
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x04, // Usage (Joystick)
+            0xA1, 0x01, // Collection (Application)
+            0x85, 0x01, //   Report ID (1)
+            0x09, 0x30, //   Usage (X)
+            0x75, 0x10, //   Report Size (16)
+            0x95, 0x01, //   Report Count (1)
+            0x81, 0x02, //   Input (Data, Var, Abs)
+            0x05, 0x09, //   Usage Page (Button)
+            0x19, 0x01, //   Usage Minimum (1)
+            0x29, 0x18, //   Usage Maximum (24)
+            0x75, 0x01, //   Report Size (1)
+            0x95, 0x18, //   Report Count (24)
+            0x81, 0x02, //   Input (Data, Var, Abs)
+            0xC0, // End Collection
+        ];
+
+        let layout = parse_hid_button_layout(&descriptor).unwrap();
+        assert_eq!(
+            layout,
+            HidButtonLayout {
+                report_id: Some(1),
+                bit_offset: 16,
+                button_count: 24,
+            }
+        );
+    }
+
     #[test]
     fn test_axis_tracker() {
         let mut tracker = HidAxisTracker::new();
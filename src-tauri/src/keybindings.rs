@@ -3,9 +3,17 @@ use serde::Serialize;
 use std::collections::HashMap;
 
 /// Represents the entire Star Citizen keybinding file
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ActionMaps {
     pub profile_name: String,
+    #[serde(default)]
+    pub description: String, // CustomisationUIHeader description, stored unescaped and re-escaped on export
+    #[serde(default)]
+    pub image: String, // CustomisationUIHeader image
+    #[serde(default)]
+    pub options_version: Option<u32>, // optionsVersion attribute as read, None when missing; exports write the expected one
+    #[serde(default)]
+    pub rebind_version: Option<u32>, // rebindVersion attribute as read, None when missing; exports write the expected one
     pub action_maps: Vec<ActionMap>,
     pub categories: Vec<Category>,
     pub devices: DeviceInfo,
@@ -15,6 +23,9 @@ pub struct ActionMaps {
 #[derive(Debug, Serialize, Clone)]
 pub struct AllBinds {
     pub action_maps: Vec<AllBindsActionMap>,
+    /// Lowercased localization key (without '@') -> translated text, from `load_localization`
+    #[serde(skip)]
+    pub localization: HashMap<String, String>,
 }
 
 /// Action map from AllBinds.xml with UI metadata
@@ -42,46 +53,130 @@ pub struct AllBindsAction {
     pub default_joystick: String,
 }
 
+impl AllBindsAction {
+    /// The default binding for a device type, None when SC has no default for it
+    pub fn default_input(&self, input_type: &InputType) -> Option<&str> {
+        let default = match input_type {
+            InputType::Keyboard => &self.default_keyboard,
+            InputType::Mouse => &self.default_mouse,
+            InputType::Joystick => &self.default_joystick,
+            InputType::Gamepad => &self.default_gamepad,
+            InputType::Unknown => return None,
+        }
+        .trim();
+
+        if default.is_empty() {
+            None
+        } else {
+            Some(default)
+        }
+    }
+}
+
 /// UI header containing metadata about devices and categories
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct DeviceInfo {
     pub keyboards: Vec<String>,
     pub mice: Vec<String>,
     pub joysticks: Vec<String>,
+    /// Joystick instances declared by `<options>`, including ones without a Product
+    #[serde(default)]
+    pub declared_joystick_instances: Vec<u32>,
+}
+
+impl DeviceInfo {
+    /// Take the other profile's Products for instances we have none for, and its declared
+    /// joystick instances
+    pub fn merge(&mut self, other: &DeviceInfo) {
+        fn fill_products(ours: &mut Vec<String>, theirs: &[String]) {
+            for (idx, product) in theirs.iter().enumerate() {
+                if product.is_empty() {
+                    continue;
+                }
+                if ours.len() <= idx {
+                    ours.resize(idx + 1, String::new());
+                }
+                if ours[idx].is_empty() {
+                    ours[idx] = product.clone();
+                }
+            }
+        }
+
+        fill_products(&mut self.keyboards, &other.keyboards);
+        fill_products(&mut self.mice, &other.mice);
+        fill_products(&mut self.joysticks, &other.joysticks);
+
+        self.declared_joystick_instances
+            .extend(other.declared_joystick_instances.iter().copied());
+        self.declared_joystick_instances.sort_unstable();
+        self.declared_joystick_instances.dedup();
+    }
+
+    /// Highest joystick instance with a Product or a declaration, 0 when there are none
+    pub fn joystick_instance_count(&self) -> usize {
+        let declared = self
+            .declared_joystick_instances
+            .iter()
+            .max()
+            .map_or(0, |i| *i as usize);
+        self.joysticks.len().max(declared)
+    }
 }
 
 /// A single category
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Category {
     pub label: String,
 }
 
 /// A group of actions (e.g., "seat_general", "spaceship_general")
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ActionMap {
     pub name: String,
     pub actions: Vec<Action>,
 }
 
 /// A single action that can be bound to inputs
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Action {
     pub name: String,
     pub rebinds: Vec<Rebind>,
 }
 
 /// A keybinding for an action
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Rebind {
     pub input: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub multi_tap: Option<u32>,
+    /// multiTapBlock: suppress the single-tap action while a multi-tap is pending
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub multi_tap_block: Option<bool>,
+    /// pressTriggerThreshold in milliseconds (SC writes it in seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub press_trigger_threshold: Option<u32>,
     #[serde(skip_serializing_if = "String::is_empty")]
     #[serde(default)]
     pub activation_mode: String,
 }
 
+/// Parse a pressTriggerThreshold attribute (seconds, e.g. "0.25") into milliseconds
+pub fn parse_press_threshold_ms(value: &str) -> Option<u32> {
+    let seconds = value.trim().parse::<f64>().ok()?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Some((seconds * 1000.0).round() as u32)
+}
+
+/// Format a threshold in milliseconds as SC's seconds value (250 -> "0.25")
+pub fn format_press_threshold(ms: u32) -> String {
+    format!("{}", ms as f64 / 1000.0)
+}
+
 /// Parsed input type for easier filtering
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum InputType {
@@ -92,52 +187,252 @@ pub enum InputType {
     Unknown,
 }
 
+/// Activation modes Star Citizen accepts on a rebind
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ActivationMode {
+    Press,
+    Tap,
+    Hold,
+    DoubleTap,
+    DoubleTapNonblocking,
+    DelayedPress,
+    DelayedPressMedium,
+    DelayedHold,
+    DelayedHoldLong,
+    DelayedHoldNoRetrigger,
+    HoldNoRetrigger,
+    HoldToggle,
+    SmartToggle,
+    All,
+}
+
+/// Activation mode details for the UI dropdown
+#[derive(Debug, Serialize, Clone)]
+pub struct ActivationModeInfo {
+    pub value: String,
+    pub label: String,
+    pub description: String,
+}
+
+impl ActivationMode {
+    pub const ALL_MODES: [ActivationMode; 14] = [
+        ActivationMode::Press,
+        ActivationMode::Tap,
+        ActivationMode::Hold,
+        ActivationMode::DoubleTap,
+        ActivationMode::DoubleTapNonblocking,
+        ActivationMode::DelayedPress,
+        ActivationMode::DelayedPressMedium,
+        ActivationMode::DelayedHold,
+        ActivationMode::DelayedHoldLong,
+        ActivationMode::DelayedHoldNoRetrigger,
+        ActivationMode::HoldNoRetrigger,
+        ActivationMode::HoldToggle,
+        ActivationMode::SmartToggle,
+        ActivationMode::All,
+    ];
+
+    /// The activationMode attribute value written to the XML
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivationMode::Press => "press",
+            ActivationMode::Tap => "tap",
+            ActivationMode::Hold => "hold",
+            ActivationMode::DoubleTap => "double_tap",
+            ActivationMode::DoubleTapNonblocking => "double_tap_nonblocking",
+            ActivationMode::DelayedPress => "delayed_press",
+            ActivationMode::DelayedPressMedium => "delayed_press_medium",
+            ActivationMode::DelayedHold => "delayed_hold",
+            ActivationMode::DelayedHoldLong => "delayed_hold_long",
+            ActivationMode::DelayedHoldNoRetrigger => "delayed_hold_no_retrigger",
+            ActivationMode::HoldNoRetrigger => "hold_no_retrigger",
+            ActivationMode::HoldToggle => "hold_toggle",
+            ActivationMode::SmartToggle => "smart_toggle",
+            ActivationMode::All => "all",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ActivationMode::Press => "Fires as soon as the input is pressed",
+            ActivationMode::Tap => "Fires on a quick press and release",
+            ActivationMode::Hold => "Active for as long as the input is held",
+            ActivationMode::DoubleTap => "Fires on two quick presses",
+            ActivationMode::DoubleTapNonblocking => {
+                "Fires on two quick presses without blocking the single press"
+            }
+            ActivationMode::DelayedPress => "Fires after the input is held briefly",
+            ActivationMode::DelayedPressMedium => "Fires after the input is held a little longer",
+            ActivationMode::DelayedHold => "Active while held, after a short delay",
+            ActivationMode::DelayedHoldLong => "Active while held, after a long delay",
+            ActivationMode::DelayedHoldNoRetrigger => {
+                "Active while held after a short delay, fires once per hold"
+            }
+            ActivationMode::HoldNoRetrigger => "Active while held, fires once per hold",
+            ActivationMode::HoldToggle => "Toggles on press, momentary while held",
+            ActivationMode::SmartToggle => "Tap to toggle, hold for momentary",
+            ActivationMode::All => "Fires on press, hold and release",
+        }
+    }
+
+    /// Label and description for the UI
+    pub fn info(&self) -> ActivationModeInfo {
+        ActivationModeInfo {
+            value: self.as_str().to_string(),
+            label: format_display_name(self.as_str()),
+            description: self.description().to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for ActivationMode {
+    type Err = String;
+
+    /// Parse an activation mode, accepting case and separator variations ("Double Tap", "double-tap")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace(['-', ' '], "_");
+
+        ActivationMode::ALL_MODES
+            .iter()
+            .find(|mode| mode.as_str() == normalized)
+            .copied()
+            .ok_or_else(|| {
+                let valid: Vec<&str> = ActivationMode::ALL_MODES
+                    .iter()
+                    .map(|m| m.as_str())
+                    .collect();
+                format!(
+                    "Unknown activation mode '{}'. Valid modes: {}",
+                    s,
+                    valid.join(", ")
+                )
+            })
+    }
+}
+
+/// Validate an activation mode string and return its canonical form
+/// An empty string means "no activation mode" and is passed through
+pub fn normalize_activation_mode(mode: &str) -> Result<String, String> {
+    if mode.trim().is_empty() {
+        return Ok(String::new());
+    }
+
+    mode.parse::<ActivationMode>()
+        .map(|m| m.as_str().to_string())
+}
+
+/// Modifier keys SC allows in combos ("lalt+js1_button3", "kb1_lshift+c")
+const MODIFIER_KEYS: [&str; 6] = ["lalt", "ralt", "lctrl", "rctrl", "lshift", "rshift"];
+
+/// Everything parsed from a rebind input
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct InputClassification {
+    pub input_type: InputType,
+    pub is_cleared: bool,
+    pub device_instance: Option<u32>, // Instance-less prefixes ("kb_") count as 1
+    pub modifiers: Vec<String>,
+}
+
+impl Action {
+    /// At least one rebind has a real input; empty inputs and cleared placeholders
+    /// (anything ending in "_" once trimmed) don't count. `to_xml` only writes these actions
+    pub fn has_bound_rebind(&self) -> bool {
+        self.rebinds.iter().any(|r| {
+            let trimmed = r.input.trim();
+            !trimmed.is_empty() && !trimmed.ends_with('_')
+        })
+    }
+
+    /// Every rebind is a cleared placeholder, so the user unbound the action on all devices
+    /// they touched. An action without rebinds isn't cleared, it's just not customized
+    pub fn is_fully_cleared(&self) -> bool {
+        !self.rebinds.is_empty() && self.rebinds.iter().all(|r| r.is_cleared())
+    }
+}
+
 impl Rebind {
     /// Parse the input type from the rebind string
     /// Examples: "kb_space", "js1_button3", "js2_button15", "mouse1_left", "LALT+js1_button3", "kb_u+lshift"
+    /// Cleared placeholders ("js1_ ") and unrecognised inputs are both Unknown; use
+    /// `classify_input` to tell them apart
     pub fn get_input_type(&self) -> InputType {
-        let input = self.input.trim();
+        let classification = self.classify_input();
+        if classification.is_cleared {
+            InputType::Unknown
+        } else {
+            classification.input_type
+        }
+    }
 
-        // Check if this is an unbound placeholder (device prefix followed by underscore and only whitespace)
-        // Examples: "kb_ ", "kb_", "js1_ ", "mouse1_", "gp1_ "
-        if let Some(after_underscore_pos) = input.find('_') {
-            let after_underscore = &input[after_underscore_pos + 1..];
-            
-            // If everything after the underscore is whitespace or empty, it's unbound
-            if after_underscore.trim().is_empty() {
-                return InputType::Unknown;
-            }
+    /// Parse everything callers need about the input in one pass
+    /// For a cleared placeholder, input_type is the device type being cleared
+    pub fn classify_input(&self) -> InputClassification {
+        let input = self.input.trim();
+        let parts: Vec<&str> = input.split('+').map(|part| part.trim()).collect();
+
+        let device_instance = input_device_instance(input).and_then(|device| {
+            device
+                .trim_start_matches(|c: char| c.is_alphabetic())
+                .parse::<u32>()
+                .ok()
+        });
+
+        if let Some(cleared_type) = self.cleared_device_type() {
+            return InputClassification {
+                input_type: cleared_type,
+                is_cleared: true,
+                device_instance,
+                modifiers: Vec::new(),
+            };
         }
 
+        // Something followed by an underscore and nothing else, but not a known device ("xyz_ ")
+        let empty_placeholder = input
+            .find('_')
+            .is_some_and(|pos| input[pos + 1..].trim().is_empty());
+
         // Check all parts for device prefixes (handles modifiers in any position)
-        if input.contains('+') {
-            // Split and check each part for device prefix
-            for part in input.split('+') {
-                let part = part.trim();
-                if part.starts_with("kb") {
-                    return InputType::Keyboard;
-                } else if part.starts_with("mouse") {
-                    return InputType::Mouse;
-                } else if part.starts_with("js") {
-                    return InputType::Joystick;
-                } else if part.starts_with("gp") {
-                    return InputType::Gamepad;
-                }
-            }
+        let input_type = if empty_placeholder {
+            InputType::Unknown
         } else {
-            // No modifiers, check the whole string
-            if input.starts_with("kb") {
-                return InputType::Keyboard;
-            } else if input.starts_with("mouse") {
-                return InputType::Mouse;
-            } else if input.starts_with("js") {
-                return InputType::Joystick;
-            } else if input.starts_with("gp") {
-                return InputType::Gamepad;
-            }
+            parts
+                .iter()
+                .find_map(|part| {
+                    let (device, _) = part.split_once('_')?;
+                    device_prefix_type(device)
+                })
+                .unwrap_or(InputType::Unknown)
+        };
+
+        let modifiers = parts
+            .iter()
+            .map(|part| {
+                let key = part.split_once('_').map_or(*part, |(_, key)| key);
+                key.to_lowercase()
+            })
+            .filter(|key| MODIFIER_KEYS.contains(&key.as_str()))
+            .collect();
+
+        InputClassification {
+            input_type,
+            is_cleared: false,
+            device_instance,
+            modifiers,
         }
+    }
 
-        InputType::Unknown
+    /// The input is modifier keys with no base key or button ("LALT", "lalt+lctrl",
+    /// "kb1_lalt+rshift"), which usually means the capture went wrong. A lone shift
+    /// ("kb1_lshift") is commonly bound as a key of its own and doesn't count
+    pub fn is_modifier_only(&self) -> bool {
+        let classification = self.classify_input();
+        let parts = self.input.trim().split('+').count();
+        let lone_shift = parts == 1
+            && classification
+                .modifiers
+                .first()
+                .is_some_and(|key| key.ends_with("shift"));
+        !classification.is_cleared && classification.modifiers.len() == parts && !lone_shift
     }
 
     /// Get a human-readable description of the binding
@@ -153,79 +448,223 @@ impl Rebind {
             return "Unbound".to_string();
         }
 
-        // Check for modifier prefixes (e.g., "lalt+rctrl+js1_button3" or "LALT+RCTRL+js1_button3")
-        let mut modifiers = Vec::new();
-        let mut remaining_input = input;
+        // Split the chord into its '+'-joined parts (e.g. "lalt+rctrl+js1_button3",
+        // "kb1_lctrl+kb1_w"); the first part carrying a device prefix is the base binding
+        let parts: Vec<&str> = input.split('+').map(|part| part.trim()).collect();
+        let has_device = |part: &str| {
+            part.split_once('_')
+                .is_some_and(|(device, _)| device_prefix_type(device).is_some())
+        };
 
-        // Extract all modifiers from the beginning (case-insensitive)
-        loop {
-            if let Some((prefix, rest)) = remaining_input.split_once('+') {
-                let prefix_upper = prefix.trim().to_uppercase();
-                if matches!(
-                    prefix_upper.as_str(),
-                    "LALT" | "RALT" | "LCTRL" | "RCTRL" | "LSHIFT" | "RSHIFT"
-                ) {
-                    modifiers.push(prefix_upper);
-                    remaining_input = rest;
-                } else {
-                    break;
-                }
+        let Some(base_index) = parts.iter().position(|part| has_device(part)) else {
+            if parts.len() == 1 {
+                return input.to_string();
+            }
+            return parts
+                .iter()
+                .map(|part| Self::format_keyboard_binding(&part.to_lowercase()))
+                .collect::<Vec<_>>()
+                .join(" + ");
+        };
+
+        // Leading parts are modifiers or other held keys (e.g. "LALT", "capslock")
+        let mut labels: Vec<String> = parts[..base_index]
+            .iter()
+            .map(|part| Self::format_keyboard_binding(&part.to_lowercase()))
+            .collect();
+
+        // Parts without a device prefix belong to the base binding ("kb1_ralt+np_1");
+        // later parts with their own prefix are further chord keys ("kb1_lctrl+kb1_w")
+        let mut base = parts[base_index].to_string();
+        let mut chord_keys = Vec::new();
+        for part in &parts[base_index + 1..] {
+            if has_device(part) || !chord_keys.is_empty() {
+                chord_keys.push(*part);
             } else {
-                break;
+                base.push('+');
+                base.push_str(part);
             }
         }
 
-        // Parse the remaining input string (the actual binding)
-        let base_display = if let Some((device, binding)) = remaining_input.split_once('_') {
-            let binding = binding.trim();
-            if binding.is_empty() {
-                "Unbound".to_string()
+        let (device, binding) = base.split_once('_').unwrap_or((&base, ""));
+        let binding = binding.trim();
+        labels.push(if binding.is_empty() {
+            "Unbound".to_string()
+        } else {
+            Self::format_device_binding(device, binding)
+        });
+
+        // Further chord keys repeat the device label only when it changes
+        // ("js1_button1+js2_button2"), not for "kb1_lctrl+kb1_w"
+        let mut previous_device = device.to_lowercase();
+        for key in chord_keys {
+            let (device, binding) = key.split_once('_').unwrap_or(("", key));
+            let device = device.to_lowercase();
+            labels.push(if device != previous_device {
+                Self::format_device_binding(&device, binding)
             } else {
-                match &device[..2.min(device.len())] {
-                    "kb" => format!("Keyboard - {}", Self::format_binding(binding)),
-                    "js" => {
-                        let instance = device.get(2..3).unwrap_or("1");
-                        format!("Joystick {} - {}", instance, Self::format_binding(binding))
-                    }
-                    "mo" => format!("Mouse - {}", Self::format_binding(binding)),
-                    "gp" => format!("Gamepad - {}", Self::format_binding(binding)),
-                    _ => format!("{} - {}", device, Self::format_binding(binding)),
+                match device_prefix_type(&device) {
+                    Some(InputType::Keyboard) => Self::format_keyboard_binding(binding),
+                    Some(InputType::Mouse) => Self::format_mouse_binding(binding),
+                    _ => Self::format_binding(binding),
                 }
+            });
+            previous_device = device;
+        }
+
+        labels.join(" + ")
+    }
+
+    /// Format a binding with its device label (e.g. "Joystick 2 - Button 3")
+    fn format_device_binding(device: &str, binding: &str) -> String {
+        match device_prefix_type(device) {
+            Some(InputType::Keyboard) => {
+                format!("Keyboard - {}", Self::format_keyboard_binding(binding))
             }
-        } else {
-            remaining_input.to_string()
-        };
+            Some(InputType::Joystick) => {
+                let instance = device.get(2..3).unwrap_or("1");
+                format!("Joystick {} - {}", instance, Self::format_binding(binding))
+            }
+            Some(InputType::Mouse) => {
+                format!("Mouse - {}", Self::format_mouse_binding(binding))
+            }
+            Some(InputType::Gamepad) => {
+                format!("Gamepad - {}", Self::format_binding(binding))
+            }
+            _ => format!("{} - {}", device, Self::format_binding(binding)),
+        }
+    }
 
-        // Format modifiers for display
-        if !modifiers.is_empty() {
-            let modifier_display: Vec<String> = modifiers
-                .iter()
-                .map(|m| match m.as_str() {
-                    "LALT" => "Left Alt".to_string(),
-                    "RALT" => "Right Alt".to_string(),
-                    "LCTRL" => "Left Ctrl".to_string(),
-                    "RCTRL" => "Right Ctrl".to_string(),
-                    "LSHIFT" => "Left Shift".to_string(),
-                    "RSHIFT" => "Right Shift".to_string(),
-                    _ => m.clone(),
-                })
-                .collect();
+    /// Check if this rebind is a cleared placeholder: a device prefix followed by
+    /// an underscore and only whitespace (e.g. "kb1_ ", "js2_ ", "mouse1_", "gp1_ ")
+    pub fn is_cleared(&self) -> bool {
+        self.cleared_device_type().is_some()
+    }
+
+    /// For a cleared placeholder, the device type it clears
+    pub fn cleared_device_type(&self) -> Option<InputType> {
+        let (prefix, rest) = self.input.trim_start().split_once('_')?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
 
-            format!("{} + {}", modifier_display.join(" + "), base_display)
+        let has_instance = |device: &str| {
+            prefix
+                .strip_prefix(device)
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        };
+        let optional_instance = |device: &str| {
+            prefix
+                .strip_prefix(device)
+                .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+        };
+
+        if optional_instance("kb") {
+            Some(InputType::Keyboard)
+        } else if optional_instance("mouse") || optional_instance("mo") {
+            Some(InputType::Mouse)
+        } else if has_instance("js") {
+            Some(InputType::Joystick)
+        } else if has_instance("gp") {
+            Some(InputType::Gamepad)
         } else {
-            base_display
+            None
+        }
+    }
+
+    /// Joystick instance number of the input, modifiers allowed
+    /// e.g. "js2_hat1_up_right" -> Some(2), "lalt+js1_button3" -> Some(1)
+    pub fn joystick_instance(&self) -> Option<u8> {
+        input_device_part(&self.input)?
+            .strip_prefix("js")?
+            .parse::<u8>()
+            .ok()
+    }
+
+    /// Canonical form of a cleared placeholder: the device prefix and a single space ("js1_ ")
+    /// None if this rebind is not cleared
+    pub fn canonical_cleared_input(&self) -> Option<String> {
+        if !self.is_cleared() {
+            return None;
         }
+        let (prefix, _) = self.input.trim_start().split_once('_')?;
+        Some(format!("{}_ ", prefix))
     }
 
     /// Format binding name to be more readable
     fn format_binding(binding: &str) -> String {
+        Self::format_binding_with(binding, device_key_label)
+    }
+
+    /// Format a keyboard binding, where "up"/"down"/... are the arrow keys
+    fn format_keyboard_binding(binding: &str) -> String {
+        Self::format_binding_with(binding, keyname_to_label)
+    }
+
+    fn format_binding_with(binding: &str, key_label: fn(&str) -> Option<&'static str>) -> String {
         let clean = binding.trim();
         if clean.starts_with("button") {
             if let Some(num) = clean.strip_prefix("button") {
                 return format!("Button {}", num.trim());
             }
         }
-        clean.replace('_', " ").to_uppercase()
+        if let Some(hat) = Self::format_hat(clean) {
+            return hat;
+        }
+        if let Some(label) = key_label(clean) {
+            return label.to_string();
+        }
+        // Key combos ("ralt+np_1"): label each key separately
+        clean
+            .split('+')
+            .map(|key| match key_label(key) {
+                Some(label) => label.to_string(),
+                None => key.replace('_', " ").to_uppercase(),
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    /// Format mouse axes and wheel directions, falling back to `format_binding`
+    /// e.g. "maxis_x" -> "X Axis", "mwheel_up" -> "Wheel Up"
+    fn format_mouse_binding(binding: &str) -> String {
+        let clean = binding.trim().to_lowercase();
+        if let Some(axis) = clean.strip_prefix("maxis_") {
+            if !axis.is_empty() {
+                return format!("{} Axis", axis.to_uppercase());
+            }
+        }
+        match clean.as_str() {
+            "mwheel_up" => "Wheel Up".to_string(),
+            "mwheel_down" => "Wheel Down".to_string(),
+            "mwheel" => "Wheel".to_string(),
+            _ => Self::format_binding(binding),
+        }
+    }
+
+    /// Format hat inputs, including 8-way diagonals
+    /// e.g. "hat1_up" -> "Hat 1 Up", "hat1_up_right" -> "Hat 1 Up-Right"
+    fn format_hat(binding: &str) -> Option<String> {
+        let (num, directions) = binding.strip_prefix("hat")?.split_once('_')?;
+        if num.is_empty() || !num.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let directions: Vec<&str> = directions.split('_').collect();
+        if directions.len() > 2
+            || !directions
+                .iter()
+                .all(|d| matches!(*d, "up" | "down" | "left" | "right"))
+        {
+            return None;
+        }
+
+        let directions: Vec<String> = directions
+            .iter()
+            .map(|d| d[..1].to_uppercase() + &d[1..])
+            .collect();
+
+        Some(format!("Hat {} {}", num, directions.join("-")))
     }
 }
 
@@ -233,11 +672,20 @@ impl Rebind {
 #[derive(Debug, Serialize, Clone)]
 pub struct OrganizedKeybindings {
     pub profile_name: String,
+    pub profile_name_source: ProfileNameSource,
     pub categories: Vec<String>,
     pub devices: DeviceInfo,
     pub action_maps: Vec<OrganizedActionMap>,
 }
 
+/// Where the profile name shown in the UI came from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum ProfileNameSource {
+    FromFile,     // profileName attribute
+    FromFilename, // attribute was blank, derived from the file name
+    Empty,        // blank and nothing usable in the file name; the UI should prompt
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct OrganizedActionMap {
     pub name: String,
@@ -259,6 +707,7 @@ pub struct BindingInfo {
     pub display_name: String,
     pub activation_mode: String,
     pub multi_tap: Option<u32>,
+    pub differs_from_default: Option<bool>, // None when AllBinds isn't available
 }
 
 impl ActionMaps {
@@ -286,16 +735,44 @@ impl ActionMaps {
         })
     }
 
+    /// Read only the profileName of a profile, stopping at the root element
+    /// Much cheaper than `from_xml` when scanning many files. None if there is no
+    /// ActionMaps root or the name is blank
+    pub fn read_profile_name(xml: &str) -> Option<String> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut buf = vec![];
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(quick_xml::events::Event::Start(ref e))
+                | Ok(quick_xml::events::Event::Empty(ref e)) => {
+                    if e.name().as_ref() != b"ActionMaps" {
+                        return None;
+                    }
+                    return e
+                        .attributes()
+                        .flatten()
+                        .find(|attr| attr.key.as_ref() == b"profileName")
+                        .map(|attr| attr_text(&attr))
+                        .filter(|name| !name.trim().is_empty());
+                }
+                Ok(quick_xml::events::Event::Eof) | Err(_) => return None,
+                _ => {}
+            }
+            buf.clear();
+        }
+    }
+
     /// Parse XML file into ActionMaps structure using event-based parser
     pub fn from_xml(xml: &str) -> Result<Self, String> {
         let mut profile_name = String::new();
+        let mut description = String::new();
+        let mut image = String::new();
+        let mut options_version = None;
+        let mut rebind_version = None;
         let mut action_maps = Vec::new();
         let mut categories = Vec::new();
-        let mut devices = DeviceInfo {
-            keyboards: Vec::new(),
-            mice: Vec::new(),
-            joysticks: Vec::new(),
-        };
+        let mut devices = DeviceInfo::default();
 
         // Use quick-xml's Reader
         let mut reader = quick_xml::Reader::from_str(xml);
@@ -309,11 +786,27 @@ impl ActionMaps {
                 | Ok(quick_xml::events::Event::Empty(ref e)) => {
                     match e.name().as_ref() {
                         b"ActionMaps" => {
-                            // Get profile name
+                            // Get profile name and format versions
+                            for attr in e.attributes().flatten() {
+                                let value = attr_text(&attr);
+                                match attr.key.as_ref() {
+                                    b"profileName" => profile_name = value,
+                                    b"optionsVersion" => {
+                                        options_version = value.trim().parse::<u32>().ok()
+                                    }
+                                    b"rebindVersion" => {
+                                        rebind_version = value.trim().parse::<u32>().ok()
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        b"CustomisationUIHeader" => {
                             for attr in e.attributes().flatten() {
-                                if attr.key.as_ref() == b"profileName" {
-                                    profile_name =
-                                        String::from_utf8(attr.value.to_vec()).unwrap_or_default();
+                                match attr.key.as_ref() {
+                                    b"description" => description = attr_text(&attr),
+                                    b"image" => image = attr_text(&attr),
+                                    _ => {}
                                 }
                             }
                         }
@@ -321,8 +814,7 @@ impl ActionMaps {
                             // Get category label
                             for attr in e.attributes().flatten() {
                                 if attr.key.as_ref() == b"label" {
-                                    let label =
-                                        String::from_utf8(attr.value.to_vec()).unwrap_or_default();
+                                    let label = attr_text(&attr);
                                     if !label.is_empty() {
                                         categories.push(Category { label });
                                     }
@@ -332,26 +824,47 @@ impl ActionMaps {
                         b"options" => {
                             let mut device_type = String::new();
                             let mut product = String::new();
+                            let mut instance = None;
 
                             for attr in e.attributes().flatten() {
                                 match attr.key.as_ref() {
-                                    b"type" => {
-                                        device_type = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
-                                    }
-                                    b"Product" => {
-                                        product = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
+                                    b"type" => device_type = attr_text(&attr),
+                                    b"Product" => product = attr_text(&attr),
+                                    b"instance" => {
+                                        instance = String::from_utf8(attr.value.to_vec())
+                                            .ok()
+                                            .and_then(|v| v.parse::<usize>().ok())
+                                            .filter(|i| *i > 0)
                                     }
                                     _ => {}
                                 }
                             }
 
+                            if device_type == "joystick" {
+                                if let Some(instance) = instance {
+                                    if !devices
+                                        .declared_joystick_instances
+                                        .contains(&(instance as u32))
+                                    {
+                                        devices.declared_joystick_instances.push(instance as u32);
+                                    }
+                                }
+                            }
+
                             if !product.is_empty() {
                                 match device_type.as_str() {
                                     "keyboard" => devices.keyboards.push(product),
                                     "mouse" => devices.mice.push(product),
-                                    "joystick" => devices.joysticks.push(product),
+                                    // Joysticks are stored by instance (index 0 = js1)
+                                    "joystick" => match instance {
+                                        Some(instance) => {
+                                            if devices.joysticks.len() < instance {
+                                                devices.joysticks.resize(instance, String::new());
+                                            }
+                                            devices.joysticks[instance - 1] = product;
+                                        }
+                                        None => devices.joysticks.push(product),
+                                    },
                                     _ => {}
                                 }
                             }
@@ -360,8 +873,7 @@ impl ActionMaps {
                             let mut name = String::new();
                             for attr in e.attributes().flatten() {
                                 if attr.key.as_ref() == b"name" {
-                                    name =
-                                        String::from_utf8(attr.value.to_vec()).unwrap_or_default();
+                                    name = attr_text(&attr);
                                 }
                             }
                             current_action_map = Some(ActionMap {
@@ -373,8 +885,7 @@ impl ActionMaps {
                             let mut name = String::new();
                             for attr in e.attributes().flatten() {
                                 if attr.key.as_ref() == b"name" {
-                                    name =
-                                        String::from_utf8(attr.value.to_vec()).unwrap_or_default();
+                                    name = attr_text(&attr);
                                 }
                             }
                             current_action = Some(Action {
@@ -385,23 +896,28 @@ impl ActionMaps {
                         b"rebind" => {
                             let mut input = String::new();
                             let mut multi_tap: Option<u32> = None;
+                            let mut multi_tap_block: Option<bool> = None;
+                            let mut press_trigger_threshold: Option<u32> = None;
                             let mut activation_mode_attr = String::new();
                             for attr in e.attributes().flatten() {
                                 match attr.key.as_ref() {
-                                    b"input" => {
-                                        input = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
-                                    }
+                                    b"input" => input = attr_text(&attr),
                                     b"multiTap" => {
                                         if let Ok(s) = String::from_utf8(attr.value.to_vec()) {
                                             multi_tap = s.parse::<u32>().ok();
                                         }
                                     }
-                                    b"activationMode" => {
-                                        activation_mode_attr =
-                                            String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
+                                    b"multiTapBlock" => {
+                                        if let Ok(s) = String::from_utf8(attr.value.to_vec()) {
+                                            multi_tap_block = Some(s.trim() == "1");
+                                        }
+                                    }
+                                    b"pressTriggerThreshold" => {
+                                        if let Ok(s) = String::from_utf8(attr.value.to_vec()) {
+                                            press_trigger_threshold = parse_press_threshold_ms(&s);
+                                        }
                                     }
+                                    b"activationMode" => activation_mode_attr = attr_text(&attr),
                                     _ => {}
                                 }
                             }
@@ -409,6 +925,8 @@ impl ActionMaps {
                                 action.rebinds.push(Rebind {
                                     input,
                                     multi_tap,
+                                    multi_tap_block,
+                                    press_trigger_threshold,
                                     activation_mode: activation_mode_attr,
                                 });
                             }
@@ -442,6 +960,10 @@ impl ActionMaps {
 
         Ok(ActionMaps {
             profile_name,
+            description,
+            image,
+            options_version,
+            rebind_version,
             action_maps,
             categories,
             devices,
@@ -449,23 +971,35 @@ impl ActionMaps {
     }
 
     /// Serialize ActionMaps to XML format matching Star Citizen's keybinding format
-    /// Only exports actions that have actual rebinds (customizations)
-    pub fn to_xml(&self) -> String {
+    /// Only exports actions with at least one real input (`has_bound_rebind`), so an action
+    /// whose rebinds are all cleared placeholders ("js1_ ") is dropped and SC's default stays.
+    /// `to_xml_with_categories`, which the app exports with, keeps those overrides; see
+    /// `find_export_discrepancies`
+    /// `generated_comment` adds the tool/version/date comment; off gives deterministic output
+    pub fn to_xml(&self, generated_comment: bool) -> String {
         let mut xml = String::new();
 
         // XML declaration (no BOM, UTF-8)
         xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        if generated_comment {
+            xml.push_str(&generated_by_comment());
+        }
 
         // Root ActionMaps element
         xml.push_str(&format!(
-            "<ActionMaps version=\"1\" optionsVersion=\"2\" rebindVersion=\"2\" profileName=\"{}\">\n",
-            self.profile_name
+            "<ActionMaps version=\"1\" optionsVersion=\"{}\" rebindVersion=\"{}\" profileName=\"{}\">\n",
+            EXPECTED_OPTIONS_VERSION,
+            EXPECTED_REBIND_VERSION,
+            xml_escape(&self.profile_name)
         ));
 
         // Write CustomisationUIHeader
-        xml.push_str(" <CustomisationUIHeader label=\"");
-        xml.push_str(&self.profile_name);
-        xml.push_str("\" description=\"\" image=\"\">\n");
+        xml.push_str(&format!(
+            " <CustomisationUIHeader label=\"{}\" description=\"{}\" image=\"{}\">\n",
+            xml_escape(&self.profile_name),
+            xml_escape(&self.description),
+            xml_escape(&self.image)
+        ));
 
         // Check if we have keyboard or mouse customizations
         let has_keyboard = self.has_keyboard_bindings();
@@ -480,7 +1014,7 @@ impl ActionMaps {
             xml.push_str("   <mouse instance=\"1\"/>\n");
         }
         // Write joystick instances
-        for i in 1..=self.devices.joysticks.len().max(2) {
+        for i in 1..=self.devices.joystick_instance_count().max(2) {
             xml.push_str(&format!("   <joystick instance=\"{}\"/>\n", i));
         }
         xml.push_str("  </devices>\n");
@@ -490,7 +1024,7 @@ impl ActionMaps {
             xml.push_str("  <categories>\n");
             for category in &self.categories {
                 xml.push_str("   <category label=\"");
-                xml.push_str(&category.label);
+                xml.push_str(&xml_escape(&category.label));
                 xml.push_str("\"/>\n");
             }
             xml.push_str("  </categories>\n");
@@ -546,40 +1080,42 @@ impl ActionMaps {
             let actions_with_rebinds: Vec<&Action> = action_map
                 .actions
                 .iter()
-                .filter(|action| {
-                    !action.rebinds.is_empty()
-                        && action.rebinds.iter().any(|r| {
-                            let trimmed = r.input.trim();
-                            // Skip if empty, just underscore+space, or ends with underscore
-                            !trimmed.is_empty()
-                                && trimmed != " "
-                                && !trimmed.ends_with("_ ")
-                                && !trimmed.ends_with("_")
-                        })
-                })
+                .filter(|action| action.has_bound_rebind())
                 .collect();
 
             // Only write action map if it has actions with rebinds
             if !actions_with_rebinds.is_empty() {
                 xml.push_str(" <actionmap name=\"");
-                xml.push_str(&action_map.name);
+                xml.push_str(&xml_escape(&action_map.name));
                 xml.push_str("\">\n");
 
                 // Write actions
                 for action in actions_with_rebinds {
                     xml.push_str("  <action name=\"");
-                    xml.push_str(&action.name);
+                    xml.push_str(&xml_escape(&action.name));
                     xml.push_str("\">\n");
 
                     // Write rebinds
                     for rebind in &action.rebinds {
                         xml.push_str("   <rebind input=\"");
-                        xml.push_str(&rebind.input);
+                        xml.push_str(&xml_escape(&rebind.input));
                         xml.push_str("\"");
                         // Add multiTap attribute if present
                         if let Some(tap_count) = rebind.multi_tap {
                             xml.push_str(&format!(" multiTap=\"{}\"", tap_count));
                         }
+                        if let Some(block) = rebind.multi_tap_block {
+                            xml.push_str(&format!(
+                                " multiTapBlock=\"{}\"",
+                                if block { 1 } else { 0 }
+                            ));
+                        }
+                        if let Some(threshold_ms) = rebind.press_trigger_threshold {
+                            xml.push_str(&format!(
+                                " pressTriggerThreshold=\"{}\"",
+                                format_press_threshold(threshold_ms)
+                            ));
+                        }
                         xml.push_str("/>\n");
                     }
 
@@ -596,20 +1132,174 @@ impl ActionMaps {
         xml
     }
 
+    /// Like `to_xml_with_categories`, but only the listed action maps are written
+    /// The device header is built from the included maps alone
+    pub fn to_xml_with_categories_filtered(
+        &self,
+        all_binds: Option<&AllBinds>,
+        action_map_names: &[String],
+        generated_comment: bool,
+    ) -> String {
+        let filtered = ActionMaps {
+            profile_name: self.profile_name.clone(),
+            description: self.description.clone(),
+            image: self.image.clone(),
+            options_version: self.options_version,
+            rebind_version: self.rebind_version,
+            action_maps: self
+                .action_maps
+                .iter()
+                .filter(|am| action_map_names.contains(&am.name))
+                .cloned()
+                .collect(),
+            categories: self.categories.clone(),
+            devices: self.devices.clone(),
+        };
+
+        filtered.to_xml_with_categories(all_binds, generated_comment)
+    }
+
+    /// A standalone profile holding just one action's rebinds, for testing a binding in
+    /// isolation. Built with `to_xml_with_categories_filtered`, so the device header matches
+    pub fn to_xml_single_action(
+        &self,
+        all_binds: Option<&AllBinds>,
+        action_map_name: &str,
+        action_name: &str,
+        generated_comment: bool,
+    ) -> Result<String, String> {
+        let action = self
+            .action_maps
+            .iter()
+            .find(|am| am.name == action_map_name)
+            .and_then(|am| am.actions.iter().find(|a| a.name == action_name))
+            .ok_or_else(|| format!("Action not found: {}/{}", action_map_name, action_name))?;
+        if action.rebinds.is_empty() {
+            return Err(format!(
+                "{}/{} has no bindings to extract",
+                action_map_name, action_name
+            ));
+        }
+
+        let mut single = self.clone();
+        single.action_maps = vec![ActionMap {
+            name: action_map_name.to_string(),
+            actions: vec![action.clone()],
+        }];
+
+        Ok(single.to_xml_with_categories_filtered(
+            all_binds,
+            &[action_map_name.to_string()],
+            generated_comment,
+        ))
+    }
+
+    /// Like `to_xml_with_categories`, but only rebinds on one device instance are written
+    /// (cleared placeholders included); the joystick options keep only that instance
+    pub fn to_xml_with_categories_for_device(
+        &self,
+        all_binds: Option<&AllBinds>,
+        device: &DeviceInstance,
+        generated_comment: bool,
+    ) -> String {
+        let mut filtered = self.clone();
+
+        for action_map in &mut filtered.action_maps {
+            for action in &mut action_map.actions {
+                action
+                    .rebinds
+                    .retain(|r| DeviceInstance::of_input(&r.input).as_ref() == Some(device));
+            }
+            action_map.actions.retain(|a| !a.rebinds.is_empty());
+        }
+        filtered.action_maps.retain(|am| !am.actions.is_empty());
+
+        let joystick_instance = match device.input_type {
+            InputType::Joystick => Some(device.instance),
+            _ => None,
+        };
+        for (idx, product) in filtered.devices.joysticks.iter_mut().enumerate() {
+            if joystick_instance != Some(idx as u32 + 1) {
+                product.clear();
+            }
+        }
+        filtered
+            .devices
+            .declared_joystick_instances
+            .retain(|i| Some(*i) == joystick_instance);
+
+        filtered.to_xml_with_categories(all_binds, generated_comment)
+    }
+
+    /// Device instances this profile has rebinds on, keyboard first, then mouse, joysticks
+    /// and gamepads in instance order
+    pub fn device_instances(&self) -> Vec<DeviceInstance> {
+        let mut devices: Vec<DeviceInstance> = Vec::new();
+        for rebind in self
+            .action_maps
+            .iter()
+            .flat_map(|am| &am.actions)
+            .flat_map(|a| &a.rebinds)
+        {
+            if let Some(device) = DeviceInstance::of_input(&rebind.input) {
+                if !devices.contains(&device) {
+                    devices.push(device);
+                }
+            }
+        }
+
+        let type_order = |input_type: &InputType| match input_type {
+            InputType::Keyboard => 0,
+            InputType::Mouse => 1,
+            InputType::Joystick => 2,
+            InputType::Gamepad => 3,
+            InputType::Unknown => 4,
+        };
+        devices.sort_by_key(|d| (type_order(&d.input_type), d.instance));
+        devices
+    }
+
+    /// Names of this profile's action maps whose AllBinds UICategory is `category`
+    /// Action maps AllBinds doesn't know count as uncategorized, so they only match ""
+    pub fn action_maps_in_category(&self, all_binds: &AllBinds, category: &str) -> Vec<String> {
+        self.action_maps
+            .iter()
+            .filter(|am| {
+                let ui_category = all_binds
+                    .action_maps
+                    .iter()
+                    .find(|ab| ab.name == am.name)
+                    .map_or("", |ab| ab.ui_category.as_str());
+                ui_category == category
+            })
+            .map(|am| am.name.clone())
+            .collect()
+    }
+
     /// Enhanced export that determines categories from actionmaps with custom bindings
     /// and preserves the order from AllBinds.xml
-    pub fn to_xml_with_categories(&self, all_binds: Option<&AllBinds>) -> String {
+    /// `generated_comment` adds the tool/version/date comment; off gives deterministic output
+    pub fn to_xml_with_categories(
+        &self,
+        all_binds: Option<&AllBinds>,
+        generated_comment: bool,
+    ) -> String {
         use std::collections::{HashMap, HashSet};
 
         let mut xml = String::new();
 
         // XML declaration
         xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        if generated_comment {
+            xml.push_str(&generated_by_comment());
+        }
 
         // Root ActionMaps element
         xml.push_str(&format!(
-            "<ActionMaps version=\"1\" optionsVersion=\"2\" rebindVersion=\"2\" profileName=\"{}\">\n",
-            self.profile_name
+            "<ActionMaps version=\"1\" optionsVersion=\"{}\" rebindVersion=\"{}\" profileName=\"{}\">\n",
+            EXPECTED_OPTIONS_VERSION,
+            EXPECTED_REBIND_VERSION,
+            xml_escape(&self.profile_name)
         ));
 
         // Build a map of actionmap names to their categories and order
@@ -657,9 +1347,12 @@ impl ActionMaps {
         }
 
         // Write CustomisationUIHeader
-        xml.push_str(" <CustomisationUIHeader label=\"");
-        xml.push_str(&self.profile_name);
-        xml.push_str("\" description=\"\" image=\"\">\n");
+        xml.push_str(&format!(
+            " <CustomisationUIHeader label=\"{}\" description=\"{}\" image=\"{}\">\n",
+            xml_escape(&self.profile_name),
+            xml_escape(&self.description),
+            xml_escape(&self.image)
+        ));
 
         // Check if we have keyboard or mouse customizations
         let has_keyboard = self.has_keyboard_bindings();
@@ -674,7 +1367,7 @@ impl ActionMaps {
             xml.push_str("   <mouse instance=\"1\"/>\n");
         }
         // Write joystick instances
-        for i in 1..=self.devices.joysticks.len().max(2) {
+        for i in 1..=self.devices.joystick_instance_count().max(2) {
             xml.push_str(&format!("   <joystick instance=\"{}\"/>\n", i));
         }
         xml.push_str("  </devices>\n");
@@ -723,15 +1416,32 @@ impl ActionMaps {
         //     }
         // }
 
-        // Joystick options last
-        // if !self.devices.joysticks.is_empty() {
-        //     for (idx, joystick) in self.devices.joysticks.iter().enumerate() {
-        //         let instance = idx + 1;
-        //         xml.push_str(&format!(" <options type=\"joystick\" instance=\"{}\" Product=\"", instance));
-        //         xml.push_str(joystick);
-        //         xml.push_str("\"/>\n");
-        //     }
-        // }
+        // Joystick options last, in instance order. Instances declared without a Product
+        // are written bare; unassigned instances are skipped
+        for instance in 1..=self.devices.joystick_instance_count() {
+            let joystick = self
+                .devices
+                .joysticks
+                .get(instance - 1)
+                .map_or("", |j| j.as_str());
+            if !joystick.is_empty() {
+                xml.push_str(&format!(
+                    " <options type=\"joystick\" instance=\"{}\" Product=\"",
+                    instance
+                ));
+                xml.push_str(&xml_escape(joystick));
+                xml.push_str("\"/>\n");
+            } else if self
+                .devices
+                .declared_joystick_instances
+                .contains(&(instance as u32))
+            {
+                xml.push_str(&format!(
+                    " <options type=\"joystick\" instance=\"{}\"/>\n",
+                    instance
+                ));
+            }
+        }
 
         xml.push_str(" <modifiers />\n");
 
@@ -765,27 +1475,42 @@ impl ActionMaps {
 
                 if !actions_with_rebinds.is_empty() {
                     xml.push_str(" <actionmap name=\"");
-                    xml.push_str(&action_map.name);
+                    xml.push_str(&xml_escape(&action_map.name));
                     xml.push_str("\">\n");
 
                     for action in actions_with_rebinds {
                         xml.push_str("  <action name=\"");
-                        xml.push_str(&action.name);
+                        xml.push_str(&xml_escape(&action.name));
                         xml.push_str("\">\n");
 
-                        for rebind in &action.rebinds {
+                        // SC treats rebind order as priority: real bindings go before cleared ones
+                        let (bound, cleared): (Vec<&Rebind>, Vec<&Rebind>) =
+                            action.rebinds.iter().partition(|r| !r.is_cleared());
+                        for rebind in bound.into_iter().chain(cleared) {
                             xml.push_str("   <rebind input=\"");
-                            xml.push_str(&rebind.input);
+                            xml.push_str(&xml_escape(&rebind.input));
                             xml.push_str("\"");
                             // Add multiTap attribute if present
                             if let Some(tap_count) = rebind.multi_tap {
                                 xml.push_str(&format!(" multiTap=\"{}\"", tap_count));
                             }
+                            if let Some(block) = rebind.multi_tap_block {
+                                xml.push_str(&format!(
+                                    " multiTapBlock=\"{}\"",
+                                    if block { 1 } else { 0 }
+                                ));
+                            }
+                            if let Some(threshold_ms) = rebind.press_trigger_threshold {
+                                xml.push_str(&format!(
+                                    " pressTriggerThreshold=\"{}\"",
+                                    format_press_threshold(threshold_ms)
+                                ));
+                            }
                             // Add activationMode attribute if present
                             if !rebind.activation_mode.is_empty() {
                                 xml.push_str(&format!(
                                     " activationMode=\"{}\"",
-                                    rebind.activation_mode
+                                    xml_escape(&rebind.activation_mode)
                                 ));
                             }
                             xml.push_str("/>\n");
@@ -806,6 +1531,15 @@ impl ActionMaps {
 
     /// Organize keybindings for easier UI display
     pub fn organize(&self) -> OrganizedKeybindings {
+        self.organize_with(None)
+    }
+
+    /// Organize keybindings, flagging each binding that differs from the SC default
+    pub fn organize_with_defaults(&self, all_binds: &AllBinds) -> OrganizedKeybindings {
+        self.organize_with(Some(all_binds))
+    }
+
+    fn organize_with(&self, all_binds: Option<&AllBinds>) -> OrganizedKeybindings {
         // Get categories
         let categories: Vec<String> = self
             .categories
@@ -834,6 +1568,13 @@ impl ActionMaps {
                                     display_name: rebind.get_display_name(),
                                     activation_mode: rebind.activation_mode.clone(),
                                     multi_tap: rebind.multi_tap,
+                                    differs_from_default: all_binds.and_then(|ab| {
+                                        ab.rebind_differs_from_default(
+                                            &action_map.name,
+                                            &action.name,
+                                            rebind,
+                                        )
+                                    }),
                                 }
                             })
                             .collect();
@@ -856,436 +1597,1755 @@ impl ActionMaps {
 
         OrganizedKeybindings {
             profile_name: self.profile_name.clone(),
+            profile_name_source: if self.profile_name.trim().is_empty() {
+                ProfileNameSource::Empty
+            } else {
+                ProfileNameSource::FromFile
+            },
             categories,
             devices: self.devices.clone(),
             action_maps: organized_maps,
         }
     }
 
-    /// Create a new empty action map with the given name and actions
-    pub fn new_empty_action_map(name: String, actions: Vec<Action>) -> ActionMap {
-        ActionMap { name, actions }
+    /// Rewrite keyboard inputs so they land on the same physical keys in another layout
+    /// Returns the number of rebinds that were changed
+    pub fn remap_keyboard_layout(&mut self, from: KeyboardLayout, to: KeyboardLayout) -> usize {
+        let mut changed = 0;
+
+        for action_map in &mut self.action_maps {
+            for action in &mut action_map.actions {
+                for rebind in &mut action.rebinds {
+                    if rebind.get_input_type() != InputType::Keyboard {
+                        continue;
+                    }
+
+                    let remapped = remap_keyboard_input(&rebind.input, from, to);
+                    if remapped != rebind.input {
+                        rebind.input = remapped;
+                        changed += 1;
+                    }
+                }
+            }
+        }
+
+        changed
     }
 
-    /// Convert action_map name to display name
-    fn format_action_map_name(name: &str) -> String {
-        format_display_name(name)
+    /// Move bindings from one device type to the other ("gp" <-> "js") through
+    /// `GAMEPAD_JOYSTICK_CONTROLS`, e.g. "gp1_a" -> "js1_button1", "gp1_thumblx" -> "js1_x"
+    /// `instance_map` renumbers instances on the way (1 -> 2 turns "gp1_" into "js2_");
+    /// unmapped instances keep their number. Rebinds with a control the other device type
+    /// doesn't have, or whose action already has a rebind on the target instance, are left
+    /// as they are and reported
+    pub fn convert_device_type(
+        &mut self,
+        from_prefix: &str,
+        to_prefix: &str,
+        instance_map: &HashMap<u32, u32>,
+    ) -> Result<DeviceConversion, String> {
+        const CONVERTIBLE: [&str; 2] = ["gp", "js"];
+        for prefix in [from_prefix, to_prefix] {
+            if !CONVERTIBLE.contains(&prefix) {
+                return Err(format!(
+                    "Cannot convert '{}' bindings, expected one of: {}",
+                    prefix,
+                    CONVERTIBLE.join(", ")
+                ));
+            }
+        }
+        if from_prefix == to_prefix {
+            return Err(format!("Bindings are already on '{}'", to_prefix));
+        }
+
+        let mut conversion = DeviceConversion::default();
+        for action_map in &mut self.action_maps {
+            for action in &mut action_map.actions {
+                // Instances bound by the rebinds that stay where they are
+                let mut taken: Vec<String> = action
+                    .rebinds
+                    .iter()
+                    .filter(|r| {
+                        input_device_part(&r.input)
+                            .is_none_or(|device| device.strip_prefix(from_prefix).is_none())
+                    })
+                    .filter_map(|r| input_device_instance(&r.input))
+                    .collect();
+
+                for rebind in &mut action.rebinds {
+                    let skipped = || {
+                        (
+                            action_map.name.clone(),
+                            action.name.clone(),
+                            rebind.input.clone(),
+                        )
+                    };
+                    let converted = match convert_input_device_type(
+                        &rebind.input,
+                        from_prefix,
+                        to_prefix,
+                        instance_map,
+                    ) {
+                        None => continue,
+                        Some(None) => {
+                            conversion.unmappable.push(skipped());
+                            continue;
+                        }
+                        Some(Some(converted)) => converted,
+                    };
+
+                    let instance = input_device_instance(&converted).unwrap_or_default();
+                    if taken.contains(&instance) {
+                        conversion.collisions.push(skipped());
+                        continue;
+                    }
+                    taken.push(instance);
+                    rebind.input = converted;
+                    conversion.converted += 1;
+                }
+            }
+        }
+
+        Ok(conversion)
     }
 
-    /// Convert action name to display name
-    fn format_action_name(name: &str) -> String {
-        format_display_name(name)
+    /// Move joystick bindings and options between instances in one step: `assignment` maps
+    /// old instance -> new instance and may be any permutation (a 3-cycle 1 -> 2 -> 3 -> 1
+    /// included). Unlisted instances stay put; two instances ending up on the same number
+    /// is an error and changes nothing. Returns the number of rebinds changed
+    pub fn apply_joystick_assignment(
+        &mut self,
+        assignment: &HashMap<u32, u32>,
+    ) -> Result<usize, String> {
+        if assignment.contains_key(&0) || assignment.values().any(|to| *to == 0) {
+            return Err("Joystick instances start at 1".to_string());
+        }
+
+        // Every instance in use, from bindings, options and the assignment itself
+        let mut instances: Vec<u32> = (1..=self.devices.joystick_instance_count() as u32).collect();
+        for rebind in self
+            .action_maps
+            .iter()
+            .flat_map(|am| &am.actions)
+            .flat_map(|a| &a.rebinds)
+        {
+            if let Some(device) = DeviceInstance::of_input(&rebind.input) {
+                if device.input_type == InputType::Joystick {
+                    instances.push(device.instance);
+                }
+            }
+        }
+        instances.extend(assignment.keys());
+        instances.sort_unstable();
+        instances.dedup();
+
+        let target = |instance: u32| assignment.get(&instance).copied().unwrap_or(instance);
+        let mut taken: HashMap<u32, u32> = HashMap::new();
+        for instance in instances {
+            if let Some(other) = taken.insert(target(instance), instance) {
+                return Err(format!(
+                    "Joystick instances {} and {} would both become js{}",
+                    other,
+                    instance,
+                    target(instance)
+                ));
+            }
+        }
+
+        // Products move with their instance; gaps left behind stay unassigned
+        let mut joysticks: Vec<String> = Vec::new();
+        for (idx, product) in self.devices.joysticks.iter().enumerate() {
+            if product.is_empty() {
+                continue;
+            }
+            let slot = target(idx as u32 + 1) as usize;
+            if joysticks.len() < slot {
+                joysticks.resize(slot, String::new());
+            }
+            joysticks[slot - 1] = product.clone();
+        }
+        self.devices.joysticks = joysticks;
+        for instance in &mut self.devices.declared_joystick_instances {
+            *instance = target(*instance);
+        }
+        self.devices.declared_joystick_instances.sort_unstable();
+
+        Ok(self.remap_device_instances("js", "js", assignment))
     }
-}
 
-/// Comprehensive display name formatter for action names
-/// Handles various naming patterns:
-/// - v_action_name -> Action Name
-/// - pc_action_name -> Action Name  
-/// - PascalCase -> Pascal Case
-/// - camelCase -> Camel Case
-/// - snake_case -> Snake Case
-/// - turret_gyromode -> Turret Gyro Mode (splits compounds)
-pub fn format_display_name(name: &str) -> String {
-    if name.is_empty() {
-        return String::new();
+    /// Rewrite rebinds on `from_prefix` devices onto `to_prefix`, renumbering instances
+    /// through `instance_map` (unmapped instances keep their number). Every input is mapped
+    /// from its original instance, so swaps and longer cycles can't collide mid-way
+    fn remap_device_instances(
+        &mut self,
+        from_prefix: &str,
+        to_prefix: &str,
+        instance_map: &HashMap<u32, u32>,
+    ) -> usize {
+        let mut changed = 0;
+
+        for rebind in self
+            .action_maps
+            .iter_mut()
+            .flat_map(|am| am.actions.iter_mut())
+            .flat_map(|a| a.rebinds.iter_mut())
+        {
+            let Some(device) = input_device_part(&rebind.input) else {
+                continue;
+            };
+            let Some(number) = device.strip_prefix(from_prefix) else {
+                continue;
+            };
+            // "gp_" has no instance number and means instance 1
+            let Ok(instance) = (if number.is_empty() { "1" } else { number }).parse::<u32>() else {
+                continue;
+            };
+
+            let target = instance_map.get(&instance).copied().unwrap_or(instance);
+            let converted =
+                replace_device_instance(&rebind.input, device, &format!("{}{}", to_prefix, target));
+            if converted != rebind.input {
+                rebind.input = converted;
+                changed += 1;
+            }
+        }
+
+        changed
     }
 
-    // Remove common prefixes
-    let cleaned = name
-        .trim_start_matches("v_")
-        .trim_start_matches("pc_")
-        .trim_start_matches("ui_")
-        .trim_start_matches("spectate_");
+    /// Find actions with more real (non-cleared) bindings than SC keeps per action
+    /// The bindings past the limit are reported as excess, in file order
+    pub fn find_overbound_actions(&self, max_per_action: usize) -> Vec<OverboundAction> {
+        let mut overbound = Vec::new();
 
-    // Handle special abbreviations and technical terms
-    let result = cleaned
-        .replace("mfd", "MFD")
-        .replace("fps", "FPS")
-        .replace("esp", "ESP")
-        .replace("vjoy", "VJoy")
-        .replace("mgv", "MGV")
-        .replace("hud", "HUD")
-        .replace("ifcs", "IFCS")
-        .replace("vtol", "VTOL")
-        .replace("ptu", "PTU")
-        .replace("atc", "ATC");
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                let bound: Vec<&Rebind> =
+                    action.rebinds.iter().filter(|r| !r.is_cleared()).collect();
+
+                if bound.len() > max_per_action {
+                    overbound.push(OverboundAction {
+                        action_map_name: action_map.name.clone(),
+                        action_name: action.name.clone(),
+                        binding_count: bound.len(),
+                        excess_inputs: bound[max_per_action..]
+                            .iter()
+                            .map(|r| r.input.clone())
+                            .collect(),
+                    });
+                }
+            }
+        }
 
-    // Split on underscores first
-    let parts: Vec<&str> = result.split('_').collect();
+        overbound
+    }
 
-    let formatted_parts: Vec<String> = parts
-        .iter()
-        .map(|part| {
-            if part.is_empty() {
-                return String::new();
+    /// Find actions the user has explicitly unbound: all their rebinds are cleared placeholders
+    /// Labels come from AllBinds when available (see `AllBinds::resolve_label`), otherwise
+    /// from the formatted names
+    pub fn find_fully_unbound_actions(&self, all_binds: Option<&AllBinds>) -> Vec<UnboundAction> {
+        let label = |ui_label: Option<&String>, name: &str| match (all_binds, ui_label) {
+            (Some(all_binds), Some(ui_label)) => all_binds.resolve_label(ui_label, name),
+            _ => format_display_name(name),
+        };
+
+        let mut unbound = Vec::new();
+
+        for action_map in &self.action_maps {
+            let all_binds_map = all_binds
+                .and_then(|ab| ab.action_maps.iter().find(|am| am.name == action_map.name));
+
+            for action in action_map.actions.iter().filter(|a| a.is_fully_cleared()) {
+                let all_binds_action =
+                    all_binds_map.and_then(|am| am.actions.iter().find(|a| a.name == action.name));
+
+                unbound.push(UnboundAction {
+                    action_map_name: action_map.name.clone(),
+                    action_map_label: label(all_binds_map.map(|am| &am.ui_label), &action_map.name),
+                    action_name: action.name.clone(),
+                    action_label: label(all_binds_action.map(|a| &a.ui_label), &action.name),
+                    cleared_inputs: action.rebinds.iter().map(|r| r.input.clone()).collect(),
+                });
             }
+        }
 
-            // Check if it's all caps (abbreviation) - preserve it
-            if part.chars().all(|c| c.is_uppercase() || c.is_numeric()) {
-                return part.to_string();
+        unbound
+    }
+
+    /// Rebinds `to_xml_with_categories` writes but `to_xml` drops, as (action map, action, input)
+    /// These are actions whose rebinds are all cleared overrides: the categories export keeps
+    /// them so SC's defaults stay unbound, the plain export leaves the defaults in place.
+    /// Nothing goes the other way, since both exports skip actions without rebinds
+    pub fn find_export_discrepancies(&self) -> Vec<(String, String, String)> {
+        let mut found = Vec::new();
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                if action.has_bound_rebind() {
+                    continue;
+                }
+                for rebind in &action.rebinds {
+                    found.push((
+                        action_map.name.clone(),
+                        action.name.clone(),
+                        rebind.input.clone(),
+                    ));
+                }
             }
+        }
+        found
+    }
 
-            // Check if it's a mix of uppercase and lowercase (PascalCase or camelCase)
-            if part.chars().any(|c| c.is_uppercase()) && part.chars().any(|c| c.is_lowercase()) {
-                split_camel_case(part)
-            } else {
-                // Regular word - capitalize first letter
-                capitalize_word(part)
+    /// Keyboard rebinds using a key from `is_layout_sensitive_key`, as (action map, action, input)
+    /// These may land on a different physical key when the profile is used with another layout
+    pub fn find_layout_sensitive_bindings(&self) -> Vec<(String, String, String)> {
+        let mut found = Vec::new();
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for rebind in &action.rebinds {
+                    if rebind.get_input_type() != InputType::Keyboard {
+                        continue;
+                    }
+
+                    let sensitive = rebind.input.split('+').any(|part| {
+                        let key = part.split_once('_').map_or(part, |(_, key)| key);
+                        is_layout_sensitive_key(key)
+                    });
+                    if sensitive {
+                        found.push((
+                            action_map.name.clone(),
+                            action.name.clone(),
+                            rebind.input.clone(),
+                        ));
+                    }
+                }
             }
-        })
-        .collect();
+        }
+        found
+    }
 
-    formatted_parts.join(" ").trim().to_string()
-}
+    /// Rebinds made of modifier keys alone, as (action map, action, input)
+    pub fn find_modifier_only_bindings(&self) -> Vec<(String, String, String)> {
+        let mut found = Vec::new();
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for rebind in action.rebinds.iter().filter(|r| r.is_modifier_only()) {
+                    found.push((
+                        action_map.name.clone(),
+                        action.name.clone(),
+                        rebind.input.clone(),
+                    ));
+                }
+            }
+        }
+        found
+    }
 
-/// Split PascalCase or camelCase into separate words
-fn split_camel_case(s: &str) -> String {
-    let mut result = String::new();
-    let mut prev_was_lower = false;
-    let mut prev_was_upper = false;
+    /// Inputs bound more than once within an action under different activation modes, with
+    /// those modes in binding order, e.g. js1_button3 as "press" and "hold"
+    /// Modifier order and a missing instance number don't make an input distinct; an empty
+    /// mode (the action's default) counts as a mode of its own
+    pub fn find_same_input_different_mode(
+        &self,
+        action_map_name: &str,
+        action_name: &str,
+    ) -> Result<Vec<(String, Vec<String>)>, String> {
+        let action = self
+            .action_maps
+            .iter()
+            .find(|am| am.name == action_map_name)
+            .and_then(|am| am.actions.iter().find(|a| a.name == action_name))
+            .ok_or_else(|| format!("Action not found: {}/{}", action_map_name, action_name))?;
+
+        // (normalized input, input as written, modes)
+        let mut groups: Vec<(String, String, Vec<String>)> = Vec::new();
+        for rebind in action.rebinds.iter().filter(|r| !r.input.trim().is_empty()) {
+            let key = normalize_input(&rebind.input);
+            let mode = rebind.activation_mode.clone();
+            match groups.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, _, modes)) => {
+                    if !modes.contains(&mode) {
+                        modes.push(mode);
+                    }
+                }
+                None => groups.push((key, rebind.input.trim().to_string(), vec![mode])),
+            }
+        }
 
-    for (i, c) in s.chars().enumerate() {
-        if i == 0 {
-            result.push(c.to_ascii_uppercase());
-            prev_was_upper = c.is_uppercase();
-            prev_was_lower = c.is_lowercase();
-        } else if c.is_uppercase() {
-            // Add space before uppercase if previous was lowercase
-            // or if this is start of a new word (e.g., "HTMLParser" -> "HTML Parser")
-            if prev_was_lower {
-                result.push(' ');
-            } else if prev_was_upper && i + 1 < s.len() {
-                // Check if next char is lowercase (indicates start of new word)
-                if let Some(next_c) = s.chars().nth(i + 1) {
-                    if next_c.is_lowercase() {
-                        result.push(' ');
+        Ok(groups
+            .into_iter()
+            .filter(|(_, _, modes)| modes.len() > 1)
+            .map(|(_, input, modes)| (input, modes))
+            .collect())
+    }
+
+    /// Rebinds on a joystick or gamepad instance above `max_instances`, as (action map, action, input)
+    /// SC never hands out instances that high, so e.g. "js9_button1" points to a broken import
+    pub fn find_implausible_instances(&self, max_instances: u32) -> Vec<(String, String, String)> {
+        let mut found = Vec::new();
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for rebind in &action.rebinds {
+                    let implausible = DeviceInstance::of_input(&rebind.input).is_some_and(|d| {
+                        matches!(d.input_type, InputType::Joystick | InputType::Gamepad)
+                            && d.instance > max_instances
+                    });
+                    if implausible {
+                        found.push((
+                            action_map.name.clone(),
+                            action.name.clone(),
+                            rebind.input.clone(),
+                        ));
                     }
                 }
             }
-            result.push(c);
-            prev_was_upper = true;
-            prev_was_lower = false;
-        } else {
-            result.push(c);
-            prev_was_upper = false;
-            prev_was_lower = true;
         }
+        found
     }
 
-    result
-}
+    /// Action maps and actions that would export nothing: actions without any rebind, and
+    /// action maps whose actions are all like that. Cleared placeholders count as rebinds
+    pub fn find_empty_containers(&self) -> EmptyContainers {
+        let mut empty = EmptyContainers {
+            action_maps: Vec::new(),
+            actions: Vec::new(),
+        };
 
-/// Capitalize the first letter of a word
-fn capitalize_word(word: &str) -> String {
-    let mut chars = word.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => {
-            let rest: String = chars.collect();
-            // Handle special cases
-            match word.to_lowercase().as_str() {
-                "up" | "down" | "left" | "right" => word.to_uppercase(),
-                "x" | "y" | "z" => word.to_uppercase(),
-                "1to1" => "1:1".to_string(),
-                _ => first.to_uppercase().collect::<String>() + &rest.to_lowercase(),
+        for action_map in &self.action_maps {
+            for action in action_map.actions.iter().filter(|a| a.rebinds.is_empty()) {
+                empty
+                    .actions
+                    .push((action_map.name.clone(), action.name.clone()));
+            }
+            if action_map.actions.iter().all(|a| a.rebinds.is_empty()) {
+                empty.action_maps.push(action_map.name.clone());
             }
         }
+
+        empty
     }
-}
 
-impl AllBinds {
-    /// Parse AllBinds.xml file into AllBinds structure
-    pub fn from_xml(xml: &str) -> Result<Self, String> {
-        let mut action_maps = Vec::new();
-        let mut reader = quick_xml::Reader::from_str(xml);
-        let mut buf = vec![];
+    /// Remove what `find_empty_containers` reports, returning how many action maps and
+    /// actions were removed. Actions with only cleared placeholders are kept, they override
+    /// SC's defaults
+    pub fn prune_empty_containers(&mut self) -> usize {
+        let mut removed = 0;
 
-        let mut current_action_map: Option<AllBindsActionMap> = None;
+        for action_map in &mut self.action_maps {
+            let before = action_map.actions.len();
+            action_map.actions.retain(|a| !a.rebinds.is_empty());
+            removed += before - action_map.actions.len();
+        }
 
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(quick_xml::events::Event::Start(ref e))
-                | Ok(quick_xml::events::Event::Empty(ref e)) => {
-                    match e.name().as_ref() {
-                        b"actionmap" => {
-                            let mut name = String::new();
-                            let mut version = String::new();
-                            let mut ui_label = String::new();
-                            let mut ui_category = String::new();
+        let before = self.action_maps.len();
+        self.action_maps.retain(|am| !am.actions.is_empty());
+        removed + before - self.action_maps.len()
+    }
 
-                            for attr in e.attributes().flatten() {
-                                match attr.key.as_ref() {
-                                    b"name" => {
-                                        name = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
-                                    }
-                                    b"version" => {
-                                        version = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
-                                    }
-                                    b"UILabel" => {
-                                        ui_label = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
-                                    }
-                                    b"UICategory" => {
-                                        ui_category = String::from_utf8(attr.value.to_vec())
-                                            .unwrap_or_default()
-                                    }
-                                    _ => {}
-                                }
-                            }
+    /// Action maps without a single real input: every rebind is empty or a cleared
+    /// placeholder, or there are none. With `keep_clears`, maps holding a cleared placeholder
+    /// aren't listed, since those clears intentionally unbind SC's defaults
+    pub fn find_noncontributing_action_maps(&self, keep_clears: bool) -> Vec<String> {
+        self.action_maps
+            .iter()
+            .filter(|am| Self::is_noncontributing(am, keep_clears))
+            .map(|am| am.name.clone())
+            .collect()
+    }
 
-                            current_action_map = Some(AllBindsActionMap {
-                                name,
-                                version,
-                                ui_label,
-                                ui_category,
-                                actions: Vec::new(),
-                            });
-                        }
-                        b"action" => {
-                            if let Some(ref mut action_map) = current_action_map {
-                                let mut name = String::new();
-                                let mut ui_label = String::new();
-                                let mut ui_description = String::new();
-                                let mut category = String::new();
-                                let mut activation_mode = String::new();
-                                let mut on_hold = false;
-                                let mut keyboard = String::new();
-                                let mut mouse = String::new();
-                                let mut gamepad = String::new();
-                                let mut joystick = String::new();
+    /// Remove the action maps `find_noncontributing_action_maps` lists, returning their names
+    pub fn remove_noncontributing_action_maps(&mut self, keep_clears: bool) -> Vec<String> {
+        let removed = self.find_noncontributing_action_maps(keep_clears);
+        self.action_maps
+            .retain(|am| !Self::is_noncontributing(am, keep_clears));
+        removed
+    }
 
-                                for attr in e.attributes().flatten() {
-                                    match attr.key.as_ref() {
-                                        b"name" => {
-                                            name = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"UILabel" => {
-                                            ui_label = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"UIDescription" => {
-                                            ui_description = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"Category" => {
-                                            category = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"activationMode" => {
-                                            activation_mode = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"onHold" => {
-                                            let val = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default();
-                                            on_hold = val == "1";
-                                        }
-                                        b"keyboard" => {
-                                            keyboard = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"mouse" => {
-                                            mouse = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"gamepad" => {
-                                            gamepad = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        b"joystick" => {
-                                            joystick = String::from_utf8(attr.value.to_vec())
-                                                .unwrap_or_default()
-                                        }
-                                        _ => {}
-                                    }
-                                }
+    fn is_noncontributing(action_map: &ActionMap, keep_clears: bool) -> bool {
+        if action_map.actions.iter().any(|a| a.has_bound_rebind()) {
+            return false;
+        }
+        let has_clears = action_map
+            .actions
+            .iter()
+            .flat_map(|a| &a.rebinds)
+            .any(|r| r.is_cleared());
+        !(keep_clears && has_clears)
+    }
 
-                                // Debug: log quantum actions
-                                if name.contains("quantum") {
-                                    println!("Parsing action: {} with kb='{}', mouse='{}', gamepad='{}', js='{}'", 
-                                        name, keyboard, mouse, gamepad, joystick);
-                                }
+    /// Find joystick bindings whose button, axis or hat number is past what the device on that
+    /// instance reports. Instances missing from `capabilities` are not checked
+    pub fn find_out_of_range_bindings(
+        &self,
+        capabilities: &HashMap<u8, DeviceCapabilities>,
+    ) -> Vec<OutOfRangeBinding> {
+        let mut out_of_range = Vec::new();
 
-                                action_map.actions.push(AllBindsAction {
-                                    name,
-                                    ui_label,
-                                    ui_description,
-                                    category,
-                                    activation_mode,
-                                    on_hold,
-                                    default_keyboard: keyboard,
-                                    default_mouse: mouse,
-                                    default_gamepad: gamepad,
-                                    default_joystick: joystick,
-                                });
-                            }
-                        }
-                        _ => {}
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for rebind in &action.rebinds {
+                    let Some(caps) = rebind
+                        .joystick_instance()
+                        .and_then(|i| capabilities.get(&i))
+                    else {
+                        continue;
+                    };
+                    let Some((kind, index)) = joystick_input_index(&rebind.input) else {
+                        continue;
+                    };
+
+                    let available = match kind {
+                        "button" => caps.button_count,
+                        "axis" => caps.axis_count,
+                        _ => caps.hat_count,
+                    };
+                    if index > available {
+                        out_of_range.push(OutOfRangeBinding {
+                            action_map_name: action_map.name.clone(),
+                            action_name: action.name.clone(),
+                            input: rebind.input.clone(),
+                            kind: kind.to_string(),
+                            index,
+                            available,
+                        });
                     }
                 }
-                Ok(quick_xml::events::Event::End(ref e)) => {
-                    if e.name().as_ref() == b"actionmap" {
-                        if let Some(action_map) = current_action_map.take() {
-                            action_maps.push(action_map);
-                        }
-                    }
+            }
+        }
+
+        out_of_range
+    }
+
+    /// Rewrite cleared placeholders ("js1_", "js1_  ") to the canonical "js1_ " form
+    /// Returns the number of rebinds changed
+    pub fn normalize_cleared_bindings(&mut self) -> usize {
+        let mut changed = 0;
+
+        for rebind in self
+            .action_maps
+            .iter_mut()
+            .flat_map(|am| am.actions.iter_mut())
+            .flat_map(|a| a.rebinds.iter_mut())
+        {
+            if let Some(canonical) = rebind.canonical_cleared_input() {
+                if rebind.input != canonical {
+                    rebind.input = canonical;
+                    changed += 1;
                 }
-                Ok(quick_xml::events::Event::Eof) => break,
-                Err(e) => {
-                    return Err(format!("XML parsing error: {}", e));
+            }
+        }
+
+        changed
+    }
+
+    /// Find actions whose rebinds contain the same input more than once
+    /// Inputs are compared normalized (case, "kb_" vs "kb1_", modifier order); cleared placeholders are ignored
+    pub fn find_intra_action_duplicates(&self) -> Vec<DuplicateBinding> {
+        let mut duplicates = Vec::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                let mut counts: Vec<(String, usize)> = Vec::new();
+                for rebind in action.rebinds.iter().filter(|r| !r.is_cleared()) {
+                    let input = normalize_input(&rebind.input);
+                    if input.is_empty() {
+                        continue;
+                    }
+                    match counts.iter_mut().find(|(i, _)| *i == input) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((input, 1)),
+                    }
                 }
-                _ => {}
+
+                duplicates.extend(counts.into_iter().filter(|(_, count)| *count > 1).map(
+                    |(input, count)| DuplicateBinding {
+                        action_map_name: action_map.name.clone(),
+                        action_name: action.name.clone(),
+                        input,
+                        count,
+                    },
+                ));
             }
-            buf.clear();
         }
 
-        Ok(AllBinds { action_maps })
+        duplicates
     }
-}
 
-/// Merged view of AllBinds with user customizations
-#[derive(Debug, Serialize, Clone)]
-pub struct MergedBindings {
-    pub action_maps: Vec<MergedActionMap>,
-}
+    /// Remove cleared placeholder rebinds, dropping actions and actionmaps left empty
+    /// When `keep_default_overrides` is set, cleared entries that unbind an AllBinds default
+    /// are kept (without AllBinds every cleared entry is assumed to be an override)
+    /// Returns the number of rebinds removed
+    pub fn purge_cleared_bindings(
+        &mut self,
+        all_binds: Option<&AllBinds>,
+        keep_default_overrides: bool,
+    ) -> usize {
+        let mut removed = 0;
+
+        for action_map in &mut self.action_maps {
+            for action in &mut action_map.actions {
+                let before = action.rebinds.len();
+                action.rebinds.retain(|rebind| {
+                    if !rebind.is_cleared() {
+                        return true;
+                    }
+                    if !keep_default_overrides {
+                        return false;
+                    }
+                    match (all_binds, rebind.cleared_device_type()) {
+                        (Some(all_binds), Some(device_type)) => all_binds.has_default_binding(
+                            &action_map.name,
+                            &action.name,
+                            &device_type,
+                        ),
+                        _ => true,
+                    }
+                });
+                removed += before - action.rebinds.len();
+            }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct MergedActionMap {
-    pub name: String,
-    pub ui_label: String,
-    pub ui_category: String,
-    pub actions: Vec<MergedAction>,
-}
+            action_map
+                .actions
+                .retain(|action| !action.rebinds.is_empty());
+        }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct MergedAction {
-    pub name: String,
-    pub ui_label: String,
-    pub ui_description: String,
-    pub category: String,
-    pub is_customized: bool,
-    pub on_hold: bool,
-    pub bindings: Vec<MergedBinding>,
-}
+        self.action_maps.retain(|am| !am.actions.is_empty());
 
-#[derive(Debug, Serialize, Clone)]
-pub struct MergedBinding {
-    pub input: String,
-    pub display_name: String,
-    pub input_type: String,
-    pub is_default: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub multi_tap: Option<u32>,
-    pub activation_mode: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub original_default: Option<String>, // For cleared bindings, store the original default binding text
-}
+        removed
+    }
 
-impl AllBinds {
-    /// Merge AllBinds with user customizations from ActionMaps
-    pub fn merge_with_user_bindings(&self, user_bindings: Option<&ActionMaps>) -> MergedBindings {
-        // Build a lookup map for user bindings
-        let mut user_actions: HashMap<(String, String), Vec<Rebind>> = HashMap::new();
+    /// Copy every AllBinds default for a device type into the profile as real rebinds on
+    /// instance 1, so they can be edited. Actions that already have a rebind for that device
+    /// type (including a cleared one) keep it. Returns the number of rebinds added
+    pub fn materialize_defaults(&mut self, all_binds: &AllBinds, input_type: &InputType) -> usize {
+        let prefix = match input_type {
+            InputType::Keyboard => "kb1",
+            InputType::Mouse => "mouse1",
+            InputType::Joystick => "js1",
+            InputType::Gamepad => "gp1",
+            InputType::Unknown => return 0,
+        };
 
-        if let Some(user_maps) = user_bindings {
-            for action_map in &user_maps.action_maps {
-                for action in &action_map.actions {
-                    user_actions.insert(
-                        (action_map.name.clone(), action.name.clone()),
-                        action.rebinds.clone(),
-                    );
+        let mut added = 0;
+
+        for all_binds_map in &all_binds.action_maps {
+            for all_binds_action in &all_binds_map.actions {
+                let Some(default) = all_binds_action.default_input(input_type) else {
+                    continue;
+                };
+
+                let already_bound = self
+                    .action_maps
+                    .iter()
+                    .filter(|am| am.name == all_binds_map.name)
+                    .flat_map(|am| am.actions.iter())
+                    .filter(|a| a.name == all_binds_action.name)
+                    .flat_map(|a| a.rebinds.iter())
+                    .any(|r| r.classify_input().input_type == *input_type);
+                if already_bound {
+                    continue;
                 }
+
+                self.set_rebind(
+                    &all_binds_map.name,
+                    &all_binds_action.name,
+                    Rebind {
+                        input: format!("{}_{}", prefix, default),
+                        multi_tap: None,
+                        multi_tap_block: None,
+                        press_trigger_threshold: None,
+                        activation_mode: String::new(),
+                    },
+                );
+                added += 1;
             }
         }
 
-        let merged_maps: Vec<MergedActionMap> = self
-            .action_maps
-            .iter()
-            .map(|all_binds_map| {
-                // Debug: log quantum actionmap
-                if all_binds_map.name.contains("quantum") || all_binds_map.name == "seat_general" {
-                    let quantum_actions: Vec<_> = all_binds_map
-                        .actions
-                        .iter()
-                        .filter(|a| a.name.contains("quantum"))
-                        .map(|a| &a.name)
-                        .collect();
-                    if !quantum_actions.is_empty() {
-                        println!(
-                            "ActionMap '{}' has quantum actions: {:?}",
-                            all_binds_map.name, quantum_actions
-                        );
+        added
+    }
+
+    /// Layer another profile's bindings on top of this one. For each incoming action, its
+    /// rebinds replace ours on the same device instances (js1, kb1, ...); bindings on other
+    /// instances and actions the incoming profile doesn't mention are kept. Keyboard, mouse and
+    /// joystick options fill instances this profile has no Product for, and declared joystick
+    /// instances are combined. Returns the number of rebinds merged
+    pub fn merge_profile(&mut self, incoming: &ActionMaps) -> usize {
+        let mut merged = 0;
+
+        for incoming_map in &incoming.action_maps {
+            for incoming_action in incoming_map
+                .actions
+                .iter()
+                .filter(|a| !a.rebinds.is_empty())
+            {
+                let instances: Vec<Option<String>> = incoming_action
+                    .rebinds
+                    .iter()
+                    .map(|r| input_device_instance(&r.input))
+                    .collect();
+
+                let action_map = match self
+                    .action_maps
+                    .iter()
+                    .position(|am| am.name == incoming_map.name)
+                {
+                    Some(pos) => &mut self.action_maps[pos],
+                    None => {
+                        self.action_maps.push(ActionMap {
+                            name: incoming_map.name.clone(),
+                            actions: Vec::new(),
+                        });
+                        self.action_maps.last_mut().unwrap()
                     }
-                }
+                };
 
-                let merged_actions: Vec<MergedAction> = all_binds_map
+                match action_map
                     .actions
-                    .iter()
-                    .map(|all_binds_action| {
-                        // Check if user has custom bindings for this action
-                        let user_rebinds = user_actions
-                            .get(&(all_binds_map.name.clone(), all_binds_action.name.clone()));
+                    .iter_mut()
+                    .find(|a| a.name == incoming_action.name)
+                {
+                    Some(action) => {
+                        action.rebinds.retain(|r| {
+                            let instance = input_device_instance(&r.input);
+                            instance.is_none() || !instances.contains(&instance)
+                        });
+                        action
+                            .rebinds
+                            .extend(incoming_action.rebinds.iter().cloned());
+                    }
+                    None => action_map.actions.push(incoming_action.clone()),
+                }
+                merged += incoming_action.rebinds.len();
+            }
+        }
 
-                        // Only consider it customized if there are actual non-empty rebinds
-                        let is_customized = if let Some(rebinds) = user_rebinds {
-                            // Action is customized if there are ANY rebinds from the user
-                            // This includes cleared bindings (e.g., "js1_ ") which represent explicit user action
-                            !rebinds.is_empty()
-                        } else {
-                            false
-                        };
+        self.devices.merge(&incoming.devices);
 
-                        let bindings: Vec<MergedBinding> = if let Some(rebinds) = user_rebinds {
-                            // User has custom bindings - include them plus defaults for other input types
-                            let mut all_bindings: Vec<MergedBinding> = rebinds
-                                .iter()
-                                .map(|rebind| {
-                                    let input_type = rebind.get_input_type();
-                                    let input_trimmed = rebind.input.trim();
+        merged
+    }
 
-                                    // Check if this is a cleared binding (e.g., "js1_ ", "kb_ ", etc.)
-                                    // Pattern: (js|kb|mouse|gp)\d+_\s* (device + number + underscore + optional spaces)
-                                    let is_cleared_binding = if input_trimmed.len() >= 3 {
-                                        let parts: Vec<&str> = input_trimmed.split('_').collect();
-                                        parts.len() == 2 && parts[1].trim().is_empty()
-                                    } else {
-                                        false
-                                    };
+    /// Set a rebind on an action, creating the actionmap/action if needed
+    /// Any existing rebind on the same device instance (js1, js2, kb1, ...) is replaced
+    pub fn set_rebind(&mut self, action_map_name: &str, action_name: &str, rebind: Rebind) {
+        let action = self.action_entry(action_map_name, action_name);
 
-                                    // If it's a cleared binding, mark it as is_default: true
-                                    // because it's clearing/overriding a default binding
-                                    let is_default_flag = is_cleared_binding;
+        let new_instance = input_device_instance(&rebind.input);
+        action
+            .rebinds
+            .retain(|r| new_instance.is_none() || input_device_instance(&r.input) != new_instance);
+        action.rebinds.push(rebind);
+    }
 
-                                    // For cleared bindings, get the original default value
-                                    let original_default = if is_cleared_binding {
-                                        match input_type {
-                                            InputType::Keyboard => {
-                                                let default_value =
-                                                    all_binds_action.default_keyboard.trim();
-                                                if default_value.is_empty() {
-                                                    None
-                                                } else {
-                                                    let rebind = Rebind {
-                                                        input: format!("kb_{}", default_value),
-                                                        multi_tap: None,
-                                                        activation_mode: String::new(),
-                                                    };
-                                                    Some(rebind.get_display_name())
-                                                }
-                                            }
-                                            InputType::Mouse => {
-                                                let default_value =
-                                                    all_binds_action.default_mouse.trim();
-                                                if default_value.is_empty() {
-                                                    None
+    /// Add a rebind next to the action's existing ones, creating the actionmap/action if needed
+    pub fn add_rebind(&mut self, action_map_name: &str, action_name: &str, rebind: Rebind) {
+        self.action_entry(action_map_name, action_name)
+            .rebinds
+            .push(rebind);
+    }
+
+    /// The named action, created (with its actionmap) when the profile doesn't have it yet
+    fn action_entry(&mut self, action_map_name: &str, action_name: &str) -> &mut Action {
+        let action_map = match self
+            .action_maps
+            .iter()
+            .position(|am| am.name == action_map_name)
+        {
+            Some(pos) => &mut self.action_maps[pos],
+            None => {
+                self.action_maps.push(ActionMap {
+                    name: action_map_name.to_string(),
+                    actions: Vec::new(),
+                });
+                self.action_maps.last_mut().unwrap()
+            }
+        };
+
+        match action_map
+            .actions
+            .iter()
+            .position(|a| a.name == action_name)
+        {
+            Some(pos) => &mut action_map.actions[pos],
+            None => {
+                action_map.actions.push(Action {
+                    name: action_name.to_string(),
+                    rebinds: Vec::new(),
+                });
+                action_map.actions.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Fill a blank profileName from the file name SC gave the profile
+    /// ("layout_hosas_exported.xml" -> "hosas"); left blank if nothing usable remains
+    pub fn fill_blank_profile_name(&mut self, file_name: &str) -> ProfileNameSource {
+        if !self.profile_name.trim().is_empty() {
+            return ProfileNameSource::FromFile;
+        }
+
+        let stem = std::path::Path::new(file_name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("");
+        let stem = stem.strip_prefix("layout_").unwrap_or(stem);
+        let stem = stem.strip_suffix("_exported").unwrap_or(stem).trim();
+
+        if stem.is_empty() {
+            self.profile_name = String::new();
+            ProfileNameSource::Empty
+        } else {
+            self.profile_name = stem.to_string();
+            ProfileNameSource::FromFilename
+        }
+    }
+
+    /// Assign SC Product strings ("name  {GUID}") to joystick instances
+    /// Instances not in `assignments` are left unassigned
+    pub fn set_joystick_products(&mut self, assignments: &[(u32, String)]) -> Result<(), String> {
+        let mut joysticks = Vec::new();
+
+        for (instance, product) in assignments {
+            if *instance == 0 {
+                return Err("Joystick instances start at 1".to_string());
+            }
+            let index = *instance as usize - 1;
+            if joysticks.len() <= index {
+                joysticks.resize(index + 1, String::new());
+            }
+            joysticks[index] = product.clone();
+        }
+
+        self.devices.joysticks = joysticks;
+        self.devices.declared_joystick_instances =
+            assignments.iter().map(|(instance, _)| *instance).collect();
+        Ok(())
+    }
+
+    /// Joystick instances that bindings refer to but that have no Product in the devices list
+    /// SC may ignore bindings on an instance without an `<options type="joystick">` entry
+    pub fn missing_joystick_options(&self) -> Vec<u32> {
+        let mut instances: Vec<u32> = self
+            .action_maps
+            .iter()
+            .flat_map(|am| &am.actions)
+            .flat_map(|a| &a.rebinds)
+            .filter_map(|r| r.joystick_instance())
+            .filter(|i| *i > 0)
+            .map(u32::from)
+            .collect();
+        instances.sort_unstable();
+        instances.dedup();
+
+        instances
+            .into_iter()
+            .filter(|i| {
+                self.devices
+                    .joysticks
+                    .get(*i as usize - 1)
+                    .is_none_or(|product| product.trim().is_empty())
+            })
+            .collect()
+    }
+
+    /// Give every joystick instance missing from the devices list a placeholder Product
+    /// ("Joystick 3") so an options entry is written for it; returns the instances filled
+    pub fn autofill_joystick_options(&mut self) -> Vec<u32> {
+        let missing = self.missing_joystick_options();
+
+        for instance in &missing {
+            let index = *instance as usize - 1;
+            if self.devices.joysticks.len() <= index {
+                self.devices.joysticks.resize(index + 1, String::new());
+            }
+            self.devices.joysticks[index] = format!("Joystick {}", instance);
+        }
+
+        missing
+    }
+
+    /// Invert the joystick axis binding of an action by flipping its direction
+    /// Returns the new input
+    pub fn toggle_axis_inversion(
+        &mut self,
+        action_map_name: &str,
+        action_name: &str,
+    ) -> Result<String, String> {
+        let action = self
+            .action_maps
+            .iter_mut()
+            .find(|am| am.name == action_map_name)
+            .and_then(|am| am.actions.iter_mut().find(|a| a.name == action_name))
+            .ok_or_else(|| format!("Action not found: {}/{}", action_map_name, action_name))?;
+
+        let mut joysticks = action
+            .rebinds
+            .iter_mut()
+            .filter(|r| r.get_input_type() == InputType::Joystick)
+            .peekable();
+        if joysticks.peek().is_none() {
+            return Err(format!("No joystick binding for {}", action_name));
+        }
+
+        // Use the first joystick binding that is a directional axis
+        let mut last_error = String::new();
+        for rebind in joysticks {
+            match toggle_axis_direction(&rebind.input) {
+                Ok(toggled) => {
+                    rebind.input = toggled;
+                    return Ok(rebind.input.clone());
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Move the rebind for `input` to the front of the action's list so SC treats it as primary
+    pub fn set_primary_binding(
+        &mut self,
+        action_map_name: &str,
+        action_name: &str,
+        input: &str,
+    ) -> Result<(), String> {
+        let action = self
+            .action_maps
+            .iter_mut()
+            .find(|am| am.name == action_map_name)
+            .and_then(|am| am.actions.iter_mut().find(|a| a.name == action_name))
+            .ok_or_else(|| format!("Action not found: {}/{}", action_map_name, action_name))?;
+
+        let target = normalize_input(input);
+        let position = action
+            .rebinds
+            .iter()
+            .position(|r| normalize_input(&r.input) == target)
+            .ok_or_else(|| format!("{} is not bound to {}", input, action_name))?;
+
+        let rebind = action.rebinds.remove(position);
+        action.rebinds.insert(0, rebind);
+        Ok(())
+    }
+
+    /// Rename an action map, e.g. after SC renames it in a patch
+    /// If the new name already exists the actions are merged into it, and a rebind is only
+    /// moved when the target action has nothing on that device instance yet; the others
+    /// are dropped and listed in the result
+    pub fn rename_action_map(&mut self, old_name: &str, new_name: &str) -> RenameOutcome {
+        let mut outcome = RenameOutcome::default();
+        if old_name == new_name {
+            return outcome;
+        }
+        let Some(old_pos) = self.action_maps.iter().position(|am| am.name == old_name) else {
+            return outcome;
+        };
+
+        if !self.action_maps.iter().any(|am| am.name == new_name) {
+            let action_map = &mut self.action_maps[old_pos];
+            action_map.name = new_name.to_string();
+            outcome.moved = action_map.actions.iter().map(|a| a.rebinds.len()).sum();
+            return outcome;
+        }
+
+        let old_map = self.action_maps.remove(old_pos);
+        let target = self
+            .action_maps
+            .iter_mut()
+            .find(|am| am.name == new_name)
+            .unwrap();
+
+        for action in old_map.actions {
+            let target_action = match target.actions.iter().position(|a| a.name == action.name) {
+                Some(pos) => &mut target.actions[pos],
+                None => {
+                    outcome.moved += action.rebinds.len();
+                    target.actions.push(action);
+                    continue;
+                }
+            };
+
+            for rebind in action.rebinds {
+                let instance = input_device_instance(&rebind.input);
+                let taken = target_action
+                    .rebinds
+                    .iter()
+                    .any(|r| input_device_instance(&r.input) == instance);
+                if taken {
+                    outcome.dropped.push((action.name.clone(), rebind.input));
+                } else {
+                    target_action.rebinds.push(rebind);
+                    outcome.moved += 1;
+                }
+            }
+        }
+
+        outcome
+    }
+
+    /// Profile with nothing in it yet, for edits made before any profile is loaded
+    pub fn empty() -> Self {
+        ActionMaps {
+            profile_name: "User Customizations".to_string(),
+            description: String::new(),
+            image: String::new(),
+            options_version: None,
+            rebind_version: None,
+            action_maps: Vec::new(),
+            categories: Vec::new(),
+            devices: DeviceInfo::default(),
+        }
+    }
+
+    /// Create a new empty action map with the given name and actions
+    pub fn new_empty_action_map(name: String, actions: Vec<Action>) -> ActionMap {
+        ActionMap { name, actions }
+    }
+
+    /// Convert action_map name to display name
+    fn format_action_map_name(name: &str) -> String {
+        format_display_name(name)
+    }
+
+    /// Convert action name to display name
+    fn format_action_name(name: &str) -> String {
+        format_display_name(name)
+    }
+}
+
+/// Parse SC's global.ini localization ("key=value" lines) into lowercased key -> text
+/// Keys may carry a ",P" style suffix, which is dropped; blank lines and ';'/'#' comments
+/// are skipped
+pub fn parse_localization(content: &str) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.split(',').next().unwrap_or("").trim().to_lowercase();
+        if !key.is_empty() {
+            entries.insert(key, value.trim_end_matches('\r').to_string());
+        }
+    }
+    entries
+}
+
+/// Comprehensive display name formatter for action names
+/// Handles various naming patterns:
+/// - v_action_name -> Action Name
+/// - pc_action_name -> Action Name  
+/// - PascalCase -> Pascal Case
+/// - camelCase -> Camel Case
+/// - snake_case -> Snake Case
+/// - turret_gyromode -> Turret Gyro Mode (splits compounds)
+pub fn format_display_name(name: &str) -> String {
+    if name.is_empty() {
+        return String::new();
+    }
+
+    // Remove common prefixes
+    let cleaned = name
+        .trim_start_matches("v_")
+        .trim_start_matches("pc_")
+        .trim_start_matches("ui_")
+        .trim_start_matches("spectate_");
+
+    // Handle special abbreviations and technical terms
+    let result = cleaned
+        .replace("mfd", "MFD")
+        .replace("fps", "FPS")
+        .replace("esp", "ESP")
+        .replace("vjoy", "VJoy")
+        .replace("mgv", "MGV")
+        .replace("hud", "HUD")
+        .replace("ifcs", "IFCS")
+        .replace("vtol", "VTOL")
+        .replace("ptu", "PTU")
+        .replace("atc", "ATC");
+
+    // Split on underscores first
+    let parts: Vec<&str> = result.split('_').collect();
+
+    let formatted_parts: Vec<String> = parts
+        .iter()
+        .map(|part| {
+            if part.is_empty() {
+                return String::new();
+            }
+
+            if let Some((_, label)) = DISPLAY_NAME_TOKENS
+                .iter()
+                .find(|(token, _)| part.eq_ignore_ascii_case(token))
+            {
+                return label.to_string();
+            }
+
+            // Check if it's all caps (abbreviation) - preserve it
+            if part.chars().all(|c| c.is_uppercase() || c.is_numeric()) {
+                return part.to_string();
+            }
+
+            // Check if it's a mix of uppercase and lowercase (PascalCase or camelCase)
+            // Letters glued to digits ("mode2toggle") are split the same way
+            let has_digit_boundary =
+                part.chars().any(|c| c.is_ascii_digit()) && part.chars().any(|c| c.is_alphabetic());
+            if has_digit_boundary
+                || (part.chars().any(|c| c.is_uppercase())
+                    && part.chars().any(|c| c.is_lowercase()))
+            {
+                split_camel_case(part)
+            } else {
+                // Regular word - capitalize first letter
+                capitalize_word(part)
+            }
+        })
+        .collect();
+
+    formatted_parts.join(" ").trim().to_string()
+}
+
+/// Name parts shown as-is instead of being split and capitalized
+const DISPLAY_NAME_TOKENS: &[(&str, &str)] = &[("1to1", "1:1")];
+
+/// Split PascalCase or camelCase into separate words
+/// Letter/digit boundaries also split ("mode2toggle" -> "Mode 2 Toggle"),
+/// while uppercase runs stay joined ("MFDLeft" -> "MFD Left")
+fn split_camel_case(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut result = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if i == 0 {
+            result.push(c.to_ascii_uppercase());
+            continue;
+        }
+
+        let prev = chars[i - 1];
+        let next = chars.get(i + 1);
+
+        if c.is_ascii_digit() {
+            // Digits start a new word after a letter ("target1" -> "Target 1")
+            if prev.is_alphabetic() {
+                result.push(' ');
+            }
+            result.push(c);
+        } else if prev.is_ascii_digit() && c.is_alphabetic() {
+            // A letter after a digit starts a new word
+            result.push(' ');
+            result.push(c.to_ascii_uppercase());
+        } else if c.is_uppercase() {
+            // Add space before uppercase if previous was lowercase
+            // or if this is start of a new word (e.g., "HTMLParser" -> "HTML Parser")
+            if prev.is_lowercase()
+                || (prev.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            {
+                result.push(' ');
+            }
+            result.push(c);
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Capitalize the first letter of a word
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => {
+            let rest: String = chars.collect();
+            // Handle special cases
+            match word.to_lowercase().as_str() {
+                "up" | "down" | "left" | "right" => word.to_uppercase(),
+                "x" | "y" | "z" => word.to_uppercase(),
+                _ => first.to_uppercase().collect::<String>() + &rest.to_lowercase(),
+            }
+        }
+    }
+}
+
+/// SC key tokens and their readable labels
+const KEY_LABELS: &[(&str, &str)] = &[
+    // Numpad
+    ("np_0", "Numpad 0"),
+    ("np_1", "Numpad 1"),
+    ("np_2", "Numpad 2"),
+    ("np_3", "Numpad 3"),
+    ("np_4", "Numpad 4"),
+    ("np_5", "Numpad 5"),
+    ("np_6", "Numpad 6"),
+    ("np_7", "Numpad 7"),
+    ("np_8", "Numpad 8"),
+    ("np_9", "Numpad 9"),
+    ("np_add", "Numpad +"),
+    ("np_subtract", "Numpad -"),
+    ("np_multiply", "Numpad *"),
+    ("np_divide", "Numpad /"),
+    ("np_period", "Numpad ."),
+    ("np_enter", "Numpad Enter"),
+    ("numlock", "Num Lock"),
+    // Punctuation
+    ("lbracket", "["),
+    ("rbracket", "]"),
+    ("semicolon", ";"),
+    ("apostrophe", "'"),
+    ("comma", ","),
+    ("period", "."),
+    ("slash", "/"),
+    ("backslash", "\\"),
+    ("minus", "-"),
+    ("equals", "="),
+    ("grave", "`"),
+    // Modifiers
+    ("lctrl", "Left Ctrl"),
+    ("rctrl", "Right Ctrl"),
+    ("lalt", "Left Alt"),
+    ("ralt", "Right Alt"),
+    ("lshift", "Left Shift"),
+    ("rshift", "Right Shift"),
+    // Navigation and editing
+    ("space", "Space"),
+    ("enter", "Enter"),
+    ("escape", "Esc"),
+    ("backspace", "Backspace"),
+    ("tab", "Tab"),
+    ("capslock", "Caps Lock"),
+    ("insert", "Insert"),
+    ("delete", "Delete"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("pgup", "Page Up"),
+    ("pgdn", "Page Down"),
+    // Mouse wheel
+    ("mwheel_up", "Mouse Wheel Up"),
+    ("mwheel_down", "Mouse Wheel Down"),
+];
+
+/// Arrow keys; only keyboard inputs get these labels, since hats and other devices use the
+/// same direction words
+const ARROW_KEY_LABELS: &[(&str, &str)] = &[
+    ("up", "Arrow Up"),
+    ("down", "Arrow Down"),
+    ("left", "Arrow Left"),
+    ("right", "Arrow Right"),
+];
+
+/// Readable label for an SC key token (e.g. "np_1" -> "Numpad 1", "lbracket" -> "[")
+pub fn keyname_to_label(token: &str) -> Option<&'static str> {
+    let token = token.trim().to_lowercase();
+    KEY_LABELS
+        .iter()
+        .chain(ARROW_KEY_LABELS)
+        .find(|(key, _)| *key == token)
+        .map(|(_, label)| *label)
+}
+
+/// Label for a key token on a device other than the keyboard, arrow keys excluded
+fn device_key_label(token: &str) -> Option<&'static str> {
+    let token = token.trim().to_lowercase();
+    KEY_LABELS
+        .iter()
+        .find(|(key, _)| *key == token)
+        .map(|(_, label)| *label)
+}
+
+/// SC key token for a readable label, the inverse of `keyname_to_label`
+pub fn label_to_keyname(label: &str) -> Option<&'static str> {
+    let label = label.trim();
+    KEY_LABELS
+        .iter()
+        .chain(ARROW_KEY_LABELS)
+        .find(|(_, l)| l.eq_ignore_ascii_case(label))
+        .map(|(key, _)| *key)
+}
+
+/// Physical keyboard layouts supported for key name remapping
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Azerty,
+    Qwertz,
+}
+
+/// Positional key table: each row is the key name at the same physical position
+/// in (QWERTY, AZERTY, QWERTZ). Keys not listed are in the same place on all layouts.
+/// Every column is a permutation of the same key set, so remapping is reversible.
+const LAYOUT_KEY_TABLE: &[(&str, &str, &str)] = &[
+    // Top letter row
+    ("q", "a", "q"),
+    ("w", "z", "w"),
+    ("y", "y", "z"),
+    // Home row
+    ("a", "q", "a"),
+    ("semicolon", "m", "semicolon"),
+    // Bottom row
+    ("z", "w", "y"),
+    ("m", "comma", "m"),
+    ("comma", "semicolon", "comma"),
+];
+
+/// Get the key name for a layout from a table row
+fn layout_key<'a>(row: &(&'a str, &'a str, &'a str), layout: KeyboardLayout) -> &'a str {
+    match layout {
+        KeyboardLayout::Qwerty => row.0,
+        KeyboardLayout::Azerty => row.1,
+        KeyboardLayout::Qwertz => row.2,
+    }
+}
+
+/// Punctuation keys whose printed symbol and position vary between national layouts
+/// Letters that move between layouts are covered by LAYOUT_KEY_TABLE instead
+const LAYOUT_SENSITIVE_KEYS: &[&str] = &[
+    "lbracket",
+    "rbracket",
+    "semicolon",
+    "apostrophe",
+    "comma",
+    "period",
+    "slash",
+    "backslash",
+    "minus",
+    "equals",
+    "grave",
+];
+
+/// Whether a key token sits somewhere else, or prints something else, on another layout
+pub fn is_layout_sensitive_key(key: &str) -> bool {
+    let key = key.trim().to_lowercase();
+    LAYOUT_SENSITIVE_KEYS.contains(&key.as_str())
+        || LAYOUT_KEY_TABLE
+            .iter()
+            .any(|row| row.0 == key || row.1 == key || row.2 == key)
+}
+
+/// Map a single key name (e.g. "q", "semicolon") to the key at the same physical position
+pub fn remap_key_name(key: &str, from: KeyboardLayout, to: KeyboardLayout) -> String {
+    if from == to {
+        return key.to_string();
+    }
+
+    let lower = key.to_lowercase();
+    LAYOUT_KEY_TABLE
+        .iter()
+        .find(|row| layout_key(row, from) == lower)
+        .map(|row| layout_key(row, to).to_string())
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Remap a full keyboard input string between layouts
+/// Handles modifiers in any position, e.g. "kb1_q", "kb1_lalt+q", "LALT+kb1_q", "kb_u+lshift"
+pub fn remap_keyboard_input(input: &str, from: KeyboardLayout, to: KeyboardLayout) -> String {
+    input
+        .split('+')
+        .map(|part| {
+            // Device-prefixed part: keep the prefix ("kb_", "kb1_") and remap the key
+            if part.starts_with("kb") {
+                if let Some(underscore_pos) = part.find('_') {
+                    let (prefix, key) = part.split_at(underscore_pos + 1);
+                    if key.trim().is_empty() {
+                        return part.to_string();
+                    }
+                    return format!("{}{}", prefix, remap_key_name(key, from, to));
+                }
+            }
+
+            // Bare key or modifier (modifiers are not in the table and pass through)
+            remap_key_name(part, from, to)
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+impl AllBinds {
+    /// Check if an action has a non-empty default binding for the given input type
+    pub fn has_default_binding(
+        &self,
+        action_map_name: &str,
+        action_name: &str,
+        input_type: &InputType,
+    ) -> bool {
+        self.action_maps
+            .iter()
+            .filter(|am| am.name == action_map_name)
+            .flat_map(|am| am.actions.iter())
+            .filter(|a| a.name == action_name)
+            .any(|a| a.default_input(input_type).is_some())
+    }
+
+    /// Whether a user rebind differs from the action's default for its device type
+    /// A cleared rebind differs when there was a default to clear
+    /// None if the action isn't in AllBinds or the device type is unknown
+    pub fn rebind_differs_from_default(
+        &self,
+        action_map_name: &str,
+        action_name: &str,
+        rebind: &Rebind,
+    ) -> Option<bool> {
+        let action = self
+            .action_maps
+            .iter()
+            .filter(|am| am.name == action_map_name)
+            .flat_map(|am| am.actions.iter())
+            .find(|a| a.name == action_name)?;
+
+        if let Some(cleared_type) = rebind.cleared_device_type() {
+            return Some(action.default_input(&cleared_type).is_some());
+        }
+
+        let input_type = rebind.get_input_type();
+        if input_type == InputType::Unknown {
+            return None;
+        }
+
+        match action.default_input(&input_type) {
+            Some(default) => {
+                // Defaults carry no device prefix; compare on the rebind's own instance
+                let device = input_device_instance(&rebind.input)?;
+                let default_input = format!("{}_{}", device, default);
+                Some(normalize_input(&default_input) != normalize_input(&rebind.input))
+            }
+            None => Some(true),
+        }
+    }
+
+    /// Parse AllBinds.xml file into AllBinds structure
+    pub fn from_xml(xml: &str) -> Result<Self, String> {
+        Self::from_xml_with_progress(xml, 0, |_, _| {})
+    }
+
+    /// Parse AllBinds.xml, calling `on_progress(action_maps_parsed, bytes_read)` every
+    /// `report_every` completed actionmaps (0 disables progress reporting)
+    pub fn from_xml_with_progress<F>(
+        xml: &str,
+        report_every: usize,
+        mut on_progress: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(usize, usize),
+    {
+        let mut action_maps = Vec::new();
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut buf = vec![];
+
+        let mut current_action_map: Option<AllBindsActionMap> = None;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(quick_xml::events::Event::Start(ref e))
+                | Ok(quick_xml::events::Event::Empty(ref e)) => {
+                    match e.name().as_ref() {
+                        b"actionmap" => {
+                            let mut name = String::new();
+                            let mut version = String::new();
+                            let mut ui_label = String::new();
+                            let mut ui_category = String::new();
+
+                            for attr in e.attributes().flatten() {
+                                match attr.key.as_ref() {
+                                    b"name" => {
+                                        name = String::from_utf8(attr.value.to_vec())
+                                            .unwrap_or_default()
+                                    }
+                                    b"version" => {
+                                        version = String::from_utf8(attr.value.to_vec())
+                                            .unwrap_or_default()
+                                    }
+                                    b"UILabel" => {
+                                        ui_label = String::from_utf8(attr.value.to_vec())
+                                            .unwrap_or_default()
+                                    }
+                                    b"UICategory" => {
+                                        ui_category = String::from_utf8(attr.value.to_vec())
+                                            .unwrap_or_default()
+                                    }
+                                    _ => {}
+                                }
+                            }
+
+                            current_action_map = Some(AllBindsActionMap {
+                                name,
+                                version,
+                                ui_label,
+                                ui_category,
+                                actions: Vec::new(),
+                            });
+                        }
+                        b"action" => {
+                            if let Some(ref mut action_map) = current_action_map {
+                                let mut name = String::new();
+                                let mut ui_label = String::new();
+                                let mut ui_description = String::new();
+                                let mut category = String::new();
+                                let mut activation_mode = String::new();
+                                let mut on_hold = false;
+                                let mut keyboard = String::new();
+                                let mut mouse = String::new();
+                                let mut gamepad = String::new();
+                                let mut joystick = String::new();
+
+                                for attr in e.attributes().flatten() {
+                                    match attr.key.as_ref() {
+                                        b"name" => {
+                                            name = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"UILabel" => {
+                                            ui_label = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"UIDescription" => {
+                                            ui_description = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"Category" => {
+                                            category = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"activationMode" => {
+                                            activation_mode = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"onHold" => {
+                                            let val = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default();
+                                            on_hold = val == "1";
+                                        }
+                                        b"keyboard" => {
+                                            keyboard = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"mouse" => {
+                                            mouse = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"gamepad" => {
+                                            gamepad = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        b"joystick" => {
+                                            joystick = String::from_utf8(attr.value.to_vec())
+                                                .unwrap_or_default()
+                                        }
+                                        _ => {}
+                                    }
+                                }
+
+                                // Debug: log quantum actions
+                                if name.contains("quantum") {
+                                    println!("Parsing action: {} with kb='{}', mouse='{}', gamepad='{}', js='{}'", 
+                                        name, keyboard, mouse, gamepad, joystick);
+                                }
+
+                                action_map.actions.push(AllBindsAction {
+                                    name,
+                                    ui_label,
+                                    ui_description,
+                                    category,
+                                    activation_mode,
+                                    on_hold,
+                                    default_keyboard: keyboard,
+                                    default_mouse: mouse,
+                                    default_gamepad: gamepad,
+                                    default_joystick: joystick,
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(quick_xml::events::Event::End(ref e)) => {
+                    if e.name().as_ref() == b"actionmap" {
+                        if let Some(action_map) = current_action_map.take() {
+                            action_maps.push(action_map);
+
+                            if report_every > 0 && action_maps.len() % report_every == 0 {
+                                on_progress(action_maps.len(), reader.buffer_position() as usize);
+                            }
+                        }
+                    }
+                }
+                Ok(quick_xml::events::Event::Eof) => break,
+                Err(e) => {
+                    return Err(format!("XML parsing error: {}", e));
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(AllBinds {
+            action_maps,
+            localization: HashMap::new(),
+        })
+    }
+}
+
+/// Merged view of AllBinds with user customizations
+#[derive(Debug, Serialize, Clone)]
+pub struct MergedBindings {
+    pub action_maps: Vec<MergedActionMap>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MergedActionMap {
+    pub name: String,
+    pub ui_label: String,
+    pub ui_category: String,
+    pub actions: Vec<MergedAction>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MergedAction {
+    pub name: String,
+    pub ui_label: String,
+    pub ui_description: String,
+    pub category: String,
+    pub is_customized: bool,
+    pub on_hold: bool,
+    pub bindings: Vec<MergedBinding>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MergedBinding {
+    pub input: String,
+    pub display_name: String,
+    pub input_type: String,
+    pub is_default: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_tap: Option<u32>,
+    pub activation_mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_default: Option<String>, // For cleared bindings, store the original default binding text
+}
+
+impl AllBinds {
+    /// Label to show for a UILabel: plain labels as is, localization keys ("@ui_...")
+    /// translated when a localization is loaded, otherwise the formatted name
+    pub fn resolve_label(&self, ui_label: &str, name: &str) -> String {
+        if ui_label.is_empty() {
+            return format_display_name(name);
+        }
+
+        match ui_label.strip_prefix('@') {
+            Some(key) => self
+                .localization
+                .get(&key.to_lowercase())
+                .filter(|text| !text.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format_display_name(name)),
+            None => ui_label.to_string(),
+        }
+    }
+
+    /// Merge AllBinds with user customizations from ActionMaps
+    pub fn merge_with_user_bindings(&self, user_bindings: Option<&ActionMaps>) -> MergedBindings {
+        // Build a lookup map for user bindings
+        let mut user_actions: HashMap<(String, String), Vec<Rebind>> = HashMap::new();
+
+        if let Some(user_maps) = user_bindings {
+            for action_map in &user_maps.action_maps {
+                for action in &action_map.actions {
+                    user_actions.insert(
+                        (action_map.name.clone(), action.name.clone()),
+                        action.rebinds.clone(),
+                    );
+                }
+            }
+        }
+
+        let merged_maps: Vec<MergedActionMap> = self
+            .action_maps
+            .iter()
+            .map(|all_binds_map| {
+                // Debug: log quantum actionmap
+                if all_binds_map.name.contains("quantum") || all_binds_map.name == "seat_general" {
+                    let quantum_actions: Vec<_> = all_binds_map
+                        .actions
+                        .iter()
+                        .filter(|a| a.name.contains("quantum"))
+                        .map(|a| &a.name)
+                        .collect();
+                    if !quantum_actions.is_empty() {
+                        println!(
+                            "ActionMap '{}' has quantum actions: {:?}",
+                            all_binds_map.name, quantum_actions
+                        );
+                    }
+                }
+
+                let merged_actions: Vec<MergedAction> = all_binds_map
+                    .actions
+                    .iter()
+                    .map(|all_binds_action| {
+                        // Check if user has custom bindings for this action
+                        let user_rebinds = user_actions
+                            .get(&(all_binds_map.name.clone(), all_binds_action.name.clone()));
+
+                        // Only consider it customized if there are actual non-empty rebinds
+                        let is_customized = if let Some(rebinds) = user_rebinds {
+                            // Action is customized if there are ANY rebinds from the user
+                            // This includes cleared bindings (e.g., "js1_ ") which represent explicit user action
+                            !rebinds.is_empty()
+                        } else {
+                            false
+                        };
+
+                        let bindings: Vec<MergedBinding> = if let Some(rebinds) = user_rebinds {
+                            // User has custom bindings - include them plus defaults for other input types
+                            let mut all_bindings: Vec<MergedBinding> = rebinds
+                                .iter()
+                                .map(|rebind| {
+                                    let input_type = rebind.get_input_type();
+                                    let input_trimmed = rebind.input.trim();
+
+                                    // Check if this is a cleared binding (e.g., "js1_ ", "kb_ ", etc.)
+                                    // Pattern: (js|kb|mouse|gp)\d+_\s* (device + number + underscore + optional spaces)
+                                    let is_cleared_binding = if input_trimmed.len() >= 3 {
+                                        let parts: Vec<&str> = input_trimmed.split('_').collect();
+                                        parts.len() == 2 && parts[1].trim().is_empty()
+                                    } else {
+                                        false
+                                    };
+
+                                    // If it's a cleared binding, mark it as is_default: true
+                                    // because it's clearing/overriding a default binding
+                                    let is_default_flag = is_cleared_binding;
+
+                                    // For cleared bindings, get the original default value
+                                    let original_default = if is_cleared_binding {
+                                        match input_type {
+                                            InputType::Keyboard => {
+                                                let default_value =
+                                                    all_binds_action.default_keyboard.trim();
+                                                if default_value.is_empty() {
+                                                    None
+                                                } else {
+                                                    let rebind = Rebind {
+                                                        input: format!("kb_{}", default_value),
+                                                        multi_tap: None,
+                                                        multi_tap_block: None,
+                                                        press_trigger_threshold: None,
+                                                        activation_mode: String::new(),
+                                                    };
+                                                    Some(rebind.get_display_name())
+                                                }
+                                            }
+                                            InputType::Mouse => {
+                                                let default_value =
+                                                    all_binds_action.default_mouse.trim();
+                                                if default_value.is_empty() {
+                                                    None
                                                 } else {
                                                     let rebind = Rebind {
                                                         input: format!("mouse1_{}", default_value),
                                                         multi_tap: None,
+                                                        multi_tap_block: None,
+                                                        press_trigger_threshold: None,
                                                         activation_mode: String::new(),
                                                     };
                                                     Some(rebind.get_display_name())
@@ -1300,6 +3360,8 @@ impl AllBinds {
                                                     let rebind = Rebind {
                                                         input: format!("js1_{}", default_value),
                                                         multi_tap: None,
+                                                        multi_tap_block: None,
+                                                        press_trigger_threshold: None,
                                                         activation_mode: String::new(),
                                                     };
                                                     Some(rebind.get_display_name())
@@ -1314,6 +3376,8 @@ impl AllBinds {
                                                     let rebind = Rebind {
                                                         input: format!("gp1_{}", default_value),
                                                         multi_tap: None,
+                                                        multi_tap_block: None,
+                                                        press_trigger_threshold: None,
                                                         activation_mode: String::new(),
                                                     };
                                                     Some(rebind.get_display_name())
@@ -1325,436 +3389,4795 @@ impl AllBinds {
                                         None
                                     };
 
-                                    MergedBinding {
-                                        input: rebind.input.clone(),
-                                        display_name: if is_cleared_binding {
-                                            original_default
-                                                .clone()
-                                                .unwrap_or_else(|| "Unbound".to_string())
-                                        } else {
-                                            rebind.get_display_name()
-                                        },
-                                        input_type: format!("{:?}", input_type),
-                                        is_default: is_default_flag,
-                                        multi_tap: rebind.multi_tap,
-                                        activation_mode: rebind.activation_mode.clone(),
-                                        original_default,
-                                    }
-                                })
-                                .collect();
+                                    MergedBinding {
+                                        input: rebind.input.clone(),
+                                        display_name: if is_cleared_binding {
+                                            original_default
+                                                .clone()
+                                                .unwrap_or_else(|| "Unbound".to_string())
+                                        } else {
+                                            rebind.get_display_name()
+                                        },
+                                        input_type: format!("{:?}", input_type),
+                                        is_default: is_default_flag,
+                                        multi_tap: rebind.multi_tap,
+                                        activation_mode: rebind.activation_mode.clone(),
+                                        original_default,
+                                    }
+                                })
+                                .collect();
+
+                            // Build a set of input types we already have from user bindings
+                            use std::collections::HashSet;
+                            let custom_input_types: HashSet<String> =
+                                all_bindings.iter().map(|b| b.input_type.clone()).collect();
+
+                            // Add keyboard default if not customized
+                            if !custom_input_types.contains("Keyboard") {
+                                let kb_trimmed = all_binds_action.default_keyboard.trim();
+                                // Only add if there's an actual binding (not just space)
+                                if !kb_trimmed.is_empty() {
+                                    let input = format!("kb_{}", kb_trimmed);
+                                    let rebind = Rebind {
+                                        input: input.clone(),
+                                        multi_tap: None,
+                                        multi_tap_block: None,
+                                        press_trigger_threshold: None,
+                                        activation_mode: String::new(),
+                                    };
+                                    let input_type = rebind.get_input_type();
+                                    all_bindings.push(MergedBinding {
+                                        input: rebind.input.clone(),
+                                        display_name: rebind.get_display_name(),
+                                        input_type: format!("{:?}", input_type),
+                                        is_default: true,
+                                        multi_tap: None,
+                                        activation_mode: String::new(),
+                                        original_default: None,
+                                    });
+                                }
+                            }
+
+                            // Add gamepad default if not customized
+                            if !custom_input_types.contains("Gamepad") {
+                                let gp_trimmed = all_binds_action.default_gamepad.trim();
+                                // Only add if there's an actual binding (not just space)
+                                if !gp_trimmed.is_empty() {
+                                    let input = format!("gp1_{}", gp_trimmed);
+                                    let rebind = Rebind {
+                                        input: input.clone(),
+                                        multi_tap: None,
+                                        multi_tap_block: None,
+                                        press_trigger_threshold: None,
+                                        activation_mode: String::new(),
+                                    };
+                                    let input_type = rebind.get_input_type();
+                                    all_bindings.push(MergedBinding {
+                                        input: rebind.input.clone(),
+                                        display_name: rebind.get_display_name(),
+                                        input_type: format!("{:?}", input_type),
+                                        is_default: true,
+                                        multi_tap: None,
+                                        activation_mode: String::new(),
+                                        original_default: None,
+                                    });
+                                }
+                            }
+
+                            // Add joystick default if not customized
+                            if !custom_input_types.contains("Joystick") {
+                                let js_trimmed = all_binds_action.default_joystick.trim();
+                                // Only add if there's an actual binding (not just space)
+                                if !js_trimmed.is_empty() {
+                                    let input = format!("js1_{}", js_trimmed);
+                                    let rebind = Rebind {
+                                        input: input.clone(),
+                                        multi_tap: None,
+                                        multi_tap_block: None,
+                                        press_trigger_threshold: None,
+                                        activation_mode: String::new(),
+                                    };
+                                    let input_type = rebind.get_input_type();
+                                    all_bindings.push(MergedBinding {
+                                        input: rebind.input.clone(),
+                                        display_name: rebind.get_display_name(),
+                                        input_type: format!("{:?}", input_type),
+                                        is_default: true,
+                                        multi_tap: None,
+                                        activation_mode: String::new(),
+                                        original_default: None,
+                                    });
+                                }
+                            }
+
+                            // Add mouse default if not customized
+                            if !custom_input_types.contains("Mouse") {
+                                let mouse_trimmed = all_binds_action.default_mouse.trim();
+                                // Only add if there's an actual binding (not just space)
+                                if !mouse_trimmed.is_empty() {
+                                    let input = format!("mouse1_{}", mouse_trimmed);
+                                    let rebind = Rebind {
+                                        input: input.clone(),
+                                        multi_tap: None,
+                                        multi_tap_block: None,
+                                        press_trigger_threshold: None,
+                                        activation_mode: String::new(),
+                                    };
+                                    let input_type = rebind.get_input_type();
+                                    all_bindings.push(MergedBinding {
+                                        input: rebind.input.clone(),
+                                        display_name: rebind.get_display_name(),
+                                        input_type: format!("{:?}", input_type),
+                                        is_default: true,
+                                        multi_tap: None,
+                                        activation_mode: String::new(),
+                                        original_default: None,
+                                    });
+                                }
+                            }
+
+                            // If we ended up with no bindings at all (all defaults were spaces),
+                            // add an empty keyboard binding so the action still appears in the UI
+                            if all_bindings.is_empty() {
+                                all_bindings.push(MergedBinding {
+                                    input: "kb_ ".to_string(),
+                                    display_name: "Unbound".to_string(),
+                                    input_type: "Unknown".to_string(),
+                                    is_default: true,
+                                    multi_tap: None,
+                                    activation_mode: String::new(),
+                                    original_default: None,
+                                });
+                            }
+
+                            all_bindings
+                        } else {
+                            // No custom bindings, use defaults from AllBinds
+                            let mut default_bindings = Vec::new();
+
+                            // Add keyboard default if exists
+                            let kb_trimmed = all_binds_action.default_keyboard.trim();
+                            // Only add if there's an actual binding (not just space)
+                            if !kb_trimmed.is_empty() {
+                                let input = format!("kb_{}", kb_trimmed);
+                                let rebind = Rebind {
+                                    input: input.clone(),
+                                    multi_tap: None,
+                                    multi_tap_block: None,
+                                    press_trigger_threshold: None,
+                                    activation_mode: String::new(),
+                                };
+                                let input_type = rebind.get_input_type();
+                                default_bindings.push(MergedBinding {
+                                    input: rebind.input.clone(),
+                                    display_name: rebind.get_display_name(),
+                                    input_type: format!("{:?}", input_type),
+                                    is_default: true,
+                                    multi_tap: None,
+                                    activation_mode: String::new(),
+                                    original_default: None,
+                                });
+                            }
+
+                            // Add gamepad default if exists
+                            let gp_trimmed = all_binds_action.default_gamepad.trim();
+                            // Only add if there's an actual binding (not just space)
+                            if !gp_trimmed.is_empty() {
+                                let input = format!("gp1_{}", gp_trimmed);
+                                let rebind = Rebind {
+                                    input: input.clone(),
+                                    multi_tap: None,
+                                    multi_tap_block: None,
+                                    press_trigger_threshold: None,
+                                    activation_mode: String::new(),
+                                };
+                                let input_type = rebind.get_input_type();
+                                default_bindings.push(MergedBinding {
+                                    input: rebind.input.clone(),
+                                    display_name: rebind.get_display_name(),
+                                    input_type: format!("{:?}", input_type),
+                                    is_default: true,
+                                    multi_tap: None,
+                                    activation_mode: String::new(),
+                                    original_default: None,
+                                });
+                            }
+
+                            // Add joystick default if exists
+                            let js_trimmed = all_binds_action.default_joystick.trim();
+                            // Only add if there's an actual binding (not just space)
+                            if !js_trimmed.is_empty() {
+                                let input = format!("js1_{}", js_trimmed);
+                                let rebind = Rebind {
+                                    input: input.clone(),
+                                    multi_tap: None,
+                                    multi_tap_block: None,
+                                    press_trigger_threshold: None,
+                                    activation_mode: String::new(),
+                                };
+                                let input_type = rebind.get_input_type();
+                                default_bindings.push(MergedBinding {
+                                    input: rebind.input.clone(),
+                                    display_name: rebind.get_display_name(),
+                                    input_type: format!("{:?}", input_type),
+                                    is_default: true,
+                                    multi_tap: None,
+                                    activation_mode: String::new(),
+                                    original_default: None,
+                                });
+                            }
+
+                            // Add mouse default if exists
+                            let mouse_trimmed = all_binds_action.default_mouse.trim();
+                            // Only add if there's an actual binding (not just space)
+                            if !mouse_trimmed.is_empty() {
+                                let input = format!("mouse1_{}", mouse_trimmed);
+                                let rebind = Rebind {
+                                    input: input.clone(),
+                                    multi_tap: None,
+                                    multi_tap_block: None,
+                                    press_trigger_threshold: None,
+                                    activation_mode: String::new(),
+                                };
+                                let input_type = rebind.get_input_type();
+                                default_bindings.push(MergedBinding {
+                                    input: rebind.input.clone(),
+                                    display_name: rebind.get_display_name(),
+                                    input_type: format!("{:?}", input_type),
+                                    is_default: true,
+                                    multi_tap: None,
+                                    activation_mode: String::new(),
+                                    original_default: None,
+                                });
+                            }
+
+                            // If we ended up with no bindings at all (all defaults were spaces),
+                            // add an empty keyboard binding so the action still appears in the UI
+                            if default_bindings.is_empty() {
+                                default_bindings.push(MergedBinding {
+                                    input: "kb_ ".to_string(),
+                                    display_name: "Unbound".to_string(),
+                                    input_type: "Unknown".to_string(),
+                                    is_default: true,
+                                    multi_tap: None,
+                                    activation_mode: String::new(),
+                                    original_default: None,
+                                });
+                            }
+
+                            default_bindings
+                        };
+
+                        let display_label =
+                            self.resolve_label(&all_binds_action.ui_label, &all_binds_action.name);
+
+                        // Debug: log quantum actions in merge
+                        if all_binds_action.name.contains("quantum") {
+                            println!(
+                                "Merging action: {} with {} bindings, display_label: {}",
+                                all_binds_action.name,
+                                bindings.len(),
+                                display_label
+                            );
+                            for (i, binding) in bindings.iter().enumerate() {
+                                println!(
+                                    "  Binding {}: input='{}', type={}, is_default={}",
+                                    i, binding.input, binding.input_type, binding.is_default
+                                );
+                            }
+                        }
+
+                        MergedAction {
+                            name: all_binds_action.name.clone(),
+                            ui_label: display_label,
+                            ui_description: all_binds_action.ui_description.clone(),
+                            category: all_binds_action.category.clone(),
+                            is_customized,
+                            on_hold: all_binds_action.on_hold,
+                            bindings,
+                        }
+                    })
+                    .collect();
+
+                let display_map_label =
+                    self.resolve_label(&all_binds_map.ui_label, &all_binds_map.name);
+
+                MergedActionMap {
+                    name: all_binds_map.name.clone(),
+                    ui_label: display_map_label,
+                    ui_category: all_binds_map.ui_category.clone(),
+                    actions: merged_actions,
+                }
+            })
+            .collect();
+
+        MergedBindings {
+            action_maps: merged_maps,
+        }
+    }
+}
+
+/// The binding Star Citizen would actually use for an action
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct EffectiveBinding {
+    pub input: String,
+    pub display_name: String,
+    pub input_type: String,
+    pub device_instance: String,
+    pub is_default: bool,
+    pub activation_mode: String,
+}
+
+/// Get the raw device part of an input (e.g. "lalt+js1_button3" -> "js1", "kb_space" -> "kb")
+fn input_device_part(input: &str) -> Option<&str> {
+    input
+        .split('+')
+        .filter_map(|part| part.trim().split_once('_'))
+        .map(|(device, _)| device)
+        .find(|device| device_prefix_type(device).is_some())
+}
+
+/// Device type for the device part of an input, instance number optional
+/// Mice are written both as "mouse1_" and the short "mo_"
+/// e.g. "js2" -> Joystick, "mo" -> Mouse, "mwheel" -> None
+fn device_prefix_type(device: &str) -> Option<InputType> {
+    [
+        ("kb", InputType::Keyboard),
+        ("mouse", InputType::Mouse),
+        ("mo", InputType::Mouse),
+        ("js", InputType::Joystick),
+        ("gp", InputType::Gamepad),
+    ]
+    .into_iter()
+    .find(|(prefix, _)| {
+        device
+            .strip_prefix(prefix)
+            .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+    })
+    .map(|(_, input_type)| input_type)
+}
+
+/// Joystick/gamepad instances SC will assign at most
+pub const DEFAULT_MAX_DEVICE_INSTANCES: u32 = 8;
+
+/// A device type and SC instance number, e.g. js2 -> (Joystick, 2)
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DeviceInstance {
+    pub input_type: InputType,
+    pub instance: u32,
+}
+
+impl DeviceInstance {
+    /// Device an input is on; "kb_x" and "mo_x" count as instance 1
+    pub fn of_input(input: &str) -> Option<DeviceInstance> {
+        let device = input_device_part(input)?;
+        let input_type = device_prefix_type(device)?;
+        let instance = device
+            .trim_start_matches(|c: char| c.is_alphabetic())
+            .parse::<u32>()
+            .unwrap_or(1);
+        Some(DeviceInstance {
+            input_type,
+            instance,
+        })
+    }
+
+    /// Suffix naming the device in split profile files: "keyboard", "mouse", "joystick2", ...
+    /// Keyboard and mouse only have one instance in SC, so they aren't numbered
+    pub fn file_suffix(&self) -> String {
+        match self.input_type {
+            InputType::Keyboard => "keyboard".to_string(),
+            InputType::Mouse => "mouse".to_string(),
+            InputType::Joystick => format!("joystick{}", self.instance),
+            InputType::Gamepad => format!("gamepad{}", self.instance),
+            InputType::Unknown => format!("device{}", self.instance),
+        }
+    }
+}
+
+/// Escape text for use inside a double-quoted XML attribute
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Attribute value with XML entities resolved; a value with a stray '&' that isn't a
+/// valid entity (hand-edited files) is kept as written
+fn attr_text(attr: &quick_xml::events::attributes::Attribute) -> String {
+    attr.unescape_value()
+        .map(|value| value.into_owned())
+        .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Comment written after the XML declaration of exported profiles, SC ignores it on import
+/// e.g. "<!-- Generated by SC-Binding-Utility v0.5.0 on 2025-01-31 18:04:12 -->"
+fn generated_by_comment() -> String {
+    format!(
+        "<!-- Generated by SC-Binding-Utility v{} on {} -->\n",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+/// Flip a directional axis input between "_positive" and "_negative", modifiers allowed
+/// e.g. "js1_axis2_positive" -> "js1_axis2_negative", "lalt+js1_axis2_negative" -> "lalt+js1_axis2_positive"
+pub fn toggle_axis_direction(input: &str) -> Result<String, String> {
+    let mut toggled = false;
+
+    let parts: Vec<String> = input
+        .split('+')
+        .map(|part| {
+            if let Some(base) = part.strip_suffix("_positive") {
+                toggled = true;
+                format!("{}_negative", base)
+            } else if let Some(base) = part.strip_suffix("_negative") {
+                toggled = true;
+                format!("{}_positive", base)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect();
+
+    if toggled {
+        Ok(parts.join("+"))
+    } else {
+        Err(format!("Not a directional axis binding: {}", input))
+    }
+}
+
+/// DirectInput axes that SC names instead of numbering, in axis order: "x" is axis 1
+/// and "slider2" axis 8
+const JOYSTICK_NAMED_AXES: [&str; 8] =
+    ["x", "y", "z", "rotx", "roty", "rotz", "slider1", "slider2"];
+
+/// Joystick key of a binding without its device prefix, modifiers allowed
+/// e.g. "lalt+js1_button3" -> "button3"
+fn joystick_input_key(input: &str) -> Option<String> {
+    Some(
+        input
+            .split('+')
+            .map(|part| part.trim().to_lowercase())
+            .find(|part| part.starts_with("js"))?
+            .split_once('_')?
+            .1
+            .to_string(),
+    )
+}
+
+/// Numbered joystick input of a binding, modifiers allowed
+/// e.g. "js1_button50" -> ("button", 50), "js2_axis3_positive" -> ("axis", 3), "js1_hat1_up" -> ("hat", 1)
+fn joystick_input_index(input: &str) -> Option<(&'static str, usize)> {
+    let key = joystick_input_key(input)?;
+    ["button", "axis", "hat"].into_iter().find_map(|kind| {
+        let rest = key.strip_prefix(kind)?;
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse::<usize>().ok().map(|index| (kind, index))
+    })
+}
+
+/// Normalize an input for comparison: lowercase, instance numbers filled in
+/// ("kb_a" -> "kb1_a") and keys sorted so modifier order doesn't matter
+/// ("kb1_lalt+c", "kb1_c+lalt" and "lalt+kb1_c" all become "kb1_c+lalt")
+pub fn normalize_input(input: &str) -> String {
+    let lower = input.trim().to_lowercase();
+    let device = input_device_part(&lower).map(|d| d.to_string());
+
+    let mut keys: Vec<&str> = lower
+        .split('+')
+        .map(|part| {
+            let part = part.trim();
+            match (&device, part.split_once('_')) {
+                (Some(device), Some((prefix, key))) if prefix == device => key,
+                _ => part,
+            }
+        })
+        .filter(|key| !key.is_empty())
+        .collect();
+    keys.sort();
+
+    match input_device_instance(&lower) {
+        Some(instance) => format!("{}_{}", instance, keys.join("+")),
+        None => keys.join("+"),
+    }
+}
+
+/// Get the device instance an input belongs to (e.g. "js1_button3" -> "js1")
+/// Inputs without an instance number ("kb_space") are treated as instance 1
+fn input_device_instance(input: &str) -> Option<String> {
+    input_device_part(input).map(|device| {
+        if device.ends_with(|c: char| c.is_ascii_digit()) {
+            device.to_string()
+        } else {
+            format!("{}1", device)
+        }
+    })
+}
+
+/// Device instance and physical control an input targets, modifiers ignored, both lowercase
+/// e.g. "LALT+js2_button12" -> ("js2", "button12"), "js_hat1_up" -> ("js1", "hat1_up")
+fn input_control(input: &str) -> Option<(String, String)> {
+    let device = input_device_instance(&input.to_lowercase())?;
+    let control = input
+        .split('+')
+        .filter_map(|part| part.trim().split_once('_'))
+        .find(|(prefix, _)| device_prefix_type(&prefix.to_lowercase()).is_some())
+        .map(|(_, control)| control.trim().to_lowercase())?;
+
+    if control.is_empty() {
+        None
+    } else {
+        Some((device, control))
+    }
+}
+
+impl MergedBindings {
+    /// Resolve the binding that would fire in game for an action
+    /// Only bindings on connected device instances (e.g. "kb1", "js1") are considered,
+    /// and user overrides win over AllBinds defaults
+    pub fn effective_binding(
+        &self,
+        action_map_name: &str,
+        action_name: &str,
+        connected_instances: &[String],
+    ) -> Option<EffectiveBinding> {
+        let action = self
+            .action_maps
+            .iter()
+            .find(|am| am.name == action_map_name)?
+            .actions
+            .iter()
+            .find(|a| a.name == action_name)?;
+
+        let candidates: Vec<(&MergedBinding, String)> = action
+            .bindings
+            .iter()
+            .filter(|b| b.input_type != "Unknown")
+            .filter_map(|b| input_device_instance(&b.input).map(|instance| (b, instance)))
+            .filter(|(_, instance)| connected_instances.iter().any(|c| c == instance))
+            .collect();
+
+        let (binding, instance) = candidates
+            .iter()
+            .find(|(b, _)| !b.is_default)
+            .or_else(|| candidates.first())?;
+
+        Some(EffectiveBinding {
+            input: binding.input.clone(),
+            display_name: binding.display_name.clone(),
+            input_type: binding.input_type.clone(),
+            device_instance: instance.clone(),
+            is_default: binding.is_default,
+            activation_mode: binding.activation_mode.clone(),
+        })
+    }
+
+    /// Report which of the given (actionmap, action) pairs have any binding, custom or
+    /// default, on the device matching `device_prefix` (e.g. "js1", "js", "kb1")
+    pub fn binding_coverage(
+        &self,
+        device_prefix: &str,
+        essential_actions: &[(String, String)],
+    ) -> CoverageReport {
+        let mut report = CoverageReport {
+            device_prefix: device_prefix.to_string(),
+            bound: Vec::new(),
+            missing: Vec::new(),
+        };
+
+        for (action_map_name, action_name) in essential_actions {
+            let action = self
+                .action_maps
+                .iter()
+                .find(|am| &am.name == action_map_name)
+                .and_then(|am| am.actions.iter().find(|a| &a.name == action_name));
+
+            let entry = CoverageEntry {
+                action_map_name: action_map_name.clone(),
+                action_name: action_name.clone(),
+                action_label: action
+                    .map(|a| a.ui_label.clone())
+                    .unwrap_or_else(|| format_display_name(action_name)),
+            };
+
+            let is_bound = action.is_some_and(|a| {
+                a.bindings.iter().any(|b| {
+                    b.input_type != "Unknown" && input_matches_device(&b.input, device_prefix)
+                })
+            });
+
+            if is_bound {
+                report.bound.push(entry);
+            } else {
+                report.missing.push(entry);
+            }
+        }
+
+        report
+    }
+
+    /// Count bound inputs per device ("Keyboard", "Joystick 1", ...) and per category
+    /// Cleared placeholders are not counted
+    pub fn binding_statistics(&self) -> BindingStats {
+        let mut stats = BindingStats {
+            per_device: HashMap::new(),
+            per_category: HashMap::new(),
+            total_customized: 0,
+            total_default: 0,
+        };
+
+        for action_map in &self.action_maps {
+            let category = if action_map.ui_category.is_empty() {
+                "Uncategorized".to_string()
+            } else {
+                action_map.ui_category.clone()
+            };
+
+            for binding in action_map.actions.iter().flat_map(|a| &a.bindings) {
+                let Some(device) = stats_device_label(binding) else {
+                    continue;
+                };
+
+                *stats.per_device.entry(device).or_insert(0) += 1;
+                *stats.per_category.entry(category.clone()).or_insert(0) += 1;
+                if binding.is_default {
+                    stats.total_default += 1;
+                } else {
+                    stats.total_customized += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Reverse lookup from normalized input to the (action_map, action) pairs bound to it
+    /// Cleared placeholders are skipped; an action is listed once per input
+    pub fn build_input_index(&self) -> HashMap<String, Vec<(String, String)>> {
+        let mut index: HashMap<String, Vec<(String, String)>> = HashMap::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for binding in &action.bindings {
+                    if binding.input_type == "Unknown" {
+                        continue;
+                    }
+
+                    let entry = (action_map.name.clone(), action.name.clone());
+                    let actions = index.entry(normalize_input(&binding.input)).or_default();
+                    if !actions.contains(&entry) {
+                        actions.push(entry);
+                    }
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Every (action_map, action, input) bound to one physical control, with or without
+    /// modifiers: `kind` is "button", "hat" or "axis" and `number` its index, so
+    /// ("js2", "button", 12) matches "js2_button12" and "lalt+js2_button12" but not "js2_button2"
+    /// Axes also match their named form, so ("js1", "axis", 6) finds "js1_rotz"
+    pub fn usages_of_control(
+        &self,
+        device_instance: &str,
+        kind: &str,
+        number: u32,
+    ) -> Vec<(String, String, String)> {
+        let device_instance = device_instance.trim().to_lowercase();
+        let kind = kind.to_lowercase();
+        let mut targets = vec![format!("{}{}", kind, number)];
+        if kind == "axis" {
+            if let Some(name) = (number as usize)
+                .checked_sub(1)
+                .and_then(|index| JOYSTICK_NAMED_AXES.get(index))
+            {
+                targets.push(name.to_string());
+            }
+        }
+        let mut usages = Vec::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for binding in &action.bindings {
+                    if binding.input_type == "Unknown" {
+                        continue;
+                    }
+
+                    let matches = input_control(&binding.input).is_some_and(|(device, control)| {
+                        device == device_instance
+                            && targets.iter().any(|target| {
+                                control
+                                    .strip_prefix(target.as_str())
+                                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+                            })
+                    });
+                    if matches {
+                        usages.push((
+                            action_map.name.clone(),
+                            action.name.clone(),
+                            binding.input.trim().to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        usages
+    }
+
+    /// Control ids `{kind}1..={kind}{max}` on a device instance that nothing is bound to,
+    /// e.g. ("js1", "button", 6) with buttons 1, 2 and 5 used -> button3, button4, button6
+    /// A hat counts as used when any of its directions is bound
+    pub fn free_controls(&self, device_instance: &str, kind: &str, max: u32) -> Vec<String> {
+        let device_instance = device_instance.trim().to_lowercase();
+        let used: Vec<String> = self
+            .action_maps
+            .iter()
+            .flat_map(|am| &am.actions)
+            .flat_map(|a| &a.bindings)
+            .filter(|b| b.input_type != "Unknown")
+            .filter_map(|b| input_control(&b.input))
+            .filter(|(device, _)| *device == device_instance)
+            .filter_map(|(_, control)| control.split('_').next().map(|c| c.to_string()))
+            .collect();
+
+        (1..=max)
+            .map(|number| format!("{}{}", kind.to_lowercase(), number))
+            .filter(|control| !used.contains(control))
+            .collect()
+    }
+
+    /// Highest button, axis and hat number bound on a device instance, 0 when none is used
+    /// Named axes count as their axis number, so "js1_rotz" makes `axes` at least 6
+    pub fn max_indices(&self, device_instance: &str) -> MaxIndices {
+        let device_instance = device_instance.trim().to_lowercase();
+        let mut max = MaxIndices::default();
+
+        for binding in self
+            .action_maps
+            .iter()
+            .flat_map(|am| &am.actions)
+            .flat_map(|a| &a.bindings)
+            .filter(|b| b.input_type != "Unknown")
+        {
+            if input_device_instance(&binding.input.to_lowercase()).as_deref()
+                != Some(device_instance.as_str())
+            {
+                continue;
+            }
+
+            let named_axis = || {
+                let key = joystick_input_key(&binding.input)?;
+                let control = key.split('_').next().unwrap_or("");
+                let pos = JOYSTICK_NAMED_AXES
+                    .iter()
+                    .position(|name| *name == control)?;
+                Some(("axis", pos + 1))
+            };
+            let Some((kind, index)) = joystick_input_index(&binding.input).or_else(named_axis)
+            else {
+                continue;
+            };
+            let slot = match kind {
+                "button" => &mut max.buttons,
+                "axis" => &mut max.axes,
+                _ => &mut max.hats,
+            };
+            *slot = (*slot).max(index as u32);
+        }
+
+        max
+    }
+
+    /// Keep only bindings whose input_type ("Keyboard", "Joystick", ...) is in `input_types`
+    /// Actions left without bindings, and action maps left without actions, are dropped
+    pub fn filter_input_types(mut self, input_types: &[String]) -> MergedBindings {
+        for action_map in &mut self.action_maps {
+            for action in &mut action_map.actions {
+                action
+                    .bindings
+                    .retain(|b| input_types.contains(&b.input_type));
+            }
+            action_map.actions.retain(|a| !a.bindings.is_empty());
+        }
+        self.action_maps.retain(|am| !am.actions.is_empty());
+
+        self
+    }
+}
+
+/// Highest control numbers used on one device, for sizing the binding grid
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct MaxIndices {
+    pub buttons: u32,
+    pub axes: u32,
+    pub hats: u32,
+}
+
+/// Device label used for binding statistics, None for cleared or unknown inputs
+fn stats_device_label(binding: &MergedBinding) -> Option<String> {
+    let instance = || {
+        input_device_instance(&binding.input)
+            .map(|d| {
+                d.trim_start_matches(|c: char| c.is_alphabetic())
+                    .to_string()
+            })
+            .unwrap_or_else(|| "1".to_string())
+    };
+
+    match binding.input_type.as_str() {
+        "Keyboard" => Some("Keyboard".to_string()),
+        "Mouse" => Some("Mouse".to_string()),
+        "Joystick" => Some(format!("Joystick {}", instance())),
+        "Gamepad" => Some(format!("Gamepad {}", instance())),
+        _ => None,
+    }
+}
+
+/// Binding counts for the dashboard
+#[derive(Debug, Serialize, Clone)]
+pub struct BindingStats {
+    pub per_device: HashMap<String, usize>,
+    pub per_category: HashMap<String, usize>,
+    pub total_customized: usize,
+    pub total_default: usize,
+}
+
+/// SC gamepad controls and the joystick control each becomes when the pad is used as a
+/// joystick, buttons in the XInput order input detection uses
+/// Triggers read as their own axes; their digital "_btn" variants have no joystick equivalent
+pub const GAMEPAD_JOYSTICK_CONTROLS: &[(&str, &str)] = &[
+    ("a", "button1"),
+    ("b", "button2"),
+    ("x", "button3"),
+    ("y", "button4"),
+    ("shoulderl", "button5"),
+    ("shoulderr", "button6"),
+    ("back", "button7"),
+    ("start", "button8"),
+    ("thumbl", "button9"),
+    ("thumbr", "button10"),
+    ("dpad_up", "hat1_up"),
+    ("dpad_down", "hat1_down"),
+    ("dpad_left", "hat1_left"),
+    ("dpad_right", "hat1_right"),
+    ("thumblx", "x"),
+    ("thumbly", "y"),
+    ("thumbrx", "rotx"),
+    ("thumbry", "roty"),
+    ("triggerl_axis", "z"),
+    ("triggerr_axis", "rotz"),
+];
+
+/// The same control on the other device type, None when it has no equivalent
+/// Numbered gamepad controls ("button3", "hat1_up", "axis2_positive", as input detection
+/// writes them) keep their number on a joystick
+fn convert_control(control: &str, to_joystick: bool) -> Option<String> {
+    let control = control.trim().to_lowercase();
+    if control.is_empty() {
+        // Cleared placeholder, e.g. "gp1_ "
+        return Some(control);
+    }
+
+    let mapped = GAMEPAD_JOYSTICK_CONTROLS
+        .iter()
+        .find_map(|(gamepad, joystick)| {
+            let (from, to) = if to_joystick {
+                (gamepad, joystick)
+            } else {
+                (joystick, gamepad)
+            };
+            (control == *from).then(|| to.to_string())
+        });
+    if mapped.is_some() || !to_joystick {
+        return mapped;
+    }
+
+    let numbered = ["button", "hat", "axis"].iter().any(|kind| {
+        control
+            .strip_prefix(kind)
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+    });
+    numbered.then_some(control)
+}
+
+/// Rewrite one input from `from_prefix` onto `to_prefix`: None when it isn't on that device
+/// type, Some(None) when one of its controls has no equivalent there
+fn convert_input_device_type(
+    input: &str,
+    from_prefix: &str,
+    to_prefix: &str,
+    instance_map: &HashMap<u32, u32>,
+) -> Option<Option<String>> {
+    let device = input_device_part(input)?;
+    let number = device.strip_prefix(from_prefix)?;
+    // "gp_" has no instance number and means instance 1
+    let instance = (if number.is_empty() { "1" } else { number })
+        .parse::<u32>()
+        .ok()?;
+    let target = instance_map.get(&instance).copied().unwrap_or(instance);
+
+    let to_joystick = to_prefix == "js";
+    let device_prefix = format!("{}_", device);
+    let mut parts = Vec::new();
+    for part in input.split('+') {
+        let converted = match part.strip_prefix(&device_prefix) {
+            Some(control) => convert_control(control, to_joystick)
+                .map(|control| format!("{}{}_{}", to_prefix, target, control)),
+            // Keyboard modifiers stay, other bare parts are controls of the same device
+            None if MODIFIER_KEYS.contains(&part.trim().to_lowercase().as_str()) => {
+                Some(part.to_string())
+            }
+            None => convert_control(part, to_joystick),
+        };
+        match converted {
+            Some(converted) => parts.push(converted),
+            None => return Some(None),
+        }
+    }
+
+    Some(Some(parts.join("+")))
+}
+
+/// Result of moving bindings between device types, skipped rebinds as (action_map, action, input)
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct DeviceConversion {
+    pub converted: usize,
+    pub unmappable: Vec<(String, String, String)>, // A control the target device type doesn't have
+    pub collisions: Vec<(String, String, String)>, // The action already has a rebind on the target instance
+}
+
+/// Result of renaming an action map in a profile
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct RenameOutcome {
+    pub moved: usize,
+    pub dropped: Vec<(String, String)>, // (action, input) whose device instance the target already had bound
+}
+
+impl AllBinds {
+    /// Renames found from the profile itself, as (old name, new name): an actionmap this
+    /// AllBinds.xml no longer has, whose actions now all exist in exactly one other actionmap
+    pub fn inferred_migrations(&self, profile: &ActionMaps) -> Vec<(String, String)> {
+        let mut migrations = Vec::new();
+
+        for action_map in &profile.action_maps {
+            if action_map.actions.is_empty()
+                || self.action_maps.iter().any(|am| am.name == action_map.name)
+            {
+                continue;
+            }
+
+            let candidates: Vec<&AllBindsActionMap> = self
+                .action_maps
+                .iter()
+                .filter(|am| {
+                    action_map
+                        .actions
+                        .iter()
+                        .all(|a| am.actions.iter().any(|known| known.name == a.name))
+                })
+                .collect();
+            if let [target] = candidates.as_slice() {
+                migrations.push((action_map.name.clone(), target.name.clone()));
+            }
+        }
+
+        migrations
+    }
+}
+
+/// An action present in only one of two AllBinds.xml files
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AllBindsActionRef {
+    pub action_map_name: String,
+    pub action_name: String,
+}
+
+/// A device default that differs between two AllBinds.xml files; None when there is no default
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ChangedDefault {
+    pub action_map_name: String,
+    pub action_name: String,
+    pub device: InputType,
+    pub old_default: Option<String>,
+    pub new_default: Option<String>,
+}
+
+/// Differences between an older AllBinds.xml and the loaded one
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AllBindsDiff {
+    pub added: Vec<AllBindsActionRef>,
+    pub removed: Vec<AllBindsActionRef>,
+    pub changed_defaults: Vec<ChangedDefault>,
+}
+
+impl AllBinds {
+    fn find_action(&self, action_map_name: &str, action_name: &str) -> Option<&AllBindsAction> {
+        self.action_maps
+            .iter()
+            .find(|am| am.name == action_map_name)?
+            .actions
+            .iter()
+            .find(|a| a.name == action_name)
+    }
+
+    /// Compare against an older AllBinds.xml: actions new in this one, actions it dropped,
+    /// and actions in both whose default bindings changed. Lists follow each file's order
+    pub fn diff_from(&self, old: &AllBinds) -> AllBindsDiff {
+        let mut diff = AllBindsDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed_defaults: Vec::new(),
+        };
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                let Some(old_action) = old.find_action(&action_map.name, &action.name) else {
+                    diff.added.push(AllBindsActionRef {
+                        action_map_name: action_map.name.clone(),
+                        action_name: action.name.clone(),
+                    });
+                    continue;
+                };
+
+                for device in [
+                    InputType::Keyboard,
+                    InputType::Mouse,
+                    InputType::Joystick,
+                    InputType::Gamepad,
+                ] {
+                    let old_default = old_action.default_input(&device);
+                    let new_default = action.default_input(&device);
+                    if old_default != new_default {
+                        diff.changed_defaults.push(ChangedDefault {
+                            action_map_name: action_map.name.clone(),
+                            action_name: action.name.clone(),
+                            device,
+                            old_default: old_default.map(str::to_string),
+                            new_default: new_default.map(str::to_string),
+                        });
+                    }
+                }
+            }
+        }
+
+        for action_map in &old.action_maps {
+            for action in &action_map.actions {
+                if self.find_action(&action_map.name, &action.name).is_none() {
+                    diff.removed.push(AllBindsActionRef {
+                        action_map_name: action_map.name.clone(),
+                        action_name: action.name.clone(),
+                    });
+                }
+            }
+        }
+
+        diff
+    }
+}
+
+/// A device prefix SC no longer accepts ("xi1" in "xi1_a"); files using it fail to import
+/// Keys are never matched, so a key that happens to share the name is left alone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeprecatedToken {
+    pub token: &'static str,
+    pub replacement: Option<&'static str>, // None when there is no equivalent to suggest
+}
+
+/// Tokens removed across SC patches, add entries as SC drops them
+pub const DEPRECATED_TOKENS: &[DeprecatedToken] = &[
+    // XInput controllers were "xi" before gamepads got their own "gp" prefix
+    DeprecatedToken {
+        token: "xi1",
+        replacement: Some("gp1"),
+    },
+    DeprecatedToken {
+        token: "xi",
+        replacement: Some("gp1"),
+    },
+];
+
+/// A rebind using a deprecated token
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DeprecatedBinding {
+    pub action_map_name: String,
+    pub action_name: String,
+    pub input: String,
+    pub token: String,
+    pub suggested_input: Option<String>, // The input with every known replacement applied
+}
+
+/// Deprecated device prefixes in an input, and the input with the known replacements applied
+/// Only the part before the first '_' of each '+' piece is checked; prefixes without a
+/// replacement are left as they are
+fn replace_deprecated_tokens<'a>(
+    input: &str,
+    table: &'a [DeprecatedToken],
+) -> (Vec<&'a DeprecatedToken>, String) {
+    let mut found = Vec::new();
+
+    let parts: Vec<String> = input
+        .split('+')
+        .map(|part| {
+            let Some((device, key)) = part.split_once('_') else {
+                return part.to_string();
+            };
+            match table.iter().find(|t| t.token == device.trim()) {
+                Some(deprecated) => {
+                    found.push(deprecated);
+                    format!("{}_{}", deprecated.replacement.unwrap_or(device), key)
+                }
+                None => part.to_string(),
+            }
+        })
+        .collect();
+
+    (found, parts.join("+"))
+}
+
+impl ActionMaps {
+    /// Find rebinds using a token from `table`, one entry per deprecated token found
+    pub fn find_deprecated_tokens(&self, table: &[DeprecatedToken]) -> Vec<DeprecatedBinding> {
+        let mut deprecated = Vec::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for rebind in &action.rebinds {
+                    let (found, replaced) = replace_deprecated_tokens(&rebind.input, table);
+                    let suggested_input = (replaced != rebind.input).then_some(replaced);
+
+                    for token in found {
+                        deprecated.push(DeprecatedBinding {
+                            action_map_name: action_map.name.clone(),
+                            action_name: action.name.clone(),
+                            input: rebind.input.clone(),
+                            token: token.token.to_string(),
+                            suggested_input: suggested_input.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        deprecated
+    }
+
+    /// Rewrite deprecated tokens that have a known replacement
+    /// Returns the number of rebinds changed
+    pub fn apply_token_replacements(&mut self, table: &[DeprecatedToken]) -> usize {
+        let mut changed = 0;
+
+        for rebind in self
+            .action_maps
+            .iter_mut()
+            .flat_map(|am| am.actions.iter_mut())
+            .flat_map(|a| a.rebinds.iter_mut())
+        {
+            let (_, replaced) = replace_deprecated_tokens(&rebind.input, table);
+            if replaced != rebind.input {
+                rebind.input = replaced;
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+}
+
+/// optionsVersion the bundled AllBinds.xml's SC build writes and expects
+pub const EXPECTED_OPTIONS_VERSION: u32 = 2;
+
+/// rebindVersion the bundled AllBinds.xml's SC build writes and expects
+pub const EXPECTED_REBIND_VERSION: u32 = 2;
+
+/// Result of comparing a profile's format versions with what SC expects
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct VersionCheck {
+    pub file_rebind_version: Option<u32>,
+    pub file_options_version: Option<u32>,
+    pub expected: u32, // rebindVersion
+    pub expected_options_version: u32,
+    pub compatible: bool,
+    pub warning: Option<String>, // Shown to the user when not compatible
+}
+
+impl ActionMaps {
+    /// Compare optionsVersion/rebindVersion with the versions SC expects
+    /// A missing attribute isn't a mismatch; SC falls back to its current format
+    pub fn check_version_compatibility(&self) -> VersionCheck {
+        let mut mismatches = Vec::new();
+        if let Some(version) = self
+            .rebind_version
+            .filter(|v| *v != EXPECTED_REBIND_VERSION)
+        {
+            mismatches.push(format!(
+                "rebindVersion {} (expected {})",
+                version, EXPECTED_REBIND_VERSION
+            ));
+        }
+        if let Some(version) = self
+            .options_version
+            .filter(|v| *v != EXPECTED_OPTIONS_VERSION)
+        {
+            mismatches.push(format!(
+                "optionsVersion {} (expected {})",
+                version, EXPECTED_OPTIONS_VERSION
+            ));
+        }
+
+        VersionCheck {
+            file_rebind_version: self.rebind_version,
+            file_options_version: self.options_version,
+            expected: EXPECTED_REBIND_VERSION,
+            expected_options_version: EXPECTED_OPTIONS_VERSION,
+            compatible: mismatches.is_empty(),
+            warning: (!mismatches.is_empty()).then(|| {
+                format!(
+                    "This profile was written for a different SC version: {}. Star Citizen may ignore it on import",
+                    mismatches.join(", ")
+                )
+            }),
+        }
+    }
+}
+
+/// Essential flight actions (actionmap, action) used for coverage checks
+pub const ESSENTIAL_FLIGHT_ACTIONS: [(&str, &str); 20] = [
+    ("spaceship_movement", "v_pitch"),
+    ("spaceship_movement", "v_yaw"),
+    ("spaceship_movement", "v_roll"),
+    ("spaceship_movement", "v_strafe_forward"),
+    ("spaceship_movement", "v_strafe_back"),
+    ("spaceship_movement", "v_strafe_left"),
+    ("spaceship_movement", "v_strafe_right"),
+    ("spaceship_movement", "v_strafe_up"),
+    ("spaceship_movement", "v_strafe_down"),
+    ("spaceship_movement", "v_afterburner"),
+    ("spaceship_movement", "v_space_brake"),
+    ("spaceship_movement", "v_toggle_landing_system"),
+    ("spaceship_weapons", "v_weapon_preset_fire_guns0"),
+    ("spaceship_missiles", "v_weapon_launch_missile"),
+    (
+        "spaceship_defensive",
+        "v_weapon_countermeasure_decoy_launch",
+    ),
+    ("spaceship_targeting", "v_target_lock_selected"),
+    ("seat_general", "v_toggle_quantum_mode"),
+    ("spaceship_quantum", "v_toggle_qdrive_engagement"),
+    ("spaceship_power", "v_power_toggle"),
+    ("spaceship_general", "v_flightready"),
+];
+
+/// An action checked by a coverage report
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CoverageEntry {
+    pub action_map_name: String,
+    pub action_name: String,
+    pub action_label: String,
+}
+
+/// Default number of bindings SC keeps for a single action
+pub const DEFAULT_MAX_BINDINGS_PER_ACTION: usize = 4;
+
+/// An action with more bindings than SC keeps
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct OverboundAction {
+    pub action_map_name: String,
+    pub action_name: String,
+    pub binding_count: usize,
+    pub excess_inputs: Vec<String>,
+}
+
+/// An action whose every binding the user cleared
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct UnboundAction {
+    pub action_map_name: String,
+    pub action_map_label: String,
+    pub action_name: String,
+    pub action_label: String,
+    pub cleared_inputs: Vec<String>, // The placeholders as written, e.g. "kb1_ ", "js1_ "
+}
+
+/// Containers in a profile that hold no rebinds, as (action_map, action) for actions
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct EmptyContainers {
+    pub action_maps: Vec<String>,
+    pub actions: Vec<(String, String)>,
+}
+
+/// Button/axis/hat counts reported by the device on a joystick instance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceCapabilities {
+    pub button_count: usize,
+    pub axis_count: usize,
+    pub hat_count: usize,
+}
+
+/// A joystick binding that refers to a button, axis or hat the device doesn't have
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct OutOfRangeBinding {
+    pub action_map_name: String,
+    pub action_name: String,
+    pub input: String,
+    pub kind: String, // "button", "axis" or "hat"
+    pub index: usize,
+    pub available: usize,
+}
+
+/// An input bound more than once within the same action
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DuplicateBinding {
+    pub action_map_name: String,
+    pub action_name: String,
+    pub input: String,
+    pub count: usize,
+}
+
+/// Which essential actions have a binding on a given device
+#[derive(Debug, Serialize, Clone)]
+pub struct CoverageReport {
+    pub device_prefix: String,
+    pub bound: Vec<CoverageEntry>,
+    pub missing: Vec<CoverageEntry>,
+}
+
+/// Check if an input is on the given device
+/// "js1" matches that instance only, while "js" matches any joystick instance
+fn input_matches_device(input: &str, device_prefix: &str) -> bool {
+    let Some(instance) = input_device_instance(input) else {
+        return false;
+    };
+
+    if device_prefix.ends_with(|c: char| c.is_ascii_digit()) {
+        instance == device_prefix
+    } else {
+        instance
+            .strip_prefix(device_prefix)
+            .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+    }
+}
+
+/// Placeholder for the device instance in role-relative template inputs
+/// e.g. "js2_button3" saved for the "throttle" role becomes "{instance}_button3"
+pub const TEMPLATE_INSTANCE_PLACEHOLDER: &str = "{instance}";
+
+/// A device role in a shareable template (e.g. "primary_stick", "throttle")
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateRole {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A binding stored relative to a device role instead of a fixed instance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoleBinding {
+    pub action_map: String,
+    pub action: String,
+    /// Role the input belongs to; None for inputs kept as-is (keyboard, unassigned devices)
+    #[serde(default)]
+    pub role: Option<String>,
+    pub input: String,
+    #[serde(default)]
+    pub multi_tap: Option<u32>,
+    #[serde(default)]
+    pub multi_tap_block: Option<bool>,
+    #[serde(default)]
+    pub press_trigger_threshold: Option<u32>,
+    #[serde(default)]
+    pub activation_mode: String,
+}
+
+/// Device-agnostic template: bindings keyed by role so they survive device swaps
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateV2 {
+    pub roles: Vec<TemplateRole>,
+    pub bindings: Vec<RoleBinding>,
+}
+
+/// Replace the device instance in an input with another (or the placeholder)
+/// Only the device part is touched, so modifiers in any position are preserved
+fn replace_device_instance(input: &str, from: &str, to: &str) -> String {
+    let from_prefix = format!("{}_", from);
+    input
+        .split('+')
+        .map(|part| match part.strip_prefix(&from_prefix) {
+            Some(rest) => format!("{}_{}", to, rest),
+            None => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+impl TemplateV2 {
+    /// Build a template from a profile, using `instance_roles` (e.g. "js1" -> "primary_stick")
+    /// to make inputs on those device instances role-relative
+    pub fn from_action_maps(
+        bindings: &ActionMaps,
+        roles: Vec<TemplateRole>,
+        instance_roles: &HashMap<String, String>,
+    ) -> Self {
+        let mut role_bindings = Vec::new();
+
+        for action_map in &bindings.action_maps {
+            for action in &action_map.actions {
+                for rebind in &action.rebinds {
+                    let role = input_device_instance(&rebind.input)
+                        .and_then(|instance| instance_roles.get(&instance));
+                    let device_part = input_device_part(&rebind.input);
+
+                    let (role, input) = match (role, device_part) {
+                        (Some(role), Some(device_part)) => (
+                            Some(role.clone()),
+                            replace_device_instance(
+                                &rebind.input,
+                                device_part,
+                                TEMPLATE_INSTANCE_PLACEHOLDER,
+                            ),
+                        ),
+                        _ => (None, rebind.input.clone()),
+                    };
+
+                    role_bindings.push(RoleBinding {
+                        action_map: action_map.name.clone(),
+                        action: action.name.clone(),
+                        role,
+                        input,
+                        multi_tap: rebind.multi_tap,
+                        multi_tap_block: rebind.multi_tap_block,
+                        press_trigger_threshold: rebind.press_trigger_threshold,
+                        activation_mode: rebind.activation_mode.clone(),
+                    });
+                }
+            }
+        }
+
+        TemplateV2 {
+            roles,
+            bindings: role_bindings,
+        }
+    }
+
+    /// Resolve role-relative inputs to concrete device instances
+    /// Returns (actionmap, action, rebind) triples, or an error naming any unassigned role
+    pub fn resolve(
+        &self,
+        role_to_instance: &HashMap<String, String>,
+    ) -> Result<Vec<(String, String, Rebind)>, String> {
+        self.bindings
+            .iter()
+            .map(|binding| {
+                let input = match &binding.role {
+                    Some(role) => {
+                        let instance = role_to_instance.get(role).ok_or_else(|| {
+                            format!("No device instance assigned for role '{}'", role)
+                        })?;
+                        replace_device_instance(
+                            &binding.input,
+                            TEMPLATE_INSTANCE_PLACEHOLDER,
+                            instance,
+                        )
+                    }
+                    None => binding.input.clone(),
+                };
+
+                Ok((
+                    binding.action_map.clone(),
+                    binding.action.clone(),
+                    Rebind {
+                        input,
+                        multi_tap: binding.multi_tap,
+                        multi_tap_block: binding.multi_tap_block,
+                        press_trigger_threshold: binding.press_trigger_threshold,
+                        activation_mode: binding.activation_mode.clone(),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Apply the template to a profile, returning the number of bindings written
+    /// A template binding replaces what the profile had on that device instance, but several
+    /// template bindings on one instance (button1 and lalt+button2 on js1) are all kept
+    pub fn apply_to(
+        &self,
+        bindings: &mut ActionMaps,
+        role_to_instance: &HashMap<String, String>,
+    ) -> Result<usize, String> {
+        let resolved = self.resolve(role_to_instance)?;
+        let count = resolved.len();
+
+        let mut replaced: Vec<(String, String, Option<String>)> = Vec::new();
+        for (action_map, action, rebind) in resolved {
+            let key = (
+                action_map.clone(),
+                action.clone(),
+                input_device_instance(&rebind.input),
+            );
+            if replaced.contains(&key) {
+                bindings.add_rebind(&action_map, &action, rebind);
+            } else {
+                bindings.set_rebind(&action_map, &action, rebind);
+                replaced.push(key);
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+/// An action with effective bindings on more than one device type
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Ambiguity {
+    pub action_map: String,
+    pub action: String,
+    pub inputs: Vec<String>, // Every effective input, grouped by device type
+}
+
+impl MergedBindings {
+    /// Actions that fire from several device types at once (e.g. keyboard and joystick),
+    /// defaults included. Usually intended, but whichever device is used first wins in SC.
+    /// Several instances of one type (js1 and js2) aren't reported
+    pub fn find_multidevice_ambiguities(&self) -> Vec<Ambiguity> {
+        let mut ambiguities = Vec::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                let mut by_type: Vec<(&str, Vec<String>)> = Vec::new();
+                for binding in &action.bindings {
+                    if binding.input_type == "Unknown" {
+                        continue;
+                    }
+
+                    let input = binding.input.trim().to_string();
+                    match by_type
+                        .iter_mut()
+                        .find(|(input_type, _)| *input_type == binding.input_type)
+                    {
+                        Some((_, inputs)) => inputs.push(input),
+                        None => by_type.push((&binding.input_type, vec![input])),
+                    }
+                }
+
+                if by_type.len() > 1 {
+                    ambiguities.push(Ambiguity {
+                        action_map: action_map.name.clone(),
+                        action: action.name.clone(),
+                        inputs: by_type.into_iter().flat_map(|(_, inputs)| inputs).collect(),
+                    });
+                }
+            }
+        }
+
+        ambiguities
+    }
+}
+
+/// A flat row describing one binding, used for tabular exports
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BindingTableRow {
+    pub category: String,
+    pub action_map_label: String,
+    pub action_label: String,
+    pub device: String,
+    pub input: String,
+    pub display_name: String,
+}
+
+/// One action with its effective binding per device column, for spreadsheet analysis
+/// Several inputs for one column are joined with "; ", unbound columns are blank
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AnalysisRow {
+    pub category: String,
+    pub action_map: String,
+    pub action: String,
+    pub label: String,
+    pub keyboard: String,
+    pub mouse: String,
+    pub joystick1: String,
+    pub gamepad: String,
+    pub activation_mode: String, // First activation mode set on an effective binding
+    pub customized: bool,
+}
+
+impl MergedBindings {
+    /// One row per action, bound or not, with effective bindings split into device columns
+    /// Only js1 fills the joystick column; other joystick instances aren't shown
+    pub fn to_analysis_rows(&self) -> Vec<AnalysisRow> {
+        let mut rows = Vec::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                let mut row = AnalysisRow {
+                    category: action_map.ui_category.clone(),
+                    action_map: action_map.name.clone(),
+                    action: action.name.clone(),
+                    label: action.ui_label.clone(),
+                    keyboard: String::new(),
+                    mouse: String::new(),
+                    joystick1: String::new(),
+                    gamepad: String::new(),
+                    activation_mode: String::new(),
+                    customized: action.is_customized,
+                };
+
+                for binding in &action.bindings {
+                    let column = match binding.input_type.as_str() {
+                        "Keyboard" => &mut row.keyboard,
+                        "Mouse" => &mut row.mouse,
+                        "Joystick"
+                            if input_device_instance(&binding.input).as_deref() == Some("js1") =>
+                        {
+                            &mut row.joystick1
+                        }
+                        "Gamepad" => &mut row.gamepad,
+                        _ => continue,
+                    };
+                    if !column.is_empty() {
+                        column.push_str("; ");
+                    }
+                    column.push_str(binding.input.trim());
+
+                    if row.activation_mode.is_empty() {
+                        row.activation_mode = binding.activation_mode.clone();
+                    }
+                }
+
+                rows.push(row);
+            }
+        }
+
+        rows
+    }
+
+    /// Flatten the merged view into one row per bound input
+    /// Unbound placeholders and cleared bindings are skipped
+    pub fn to_table_rows(&self) -> Vec<BindingTableRow> {
+        let mut rows = Vec::new();
+
+        for action_map in &self.action_maps {
+            for action in &action_map.actions {
+                for binding in &action.bindings {
+                    if binding.input_type == "Unknown" {
+                        continue;
+                    }
+
+                    rows.push(BindingTableRow {
+                        category: action_map.ui_category.clone(),
+                        action_map_label: action_map.ui_label.clone(),
+                        action_label: action.ui_label.clone(),
+                        device: binding.input_type.clone(),
+                        input: binding.input.trim().to_string(),
+                        display_name: binding.display_name.clone(),
+                    });
+                }
+            }
+        }
+
+        rows
+    }
+
+    /// Group action maps under their UI category for navigation, with action counts
+    /// Categories and the maps inside them keep their AllBinds order
+    pub fn category_tree(&self) -> Vec<CategoryNode> {
+        let mut tree: Vec<CategoryNode> = Vec::new();
+
+        for action_map in &self.action_maps {
+            let entry = CategoryActionMap {
+                name: action_map.name.clone(),
+                label: action_map.ui_label.clone(),
+                action_count: action_map.actions.len(),
+            };
+
+            match tree
+                .iter_mut()
+                .find(|node| node.category == action_map.ui_category)
+            {
+                Some(node) => node.action_maps.push(entry),
+                None => tree.push(CategoryNode {
+                    category: action_map.ui_category.clone(),
+                    action_maps: vec![entry],
+                }),
+            }
+        }
+
+        tree
+    }
+}
+
+/// One UI category and the action maps it contains
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CategoryNode {
+    pub category: String,
+    pub action_maps: Vec<CategoryActionMap>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CategoryActionMap {
+    pub name: String,
+    pub label: String,
+    pub action_count: usize,
+}
+
+// Device selection struct for unbind profile generation
+#[derive(serde::Deserialize)]
+pub struct DeviceSelection {
+    pub keyboard: bool,
+    pub mouse: bool,
+    pub gamepad: bool,
+    pub joystick1: bool,
+    pub joystick2: bool,
+}
+
+/// Menu actions the unbind profile keeps on Escape instead of clearing
+const UNBIND_KEPT_ACTIONS: [&str; 2] = ["ui_toggle_pause", "ui_back"];
+
+/// How many default bindings an unbind profile would clear, per selected device
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct UnbindSummary {
+    pub per_device: HashMap<String, usize>, // keyed like DeviceSelection: "keyboard", "joystick1", ...
+}
+
+/// Count the AllBinds actions with a default for each selected device, i.e. the bindings
+/// the unbind profile disables. Both joystick instances count the joystick defaults
+pub fn summarize_unbind(all_binds: &AllBinds, devices: &DeviceSelection) -> UnbindSummary {
+    let selected = [
+        ("keyboard", devices.keyboard, InputType::Keyboard),
+        ("mouse", devices.mouse, InputType::Mouse),
+        ("gamepad", devices.gamepad, InputType::Gamepad),
+        ("joystick1", devices.joystick1, InputType::Joystick),
+        ("joystick2", devices.joystick2, InputType::Joystick),
+    ];
+
+    let actions: Vec<&AllBindsAction> = all_binds
+        .action_maps
+        .iter()
+        .flat_map(|am| am.actions.iter())
+        .filter(|a| !UNBIND_KEPT_ACTIONS.contains(&a.name.as_str()))
+        .collect();
+
+    let per_device = selected
+        .into_iter()
+        .filter(|(_, is_selected, _)| *is_selected)
+        .map(|(device, _, input_type)| {
+            let count = actions
+                .iter()
+                .filter(|a| a.default_input(&input_type).is_some())
+                .count();
+            (device.to_string(), count)
+        })
+        .collect();
+
+    UnbindSummary { per_device }
+}
+
+/// Generate an unbind profile XML that clears all bindings for selected devices
+pub fn generate_unbind_xml(
+    all_binds: &AllBinds,
+    devices: &DeviceSelection,
+) -> Result<String, String> {
+    use std::collections::HashSet;
+
+    let mut xml = String::new();
+
+    // XML declaration
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    // Root ActionMaps element
+    xml.push_str(&format!(
+        "<ActionMaps version=\"1\" optionsVersion=\"{}\" rebindVersion=\"{}\" profileName=\"UNBIND_ALL_DEVICES\">\n",
+        EXPECTED_OPTIONS_VERSION, EXPECTED_REBIND_VERSION
+    ));
+
+    // Collect all unique categories in order
+    let mut categories_ordered = Vec::new();
+    let mut seen_categories = HashSet::new();
+    for action_map in &all_binds.action_maps {
+        if !action_map.ui_category.is_empty() && !seen_categories.contains(&action_map.ui_category)
+        {
+            categories_ordered.push(action_map.ui_category.clone());
+            seen_categories.insert(action_map.ui_category.clone());
+        }
+    }
+
+    // Write CustomisationUIHeader with proper attributes
+    xml.push_str(" <CustomisationUIHeader label=\"UNBIND_ALL_DEVICES\" description=\"Clears all bindings for selected devices\" image=\"\">\n");
+
+    // Write devices section - order matters!
+    xml.push_str("  <devices>\n");
+    if devices.keyboard {
+        xml.push_str("   <keyboard instance=\"1\"/>\n");
+    }
+    if devices.mouse {
+        xml.push_str("   <mouse instance=\"1\"/>\n");
+    }
+    if devices.joystick1 {
+        xml.push_str("   <joystick instance=\"1\"/>\n");
+    }
+    if devices.joystick2 {
+        xml.push_str("   <joystick instance=\"2\"/>\n");
+    }
+    xml.push_str("  </devices>\n");
+
+    // Write categories section
+    // if !categories_ordered.is_empty() {
+    //     xml.push_str("  <categories>\n");
+    //     for category in &categories_ordered {
+    //         xml.push_str("   <category label=\"");
+    //         xml.push_str(category);
+    //         xml.push_str("\"/>\n");
+    //     }
+    //     xml.push_str("  </categories>\n");
+    // }
+
+    xml.push_str(" </CustomisationUIHeader>\n");
+
+    // Write options for each device type with default Product IDs
+    // if devices.keyboard {
+    //     xml.push_str(" <options type=\"keyboard\" instance=\"1\" Product=\"Keyboard  {6F1D2B61-D5A0-11CF-BFC7-444553540000}\"/>\n");
+    // }
+    // if devices.mouse {
+    //     xml.push_str(" <options type=\"mouse\" instance=\"1\" Product=\"Mouse  {6F1D2B62-D5A0-11CF-BFC7-444553540000}\"/>\n");
+    // }
+    // if devices.joystick1 {
+    //     xml.push_str(" <options type=\"joystick\" instance=\"1\" Product=\"\"/>\n");
+    // }
+    // if devices.joystick2 {
+    //     xml.push_str(" <options type=\"joystick\" instance=\"2\" Product=\"\"/>\n");
+    // }
+
+    // Empty modifiers
+    xml.push_str(" <modifiers />\n");
+
+    // Write actionmaps with blank rebinds
+    for action_map in &all_binds.action_maps {
+        xml.push_str(" <actionmap name=\"");
+        xml.push_str(&xml_escape(&action_map.name));
+        xml.push_str("\">\n");
+
+        for action in &action_map.actions {
+            xml.push_str("  <action name=\"");
+            xml.push_str(&xml_escape(&action.name));
+            xml.push_str("\">\n");
+
+            // Special handling for critical UI actions to ensure they always work
+            if UNBIND_KEPT_ACTIONS.contains(&action.name.as_str()) {
+                // Set escape key explicitly for menu navigation
+                xml.push_str("   <rebind input=\"kb1_escape\" activationMode=\"press\"/>\n");
+            } else {
+                // Add blank rebinds for each selected device for all other actions
+                if devices.keyboard {
+                    xml.push_str("   <rebind input=\"kb1_ \"/>\n");
+                }
+                if devices.mouse {
+                    xml.push_str("   <rebind input=\"mouse1_ \"/>\n");
+                }
+                if devices.gamepad {
+                    xml.push_str("   <rebind input=\"gp1_ \"/>\n");
+                }
+                if devices.joystick1 {
+                    xml.push_str("   <rebind input=\"js1_ \"/>\n");
+                }
+                if devices.joystick2 {
+                    xml.push_str("   <rebind input=\"js2_ \"/>\n");
+                }
+            }
+
+            xml.push_str("  </action>\n");
+        }
+
+        xml.push_str(" </actionmap>\n");
+    }
+
+    xml.push_str("</ActionMaps>\n");
+
+    Ok(xml)
+}
+
+/// Header of the app's binary profile cache; SC never reads these files
+pub const BINARY_PROFILE_MAGIC: [u8; 4] = *b"SCJM";
+
+/// Bumped whenever BinaryProfile changes shape, so stale caches are rejected instead of misread
+pub const BINARY_PROFILE_VERSION: u16 = 1;
+
+/// Payload of a binary profile. bincode is positional and can't read back the fields
+/// `Rebind` skips when serializing, so the binary form has its own types that write every field
+#[derive(Serialize, Deserialize)]
+struct BinaryProfile {
+    profile_name: String,
+    description: String,
+    image: String,
+    options_version: Option<u32>,
+    rebind_version: Option<u32>,
+    action_maps: Vec<BinaryActionMap>,
+    categories: Vec<String>,
+    keyboards: Vec<String>,
+    mice: Vec<String>,
+    joysticks: Vec<String>,
+    declared_joystick_instances: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryActionMap {
+    name: String,
+    actions: Vec<BinaryAction>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryAction {
+    name: String,
+    rebinds: Vec<BinaryRebind>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryRebind {
+    input: String,
+    multi_tap: Option<u32>,
+    multi_tap_block: Option<bool>,
+    press_trigger_threshold: Option<u32>,
+    activation_mode: String,
+}
+
+impl From<&ActionMaps> for BinaryProfile {
+    fn from(maps: &ActionMaps) -> Self {
+        BinaryProfile {
+            profile_name: maps.profile_name.clone(),
+            description: maps.description.clone(),
+            image: maps.image.clone(),
+            options_version: maps.options_version,
+            rebind_version: maps.rebind_version,
+            action_maps: maps
+                .action_maps
+                .iter()
+                .map(|am| BinaryActionMap {
+                    name: am.name.clone(),
+                    actions: am
+                        .actions
+                        .iter()
+                        .map(|a| BinaryAction {
+                            name: a.name.clone(),
+                            rebinds: a
+                                .rebinds
+                                .iter()
+                                .map(|r| BinaryRebind {
+                                    input: r.input.clone(),
+                                    multi_tap: r.multi_tap,
+                                    multi_tap_block: r.multi_tap_block,
+                                    press_trigger_threshold: r.press_trigger_threshold,
+                                    activation_mode: r.activation_mode.clone(),
+                                })
+                                .collect(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            categories: maps.categories.iter().map(|c| c.label.clone()).collect(),
+            keyboards: maps.devices.keyboards.clone(),
+            mice: maps.devices.mice.clone(),
+            joysticks: maps.devices.joysticks.clone(),
+            declared_joystick_instances: maps.devices.declared_joystick_instances.clone(),
+        }
+    }
+}
+
+impl From<BinaryProfile> for ActionMaps {
+    fn from(profile: BinaryProfile) -> Self {
+        ActionMaps {
+            profile_name: profile.profile_name,
+            description: profile.description,
+            image: profile.image,
+            options_version: profile.options_version,
+            rebind_version: profile.rebind_version,
+            action_maps: profile
+                .action_maps
+                .into_iter()
+                .map(|am| ActionMap {
+                    name: am.name,
+                    actions: am
+                        .actions
+                        .into_iter()
+                        .map(|a| Action {
+                            name: a.name,
+                            rebinds: a
+                                .rebinds
+                                .into_iter()
+                                .map(|r| Rebind {
+                                    input: r.input,
+                                    multi_tap: r.multi_tap,
+                                    multi_tap_block: r.multi_tap_block,
+                                    press_trigger_threshold: r.press_trigger_threshold,
+                                    activation_mode: r.activation_mode,
+                                })
+                                .collect(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            categories: profile
+                .categories
+                .into_iter()
+                .map(|label| Category { label })
+                .collect(),
+            devices: DeviceInfo {
+                keyboards: profile.keyboards,
+                mice: profile.mice,
+                joysticks: profile.joysticks,
+                declared_joystick_instances: profile.declared_joystick_instances,
+            },
+        }
+    }
+}
+
+impl ActionMaps {
+    /// Encode as magic, little-endian format version, then the bincode payload
+    pub fn to_binary(&self) -> Result<Vec<u8>, String> {
+        let payload = bincode::serialize(&BinaryProfile::from(self))
+            .map_err(|e| format!("Failed to encode binary profile: {}", e))?;
+
+        let mut bytes = Vec::with_capacity(payload.len() + 6);
+        bytes.extend_from_slice(&BINARY_PROFILE_MAGIC);
+        bytes.extend_from_slice(&BINARY_PROFILE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Decode a profile written by `to_binary`, checking the magic and format version first
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 6 || bytes[..4] != BINARY_PROFILE_MAGIC {
+            return Err("Not a binary profile (unexpected header)".to_string());
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != BINARY_PROFILE_VERSION {
+            return Err(format!(
+                "Unsupported binary profile version {} (expected {}), re-save it from the XML profile",
+                version, BINARY_PROFILE_VERSION
+            ));
+        }
+
+        bincode::deserialize::<BinaryProfile>(&bytes[6..])
+            .map(ActionMaps::from)
+            .map_err(|e| format!("Failed to decode binary profile: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remap_qwerty_to_azerty() {
+        assert_eq!(
+            remap_keyboard_input("kb1_q", KeyboardLayout::Qwerty, KeyboardLayout::Azerty),
+            "kb1_a"
+        );
+        assert_eq!(
+            remap_keyboard_input("kb1_lalt+q", KeyboardLayout::Qwerty, KeyboardLayout::Azerty),
+            "kb1_lalt+a"
+        );
+        assert_eq!(
+            remap_keyboard_input("LALT+kb1_w", KeyboardLayout::Qwerty, KeyboardLayout::Azerty),
+            "LALT+kb1_z"
+        );
+        // Keys in the same position are untouched
+        assert_eq!(
+            remap_keyboard_input("kb1_f", KeyboardLayout::Qwerty, KeyboardLayout::Azerty),
+            "kb1_f"
+        );
+    }
+
+    #[test]
+    fn test_remap_qwerty_qwertz_round_trip() {
+        assert_eq!(
+            remap_keyboard_input("kb1_y", KeyboardLayout::Qwerty, KeyboardLayout::Qwertz),
+            "kb1_z"
+        );
+        assert_eq!(
+            remap_keyboard_input("kb1_z", KeyboardLayout::Qwerty, KeyboardLayout::Qwertz),
+            "kb1_y"
+        );
+        assert_eq!(
+            remap_keyboard_input("kb1_z", KeyboardLayout::Qwertz, KeyboardLayout::Qwerty),
+            "kb1_y"
+        );
+        assert_eq!(
+            remap_keyboard_input(
+                "kb_y+lshift",
+                KeyboardLayout::Qwertz,
+                KeyboardLayout::Qwerty
+            ),
+            "kb_z+lshift"
+        );
+    }
+
+    #[test]
+    fn test_all_binds_progress_callback() {
+        let mut xml = String::from("<ActionMaps>\n");
+        for i in 0..5 {
+            xml.push_str(&format!(
+                " <actionmap name=\"map_{}\" version=\"1\" UILabel=\"Map\" UICategory=\"Cat\">\n  <action name=\"action_{}\" keyboard=\"f{}\"/>\n </actionmap>\n",
+                i, i, i + 1
+            ));
+        }
+        xml.push_str("</ActionMaps>\n");
+
+        let mut calls = Vec::new();
+        let all_binds =
+            AllBinds::from_xml_with_progress(&xml, 2, |parsed, _| calls.push(parsed)).unwrap();
+
+        assert_eq!(all_binds.action_maps.len(), 5);
+        assert_eq!(calls, vec![2, 4]);
+
+        // Disabled reporting never calls back
+        let mut count = 0;
+        AllBinds::from_xml_with_progress(&xml, 0, |_, _| count += 1).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    fn merged_binding(input: &str, display_name: &str, input_type: &str) -> MergedBinding {
+        MergedBinding {
+            input: input.to_string(),
+            display_name: display_name.to_string(),
+            input_type: input_type.to_string(),
+            is_default: true,
+            multi_tap: None,
+            activation_mode: String::new(),
+            original_default: None,
+        }
+    }
+
+    fn merged_action(name: &str, bindings: Vec<MergedBinding>) -> MergedAction {
+        MergedAction {
+            name: name.to_string(),
+            ui_label: format_display_name(name),
+            ui_description: String::new(),
+            category: String::new(),
+            is_customized: false,
+            on_hold: false,
+            bindings,
+        }
+    }
+
+    fn merged_action_map(
+        name: &str,
+        category: &str,
+        actions: Vec<MergedAction>,
+    ) -> MergedActionMap {
+        MergedActionMap {
+            name: name.to_string(),
+            ui_label: String::new(),
+            ui_category: category.to_string(),
+            actions,
+        }
+    }
+
+    #[test]
+    fn test_effective_binding() {
+        let mut js_override = merged_binding("js1_button3", "Joystick 1 - Button 3", "Joystick");
+        js_override.is_default = false;
+
+        let merged = MergedBindings {
+            action_maps: vec![MergedActionMap {
+                name: "spaceship_general".to_string(),
+                ui_label: String::new(),
+                ui_category: String::new(),
+                actions: vec![MergedAction {
+                    name: "v_eject".to_string(),
+                    ui_label: String::new(),
+                    ui_description: String::new(),
+                    category: String::new(),
+                    is_customized: true,
+                    on_hold: false,
+                    bindings: vec![
+                        js_override,
+                        merged_binding("kb_ralt+l", "Keyboard - RALT+L", "Keyboard"),
+                    ],
+                }],
+            }],
+        };
+
+        let connected = vec!["kb1".to_string(), "js1".to_string()];
+        let effective = merged
+            .effective_binding("spaceship_general", "v_eject", &connected)
+            .unwrap();
+        assert_eq!(effective.input, "js1_button3");
+        assert!(!effective.is_default);
+
+        // Joystick unplugged: the keyboard default is what fires
+        let connected = vec!["kb1".to_string()];
+        let effective = merged
+            .effective_binding("spaceship_general", "v_eject", &connected)
+            .unwrap();
+        assert_eq!(effective.input, "kb_ralt+l");
+        assert_eq!(effective.device_instance, "kb1");
+        assert!(effective.is_default);
+
+        assert!(merged
+            .effective_binding("spaceship_general", "v_eject", &[])
+            .is_none());
+    }
+
+    fn coverage_fixture() -> MergedBindings {
+        MergedBindings {
+            action_maps: vec![MergedActionMap {
+                name: "spaceship_movement".to_string(),
+                ui_label: String::new(),
+                ui_category: String::new(),
+                actions: vec![
+                    merged_action(
+                        "v_pitch",
+                        vec![merged_binding("js1_y", "Joystick 1 - Y", "Joystick")],
+                    ),
+                    merged_action(
+                        "v_yaw",
+                        vec![merged_binding("js1_x", "Joystick 1 - X", "Joystick")],
+                    ),
+                    merged_action(
+                        "v_roll",
+                        vec![
+                            merged_binding("kb1_a", "Keyboard - A", "Keyboard"),
+                            merged_binding("js1_ ", "Unbound", "Unknown"),
+                        ],
+                    ),
+                ],
+            }],
+        }
+    }
+
+    fn essentials(actions: &[&str]) -> Vec<(String, String)> {
+        actions
+            .iter()
+            .map(|a| ("spaceship_movement".to_string(), a.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_binding_coverage_fully_bound() {
+        let merged = coverage_fixture();
+        let report = merged.binding_coverage("js1", &essentials(&["v_pitch", "v_yaw"]));
+        assert_eq!(report.bound.len(), 2);
+        assert!(report.missing.is_empty());
+
+        // A bare device prefix matches any instance of that device
+        let report = merged.binding_coverage("js", &essentials(&["v_pitch", "v_yaw"]));
+        assert_eq!(report.bound.len(), 2);
+    }
+
+    #[test]
+    fn test_binding_coverage_partially_bound() {
+        let merged = coverage_fixture();
+        let report = merged.binding_coverage(
+            "js1",
+            &essentials(&["v_pitch", "v_roll", "v_strafe_forward"]),
+        );
+
+        assert_eq!(report.bound.len(), 1);
+        assert_eq!(report.bound[0].action_name, "v_pitch");
+        let missing: Vec<&str> = report
+            .missing
+            .iter()
+            .map(|e| e.action_name.as_str())
+            .collect();
+        assert_eq!(missing, vec!["v_roll", "v_strafe_forward"]);
+        assert_eq!(report.missing[1].action_label, "Strafe Forward");
+    }
+
+    #[test]
+    fn test_to_table_rows() {
+        let merged = MergedBindings {
+            action_maps: vec![MergedActionMap {
+                name: "spaceship_weapons".to_string(),
+                ui_label: "Weapons".to_string(),
+                ui_category: "Flight".to_string(),
+                actions: vec![MergedAction {
+                    name: "v_attack1".to_string(),
+                    ui_label: "Fire Group 1".to_string(),
+                    ui_description: String::new(),
+                    category: String::new(),
+                    is_customized: false,
+                    on_hold: false,
+                    bindings: vec![
+                        merged_binding("js1_button1", "Joystick 1 - Button 1", "Joystick"),
+                        merged_binding("kb1_ ", "Unbound", "Unknown"),
+                        merged_binding("mouse1_mouse1", "Mouse - MOUSE1", "Mouse"),
+                    ],
+                }],
+            }],
+        };
+
+        let rows = merged.to_table_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0],
+            BindingTableRow {
+                category: "Flight".to_string(),
+                action_map_label: "Weapons".to_string(),
+                action_label: "Fire Group 1".to_string(),
+                device: "Joystick".to_string(),
+                input: "js1_button1".to_string(),
+                display_name: "Joystick 1 - Button 1".to_string(),
+            }
+        );
+        assert_eq!(rows[1].device, "Mouse");
+    }
+
+    fn rebind(input: &str) -> Rebind {
+        Rebind {
+            input: input.to_string(),
+            multi_tap: None,
+            multi_tap_block: None,
+            press_trigger_threshold: None,
+            activation_mode: String::new(),
+        }
+    }
+
+    // (action name, rebind inputs)
+    type ActionSpec<'a> = (&'a str, Vec<&'a str>);
+
+    fn action_maps(maps: Vec<(&str, Vec<ActionSpec>)>) -> ActionMaps {
+        ActionMaps {
+            profile_name: "Test".to_string(),
+            description: String::new(),
+            image: String::new(),
+            options_version: None,
+            rebind_version: None,
+            action_maps: maps
+                .into_iter()
+                .map(|(name, actions)| ActionMap {
+                    name: name.to_string(),
+                    actions: actions
+                        .into_iter()
+                        .map(|(action_name, inputs)| Action {
+                            name: action_name.to_string(),
+                            rebinds: inputs.into_iter().map(rebind).collect(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            categories: Vec::new(),
+            devices: DeviceInfo::default(),
+        }
+    }
+
+    fn all_binds_action(name: &str, keyboard: &str, joystick: &str) -> AllBindsAction {
+        AllBindsAction {
+            name: name.to_string(),
+            ui_label: String::new(),
+            ui_description: String::new(),
+            category: String::new(),
+            activation_mode: String::new(),
+            on_hold: false,
+            default_keyboard: keyboard.to_string(),
+            default_mouse: String::new(),
+            default_gamepad: String::new(),
+            default_joystick: joystick.to_string(),
+        }
+    }
+
+    fn all_binds(maps: Vec<(&str, Vec<AllBindsAction>)>) -> AllBinds {
+        AllBinds {
+            action_maps: maps
+                .into_iter()
+                .map(|(name, actions)| AllBindsActionMap {
+                    name: name.to_string(),
+                    version: "1".to_string(),
+                    ui_label: String::new(),
+                    ui_category: String::new(),
+                    actions,
+                })
+                .collect(),
+            localization: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_rebind_is_cleared() {
+        assert!(rebind("kb1_ ").is_cleared());
+        assert!(rebind("kb_ ").is_cleared());
+        assert!(rebind("js2_ ").is_cleared());
+        assert!(rebind("mouse1_").is_cleared());
+        assert!(rebind("gp1_  ").is_cleared());
+
+        assert!(!rebind("kb1_space").is_cleared());
+        assert!(!rebind("js1_button3").is_cleared());
+        assert!(!rebind("js_ ").is_cleared());
+        assert!(!rebind("LALT+kb1_ ").is_cleared());
+    }
+
+    #[test]
+    fn test_purge_cleared_bindings() {
+        let defaults = all_binds(vec![(
+            "spaceship_general",
+            vec![
+                all_binds_action("v_eject", "ralt+l", ""),
+                all_binds_action("v_exit", "", ""),
+            ],
+        )]);
+
+        let mut profile = action_maps(vec![
+            (
+                "spaceship_general",
+                vec![
+                    // Cleared keyboard default: a genuine override
+                    ("v_eject", vec!["kb1_ ", "js1_button5"]),
+                    // No default to override: pure clutter
+                    ("v_exit", vec!["js1_ "]),
+                ],
+            ),
+            ("spaceship_weapons", vec![("v_attack1", vec!["js2_ "])]),
+        ]);
+
+        let mut keep = profile.clone();
+        assert_eq!(keep.purge_cleared_bindings(Some(&defaults), true), 2);
+        assert_eq!(keep.action_maps.len(), 1);
+        assert_eq!(keep.action_maps[0].actions.len(), 1);
+        assert_eq!(keep.action_maps[0].actions[0].rebinds.len(), 2);
+
+        assert_eq!(profile.purge_cleared_bindings(Some(&defaults), false), 3);
+        assert_eq!(profile.action_maps.len(), 1);
+        assert_eq!(profile.action_maps[0].actions[0].rebinds.len(), 1);
+        assert_eq!(
+            profile.action_maps[0].actions[0].rebinds[0].input,
+            "js1_button5"
+        );
+    }
+
+    #[test]
+    fn test_normalize_activation_mode() {
+        assert_eq!(normalize_activation_mode("press").unwrap(), "press");
+        assert_eq!(
+            normalize_activation_mode("Double Tap").unwrap(),
+            "double_tap"
+        );
+        assert_eq!(
+            normalize_activation_mode(" DELAYED-PRESS ").unwrap(),
+            "delayed_press"
+        );
+        assert_eq!(normalize_activation_mode("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_invalid_activation_mode() {
+        let err = normalize_activation_mode("dobule_tap").unwrap_err();
+        assert!(err.contains("Unknown activation mode 'dobule_tap'"));
+        assert!(err.contains("double_tap"));
+    }
+
+    fn find_inputs(profile: &ActionMaps, action_map: &str, action: &str) -> Vec<String> {
+        profile
+            .action_maps
+            .iter()
+            .find(|am| am.name == action_map)
+            .and_then(|am| am.actions.iter().find(|a| a.name == action))
+            .map(|a| a.rebinds.iter().map(|r| r.input.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_template_v2_swapped_devices() {
+        // Authored with the stick on js1 and the throttle on js2
+        let source = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_pitch", vec!["js1_y"]),
+                ("v_strafe_forward", vec!["js2_z"]),
+                ("v_afterburner", vec!["lalt+js2_button3", "kb1_lshift"]),
+            ],
+        )]);
+
+        let roles = vec![
+            TemplateRole {
+                name: "primary_stick".to_string(),
+                description: String::new(),
+            },
+            TemplateRole {
+                name: "throttle".to_string(),
+                description: String::new(),
+            },
+        ];
+        let instance_roles: HashMap<String, String> = [
+            ("js1".to_string(), "primary_stick".to_string()),
+            ("js2".to_string(), "throttle".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        let template = TemplateV2::from_action_maps(&source, roles, &instance_roles);
+        assert_eq!(template.bindings[0].input, "{instance}_y");
+        assert_eq!(template.bindings[0].role.as_deref(), Some("primary_stick"));
+        assert_eq!(template.bindings[2].input, "lalt+{instance}_button3");
+        assert_eq!(template.bindings[3].role, None);
+
+        // Applied on a machine where the throttle enumerates first
+        let role_to_instance: HashMap<String, String> = [
+            ("primary_stick".to_string(), "js2".to_string()),
+            ("throttle".to_string(), "js1".to_string()),
+        ]
+        .into_iter()
+        .collect();
 
-                            // Build a set of input types we already have from user bindings
-                            use std::collections::HashSet;
-                            let custom_input_types: HashSet<String> =
-                                all_bindings.iter().map(|b| b.input_type.clone()).collect();
+        let mut target = action_maps(vec![(
+            "spaceship_movement",
+            vec![("v_pitch", vec!["js2_x"])],
+        )]);
+        assert_eq!(
+            template.apply_to(&mut target, &role_to_instance).unwrap(),
+            4
+        );
+
+        assert_eq!(
+            find_inputs(&target, "spaceship_movement", "v_pitch"),
+            vec!["js2_y"]
+        );
+        assert_eq!(
+            find_inputs(&target, "spaceship_movement", "v_strafe_forward"),
+            vec!["js1_z"]
+        );
+        assert_eq!(
+            find_inputs(&target, "spaceship_movement", "v_afterburner"),
+            vec!["lalt+js1_button3", "kb1_lshift"]
+        );
+    }
 
-                            // Add keyboard default if not customized
-                            if !custom_input_types.contains("Keyboard") {
-                                let kb_trimmed = all_binds_action.default_keyboard.trim();
-                                // Only add if there's an actual binding (not just space)
-                                if !kb_trimmed.is_empty() {
-                                    let input = format!("kb_{}", kb_trimmed);
-                                    let rebind = Rebind {
-                                        input: input.clone(),
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                    };
-                                    let input_type = rebind.get_input_type();
-                                    all_bindings.push(MergedBinding {
-                                        input: rebind.input.clone(),
-                                        display_name: rebind.get_display_name(),
-                                        input_type: format!("{:?}", input_type),
-                                        is_default: true,
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                        original_default: None,
-                                    });
-                                }
-                            }
+    #[test]
+    fn test_template_v2_keeps_bindings_sharing_an_instance() {
+        let source = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["js1_button1", "lalt+js1_button2"])],
+        )]);
+        let roles = vec![TemplateRole {
+            name: "primary_stick".to_string(),
+            description: String::new(),
+        }];
+        let instance_roles: HashMap<String, String> =
+            [("js1".to_string(), "primary_stick".to_string())]
+                .into_iter()
+                .collect();
+        let template = TemplateV2::from_action_maps(&source, roles, &instance_roles);
 
-                            // Add gamepad default if not customized
-                            if !custom_input_types.contains("Gamepad") {
-                                let gp_trimmed = all_binds_action.default_gamepad.trim();
-                                // Only add if there's an actual binding (not just space)
-                                if !gp_trimmed.is_empty() {
-                                    let input = format!("gp1_{}", gp_trimmed);
-                                    let rebind = Rebind {
-                                        input: input.clone(),
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                    };
-                                    let input_type = rebind.get_input_type();
-                                    all_bindings.push(MergedBinding {
-                                        input: rebind.input.clone(),
-                                        display_name: rebind.get_display_name(),
-                                        input_type: format!("{:?}", input_type),
-                                        is_default: true,
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                        original_default: None,
-                                    });
-                                }
-                            }
+        let role_to_instance: HashMap<String, String> =
+            [("primary_stick".to_string(), "js2".to_string())]
+                .into_iter()
+                .collect();
+        let mut target = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["js2_button9", "kb1_space"])],
+        )]);
+        assert_eq!(
+            template.apply_to(&mut target, &role_to_instance).unwrap(),
+            2
+        );
+
+        // The old js2 binding is replaced, both template bindings survive
+        assert_eq!(
+            find_inputs(&target, "spaceship_weapons", "v_attack1"),
+            vec!["kb1_space", "js2_button1", "lalt+js2_button2"]
+        );
+    }
 
-                            // Add joystick default if not customized
-                            if !custom_input_types.contains("Joystick") {
-                                let js_trimmed = all_binds_action.default_joystick.trim();
-                                // Only add if there's an actual binding (not just space)
-                                if !js_trimmed.is_empty() {
-                                    let input = format!("js1_{}", js_trimmed);
-                                    let rebind = Rebind {
-                                        input: input.clone(),
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                    };
-                                    let input_type = rebind.get_input_type();
-                                    all_bindings.push(MergedBinding {
-                                        input: rebind.input.clone(),
-                                        display_name: rebind.get_display_name(),
-                                        input_type: format!("{:?}", input_type),
-                                        is_default: true,
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                        original_default: None,
-                                    });
-                                }
-                            }
+    #[test]
+    fn test_template_v2_unassigned_role() {
+        let template = TemplateV2 {
+            roles: Vec::new(),
+            bindings: vec![RoleBinding {
+                action_map: "spaceship_movement".to_string(),
+                action: "v_pitch".to_string(),
+                role: Some("pedals".to_string()),
+                input: "{instance}_rotz".to_string(),
+                multi_tap: None,
+                multi_tap_block: None,
+                press_trigger_threshold: None,
+                activation_mode: String::new(),
+            }],
+        };
 
-                            // Add mouse default if not customized
-                            if !custom_input_types.contains("Mouse") {
-                                let mouse_trimmed = all_binds_action.default_mouse.trim();
-                                // Only add if there's an actual binding (not just space)
-                                if !mouse_trimmed.is_empty() {
-                                    let input = format!("mouse1_{}", mouse_trimmed);
-                                    let rebind = Rebind {
-                                        input: input.clone(),
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                    };
-                                    let input_type = rebind.get_input_type();
-                                    all_bindings.push(MergedBinding {
-                                        input: rebind.input.clone(),
-                                        display_name: rebind.get_display_name(),
-                                        input_type: format!("{:?}", input_type),
-                                        is_default: true,
-                                        multi_tap: None,
-                                        activation_mode: String::new(),
-                                        original_default: None,
-                                    });
-                                }
-                            }
+        let err = template.resolve(&HashMap::new()).unwrap_err();
+        assert!(err.contains("pedals"));
+    }
 
-                            // If we ended up with no bindings at all (all defaults were spaces),
-                            // add an empty keyboard binding so the action still appears in the UI
-                            if all_bindings.is_empty() {
-                                all_bindings.push(MergedBinding {
-                                    input: "kb_ ".to_string(),
-                                    display_name: "Unbound".to_string(),
-                                    input_type: "Unknown".to_string(),
-                                    is_default: true,
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                    original_default: None,
-                                });
-                            }
+    #[test]
+    fn test_split_camel_case_digits_and_acronyms() {
+        assert_eq!(split_camel_case("target1"), "Target 1");
+        assert_eq!(split_camel_case("mode2toggle"), "Mode 2 Toggle");
+        assert_eq!(split_camel_case("MFDLeft"), "MFD Left");
+        assert_eq!(split_camel_case("camelCase"), "Camel Case");
+        assert_eq!(format_display_name("v_mode2toggle"), "Mode 2 Toggle");
+        assert_eq!(format_display_name("v_1to1"), "1:1");
+    }
 
-                            all_bindings
-                        } else {
-                            // No custom bindings, use defaults from AllBinds
-                            let mut default_bindings = Vec::new();
+    #[test]
+    fn test_hat_diagonal_input() {
+        let diagonal = rebind("js1_hat1_up_right");
+        assert_eq!(diagonal.get_input_type(), InputType::Joystick);
+        assert_eq!(diagonal.get_display_name(), "Joystick 1 - Hat 1 Up-Right");
+        assert_eq!(diagonal.joystick_instance(), Some(1));
+
+        assert_eq!(
+            rebind("js2_hat1_down").get_display_name(),
+            "Joystick 2 - Hat 1 Down"
+        );
+        assert_eq!(
+            rebind("lalt+js2_hat2_down_left").joystick_instance(),
+            Some(2)
+        );
+        assert_eq!(
+            rebind("lalt+js2_hat2_down_left").get_display_name(),
+            "Left Alt + Joystick 2 - Hat 2 Down-Left"
+        );
+        assert_eq!(
+            rebind("js1_button1+js2_button2").get_display_name(),
+            "Joystick 1 - Button 1 + Joystick 2 - Button 2"
+        );
+        assert_eq!(
+            rebind("js1_button1+js1_button2").get_display_name(),
+            "Joystick 1 - Button 1 + Button 2"
+        );
+        assert_eq!(rebind("kb1_space").joystick_instance(), None);
+    }
 
-                            // Add keyboard default if exists
-                            let kb_trimmed = all_binds_action.default_keyboard.trim();
-                            // Only add if there's an actual binding (not just space)
-                            if !kb_trimmed.is_empty() {
-                                let input = format!("kb_{}", kb_trimmed);
-                                let rebind = Rebind {
-                                    input: input.clone(),
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                };
-                                let input_type = rebind.get_input_type();
-                                default_bindings.push(MergedBinding {
-                                    input: rebind.input.clone(),
-                                    display_name: rebind.get_display_name(),
-                                    input_type: format!("{:?}", input_type),
-                                    is_default: true,
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                    original_default: None,
-                                });
-                            }
+    #[test]
+    fn test_key_labels_round_trip() {
+        // Numpad
+        assert_eq!(keyname_to_label("np_1"), Some("Numpad 1"));
+        assert_eq!(label_to_keyname("Numpad 1"), Some("np_1"));
+        // Punctuation
+        assert_eq!(keyname_to_label("lbracket"), Some("["));
+        assert_eq!(keyname_to_label("semicolon"), Some(";"));
+        assert_eq!(label_to_keyname(";"), Some("semicolon"));
+        assert_eq!(label_to_keyname("["), Some("lbracket"));
+        // Mouse wheel
+        assert_eq!(keyname_to_label("mwheel_up"), Some("Mouse Wheel Up"));
+        assert_eq!(label_to_keyname("mouse wheel up"), Some("mwheel_up"));
+
+        assert_eq!(keyname_to_label("f1"), None);
+        assert_eq!(label_to_keyname("Not A Key"), None);
+
+        // Direction words are arrow keys on the keyboard only
+        assert_eq!(label_to_keyname("Arrow Up"), Some("up"));
+        assert_eq!(rebind("kb1_up").get_display_name(), "Keyboard - Arrow Up");
+        assert_eq!(
+            rebind("kb1_lctrl+kb1_left").get_display_name(),
+            "Keyboard - Left Ctrl + Arrow Left"
+        );
+        assert_eq!(rebind("js1_up").get_display_name(), "Joystick 1 - UP");
+        assert_eq!(rebind("gp1_left").get_display_name(), "Gamepad - LEFT");
+
+        assert_eq!(rebind("kb1_np_1").get_display_name(), "Keyboard - Numpad 1");
+        assert_eq!(
+            rebind("kb1_ralt+np_1").get_display_name(),
+            "Keyboard - Right Alt+Numpad 1"
+        );
+        assert_eq!(
+            rebind("mo1_mwheel_up").get_display_name(),
+            "Mouse - Wheel Up"
+        );
+        assert_eq!(rebind("kb1_f1").get_display_name(), "Keyboard - F1");
+    }
 
-                            // Add gamepad default if exists
-                            let gp_trimmed = all_binds_action.default_gamepad.trim();
-                            // Only add if there's an actual binding (not just space)
-                            if !gp_trimmed.is_empty() {
-                                let input = format!("gp1_{}", gp_trimmed);
-                                let rebind = Rebind {
-                                    input: input.clone(),
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                };
-                                let input_type = rebind.get_input_type();
-                                default_bindings.push(MergedBinding {
-                                    input: rebind.input.clone(),
-                                    display_name: rebind.get_display_name(),
-                                    input_type: format!("{:?}", input_type),
-                                    is_default: true,
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                    original_default: None,
-                                });
-                            }
+    #[test]
+    fn test_binding_statistics() {
+        let custom = |input: &str, input_type: &str| MergedBinding {
+            is_default: false,
+            ..merged_binding(input, "", input_type)
+        };
 
-                            // Add joystick default if exists
-                            let js_trimmed = all_binds_action.default_joystick.trim();
-                            // Only add if there's an actual binding (not just space)
-                            if !js_trimmed.is_empty() {
-                                let input = format!("js1_{}", js_trimmed);
-                                let rebind = Rebind {
-                                    input: input.clone(),
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                };
-                                let input_type = rebind.get_input_type();
-                                default_bindings.push(MergedBinding {
-                                    input: rebind.input.clone(),
-                                    display_name: rebind.get_display_name(),
-                                    input_type: format!("{:?}", input_type),
-                                    is_default: true,
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                    original_default: None,
-                                });
-                            }
+        let merged = MergedBindings {
+            action_maps: vec![
+                merged_action_map(
+                    "spaceship_movement",
+                    "Flight",
+                    vec![
+                        merged_action(
+                            "v_pitch",
+                            vec![
+                                merged_binding("js1_y", "", "Joystick"),
+                                custom("js2_y", "Joystick"),
+                            ],
+                        ),
+                        merged_action(
+                            "v_roll",
+                            vec![
+                                merged_binding("kb1_a", "", "Keyboard"),
+                                // Cleared: not counted
+                                merged_binding("js1_ ", "", "Unknown"),
+                            ],
+                        ),
+                    ],
+                ),
+                merged_action_map(
+                    "spaceship_weapons",
+                    "",
+                    vec![merged_action(
+                        "v_attack1",
+                        vec![custom("mouse1_mouse1", "Mouse"), custom("gp1_a", "Gamepad")],
+                    )],
+                ),
+            ],
+        };
 
-                            // Add mouse default if exists
-                            let mouse_trimmed = all_binds_action.default_mouse.trim();
-                            // Only add if there's an actual binding (not just space)
-                            if !mouse_trimmed.is_empty() {
-                                let input = format!("mouse1_{}", mouse_trimmed);
-                                let rebind = Rebind {
-                                    input: input.clone(),
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                };
-                                let input_type = rebind.get_input_type();
-                                default_bindings.push(MergedBinding {
-                                    input: rebind.input.clone(),
-                                    display_name: rebind.get_display_name(),
-                                    input_type: format!("{:?}", input_type),
-                                    is_default: true,
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                    original_default: None,
-                                });
-                            }
+        let stats = merged.binding_statistics();
+        assert_eq!(stats.per_device.get("Joystick 1"), Some(&1));
+        assert_eq!(stats.per_device.get("Joystick 2"), Some(&1));
+        assert_eq!(stats.per_device.get("Keyboard"), Some(&1));
+        assert_eq!(stats.per_device.get("Mouse"), Some(&1));
+        assert_eq!(stats.per_device.get("Gamepad 1"), Some(&1));
+        assert_eq!(stats.per_device.len(), 5);
+        assert_eq!(stats.per_category.get("Flight"), Some(&3));
+        assert_eq!(stats.per_category.get("Uncategorized"), Some(&2));
+        assert_eq!(stats.total_default, 2);
+        assert_eq!(stats.total_customized, 3);
+    }
+
+    #[test]
+    fn test_find_intra_action_duplicates() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                (
+                    "v_attack1",
+                    vec!["js1_button3", "kb1_space", "JS1_button3 "],
+                ),
+                ("v_attack2", vec!["js1_button4", "kb_a", "lalt+kb1_b"]),
+                (
+                    "v_attack3",
+                    vec!["js1_ ", "js1_ ", "kb_lalt+c", "kb1_c+lalt"],
+                ),
+            ],
+        )]);
+
+        let duplicates = maps.find_intra_action_duplicates();
+        assert_eq!(
+            duplicates,
+            vec![
+                DuplicateBinding {
+                    action_map_name: "spaceship_weapons".to_string(),
+                    action_name: "v_attack1".to_string(),
+                    input: "js1_button3".to_string(),
+                    count: 2,
+                },
+                DuplicateBinding {
+                    action_map_name: "spaceship_weapons".to_string(),
+                    action_name: "v_attack3".to_string(),
+                    input: "kb1_c+lalt".to_string(),
+                    count: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rename_action_map() {
+        let mut maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_pitch", vec!["js1_y"]),
+                ("v_yaw", vec!["js1_x", "kb1_a"]),
+            ],
+        )]);
+
+        assert_eq!(
+            maps.rename_action_map("spaceship_movement", "vehicle_movement")
+                .moved,
+            3
+        );
+        assert_eq!(maps.action_maps.len(), 1);
+        assert_eq!(maps.action_maps[0].name, "vehicle_movement");
+        assert_eq!(
+            find_inputs(&maps, "vehicle_movement", "v_yaw"),
+            vec!["js1_x", "kb1_a"]
+        );
+
+        // Unknown map: nothing to do
+        assert_eq!(
+            maps.rename_action_map("spaceship_movement", "vehicle_movement"),
+            RenameOutcome::default()
+        );
+    }
+
+    #[test]
+    fn test_rename_action_map_merges_into_existing() {
+        let mut maps = action_maps(vec![
+            (
+                "spaceship_movement",
+                vec![
+                    ("v_pitch", vec!["js1_y", "kb1_w"]),
+                    ("v_roll", vec!["js2_x"]),
+                ],
+            ),
+            ("vehicle_movement", vec![("v_pitch", vec!["js1_rotx"])]),
+        ]);
+
+        // js1 is already bound on the target: only the keyboard bind and the new action move
+        assert_eq!(
+            maps.rename_action_map("spaceship_movement", "vehicle_movement"),
+            RenameOutcome {
+                moved: 2,
+                dropped: vec![("v_pitch".to_string(), "js1_y".to_string())],
+            }
+        );
+        assert_eq!(maps.action_maps.len(), 1);
+        assert_eq!(
+            find_inputs(&maps, "vehicle_movement", "v_pitch"),
+            vec!["js1_rotx", "kb1_w"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "vehicle_movement", "v_roll"),
+            vec!["js2_x"]
+        );
+    }
+
+    #[test]
+    fn test_inferred_migrations() {
+        let all_binds = all_binds(vec![
+            (
+                "vehicle_movement",
+                vec![
+                    all_binds_action("v_pitch", "", ""),
+                    all_binds_action("v_yaw", "", ""),
+                ],
+            ),
+            ("seat_general", vec![all_binds_action("v_eject", "", "")]),
+            ("spaceship_hud", vec![all_binds_action("v_eject", "", "")]),
+        ]);
+        let profile = action_maps(vec![
+            // Renamed: both actions now live in vehicle_movement
+            (
+                "spaceship_movement",
+                vec![("v_pitch", vec!["js1_y"]), ("v_yaw", vec!["js1_x"])],
+            ),
+            // Still exists
+            ("seat_general", vec![("v_eject", vec!["kb1_y"])]),
+            // Gone, but its action exists in two maps: ambiguous
+            ("spaceship_general", vec![("v_eject", vec!["kb1_u"])]),
+            // Gone, and its action exists nowhere
+            ("spaceship_radar", vec![("v_radar_toggle", vec!["kb1_r"])]),
+        ]);
+
+        assert_eq!(
+            all_binds.inferred_migrations(&profile),
+            vec![(
+                "spaceship_movement".to_string(),
+                "vehicle_movement".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_normalize_cleared_bindings() {
+        let mut maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_  ", "kb1_space"]),
+                ("v_attack2", vec!["js2_", "kb_\t"]),
+                ("v_attack3", vec!["js1_ ", "mouse1_ "]),
+            ],
+        )]);
+
+        assert!(maps.action_maps[0]
+            .actions
+            .iter()
+            .all(|a| a.rebinds[0].is_cleared()));
+        assert_eq!(maps.normalize_cleared_bindings(), 3);
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1"),
+            vec!["js1_ ", "kb1_space"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack2"),
+            vec!["js2_ ", "kb_ "]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack3"),
+            vec!["js1_ ", "mouse1_ "]
+        );
+
+        // Already canonical: nothing left to change
+        assert_eq!(maps.normalize_cleared_bindings(), 0);
+    }
+
+    #[test]
+    fn test_joystick_options_in_instance_order() {
+        let mut maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["js2_button1"])],
+        )]);
+        maps.set_joystick_products(&[
+            (
+                2,
+                "Right Stick  {0200231D-0000-0000-0000-504944564944}".to_string(),
+            ),
+            (
+                1,
+                "Left Stick  {0201231D-0000-0000-0000-504944564944}".to_string(),
+            ),
+        ])
+        .unwrap();
+
+        let xml = maps.to_xml_with_categories(None, false);
+        let js1 = xml
+            .find("<options type=\"joystick\" instance=\"1\" Product=\"Left Stick  {0201231D-0000-0000-0000-504944564944}\"/>")
+            .unwrap();
+        let js2 = xml
+            .find("<options type=\"joystick\" instance=\"2\" Product=\"Right Stick  {0200231D-0000-0000-0000-504944564944}\"/>")
+            .unwrap();
+        assert!(js1 < js2);
+
+        // Options survive a reload by instance
+        let reparsed = ActionMaps::from_xml(&xml).unwrap();
+        assert_eq!(reparsed.devices.joysticks, maps.devices.joysticks);
+
+        assert!(maps.set_joystick_products(&[(0, "x".to_string())]).is_err());
+    }
+
+    #[test]
+    fn test_header_description_and_image_round_trip() {
+        let xml = r#"<ActionMaps version="1" optionsVersion="2" rebindVersion="2" profileName="hosas">
+ <CustomisationUIHeader label="hosas" description="Dual VKB &amp; pedals" image="hosas.png">
+  <devices>
+   <joystick instance="1"/>
+  </devices>
+ </CustomisationUIHeader>
+ <actionmap name="spaceship_weapons">
+  <action name="v_attack1">
+   <rebind input="js1_button1"/>
+  </action>
+ </actionmap>
+</ActionMaps>"#;
+
+        let maps = ActionMaps::from_xml(xml).unwrap();
+        assert_eq!(maps.description, "Dual VKB & pedals");
+        assert_eq!(maps.image, "hosas.png");
+
+        for exported in [maps.to_xml(false), maps.to_xml_with_categories(None, false)] {
+            assert!(exported.contains(
+                "<CustomisationUIHeader label=\"hosas\" description=\"Dual VKB &amp; pedals\" image=\"hosas.png\">"
+            ));
+            let reparsed = ActionMaps::from_xml(&exported).unwrap();
+            assert_eq!(reparsed.description, maps.description);
+            assert_eq!(reparsed.image, maps.image);
+        }
+    }
+
+    #[test]
+    fn test_toggle_axis_inversion() {
+        assert_eq!(
+            toggle_axis_direction("js1_axis2_positive").unwrap(),
+            "js1_axis2_negative"
+        );
+        assert_eq!(
+            toggle_axis_direction("js1_axis2_negative").unwrap(),
+            "js1_axis2_positive"
+        );
+        assert_eq!(
+            toggle_axis_direction("lalt+js1_axis2_positive").unwrap(),
+            "lalt+js1_axis2_negative"
+        );
+        assert!(toggle_axis_direction("js1_button3").is_err());
+
+        let mut maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_pitch", vec!["kb1_w", "js1_axis2_positive"]),
+                ("v_attack1", vec!["js1_button3"]),
+            ],
+        )]);
+
+        assert_eq!(
+            maps.toggle_axis_inversion("spaceship_movement", "v_pitch")
+                .unwrap(),
+            "js1_axis2_negative"
+        );
+        assert_eq!(
+            maps.toggle_axis_inversion("spaceship_movement", "v_pitch")
+                .unwrap(),
+            "js1_axis2_positive"
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_movement", "v_pitch"),
+            vec!["kb1_w", "js1_axis2_positive"]
+        );
+
+        // Non-axis binding is left untouched
+        assert!(maps
+            .toggle_axis_inversion("spaceship_movement", "v_attack1")
+            .is_err());
+        assert_eq!(
+            find_inputs(&maps, "spaceship_movement", "v_attack1"),
+            vec!["js1_button3"]
+        );
+        assert!(maps
+            .toggle_axis_inversion("spaceship_movement", "v_missing")
+            .is_err());
+    }
+
+    #[test]
+    fn test_export_filtered_action_maps() {
+        let maps = action_maps(vec![
+            (
+                "spaceship_mining",
+                vec![
+                    ("v_toggle_mining_mode", vec!["js1_button5"]),
+                    ("v_mining_throttle", vec!["js1_ "]),
+                ],
+            ),
+            ("spaceship_weapons", vec![("v_attack1", vec!["kb1_space"])]),
+        ]);
+
+        let xml =
+            maps.to_xml_with_categories_filtered(None, &["spaceship_mining".to_string()], false);
+        assert!(xml.contains("<actionmap name=\"spaceship_mining\">"));
+        assert!(!xml.contains("spaceship_weapons"));
+        // Only joystick bindings are included, so no keyboard device in the header
+        assert!(!xml.contains("<keyboard instance"));
+        assert!(xml.contains("<joystick instance=\"1\"/>"));
+
+        let reparsed = ActionMaps::from_xml(&xml).unwrap();
+        assert_eq!(
+            find_inputs(&reparsed, "spaceship_mining", "v_toggle_mining_mode"),
+            vec!["js1_button5"]
+        );
+        // Cleared bindings are kept
+        assert_eq!(
+            find_inputs(&reparsed, "spaceship_mining", "v_mining_throttle"),
+            vec!["js1_ "]
+        );
+    }
+
+    #[test]
+    fn test_find_overbound_actions() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                (
+                    "v_attack1",
+                    vec![
+                        "js1_button1",
+                        "js2_button1",
+                        "kb1_space",
+                        "mouse1_mouse1",
+                        "gp1_a",
+                    ],
+                ),
+                // Exactly at the limit, cleared entries don't count
+                (
+                    "v_attack2",
+                    vec![
+                        "js1_button2",
+                        "js2_button2",
+                        "kb1_f",
+                        "mouse1_mouse2",
+                        "js3_ ",
+                    ],
+                ),
+            ],
+        )]);
+
+        let overbound = maps.find_overbound_actions(DEFAULT_MAX_BINDINGS_PER_ACTION);
+        assert_eq!(
+            overbound,
+            vec![OverboundAction {
+                action_map_name: "spaceship_weapons".to_string(),
+                action_name: "v_attack1".to_string(),
+                binding_count: 5,
+                excess_inputs: vec!["gp1_a".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_input_index_shared_default() {
+        let merged = MergedBindings {
+            action_maps: vec![MergedActionMap {
+                name: "spaceship_general".to_string(),
+                ui_label: String::new(),
+                ui_category: String::new(),
+                actions: vec![
+                    merged_action(
+                        "v_toggle_landing",
+                        vec![merged_binding("kb1_lalt+n", "", "Keyboard")],
+                    ),
+                    merged_action(
+                        "v_toggle_lights",
+                        vec![merged_binding("kb1_n+lalt", "", "Keyboard")],
+                    ),
+                    merged_action("v_eject", vec![merged_binding("kb1_ ", "", "Unknown")]),
+                ],
+            }],
+        };
+
+        let index = merged.build_input_index();
+        assert_eq!(
+            index.get(&normalize_input("kb_lalt+n")),
+            Some(&vec![
+                (
+                    "spaceship_general".to_string(),
+                    "v_toggle_landing".to_string()
+                ),
+                (
+                    "spaceship_general".to_string(),
+                    "v_toggle_lights".to_string()
+                ),
+            ])
+        );
+        assert_eq!(index.len(), 1);
+    }
 
-                            // If we ended up with no bindings at all (all defaults were spaces),
-                            // add an empty keyboard binding so the action still appears in the UI
-                            if default_bindings.is_empty() {
-                                default_bindings.push(MergedBinding {
-                                    input: "kb_ ".to_string(),
-                                    display_name: "Unbound".to_string(),
-                                    input_type: "Unknown".to_string(),
-                                    is_default: true,
-                                    multi_tap: None,
-                                    activation_mode: String::new(),
-                                    original_default: None,
-                                });
-                            }
+    #[test]
+    fn test_multi_tap_timing_round_trip() {
+        let xml = r#"<ActionMaps version="1" optionsVersion="2" rebindVersion="2" profileName="taps">
+ <actionmap name="spaceship_general">
+  <action name="v_toggle_landing">
+   <rebind input="kb1_n" multiTap="2" multiTapBlock="1" pressTriggerThreshold="0.25"/>
+  </action>
+ </actionmap>
+</ActionMaps>"#;
+
+        let maps = ActionMaps::from_xml(xml).unwrap();
+        let rebind = &maps.action_maps[0].actions[0].rebinds[0];
+        assert_eq!(rebind.multi_tap, Some(2));
+        assert_eq!(rebind.multi_tap_block, Some(true));
+        assert_eq!(rebind.press_trigger_threshold, Some(250));
+
+        let exported = maps.to_xml_with_categories(None, false);
+        assert!(exported.contains(
+            "<rebind input=\"kb1_n\" multiTap=\"2\" multiTapBlock=\"1\" pressTriggerThreshold=\"0.25\"/>"
+        ));
 
-                            default_bindings
-                        };
+        let reparsed = ActionMaps::from_xml(&exported).unwrap();
+        let rebind = &reparsed.action_maps[0].actions[0].rebinds[0];
+        assert_eq!(rebind.multi_tap, Some(2));
+        assert_eq!(rebind.multi_tap_block, Some(true));
+        assert_eq!(rebind.press_trigger_threshold, Some(250));
+    }
 
-                        // Use UILabel if available and not a localization key, otherwise format the name
-                        let display_label = if !all_binds_action.ui_label.is_empty()
-                            && !all_binds_action.ui_label.starts_with('@')
-                        {
-                            all_binds_action.ui_label.clone()
-                        } else {
-                            format_display_name(&all_binds_action.name)
-                        };
+    #[test]
+    fn test_out_of_range_bindings() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_button10"]),
+                ("v_attack2", vec!["js1_button50"]),
+                ("v_weapon_cycle", vec!["js1_hat2_up", "js2_button50"]),
+                ("v_pitch", vec!["js1_y"]),
+                // Named axes aren't numbered, so a 6-axis stick doesn't flag the slider
+                ("v_throttle_abs", vec!["js1_slider1"]),
+            ],
+        )]);
+        let capabilities = HashMap::from([(
+            1,
+            DeviceCapabilities {
+                button_count: 32,
+                axis_count: 6,
+                hat_count: 1,
+            },
+        )]);
+
+        let flagged = maps.find_out_of_range_bindings(&capabilities);
+        let inputs: Vec<&str> = flagged.iter().map(|b| b.input.as_str()).collect();
+        assert_eq!(inputs, vec!["js1_button50", "js1_hat2_up"]);
+        assert_eq!(flagged[0].kind, "button");
+        assert_eq!(flagged[0].index, 50);
+        assert_eq!(flagged[0].available, 32);
+    }
 
-                        // Debug: log quantum actions in merge
-                        if all_binds_action.name.contains("quantum") {
-                            println!(
-                                "Merging action: {} with {} bindings, display_label: {}",
-                                all_binds_action.name,
-                                bindings.len(),
-                                display_label
-                            );
-                            for (i, binding) in bindings.iter().enumerate() {
-                                println!(
-                                    "  Binding {}: input='{}', type={}, is_default={}",
-                                    i, binding.input, binding.input_type, binding.is_default
-                                );
-                            }
-                        }
+    #[test]
+    fn test_export_writes_bound_before_cleared() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![(
+                "v_attack1",
+                vec!["js1_ ", "js2_button1", "kb1_ ", "kb1_space"],
+            )],
+        )]);
+
+        let xml = maps.to_xml_with_categories(None, false);
+        let order: Vec<usize> = ["js2_button1", "kb1_space", "js1_ ", "kb1_ "]
+            .iter()
+            .map(|input| xml.find(&format!("input=\"{}\"", input)).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]), "{}", xml);
+    }
 
-                        MergedAction {
-                            name: all_binds_action.name.clone(),
-                            ui_label: display_label,
-                            ui_description: all_binds_action.ui_description.clone(),
-                            category: all_binds_action.category.clone(),
-                            is_customized,
-                            on_hold: all_binds_action.on_hold,
-                            bindings,
-                        }
-                    })
-                    .collect();
+    #[test]
+    fn test_set_primary_binding() {
+        let mut maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["mouse1_1", "kb1_space", "js1_button1"])],
+        )]);
+
+        maps.set_primary_binding("spaceship_weapons", "v_attack1", "js1_button1")
+            .unwrap();
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1"),
+            vec!["js1_button1", "mouse1_1", "kb1_space"]
+        );
+
+        // Inputs are compared normalized
+        maps.set_primary_binding("spaceship_weapons", "v_attack1", "kb_space")
+            .unwrap();
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1"),
+            vec!["kb1_space", "js1_button1", "mouse1_1"]
+        );
+
+        assert!(maps
+            .set_primary_binding("spaceship_weapons", "v_attack1", "js2_button1")
+            .is_err());
+        assert!(maps
+            .set_primary_binding("spaceship_weapons", "v_fire", "js1_button1")
+            .is_err());
+    }
 
-                // Use UILabel if available and not a localization key, otherwise format the name
-                let display_map_label = if !all_binds_map.ui_label.is_empty()
-                    && !all_binds_map.ui_label.starts_with('@')
-                {
-                    all_binds_map.ui_label.clone()
-                } else {
-                    format_display_name(&all_binds_map.name)
-                };
+    #[test]
+    fn test_organize_with_defaults_flags_changes() {
+        let defaults = all_binds(vec![(
+            "spaceship_general",
+            vec![
+                all_binds_action("v_toggle_landing", "lalt+n", "button5"),
+                all_binds_action("v_eject", "", "button10"),
+            ],
+        )]);
+        let maps = action_maps(vec![(
+            "spaceship_general",
+            vec![
+                ("v_toggle_landing", vec!["kb1_n+lalt", "js2_button6"]),
+                ("v_eject", vec!["js1_button10", "kb1_ "]),
+            ],
+        )]);
+
+        let organized = maps.organize_with_defaults(&defaults);
+        let flags = |action: usize| -> Vec<Option<bool>> {
+            organized.action_maps[0].actions[action]
+                .bindings
+                .iter()
+                .map(|b| b.differs_from_default)
+                .collect()
+        };
 
-                MergedActionMap {
-                    name: all_binds_map.name.clone(),
-                    ui_label: display_map_label,
-                    ui_category: all_binds_map.ui_category.clone(),
-                    actions: merged_actions,
-                }
-            })
+        assert_eq!(flags(0), vec![Some(false), Some(true)]);
+        // Clearing a device with no default changes nothing
+        assert_eq!(flags(1), vec![Some(false), Some(false)]);
+
+        let plain = maps.organize();
+        assert_eq!(
+            plain.action_maps[0].actions[0].bindings[0].differs_from_default,
+            None
+        );
+    }
+
+    #[test]
+    fn test_blank_profile_name_falls_back_to_filename() {
+        let xml = r#"<ActionMaps version="1" optionsVersion="2" rebindVersion="2" profileName="  ">
+ <actionmap name="spaceship_weapons">
+  <action name="v_attack1">
+   <rebind input="js1_button1"/>
+  </action>
+ </actionmap>
+</ActionMaps>"#;
+
+        let maps = ActionMaps::from_xml(xml).unwrap();
+        assert_eq!(
+            maps.organize().profile_name_source,
+            ProfileNameSource::Empty
+        );
+
+        let mut named = maps.clone();
+        assert_eq!(
+            named.fill_blank_profile_name("layout_hosas_exported.xml"),
+            ProfileNameSource::FromFilename
+        );
+        assert_eq!(named.profile_name, "hosas");
+        assert!(named
+            .to_xml_with_categories(None, false)
+            .contains("profileName=\"hosas\""));
+
+        let mut unnamed = maps.clone();
+        assert_eq!(
+            unnamed.fill_blank_profile_name("layout_.xml"),
+            ProfileNameSource::Empty
+        );
+        assert_eq!(unnamed.profile_name, "");
+
+        let mut kept =
+            ActionMaps::from_xml(&xml.replace("profileName=\"  \"", "profileName=\"pvp\""))
+                .unwrap();
+        assert_eq!(
+            kept.fill_blank_profile_name("layout_hosas_exported.xml"),
+            ProfileNameSource::FromFile
+        );
+        assert_eq!(kept.profile_name, "pvp");
+    }
+
+    #[test]
+    fn test_filter_merged_by_input_type() {
+        let merged = MergedBindings {
+            action_maps: vec![
+                merged_action_map(
+                    "spaceship_movement",
+                    "",
+                    vec![
+                        merged_action(
+                            "v_pitch",
+                            vec![
+                                merged_binding("kb1_w", "", "Keyboard"),
+                                merged_binding("js1_y", "", "Joystick"),
+                            ],
+                        ),
+                        merged_action(
+                            "v_afterburner",
+                            vec![merged_binding("kb1_lshift", "", "Keyboard")],
+                        ),
+                    ],
+                ),
+                merged_action_map(
+                    "spaceship_targeting",
+                    "",
+                    vec![merged_action(
+                        "v_target_cycle",
+                        vec![merged_binding("mouse1_3", "", "Mouse")],
+                    )],
+                ),
+            ],
+        };
+
+        let filtered = merged.filter_input_types(&["Joystick".to_string()]);
+
+        assert_eq!(filtered.action_maps.len(), 1);
+        let actions = &filtered.action_maps[0].actions;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].name, "v_pitch");
+        let inputs: Vec<&str> = actions[0]
+            .bindings
+            .iter()
+            .map(|b| b.input.as_str())
             .collect();
+        assert_eq!(inputs, vec!["js1_y"]);
+    }
 
-        MergedBindings {
-            action_maps: merged_maps,
+    #[test]
+    fn test_classify_input() {
+        let cleared = rebind("js2_ ").classify_input();
+        assert_eq!(cleared.input_type, InputType::Joystick);
+        assert!(cleared.is_cleared);
+        assert_eq!(cleared.device_instance, Some(2));
+        assert_eq!(rebind("js2_ ").get_input_type(), InputType::Unknown);
+
+        let modified = rebind("lalt+js1_button3").classify_input();
+        assert_eq!(modified.input_type, InputType::Joystick);
+        assert!(!modified.is_cleared);
+        assert_eq!(modified.device_instance, Some(1));
+        assert_eq!(modified.modifiers, vec!["lalt"]);
+
+        let keyboard = rebind("kb_lshift+lctrl+c").classify_input();
+        assert_eq!(keyboard.input_type, InputType::Keyboard);
+        assert_eq!(keyboard.device_instance, Some(1));
+        assert_eq!(keyboard.modifiers, vec!["lshift", "lctrl"]);
+
+        let invalid = rebind("xyz_foo").classify_input();
+        assert_eq!(invalid.input_type, InputType::Unknown);
+        assert!(!invalid.is_cleared);
+        assert_eq!(invalid.device_instance, None);
+        assert!(invalid.modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_missing_joystick_options() {
+        let mut maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_pitch", vec!["js1_y"]),
+                ("v_yaw", vec!["js2_x"]),
+                ("v_strafe_up", vec!["js3_button4", "lalt+js3_button5"]),
+            ],
+        )]);
+        maps.devices.joysticks = vec![
+            "T.16000M  {B10A044F-0000-0000-0000-504944564944}".to_string(),
+            "TWCS Throttle  {B687044F-0000-0000-0000-504944564944}".to_string(),
+        ];
+
+        assert_eq!(maps.missing_joystick_options(), vec![3]);
+
+        assert_eq!(maps.autofill_joystick_options(), vec![3]);
+        assert_eq!(maps.devices.joysticks[2], "Joystick 3");
+        assert!(maps.missing_joystick_options().is_empty());
+        assert!(maps
+            .to_xml_with_categories(None, false)
+            .contains("<options type=\"joystick\" instance=\"3\" Product=\"Joystick 3\"/>"));
+    }
+
+    #[test]
+    fn test_convert_gamepad_to_joystick() {
+        let mut maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_attack1", vec!["gp1_a"]),
+                ("v_pitch", vec!["gp1_thumbly"]),
+                ("v_strafe_up", vec!["lalt+gp2_shoulderl", "kb1_space"]),
+                ("v_boost", vec!["gp1_shoulderl+y"]),
+                ("v_target", vec!["gp1_dpad_up"]),
+                ("v_fire", vec!["gp1_triggerr_btn"]),
+                ("v_roll", vec!["gp1_thumbrx", "js1_x"]),
+            ],
+        )]);
+
+        let conversion = maps
+            .convert_device_type("gp", "js", &HashMap::from([(2, 3)]))
+            .unwrap();
+        assert_eq!(conversion.converted, 5);
+        assert_eq!(
+            conversion.unmappable,
+            vec![(
+                "spaceship_movement".to_string(),
+                "v_fire".to_string(),
+                "gp1_triggerr_btn".to_string()
+            )]
+        );
+        // v_roll already has js1_x, so the gamepad binding stays
+        assert_eq!(
+            conversion.collisions,
+            vec![(
+                "spaceship_movement".to_string(),
+                "v_roll".to_string(),
+                "gp1_thumbrx".to_string()
+            )]
+        );
+
+        for (action, expected) in [
+            ("v_attack1", vec!["js1_button1"]),
+            ("v_pitch", vec!["js1_y"]),
+            ("v_strafe_up", vec!["lalt+js3_button5", "kb1_space"]),
+            ("v_boost", vec!["js1_button5+button4"]),
+            ("v_target", vec!["js1_hat1_up"]),
+            ("v_fire", vec!["gp1_triggerr_btn"]),
+            ("v_roll", vec!["gp1_thumbrx", "js1_x"]),
+        ] {
+            assert_eq!(find_inputs(&maps, "spaceship_movement", action), expected);
         }
+
+        assert!(maps
+            .convert_device_type("kb", "js", &HashMap::new())
+            .is_err());
+        assert!(maps
+            .convert_device_type("gp", "gp", &HashMap::new())
+            .is_err());
     }
-}
 
-// Device selection struct for unbind profile generation
-#[derive(serde::Deserialize)]
-pub struct DeviceSelection {
-    pub keyboard: bool,
-    pub mouse: bool,
-    pub gamepad: bool,
-    pub joystick1: bool,
-    pub joystick2: bool,
-}
+    #[test]
+    fn test_convert_joystick_to_gamepad() {
+        let mut maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_attack1", vec!["js1_button1"]),
+                ("v_pitch", vec!["js1_y"]),
+                ("v_yaw", vec!["js1_rotz"]),
+                ("v_eject", vec!["js1_button20"]),
+                ("v_target", vec!["js1_hat1_left"]),
+            ],
+        )]);
+
+        let conversion = maps
+            .convert_device_type("js", "gp", &HashMap::new())
+            .unwrap();
+        assert_eq!(conversion.converted, 4);
+        assert_eq!(
+            conversion.unmappable,
+            vec![(
+                "spaceship_movement".to_string(),
+                "v_eject".to_string(),
+                "js1_button20".to_string()
+            )]
+        );
+        assert!(conversion.collisions.is_empty());
+
+        for (action, expected) in [
+            ("v_attack1", "gp1_a"),
+            ("v_pitch", "gp1_thumbly"),
+            ("v_yaw", "gp1_triggerr_axis"),
+            ("v_eject", "js1_button20"),
+            ("v_target", "gp1_dpad_left"),
+        ] {
+            assert_eq!(
+                find_inputs(&maps, "spaceship_movement", action),
+                vec![expected]
+            );
+        }
+    }
 
-/// Generate an unbind profile XML that clears all bindings for selected devices
-pub fn generate_unbind_xml(
-    all_binds: &AllBinds,
-    devices: &DeviceSelection,
-) -> Result<String, String> {
-    use std::collections::HashSet;
+    #[test]
+    fn test_mouse_axis_and_wheel_inputs() {
+        for input in [
+            "mouse1_maxis_x",
+            "mo_maxis_x",
+            "mo1_mwheel_up",
+            "lalt+mouse1_mwheel_down",
+        ] {
+            assert_eq!(
+                rebind(input).get_input_type(),
+                InputType::Mouse,
+                "{}",
+                input
+            );
+        }
 
-    let mut xml = String::new();
+        assert_eq!(
+            rebind("mouse1_maxis_x").get_display_name(),
+            "Mouse - X Axis"
+        );
+        assert_eq!(rebind("mo_maxis_y").get_display_name(), "Mouse - Y Axis");
+        assert_eq!(
+            rebind("mo_mwheel_up").get_display_name(),
+            "Mouse - Wheel Up"
+        );
+        assert_eq!(
+            rebind("lalt+mouse1_mwheel_down").get_display_name(),
+            "Left Alt + Mouse - Wheel Down"
+        );
+        assert_eq!(rebind("mo_mwheel").get_display_name(), "Mouse - Wheel");
+        assert_eq!(rebind("mo_ ").get_input_type(), InputType::Unknown);
+        assert!(rebind("mo_ ").is_cleared());
+    }
 
-    // XML declaration
-    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    #[test]
+    fn test_read_profile_name() {
+        assert_eq!(
+            ActionMaps::read_profile_name(
+                "<?xml version=\"1.0\"?>\n<!-- exported -->\n<ActionMaps version=\"1\" profileName=\"layout_combat\">\n <actionmap name=\"x\"/>\n</ActionMaps>"
+            ),
+            Some("layout_combat".to_string())
+        );
+        assert_eq!(
+            ActionMaps::read_profile_name("<ActionMaps profileName=\"  \"/>"),
+            None
+        );
+        assert_eq!(
+            ActionMaps::read_profile_name("<Other profileName=\"x\"/>"),
+            None
+        );
+        assert_eq!(ActionMaps::read_profile_name("not xml"), None);
+    }
 
-    // Root ActionMaps element
-    xml.push_str("<ActionMaps version=\"1\" optionsVersion=\"2\" rebindVersion=\"2\" profileName=\"UNBIND_ALL_DEVICES\">\n");
+    #[test]
+    fn test_generated_by_comment() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1_group1", vec!["js1_button1"])],
+        )]);
+
+        for (with_comment, without) in [
+            (maps.to_xml(true), maps.to_xml(false)),
+            (
+                maps.to_xml_with_categories(None, true),
+                maps.to_xml_with_categories(None, false),
+            ),
+        ] {
+            let mut lines = with_comment.lines();
+            assert_eq!(
+                lines.next(),
+                Some("<?xml version=\"1.0\" encoding=\"UTF-8\"?>")
+            );
+            let comment = lines.next().unwrap();
+            let prefix = format!(
+                "<!-- Generated by SC-Binding-Utility v{} on ",
+                env!("CARGO_PKG_VERSION")
+            );
+            let date = comment
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(" -->"))
+                .unwrap_or_else(|| panic!("unexpected comment: {}", comment));
+            assert!(
+                chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").is_ok(),
+                "{}",
+                date
+            );
+
+            // Dropping the comment line gives the output without it
+            let stripped: String = with_comment
+                .split_inclusive('\n')
+                .enumerate()
+                .filter(|(i, _)| *i != 1)
+                .map(|(_, line)| line)
+                .collect();
+            assert_eq!(stripped, without);
+            assert!(!without.contains("<!--"));
+
+            // Still parses with the leading comment
+            let reparsed = ActionMaps::from_xml(&with_comment).unwrap();
+            assert_eq!(
+                find_inputs(&reparsed, "spaceship_weapons", "v_attack1_group1"),
+                vec!["js1_button1"]
+            );
+        }
+    }
 
-    // Collect all unique categories in order
-    let mut categories_ordered = Vec::new();
-    let mut seen_categories = HashSet::new();
-    for action_map in &all_binds.action_maps {
-        if !action_map.ui_category.is_empty() && !seen_categories.contains(&action_map.ui_category)
-        {
-            categories_ordered.push(action_map.ui_category.clone());
-            seen_categories.insert(action_map.ui_category.clone());
+    #[test]
+    fn test_find_fully_unbound_actions() {
+        let maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_strafe_up", vec!["kb1_ ", "js1_ "]),
+                ("v_strafe_down", vec!["kb1_ ", "js1_button4"]),
+                ("v_roll", vec!["js1_x"]),
+            ],
+        )]);
+        let mut all_binds = all_binds(vec![(
+            "spaceship_movement",
+            vec![all_binds_action("v_strafe_up", "space", "")],
+        )]);
+        all_binds.action_maps[0].ui_label = "@ui_CGSpaceFlightMovement".to_string();
+        all_binds.action_maps[0].actions[0].ui_label = "Strafe Up".to_string();
+
+        let unbound = maps.find_fully_unbound_actions(None);
+        assert_eq!(unbound.len(), 1);
+        assert_eq!(unbound[0].action_name, "v_strafe_up");
+        assert_eq!(unbound[0].cleared_inputs, vec!["kb1_ ", "js1_ "]);
+
+        let labelled = maps.find_fully_unbound_actions(Some(&all_binds));
+        assert_eq!(labelled.len(), 1);
+        assert_eq!(labelled[0].action_label, "Strafe Up");
+        // Localization keys fall back to the formatted name until a localization is loaded
+        assert_eq!(
+            labelled[0].action_map_label,
+            format_display_name("spaceship_movement")
+        );
+        all_binds.localization.insert(
+            "ui_cgspaceflightmovement".to_string(),
+            "Flight - Movement".to_string(),
+        );
+        let localized = maps.find_fully_unbound_actions(Some(&all_binds));
+        assert_eq!(localized[0].action_map_label, "Flight - Movement");
+
+        assert!(!Action {
+            name: "v_roll".to_string(),
+            rebinds: Vec::new(),
         }
+        .is_fully_cleared());
     }
 
-    // Write CustomisationUIHeader with proper attributes
-    xml.push_str(" <CustomisationUIHeader label=\"UNBIND_ALL_DEVICES\" description=\"Clears all bindings for selected devices\" image=\"\">\n");
+    #[test]
+    fn test_export_single_category() {
+        let maps = action_maps(vec![
+            ("spaceship_movement", vec![("v_roll", vec!["js1_x"])]),
+            (
+                "spaceship_weapons",
+                vec![("v_attack1_group1", vec!["js1_button1"])],
+            ),
+            ("player", vec![("jump", vec!["kb1_space"])]),
+            ("custom_uncategorized", vec![("v_custom", vec!["kb1_f5"])]),
+        ]);
+        let mut all_binds = all_binds(vec![
+            ("spaceship_movement", Vec::new()),
+            ("spaceship_weapons", Vec::new()),
+            ("player", Vec::new()),
+        ]);
+        for (map, category) in all_binds.action_maps.iter_mut().zip([
+            "@ui_CCSpaceFlight",
+            "@ui_CCSpaceFlight",
+            "@ui_CCFPS",
+        ]) {
+            map.ui_category = category.to_string();
+        }
 
-    // Write devices section - order matters!
-    xml.push_str("  <devices>\n");
-    if devices.keyboard {
-        xml.push_str("   <keyboard instance=\"1\"/>\n");
-    }
-    if devices.mouse {
-        xml.push_str("   <mouse instance=\"1\"/>\n");
+        let flight = maps.action_maps_in_category(&all_binds, "@ui_CCSpaceFlight");
+        assert_eq!(flight, vec!["spaceship_movement", "spaceship_weapons"]);
+        let xml = maps.to_xml_with_categories_filtered(Some(&all_binds), &flight, false);
+        assert!(xml.contains("<actionmap name=\"spaceship_movement\">"));
+        assert!(xml.contains("<actionmap name=\"spaceship_weapons\">"));
+        assert!(!xml.contains("<actionmap name=\"player\">"));
+        assert!(!xml.contains("custom_uncategorized"));
+
+        // Maps without a category only come along when asking for ""
+        assert_eq!(
+            maps.action_maps_in_category(&all_binds, ""),
+            vec!["custom_uncategorized"]
+        );
+        assert!(maps
+            .action_maps_in_category(&all_binds, "@ui_CCVehicle")
+            .is_empty());
     }
-    if devices.joystick1 {
-        xml.push_str("   <joystick instance=\"1\"/>\n");
+
+    #[test]
+    fn test_materialize_joystick_defaults() {
+        let all_binds = all_binds(vec![
+            (
+                "spaceship_movement",
+                vec![
+                    all_binds_action("v_roll", "", "x"),
+                    all_binds_action("v_pitch", "", "y"),
+                    all_binds_action("v_strafe_up", "space", ""),
+                ],
+            ),
+            (
+                "spaceship_weapons",
+                vec![all_binds_action("v_attack1_group1", "", "button1")],
+            ),
+        ]);
+        let mut maps = action_maps(vec![(
+            "spaceship_movement",
+            vec![
+                ("v_pitch", vec!["js1_rotz"]),
+                ("v_strafe_up", vec!["kb1_r"]),
+            ],
+        )]);
+
+        assert_eq!(
+            maps.materialize_defaults(&all_binds, &InputType::Joystick),
+            2
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_movement", "v_roll"),
+            vec!["js1_x"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1_group1"),
+            vec!["js1_button1"]
+        );
+        // The user's own joystick binding is kept, keyboard-only actions get nothing new
+        assert_eq!(
+            find_inputs(&maps, "spaceship_movement", "v_pitch"),
+            vec!["js1_rotz"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_movement", "v_strafe_up"),
+            vec!["kb1_r"]
+        );
+
+        // Running it again adds nothing
+        assert_eq!(
+            maps.materialize_defaults(&all_binds, &InputType::Joystick),
+            0
+        );
     }
-    if devices.joystick2 {
-        xml.push_str("   <joystick instance=\"2\"/>\n");
+
+    #[test]
+    fn test_deprecated_tokens() {
+        let mut maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1_group1", vec!["xi1_shoulderr", "js1_button1"]),
+                ("v_attack1_group2", vec!["lalt+xi_a"]),
+                // The short mouse prefix is still valid
+                ("v_weapon_cycle", vec!["mo1_mouse3"]),
+                // Keys are never treated as device prefixes
+                ("v_weapon_select", vec!["kb1_xi"]),
+            ],
+        )]);
+
+        let flagged = maps.find_deprecated_tokens(DEPRECATED_TOKENS);
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[0].input, "xi1_shoulderr");
+        assert_eq!(flagged[0].token, "xi1");
+        assert_eq!(flagged[0].suggested_input.as_deref(), Some("gp1_shoulderr"));
+        assert_eq!(flagged[1].suggested_input.as_deref(), Some("lalt+gp1_a"));
+
+        // A token with no known replacement is flagged but left alone
+        let table = [DeprecatedToken {
+            token: "mo1",
+            replacement: None,
+        }];
+        let flagged = maps.find_deprecated_tokens(&table);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].suggested_input, None);
+        assert_eq!(maps.apply_token_replacements(&table), 0);
+
+        assert_eq!(maps.apply_token_replacements(DEPRECATED_TOKENS), 2);
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1_group1"),
+            vec!["gp1_shoulderr", "js1_button1"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1_group2"),
+            vec!["lalt+gp1_a"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_weapon_select"),
+            vec!["kb1_xi"]
+        );
+        assert!(maps.find_deprecated_tokens(DEPRECATED_TOKENS).is_empty());
     }
-    xml.push_str("  </devices>\n");
 
-    // Write categories section
-    // if !categories_ordered.is_empty() {
-    //     xml.push_str("  <categories>\n");
-    //     for category in &categories_ordered {
-    //         xml.push_str("   <category label=\"");
-    //         xml.push_str(category);
-    //         xml.push_str("\"/>\n");
-    //     }
-    //     xml.push_str("  </categories>\n");
-    // }
+    #[test]
+    fn test_summarize_unbind() {
+        let mut throttle = all_binds_action("v_throttle_up", "w", "");
+        throttle.default_gamepad = "shoulderl".to_string();
+        let all_binds = all_binds(vec![
+            (
+                "spaceship_movement",
+                vec![
+                    all_binds_action("v_roll", "", "x"),
+                    all_binds_action("v_strafe_up", "space", ""),
+                    all_binds_action("v_boost", "lshift", "button3"),
+                    all_binds_action("v_unbound", "", ""),
+                    throttle,
+                ],
+            ),
+            // Kept on Escape, never counted
+            (
+                "ui_textfield",
+                vec![all_binds_action("ui_back", "escape", "")],
+            ),
+        ]);
+        let devices = DeviceSelection {
+            keyboard: true,
+            mouse: false,
+            gamepad: true,
+            joystick1: true,
+            joystick2: true,
+        };
 
-    xml.push_str(" </CustomisationUIHeader>\n");
+        let summary = summarize_unbind(&all_binds, &devices);
+        assert_eq!(
+            summary.per_device,
+            HashMap::from([
+                ("keyboard".to_string(), 3),
+                ("gamepad".to_string(), 1),
+                ("joystick1".to_string(), 2),
+                ("joystick2".to_string(), 2),
+            ])
+        );
+    }
 
-    // Write options for each device type with default Product IDs
-    // if devices.keyboard {
-    //     xml.push_str(" <options type=\"keyboard\" instance=\"1\" Product=\"Keyboard  {6F1D2B61-D5A0-11CF-BFC7-444553540000}\"/>\n");
-    // }
-    // if devices.mouse {
-    //     xml.push_str(" <options type=\"mouse\" instance=\"1\" Product=\"Mouse  {6F1D2B62-D5A0-11CF-BFC7-444553540000}\"/>\n");
-    // }
-    // if devices.joystick1 {
-    //     xml.push_str(" <options type=\"joystick\" instance=\"1\" Product=\"\"/>\n");
-    // }
-    // if devices.joystick2 {
-    //     xml.push_str(" <options type=\"joystick\" instance=\"2\" Product=\"\"/>\n");
-    // }
+    #[test]
+    fn test_version_compatibility() {
+        let current = ActionMaps::from_xml(
+            "<ActionMaps version=\"1\" optionsVersion=\"2\" rebindVersion=\"2\" profileName=\"ok\"/>",
+        )
+        .unwrap();
+        let check = current.check_version_compatibility();
+        assert_eq!(check.file_rebind_version, Some(2));
+        assert!(check.compatible);
+        assert_eq!(check.warning, None);
+
+        let stale = ActionMaps::from_xml(
+            "<ActionMaps version=\"1\" optionsVersion=\"2\" rebindVersion=\"1\" profileName=\"old\"/>",
+        )
+        .unwrap();
+        let check = stale.check_version_compatibility();
+        assert_eq!(check.file_rebind_version, Some(1));
+        assert_eq!(check.expected, EXPECTED_REBIND_VERSION);
+        assert!(!check.compatible);
+        assert!(check.warning.unwrap().contains("rebindVersion 1"));
+
+        // Exports always write the versions SC expects, whatever the file had
+        let expected = format!(
+            "optionsVersion=\"{}\" rebindVersion=\"{}\"",
+            EXPECTED_OPTIONS_VERSION, EXPECTED_REBIND_VERSION
+        );
+        assert!(stale.to_xml(false).contains(&expected));
+        assert!(stale
+            .to_xml_with_categories(None, false)
+            .contains(&expected));
+        let unbind = generate_unbind_xml(
+            &all_binds(vec![(
+                "spaceship_weapons",
+                vec![all_binds_action("v_attack1", "space", "")],
+            )]),
+            &DeviceSelection {
+                keyboard: true,
+                mouse: false,
+                gamepad: false,
+                joystick1: false,
+                joystick2: false,
+            },
+        )
+        .unwrap();
+        assert!(unbind.contains(&expected));
+
+        // Missing attributes are not a mismatch
+        let bare = ActionMaps::from_xml("<ActionMaps profileName=\"bare\"/>").unwrap();
+        assert!(bare.check_version_compatibility().compatible);
+    }
 
-    // Empty modifiers
-    xml.push_str(" <modifiers />\n");
+    #[test]
+    fn test_category_tree_counts_and_order() {
+        let mut binds = all_binds(vec![
+            (
+                "spaceship_movement",
+                vec![
+                    all_binds_action("v_pitch", "", "js1_y"),
+                    all_binds_action("v_yaw", "", "js1_x"),
+                ],
+            ),
+            (
+                "seat_general",
+                vec![all_binds_action("v_eject", "ralt+y", "")],
+            ),
+            (
+                "spaceship_weapons",
+                vec![all_binds_action("v_attack1", "mouse1", "js1_button1")],
+            ),
+        ]);
+        binds.action_maps[0].ui_category = "Flight".to_string();
+        binds.action_maps[1].ui_category = "Vehicle".to_string();
+        binds.action_maps[2].ui_category = "Flight".to_string();
+        binds.action_maps[2].ui_label = "Weapons".to_string();
+
+        let tree = binds.merge_with_user_bindings(None).category_tree();
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].category, "Flight");
+        assert_eq!(
+            tree[0].action_maps,
+            vec![
+                CategoryActionMap {
+                    name: "spaceship_movement".to_string(),
+                    label: "Spaceship Movement".to_string(),
+                    action_count: 2,
+                },
+                CategoryActionMap {
+                    name: "spaceship_weapons".to_string(),
+                    label: "Weapons".to_string(),
+                    action_count: 1,
+                },
+            ]
+        );
+        assert_eq!(tree[1].category, "Vehicle");
+        assert_eq!(tree[1].action_maps.len(), 1);
+        assert_eq!(tree[1].action_maps[0].action_count, 1);
+    }
 
-    // Write actionmaps with blank rebinds
-    for action_map in &all_binds.action_maps {
-        xml.push_str(" <actionmap name=\"");
-        xml.push_str(&action_map.name);
-        xml.push_str("\">\n");
+    #[test]
+    fn test_chord_display_names() {
+        assert_eq!(
+            rebind("kb1_lctrl+kb1_lshift+kb1_w").get_display_name(),
+            "Keyboard - Left Ctrl + Left Shift + W"
+        );
+        assert_eq!(
+            rebind("kb_lctrl+kb_w").get_display_name(),
+            "Keyboard - Left Ctrl + W"
+        );
+        // A held key that isn't one of the standard modifiers
+        assert_eq!(
+            rebind("lalt+capslock+js1_button3").get_display_name(),
+            "Left Alt + Caps Lock + Joystick 1 - Button 3"
+        );
+        assert_eq!(
+            rebind("LALT+RCTRL+js1_button3").get_display_name(),
+            "Left Alt + Right Ctrl + Joystick 1 - Button 3"
+        );
+    }
 
-        for action in &action_map.actions {
-            xml.push_str("  <action name=\"");
-            xml.push_str(&action.name);
-            xml.push_str("\">\n");
+    #[test]
+    fn test_all_binds_diff() {
+        let old = all_binds(vec![(
+            "spaceship_weapons",
+            vec![
+                all_binds_action("v_attack1", "mouse1", "js1_button1"),
+                all_binds_action("v_weapon_cycle", "", "js1_button4"),
+            ],
+        )]);
+        let new = all_binds(vec![(
+            "spaceship_weapons",
+            vec![
+                all_binds_action("v_attack1", "mouse1", "js1_button2"),
+                all_binds_action("v_attack1_group3", "", ""),
+            ],
+        )]);
+
+        let diff = new.diff_from(&old);
+
+        assert_eq!(
+            diff.added,
+            vec![AllBindsActionRef {
+                action_map_name: "spaceship_weapons".to_string(),
+                action_name: "v_attack1_group3".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![AllBindsActionRef {
+                action_map_name: "spaceship_weapons".to_string(),
+                action_name: "v_weapon_cycle".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.changed_defaults,
+            vec![ChangedDefault {
+                action_map_name: "spaceship_weapons".to_string(),
+                action_name: "v_attack1".to_string(),
+                device: InputType::Joystick,
+                old_default: Some("js1_button1".to_string()),
+                new_default: Some("js1_button2".to_string()),
+            }]
+        );
+
+        let unchanged = new.diff_from(&new);
+        assert!(unchanged.added.is_empty() && unchanged.removed.is_empty());
+        assert!(unchanged.changed_defaults.is_empty());
+    }
 
-            // Special handling for critical UI actions to ensure they always work
-            if action.name == "ui_toggle_pause" || action.name == "ui_back" {
-                // Set escape key explicitly for menu navigation
-                xml.push_str("   <rebind input=\"kb1_escape\" activationMode=\"press\"/>\n");
-            } else {
-                // Add blank rebinds for each selected device for all other actions
-                if devices.keyboard {
-                    xml.push_str("   <rebind input=\"kb1_ \"/>\n");
-                }
-                if devices.mouse {
-                    xml.push_str("   <rebind input=\"mouse1_ \"/>\n");
-                }
-                if devices.gamepad {
-                    xml.push_str("   <rebind input=\"gp1_ \"/>\n");
-                }
-                if devices.joystick1 {
-                    xml.push_str("   <rebind input=\"js1_ \"/>\n");
-                }
-                if devices.joystick2 {
-                    xml.push_str("   <rebind input=\"js2_ \"/>\n");
+    #[test]
+    fn test_export_escapes_attribute_values() {
+        let mut maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["js1_button1"])],
+        )]);
+        maps.profile_name = "Tom & Jerry's <\"HOSAS\">".to_string();
+        maps.description = "a < b".to_string();
+
+        for xml in [maps.to_xml(false), maps.to_xml_with_categories(None, false)] {
+            assert!(
+                xml.contains("profileName=\"Tom &amp; Jerry&apos;s &lt;&quot;HOSAS&quot;&gt;\"")
+            );
+
+            // Every attribute must be well-formed for a strict parser
+            let mut reader = quick_xml::Reader::from_str(&xml);
+            loop {
+                match reader.read_event() {
+                    Ok(quick_xml::events::Event::Eof) => break,
+                    Ok(quick_xml::events::Event::Start(e))
+                    | Ok(quick_xml::events::Event::Empty(e)) => {
+                        for attr in e.attributes() {
+                            attr.unwrap().unescape_value().unwrap();
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => panic!("invalid XML: {}", e),
                 }
             }
 
-            xml.push_str("  </action>\n");
+            let parsed = ActionMaps::from_xml(&xml).unwrap();
+            assert_eq!(parsed.profile_name, maps.profile_name);
+            assert_eq!(parsed.description, maps.description);
+            assert_eq!(
+                ActionMaps::read_profile_name(&xml).as_deref(),
+                Some(maps.profile_name.as_str())
+            );
         }
+    }
 
-        xml.push_str(" </actionmap>\n");
+    #[test]
+    fn test_usages_of_control() {
+        let names = [
+            "v_attack1",
+            "v_attack2",
+            "v_weapon_cycle",
+            "v_weapon_group",
+            "v_weapon_hat",
+            "v_yaw",
+            "v_roll",
+        ];
+        let binds = all_binds(vec![(
+            "spaceship_weapons",
+            names
+                .iter()
+                .map(|name| all_binds_action(name, "", ""))
+                .collect(),
+        )]);
+        let user = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js2_button12"]),
+                ("v_attack2", vec!["LALT+js2_button12"]),
+                ("v_weapon_cycle", vec!["js2_button2"]),
+                ("v_weapon_group", vec!["js1_button12"]),
+                ("v_weapon_hat", vec!["js2_hat1_up"]),
+                ("v_yaw", vec!["js2_rotz"]),
+                ("v_roll", vec!["js2_axis6_negative"]),
+            ],
+        )]);
+        let merged = binds.merge_with_user_bindings(Some(&user));
+
+        let actions = |usages: Vec<(String, String, String)>| -> Vec<String> {
+            usages.into_iter().map(|(_, action, _)| action).collect()
+        };
+
+        let usages = merged.usages_of_control("js2", "button", 12);
+        assert_eq!(actions(usages.clone()), vec!["v_attack1", "v_attack2"]);
+        assert_eq!(usages[1].2, "LALT+js2_button12");
+        assert_eq!(
+            actions(merged.usages_of_control("js2", "hat", 1)),
+            vec!["v_weapon_hat"]
+        );
+        assert!(merged.usages_of_control("js3", "button", 12).is_empty());
+        assert_eq!(
+            actions(merged.usages_of_control("js2", "axis", 6)),
+            vec!["v_yaw", "v_roll"]
+        );
+        assert!(merged.usages_of_control("js2", "axis", 3).is_empty());
     }
 
-    xml.push_str("</ActionMaps>\n");
+    #[test]
+    fn test_export_is_idempotent() {
+        // Hand-edited: odd indentation, attribute order and spacing, unsorted maps
+        let hand_edited = "<?xml version=\"1.0\"?>\n<ActionMaps  profileName=\"Hand &amp; Edited\" version=\"1\">\n<CustomisationUIHeader description=\"mine\" label=\"x\">\n<devices><joystick instance=\"1\"/></devices>\n</CustomisationUIHeader>\n    <options type=\"joystick\" instance=\"2\" Product=\" VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}\"/>\n<actionmap name=\"spaceship_weapons\"><action name=\"v_attack1\">\n\t<rebind input=\"js2_button1\" activationMode=\"tap\" multiTap=\"2\"/>\n        <rebind input=\"kb1_ \"/>\n</action></actionmap>\n  <actionmap name=\"seat_general\">\n   <action name=\"v_eject\"><rebind input=\"lalt+kb1_y\"/></action>\n  </actionmap>\n</ActionMaps>\n";
+        let binds = all_binds(vec![
+            ("seat_general", vec![all_binds_action("v_eject", "", "")]),
+            (
+                "spaceship_weapons",
+                vec![all_binds_action("v_attack1", "", "")],
+            ),
+        ]);
+
+        let first = ActionMaps::from_xml(hand_edited)
+            .unwrap()
+            .to_xml_with_categories(Some(&binds), false);
+        let second = ActionMaps::from_xml(&first)
+            .unwrap()
+            .to_xml_with_categories(Some(&binds), false);
+
+        assert_eq!(first, second);
+        assert!(first.contains("profileName=\"Hand &amp; Edited\""));
+    }
 
-    Ok(xml)
+    #[test]
+    fn test_find_and_prune_empty_containers() {
+        let mut maps = action_maps(vec![
+            (
+                "spaceship_weapons",
+                vec![
+                    ("v_attack1", vec!["js1_button1"]),
+                    ("v_attack2", vec![]),
+                    ("v_weapon_cycle", vec!["js1_ ", "kb1_ "]),
+                ],
+            ),
+            ("seat_general", vec![("v_eject", vec![])]),
+            ("spaceship_movement", vec![]),
+        ]);
+
+        let empty = maps.find_empty_containers();
+        assert_eq!(
+            empty.action_maps,
+            vec!["seat_general", "spaceship_movement"]
+        );
+        assert_eq!(
+            empty.actions,
+            vec![
+                ("spaceship_weapons".to_string(), "v_attack2".to_string()),
+                ("seat_general".to_string(), "v_eject".to_string()),
+            ]
+        );
+
+        // Two actions and two action maps
+        assert_eq!(maps.prune_empty_containers(), 4);
+        assert_eq!(maps.action_maps.len(), 1);
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_weapon_cycle"),
+            vec!["js1_ ", "kb1_ "]
+        );
+        assert_eq!(maps.action_maps[0].actions.len(), 2);
+
+        let empty = maps.find_empty_containers();
+        assert!(empty.action_maps.is_empty() && empty.actions.is_empty());
+        assert_eq!(maps.prune_empty_containers(), 0);
+    }
+
+    #[test]
+    fn test_merge_profile_incoming_wins_per_instance() {
+        let mut flight = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_button1", "kb1_space"]),
+                ("v_attack2", vec!["js1_button2"]),
+            ],
+        )]);
+        let fps = action_maps(vec![
+            (
+                "spaceship_weapons",
+                vec![("v_attack1", vec!["js1_button5", "lalt+js1_button6"])],
+            ),
+            ("player", vec![("attack1", vec!["mouse1_mouse1"])]),
+        ]);
+
+        assert_eq!(flight.merge_profile(&fps), 3);
+
+        assert_eq!(
+            find_inputs(&flight, "spaceship_weapons", "v_attack1"),
+            vec!["kb1_space", "js1_button5", "lalt+js1_button6"]
+        );
+        assert_eq!(
+            find_inputs(&flight, "spaceship_weapons", "v_attack2"),
+            vec!["js1_button2"]
+        );
+        assert_eq!(
+            find_inputs(&flight, "player", "attack1"),
+            vec!["mouse1_mouse1"]
+        );
+    }
+
+    #[test]
+    fn test_merge_profile_merges_device_options() {
+        let mut keyboard = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["kb1_space"])],
+        )]);
+        keyboard.devices = DeviceInfo {
+            keyboards: vec!["Keyboard".to_string()],
+            ..DeviceInfo::default()
+        };
+        let mut joystick = action_maps(vec![(
+            "spaceship_weapons",
+            vec![("v_attack1", vec!["js2_button1"])],
+        )]);
+        joystick.devices = DeviceInfo {
+            keyboards: vec!["Other Keyboard".to_string()],
+            mice: vec!["Mouse".to_string()],
+            joysticks: vec![String::new(), "Right Stick".to_string()],
+            declared_joystick_instances: vec![2, 3],
+        };
+        keyboard.devices.declared_joystick_instances = vec![3, 1];
+
+        keyboard.merge_profile(&joystick);
+
+        assert_eq!(keyboard.devices.keyboards, vec!["Keyboard"]);
+        assert_eq!(keyboard.devices.mice, vec!["Mouse"]);
+        assert_eq!(keyboard.devices.joysticks, vec!["", "Right Stick"]);
+        assert_eq!(keyboard.devices.declared_joystick_instances, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_joystick_options_without_product() {
+        let xml = "<ActionMaps profileName=\"bare_options\">\n <options type=\"joystick\" instance=\"1\" Product=\" VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}\"/>\n <options type=\"joystick\" instance=\"3\"/>\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js3_button1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n";
+
+        let maps = ActionMaps::from_xml(xml).unwrap();
+        assert_eq!(maps.devices.joysticks.len(), 1);
+        assert_eq!(maps.devices.declared_joystick_instances, vec![1, 3]);
+        assert_eq!(maps.devices.joystick_instance_count(), 3);
+
+        let exported = maps.to_xml_with_categories(None, false);
+        assert!(exported.contains("<joystick instance=\"3\"/>"));
+        assert!(exported.contains("<options type=\"joystick\" instance=\"3\"/>"));
+        assert!(!exported.contains("<options type=\"joystick\" instance=\"2\""));
+
+        let reparsed = ActionMaps::from_xml(&exported).unwrap();
+        assert_eq!(reparsed.devices.declared_joystick_instances, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_export_single_device() {
+        let mut maps = action_maps(vec![
+            (
+                "spaceship_weapons",
+                vec![
+                    ("v_attack1", vec!["js1_button1", "kb1_space"]),
+                    ("v_attack2", vec!["js2_button2"]),
+                ],
+            ),
+            (
+                "seat_general",
+                vec![("v_eject", vec!["lalt+kb1_y", "js1_ "])],
+            ),
+        ]);
+        maps.devices.joysticks = vec!["Left Stick".to_string(), "Right Stick".to_string()];
+
+        let devices = maps.device_instances();
+        let suffixes: Vec<String> = devices.iter().map(|d| d.file_suffix()).collect();
+        assert_eq!(suffixes, vec!["keyboard", "joystick1", "joystick2"]);
+
+        let keyboard = maps.to_xml_with_categories_for_device(None, &devices[0], false);
+        let keyboard = ActionMaps::from_xml(&keyboard).unwrap();
+        assert_eq!(
+            find_inputs(&keyboard, "spaceship_weapons", "v_attack1"),
+            vec!["kb1_space"]
+        );
+        assert_eq!(
+            find_inputs(&keyboard, "seat_general", "v_eject"),
+            vec!["lalt+kb1_y"]
+        );
+        assert!(keyboard.action_maps[0]
+            .actions
+            .iter()
+            .all(|a| a.name != "v_attack2"));
+        assert!(keyboard.devices.joysticks.is_empty());
+
+        let js1 = maps.to_xml_with_categories_for_device(None, &devices[1], false);
+        let js1 = ActionMaps::from_xml(&js1).unwrap();
+        assert_eq!(
+            find_inputs(&js1, "spaceship_weapons", "v_attack1"),
+            vec!["js1_button1"]
+        );
+        assert_eq!(find_inputs(&js1, "seat_general", "v_eject"), vec!["js1_ "]);
+        assert_eq!(js1.devices.joysticks, vec!["Left Stick"]);
+    }
+
+    #[test]
+    fn test_binary_profile_round_trip() {
+        let xml = r#"<ActionMaps version="1" optionsVersion="2" rebindVersion="2" profileName="fast">
+ <CustomisationUIHeader label="fast" description="big profile" image="">
+  <devices>
+   <keyboard instance="1"/>
+   <joystick instance="1"/>
+  </devices>
+ </CustomisationUIHeader>
+ <options type="joystick" instance="1" Product="Left Stick"/>
+ <actionmap name="spaceship_weapons">
+  <action name="v_attack1">
+   <rebind input="js1_button1" activationMode="double_tap" multiTap="2"/>
+   <rebind input="kb1_space"/>
+  </action>
+ </actionmap>
+</ActionMaps>
+"#;
+        let maps = ActionMaps::from_xml(xml).unwrap();
+
+        let bytes = maps.to_binary().unwrap();
+        assert_eq!(bytes[..4], BINARY_PROFILE_MAGIC);
+        let loaded = ActionMaps::from_binary(&bytes).unwrap();
+        assert_eq!(loaded, maps);
+        assert_eq!(loaded.to_xml(false), maps.to_xml(false));
+        assert_eq!(loaded.description, "big profile");
+        assert_eq!(
+            loaded.action_maps[0].actions[0].rebinds[0].multi_tap,
+            Some(2)
+        );
+
+        let mut stale = bytes.clone();
+        stale[4..6].copy_from_slice(&(BINARY_PROFILE_VERSION + 1).to_le_bytes());
+        let err = ActionMaps::from_binary(&stale).unwrap_err();
+        assert!(
+            err.contains("Unsupported binary profile version"),
+            "{}",
+            err
+        );
+
+        let err = ActionMaps::from_binary(xml.as_bytes()).unwrap_err();
+        assert!(err.contains("Not a binary profile"), "{}", err);
+    }
+
+    #[test]
+    fn test_modifier_only_bindings() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["LALT+LCTRL", "LALT+js1_button3"]),
+                (
+                    "v_attack2",
+                    vec!["kb1_rshift+lalt", "kb1_lshift+c", "js1_ "],
+                ),
+                ("v_boost", vec!["kb1_lshift", "rshift"]),
+                ("v_strafe_up", vec!["LALT", "kb1_c"]),
+            ],
+        )]);
+
+        assert_eq!(
+            maps.find_modifier_only_bindings(),
+            vec![
+                (
+                    "spaceship_weapons".to_string(),
+                    "v_attack1".to_string(),
+                    "LALT+LCTRL".to_string()
+                ),
+                (
+                    "spaceship_weapons".to_string(),
+                    "v_attack2".to_string(),
+                    "kb1_rshift+lalt".to_string()
+                ),
+                (
+                    "spaceship_weapons".to_string(),
+                    "v_strafe_up".to_string(),
+                    "LALT".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_free_controls() {
+        let binds = all_binds(vec![(
+            "spaceship_weapons",
+            ["v_attack1", "v_attack2", "v_weapon_cycle", "v_weapon_hat"]
+                .iter()
+                .map(|name| all_binds_action(name, "", ""))
+                .collect(),
+        )]);
+        let user = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_button1", "js2_button3"]),
+                ("v_attack2", vec!["LALT+js1_button2"]),
+                ("v_weapon_cycle", vec!["js1_button5", "js1_ "]),
+                ("v_weapon_hat", vec!["js1_hat1_left"]),
+            ],
+        )]);
+        let merged = binds.merge_with_user_bindings(Some(&user));
+
+        assert_eq!(
+            merged.free_controls("js1", "button", 8),
+            vec!["button3", "button4", "button6", "button7", "button8"]
+        );
+        assert_eq!(merged.free_controls("js1", "hat", 2), vec!["hat2"]);
+        assert_eq!(
+            merged.free_controls("js3", "button", 2),
+            vec!["button1", "button2"]
+        );
+    }
+
+    #[test]
+    fn test_export_discrepancies() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_ "]),
+                ("v_attack2", vec!["js1_button2", "kb1_ "]),
+            ],
+        )]);
+
+        assert_eq!(
+            maps.find_export_discrepancies(),
+            vec![(
+                "spaceship_weapons".to_string(),
+                "v_attack1".to_string(),
+                "js1_ ".to_string()
+            )]
+        );
+
+        let plain = ActionMaps::from_xml(&maps.to_xml(false)).unwrap();
+        assert!(find_inputs(&plain, "spaceship_weapons", "v_attack1").is_empty());
+        assert_eq!(
+            find_inputs(&plain, "spaceship_weapons", "v_attack2"),
+            vec!["js1_button2", "kb1_ "]
+        );
+        let categories = ActionMaps::from_xml(&maps.to_xml_with_categories(None, false)).unwrap();
+        assert_eq!(
+            find_inputs(&categories, "spaceship_weapons", "v_attack1"),
+            vec!["js1_ "]
+        );
+    }
+
+    #[test]
+    fn test_noncontributing_action_maps() {
+        let mut maps = action_maps(vec![
+            (
+                "spaceship_weapons",
+                vec![("v_attack1", vec!["js1_button1", "js2_ "])],
+            ),
+            (
+                "spaceship_targeting",
+                vec![("v_target_cycle", vec!["js1_ ", "kb1_ "])],
+            ),
+            (
+                "seat_general",
+                vec![("v_eject", vec![]), ("v_emergency_exit", vec![""])],
+            ),
+        ]);
+
+        assert_eq!(
+            maps.find_noncontributing_action_maps(false),
+            vec!["spaceship_targeting", "seat_general"]
+        );
+        assert_eq!(
+            maps.find_noncontributing_action_maps(true),
+            vec!["seat_general"]
+        );
+
+        // The clears-only map is kept when its clears are wanted, the empty map goes
+        assert_eq!(
+            maps.remove_noncontributing_action_maps(true),
+            vec!["seat_general"]
+        );
+        let names: Vec<&str> = maps.action_maps.iter().map(|am| am.name.as_str()).collect();
+        assert_eq!(names, vec!["spaceship_weapons", "spaceship_targeting"]);
+
+        assert_eq!(
+            maps.remove_noncontributing_action_maps(false),
+            vec!["spaceship_targeting"]
+        );
+        assert_eq!(maps.action_maps.len(), 1);
+    }
+
+    #[test]
+    fn test_localized_labels() {
+        let mut binds = all_binds(vec![(
+            "spaceship_weapons",
+            vec![
+                all_binds_action("v_attack1", "", ""),
+                all_binds_action("v_attack2", "", ""),
+            ],
+        )]);
+        binds.action_maps[0].ui_label = "@ui_CGSpaceFlightWeapons".to_string();
+        binds.action_maps[0].actions[0].ui_label = "@ui_CIFireGroup1".to_string();
+        binds.action_maps[0].actions[1].ui_label = "@ui_CIMissingKey".to_string();
+
+        binds.localization = parse_localization(
+            "\u{feff}; SC localization\nui_CGSpaceFlightWeapons=Armes\r\nui_CIFireGroup1,P=Tirer groupe 1\n",
+        );
+
+        let merged = binds.merge_with_user_bindings(None);
+        assert_eq!(merged.action_maps[0].ui_label, "Armes");
+        assert_eq!(merged.action_maps[0].actions[0].ui_label, "Tirer groupe 1");
+        // Keys missing from the localization still fall back to the formatted name
+        assert_eq!(merged.action_maps[0].actions[1].ui_label, "Attack 2");
+    }
+
+    #[test]
+    fn test_layout_sensitive_bindings() {
+        let maps = action_maps(vec![(
+            "spaceship_targeting",
+            vec![
+                (
+                    "v_target_cycle_all_fwd",
+                    vec!["kb1_rbracket", "js1_button3"],
+                ),
+                ("v_target_cycle_all_back", vec!["lalt+kb1_lbracket"]),
+                ("v_target_nearest_hostile", vec!["kb1_f", "kb1_ "]),
+            ],
+        )]);
+
+        let flagged: Vec<String> = maps
+            .find_layout_sensitive_bindings()
+            .into_iter()
+            .map(|(_, _, input)| input)
+            .collect();
+        assert_eq!(flagged, vec!["kb1_rbracket", "lalt+kb1_lbracket"]);
+
+        assert!(is_layout_sensitive_key("q"));
+        assert!(!is_layout_sensitive_key("f"));
+    }
+
+    #[test]
+    fn test_single_action_profile() {
+        let mut maps = action_maps(vec![
+            (
+                "spaceship_weapons",
+                vec![
+                    ("v_attack1", vec!["js1_button1", "kb1_space"]),
+                    ("v_attack2", vec!["js1_button2"]),
+                ],
+            ),
+            ("seat_general", vec![("v_eject", vec!["lalt+kb1_y"])]),
+        ]);
+        maps.devices.joysticks = vec!["Left Stick".to_string()];
+
+        let xml = maps
+            .to_xml_single_action(None, "spaceship_weapons", "v_attack1", false)
+            .unwrap();
+        let single = ActionMaps::from_xml(&xml).unwrap();
+        assert_eq!(single.action_maps.len(), 1);
+        assert_eq!(single.action_maps[0].name, "spaceship_weapons");
+        assert_eq!(single.action_maps[0].actions.len(), 1);
+        assert_eq!(
+            find_inputs(&single, "spaceship_weapons", "v_attack1"),
+            vec!["js1_button1", "kb1_space"]
+        );
+        assert_eq!(single.devices.joysticks, vec!["Left Stick"]);
+        assert!(xml.contains("<keyboard instance=\"1\"/>"));
+
+        assert!(maps
+            .to_xml_single_action(None, "spaceship_weapons", "v_missing", false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_joystick_assignment_cycle() {
+        let mut maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_button1", "kb1_space"]),
+                ("v_attack2", vec!["lalt+js2_button2"]),
+                ("v_weapon_cycle", vec!["js3_hat1_up", "js3_ "]),
+            ],
+        )]);
+        maps.devices.joysticks = vec![
+            "Stick A".to_string(),
+            "Stick B".to_string(),
+            "Throttle".to_string(),
+        ];
+
+        let assignment = HashMap::from([(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(maps.apply_joystick_assignment(&assignment), Ok(4));
+
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack1"),
+            vec!["js2_button1", "kb1_space"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_attack2"),
+            vec!["lalt+js3_button2"]
+        );
+        assert_eq!(
+            find_inputs(&maps, "spaceship_weapons", "v_weapon_cycle"),
+            vec!["js1_hat1_up", "js1_ "]
+        );
+        assert_eq!(
+            maps.devices.joysticks,
+            vec!["Throttle", "Stick A", "Stick B"]
+        );
+
+        // js1 -> js2 while js2 stays would merge two devices: rejected, nothing changes
+        let err = maps
+            .apply_joystick_assignment(&HashMap::from([(1, 2)]))
+            .unwrap_err();
+        assert!(err.contains("would both become js2"), "{}", err);
+        assert_eq!(
+            maps.devices.joysticks,
+            vec!["Throttle", "Stick A", "Stick B"]
+        );
+    }
+
+    #[test]
+    fn test_multidevice_ambiguities() {
+        let binds = all_binds(vec![(
+            "spaceship_weapons",
+            vec![
+                all_binds_action("v_attack1", "", ""),
+                all_binds_action("v_attack2", "", ""),
+                all_binds_action("v_weapon_cycle", "", ""),
+            ],
+        )]);
+        let user = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js1_button1", "kb1_space", "js2_button1"]),
+                ("v_attack2", vec!["js1_button2", "js2_button2"]),
+                ("v_weapon_cycle", vec!["kb1_r", "js1_ "]),
+            ],
+        )]);
+        let merged = binds.merge_with_user_bindings(Some(&user));
+
+        assert_eq!(
+            merged.find_multidevice_ambiguities(),
+            vec![Ambiguity {
+                action_map: "spaceship_weapons".to_string(),
+                action: "v_attack1".to_string(),
+                inputs: vec![
+                    "js1_button1".to_string(),
+                    "js2_button1".to_string(),
+                    "kb1_space".to_string()
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_analysis_rows() {
+        let binds = all_binds(vec![(
+            "spaceship_weapons",
+            vec![
+                all_binds_action("v_attack1", "", ""),
+                all_binds_action("v_attack2", "mouse1", ""),
+                all_binds_action("v_weapon_cycle", "", ""),
+            ],
+        )]);
+        let user = ActionMaps::from_xml(
+            r#"<ActionMaps profileName="analysis">
+ <actionmap name="spaceship_weapons">
+  <action name="v_attack1">
+   <rebind input="js1_button1" activationMode="tap"/>
+   <rebind input="js2_button1"/>
+  </action>
+ </actionmap>
+</ActionMaps>
+"#,
+        )
+        .unwrap();
+        let rows = binds
+            .merge_with_user_bindings(Some(&user))
+            .to_analysis_rows();
+        assert_eq!(rows.len(), 3);
+
+        // Joystick-only action: keyboard stays blank, js2 has no column
+        assert_eq!(rows[0].action, "v_attack1");
+        assert_eq!(rows[0].joystick1, "js1_button1");
+        assert_eq!(rows[0].keyboard, "");
+        assert_eq!(rows[0].activation_mode, "tap");
+        assert!(rows[0].customized);
+
+        assert!(!rows[1].customized);
+        // Unbound actions still get a row
+        assert_eq!(rows[2].action, "v_weapon_cycle");
+        assert_eq!(
+            (rows[2].keyboard.as_str(), rows[2].joystick1.as_str()),
+            ("", "")
+        );
+    }
+
+    #[test]
+    fn test_max_indices() {
+        let binds = all_binds(vec![(
+            "spaceship_weapons",
+            vec![
+                all_binds_action("v_attack1", "", ""),
+                all_binds_action("v_attack2", "", ""),
+                all_binds_action("v_throttle", "", ""),
+                all_binds_action("v_strafe", "", ""),
+                all_binds_action("v_yaw", "", ""),
+            ],
+        )]);
+        let user = ActionMaps::from_xml(
+            r#"<ActionMaps profileName="indices">
+ <actionmap name="spaceship_weapons">
+  <action name="v_attack1"><rebind input="js1_button31"/></action>
+  <action name="v_attack2"><rebind input="lalt+js1_button4"/></action>
+  <action name="v_throttle"><rebind input="js1_axis8"/></action>
+  <action name="v_strafe"><rebind input="js2_button40"/></action>
+  <action name="v_yaw"><rebind input="js2_rotz"/></action>
+ </actionmap>
+</ActionMaps>
+"#,
+        )
+        .unwrap();
+        let merged = binds.merge_with_user_bindings(Some(&user));
+
+        assert_eq!(
+            merged.max_indices("js1"),
+            MaxIndices {
+                buttons: 31,
+                axes: 8,
+                hats: 0
+            }
+        );
+        assert_eq!(
+            merged.max_indices("JS2"),
+            MaxIndices {
+                buttons: 40,
+                axes: 6,
+                hats: 0
+            }
+        );
+        assert_eq!(merged.max_indices("js3"), MaxIndices::default());
+    }
+
+    #[test]
+    fn test_find_implausible_instances() {
+        let maps = action_maps(vec![(
+            "spaceship_weapons",
+            vec![
+                ("v_attack1", vec!["js9_button1", "kb1_space"]),
+                ("v_attack2", vec!["js2_button3", "lalt+gp12_a"]),
+                ("v_attack3", vec!["js8_button1"]),
+            ],
+        )]);
+
+        assert_eq!(
+            maps.find_implausible_instances(DEFAULT_MAX_DEVICE_INSTANCES),
+            vec![
+                (
+                    "spaceship_weapons".to_string(),
+                    "v_attack1".to_string(),
+                    "js9_button1".to_string()
+                ),
+                (
+                    "spaceship_weapons".to_string(),
+                    "v_attack2".to_string(),
+                    "lalt+gp12_a".to_string()
+                ),
+            ]
+        );
+        assert_eq!(maps.find_implausible_instances(1).len(), 4);
+    }
+
+    #[test]
+    fn test_find_same_input_different_mode() {
+        let maps = ActionMaps::from_xml(
+            r#"<ActionMaps profileName="modes">
+ <actionmap name="spaceship_targeting">
+  <action name="v_target_cycle">
+   <rebind input="js1_button3" activationMode="press"/>
+   <rebind input="js1_button3" activationMode="hold"/>
+   <rebind input="js1_button3" activationMode="press"/>
+   <rebind input="js1_button4" activationMode="tap"/>
+   <rebind input="kb1_lalt+t" activationMode="press"/>
+   <rebind input="lalt+kb_t" activationMode="press"/>
+  </action>
+ </actionmap>
+</ActionMaps>
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            maps.find_same_input_different_mode("spaceship_targeting", "v_target_cycle")
+                .unwrap(),
+            vec![(
+                "js1_button3".to_string(),
+                vec!["press".to_string(), "hold".to_string()]
+            )]
+        );
+        assert!(maps
+            .find_same_input_different_mode("spaceship_targeting", "v_missing")
+            .is_err());
+    }
+
+    #[test]
+    fn test_remap_keeps_cleared_input() {
+        assert_eq!(
+            remap_keyboard_input("kb1_ ", KeyboardLayout::Qwerty, KeyboardLayout::Azerty),
+            "kb1_ "
+        );
+    }
 }
@@ -1,16 +1,20 @@
-use log::{error, info};
-use std::collections::HashMap;
+use log::{error, info, warn};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Mutex;
 use tauri::Manager;
 use tauri_plugin_opener::OpenerExt;
 
+mod bundle;
 mod device_database;
+mod device_matching;
 mod device_profiles;
 mod directinput;
 mod keybindings;
 mod hid_reader;
 
-use keybindings::{Action, ActionMap, ActionMaps, AllBinds, MergedBindings, OrganizedKeybindings};
+use keybindings::{
+    Action, ActionMap, ActionMaps, AllBinds, KeyboardLayout, MergedBindings, OrganizedKeybindings,
+};
 
 // Resources subfolder name - change this to customize the bundled resources folder
 // Note: Tauri automatically names this "_up_" in the bundle, so this must match that name
@@ -51,7 +55,137 @@ struct CharacterFile {
 struct AppState {
     current_bindings: Option<ActionMaps>,
     all_binds: Option<AllBinds>,
+    all_binds_source_meta: Option<FileStamp>, // Size and mtime of the AllBinds.xml that was parsed
     current_file_name: Option<String>,
+    notes: HashMap<(String, String), String>, // (action_map, action) -> user note
+    input_index: Option<InputIndex>,
+    bindings_generation: u64, // Bumped whenever current_bindings may have changed
+    history: EditHistory,
+}
+
+// Reverse lookup of the merged view, normalized input -> (action_map, action)
+// Rebuilt on use once the bindings generation moves past the one it was built for
+struct InputIndex {
+    generation: u64,
+    index: InputIndexMap,
+}
+
+type InputIndexMap = HashMap<String, Vec<(String, String)>>;
+
+// Number of edits kept for undo; each entry is a full copy of the bindings
+const UNDO_LIMIT: usize = 50;
+
+// Snapshots of current_bindings taken before each edit, for undo/redo
+#[derive(Default)]
+struct EditHistory {
+    undo: VecDeque<Option<ActionMaps>>,
+    redo: Vec<Option<ActionMaps>>,
+}
+
+impl EditHistory {
+    // Save the bindings as they are before an edit; a new edit drops the redo history
+    fn record(&mut self, bindings: &Option<ActionMaps>) {
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(bindings.clone());
+        self.redo.clear();
+    }
+
+    // Restore the last snapshot into `bindings`, returns false if there is nothing to undo
+    fn undo(&mut self, bindings: &mut Option<ActionMaps>) -> bool {
+        match self.undo.pop_back() {
+            Some(previous) => {
+                self.redo.push(std::mem::replace(bindings, previous));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Re-apply the last undone edit, returns false if there is nothing to redo
+    fn redo(&mut self, bindings: &mut Option<ActionMaps>) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push_back(std::mem::replace(bindings, next));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+// Chunked file writes in progress, so large files don't cross the IPC boundary in one piece
+// Data goes to "<path>.<id>.part" and only replaces the target when the write is finished
+// Managed as its own state so chunk uploads don't wait on the bindings lock
+#[derive(Default)]
+struct BinaryWrites {
+    next_id: u64,
+    open: HashMap<u64, (std::fs::File, std::path::PathBuf)>, // id -> (partial file, target path)
+}
+
+impl BinaryWrites {
+    fn partial_path(path: &std::path::Path, write_id: u64) -> std::path::PathBuf {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(format!(".{}.part", write_id));
+        std::path::PathBuf::from(partial)
+    }
+
+    fn begin(&mut self, path: &str) -> Result<u64, String> {
+        let target = std::path::PathBuf::from(path);
+        let write_id = self.next_id + 1;
+        let file = std::fs::File::create(Self::partial_path(&target, write_id))
+            .map_err(|e| format!("Failed to create file: {}", e))?;
+
+        self.next_id = write_id;
+        self.open.insert(write_id, (file, target));
+        Ok(write_id)
+    }
+
+    fn append(&mut self, write_id: u64, chunk: &[u8]) -> Result<(), String> {
+        use std::io::Write;
+
+        let (file, _) = self
+            .open
+            .get_mut(&write_id)
+            .ok_or_else(|| format!("No write in progress with id {}", write_id))?;
+        file.write_all(chunk)
+            .map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    // Move the finished file into place; on failure the partial file is removed
+    fn finish(&mut self, write_id: u64) -> Result<(), String> {
+        let (file, target) = self
+            .open
+            .remove(&write_id)
+            .ok_or_else(|| format!("No write in progress with id {}", write_id))?;
+        let partial = Self::partial_path(&target, write_id);
+
+        let synced = file.sync_all();
+        drop(file);
+        let result = synced.and_then(|_| std::fs::rename(&partial, &target));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        result.map_err(|e| format!("Failed to write file: {}", e))
+    }
+
+    // Drop a write and its partial file, the target is left as it was
+    fn abort(&mut self, write_id: u64) -> bool {
+        match self.open.remove(&write_id) {
+            Some((file, target)) => {
+                drop(file);
+                let _ = std::fs::remove_file(Self::partial_path(&target, write_id));
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 impl AppState {
@@ -59,8 +193,117 @@ impl AppState {
         AppState {
             current_bindings: None,
             all_binds: None,
+            all_binds_source_meta: None,
             current_file_name: None,
+            notes: HashMap::new(),
+            input_index: None,
+            bindings_generation: 0,
+            history: EditHistory::default(),
+        }
+    }
+
+    // Replace the loaded profile; undo steps belong to the bindings being replaced
+    fn load_profile(
+        &mut self,
+        action_maps: ActionMaps,
+        file_name: String,
+        notes: HashMap<(String, String), String>,
+    ) {
+        self.replace_bindings(Some(action_maps));
+        self.current_file_name = Some(file_name);
+        self.notes = notes;
+    }
+
+    // Swap in other bindings (or none) without an undo step back to the old ones
+    fn replace_bindings(&mut self, bindings: Option<ActionMaps>) {
+        self.history.clear();
+        self.current_bindings = bindings;
+        self.bindings_changed();
+    }
+
+    // Mark the bindings as changed so data derived from them is rebuilt
+    fn bindings_changed(&mut self) {
+        self.bindings_generation += 1;
+    }
+
+    // Undo (or redo) one edit, returns false if there was nothing to step to
+    fn step_history(&mut self, undo: bool) -> bool {
+        let stepped = if undo {
+            self.history.undo(&mut self.current_bindings)
+        } else {
+            self.history.redo(&mut self.current_bindings)
+        };
+        if stepped {
+            self.bindings_changed();
+        }
+        stepped
+    }
+
+    // Snapshot the bindings for undo before an edit
+    fn record_edit(&mut self) {
+        self.history.record(&self.current_bindings);
+        self.bindings_changed();
+    }
+
+    // Record an undo step back to `before` if the bindings have changed since
+    fn record_edit_since(&mut self, before: Option<ActionMaps>) {
+        if self.current_bindings != before {
+            self.history.record(&before);
+            self.bindings_changed();
+        }
+    }
+
+    // Run an edit and record an undo step only when it succeeds and changes the bindings
+    // A failed edit is rolled back, leaving the bindings and the history as they were
+    fn edit<T>(
+        &mut self,
+        edit: impl FnOnce(&mut AppState) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let before = self.current_bindings.clone();
+        match edit(self) {
+            Ok(value) => {
+                self.record_edit_since(before);
+                Ok(value)
+            }
+            Err(e) => {
+                self.current_bindings = before;
+                self.bindings_changed();
+                Err(e)
+            }
+        }
+    }
+
+    // Edit the loaded bindings, see `edit`
+    fn edit_bindings<T>(
+        &mut self,
+        edit: impl FnOnce(&mut ActionMaps) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.edit(|app_state| match app_state.current_bindings {
+            Some(ref mut bindings) => edit(bindings),
+            None => Err("No keybindings loaded".to_string()),
+        })
+    }
+
+    // The input index for the current bindings, built on first use after a change
+    fn input_index(&mut self) -> Result<&InputIndexMap, String> {
+        let stale = self
+            .input_index
+            .as_ref()
+            .is_none_or(|cached| cached.generation != self.bindings_generation);
+        if stale {
+            let all_binds = self.all_binds.as_ref().ok_or_else(|| {
+                "AllBinds.xml not loaded. Please restart the application.".to_string()
+            })?;
+            let index = all_binds
+                .merge_with_user_bindings(self.current_bindings.as_ref())
+                .build_input_index();
+            self.input_index = Some(InputIndex {
+                generation: self.bindings_generation,
+                index,
+            });
         }
+
+        Ok(&self.input_index.as_ref().unwrap().index)
     }
 }
 
@@ -75,9 +318,214 @@ fn detect_joysticks() -> Result<Vec<directinput::JoystickInfo>, String> {
     directinput::detect_joysticks()
 }
 
+// Modifier keys held right now, for showing live modifier state while binding
+#[tauri::command]
+fn poll_active_modifiers() -> Vec<String> {
+    directinput::poll_active_modifiers()
+}
+
+#[tauri::command]
+fn get_connected_devices(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<directinput::DeviceInfo>, String> {
+    let mut devices = directinput::list_connected_devices()?;
+    let aliases = stored_device_aliases(&app_handle);
+    for device in &mut devices {
+        apply_device_alias(device, &aliases);
+    }
+    Ok(devices)
+}
+
+#[tauri::command]
+fn get_connected_devices_detailed(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<directinput::DetailedDeviceInfo>, String> {
+    let mut devices = directinput::list_connected_devices_detailed()?;
+    let aliases = stored_device_aliases(&app_handle);
+    for device in &mut devices {
+        apply_device_alias(&mut device.info, &aliases);
+    }
+    Ok(devices)
+}
+
+// Struct for a suggested pairing of a profile's joystick instance with a connected device
+#[derive(serde::Serialize)]
+struct DeviceSuggestion {
+    instance: u32,
+    declared_product: String,
+    device_uuid: Option<String>,
+    device_name: Option<String>,
+    current_instance: Option<usize>, // Instance the matched device currently uses
+    confidence: f32,
+}
+
+// Pair each declared joystick Product string with its best-scoring connected device
+fn build_device_suggestions(
+    declared_products: &[String],
+    connected: &[directinput::DeviceIdentity],
+) -> Vec<DeviceSuggestion> {
+    declared_products
+        .iter()
+        .enumerate()
+        .map(|(idx, product)| {
+            let best = connected
+                .iter()
+                .map(|device| {
+                    let score = device_matching::device_match_score(
+                        product,
+                        &device.name,
+                        device.vendor_id,
+                        device.product_id,
+                    );
+                    (device, score)
+                })
+                .filter(|(_, score)| *score >= device_matching::MIN_MATCH_SCORE)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            DeviceSuggestion {
+                instance: idx as u32 + 1,
+                declared_product: product.clone(),
+                device_uuid: best.map(|(d, _)| d.uuid.clone()),
+                device_name: best.map(|(d, _)| d.name.clone()),
+                current_instance: best.map(|(d, _)| d.sc_instance),
+                confidence: best.map(|(_, score)| score).unwrap_or(0.0),
+            }
+        })
+        .collect()
+}
+
+// Struct for one joystick instance in a full one-to-one device assignment
+#[derive(serde::Serialize)]
+struct InstanceAssignment {
+    instance: u32,
+    declared_product: String,
+    suggested_device_uuid: Option<String>,
+    confidence: f32,
+}
+
+// Pair declared joystick Products with connected devices so no device is used twice:
+// the best-scoring remaining (instance, device) pair is taken first, ties going to the
+// lower instance. Instances left without a device above MIN_MATCH_SCORE get None
+fn build_full_device_assignment(
+    declared_products: &[String],
+    connected: &[directinput::DeviceIdentity],
+) -> Vec<InstanceAssignment> {
+    let mut candidates: Vec<(usize, usize, f32)> = Vec::new();
+    for (idx, product) in declared_products.iter().enumerate() {
+        for (device_idx, device) in connected.iter().enumerate() {
+            let score = device_matching::device_match_score(
+                product,
+                &device.name,
+                device.vendor_id,
+                device.product_id,
+            );
+            if score >= device_matching::MIN_MATCH_SCORE {
+                candidates.push((idx, device_idx, score));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let mut assignments: Vec<InstanceAssignment> = declared_products
+        .iter()
+        .enumerate()
+        .map(|(idx, product)| InstanceAssignment {
+            instance: idx as u32 + 1,
+            declared_product: product.clone(),
+            suggested_device_uuid: None,
+            confidence: 0.0,
+        })
+        .collect();
+    let mut used_devices = vec![false; connected.len()];
+
+    for (idx, device_idx, score) in candidates {
+        if assignments[idx].suggested_device_uuid.is_some() || used_devices[device_idx] {
+            continue;
+        }
+        used_devices[device_idx] = true;
+        assignments[idx].suggested_device_uuid = Some(connected[device_idx].uuid.clone());
+        assignments[idx].confidence = score;
+    }
+
+    assignments
+}
+
+// Assign connected devices' SC Product strings to joystick instances: (instance, product)
+#[tauri::command]
+fn build_device_options(
+    assignments: Vec<(u32, String)>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| bindings.set_joystick_products(&assignments))
+}
+
+// Joystick instances used in bindings that have no device options entry
+#[tauri::command]
+fn ensure_device_options_complete(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<u32>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.missing_joystick_options())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+#[tauri::command]
+fn autofill_device_options(state: tauri::State<Mutex<AppState>>) -> Result<Vec<u32>, String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| Ok(bindings.autofill_joystick_options()))
+}
+
+#[tauri::command]
+fn get_device_product_string(device_uuid: String) -> Result<String, String> {
+    let device = directinput::list_device_identities()?
+        .into_iter()
+        .find(|d| d.uuid == device_uuid)
+        .ok_or_else(|| format!("Device not connected: {}", device_uuid))?;
+
+    match (device.vendor_id, device.product_id) {
+        (Some(vid), Some(pid)) => Ok(device_matching::sc_product_string(&device.name, vid, pid)),
+        _ => Err(format!("No VID/PID reported for {}", device.name)),
+    }
+}
+
+#[tauri::command]
+fn suggest_device_assignment(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<DeviceSuggestion>, String> {
+    let declared = {
+        let app_state = state.lock().unwrap();
+        app_state
+            .current_bindings
+            .as_ref()
+            .map(|b| b.devices.joysticks.clone())
+            .ok_or_else(|| "No bindings loaded".to_string())?
+    };
+
+    let connected = directinput::list_device_identities()?;
+    Ok(build_device_suggestions(&declared, &connected))
+}
+
+// Suggest a connected device for every declared joystick instance at once, one device each
 #[tauri::command]
-fn get_connected_devices() -> Result<Vec<directinput::DeviceInfo>, String> {
-    directinput::list_connected_devices()
+fn suggest_full_device_assignment(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<InstanceAssignment>, String> {
+    let declared = {
+        let app_state = state.lock().unwrap();
+        app_state
+            .current_bindings
+            .as_ref()
+            .map(|b| b.devices.joysticks.clone())
+            .ok_or_else(|| "No bindings loaded".to_string())?
+    };
+
+    let connected = directinput::list_device_identities()?;
+    Ok(build_full_device_assignment(&declared, &connected))
 }
 
 #[tauri::command]
@@ -120,6 +568,32 @@ async fn wait_for_input_binding(
         .map_err(|e| format!("Task join error: {}", e))?
 }
 
+// Capture the raw events detection sees for a session, for attaching to device bug reports
+#[tauri::command]
+fn start_recording_session(session_id: String) {
+    directinput::start_recording(&session_id);
+    info!("Recording detection session {}", session_id);
+}
+
+#[tauri::command]
+fn stop_recording_session(session_id: String) -> Vec<directinput::RecordedEvent> {
+    let events = directinput::stop_recording(&session_id);
+    info!(
+        "Stopped recording session {} with {} events",
+        session_id,
+        events.len()
+    );
+    events
+}
+
+#[tauri::command]
+async fn rumble_device(device_uuid: String, duration_ms: u64) -> Result<(), String> {
+    // Blocks while the rumble plays, so keep it off the UI thread
+    tokio::task::spawn_blocking(move || directinput::rumble_device(&device_uuid, duration_ms))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
 async fn wait_for_multiple_inputs(
     session_id: String,
@@ -158,6 +632,14 @@ async fn wait_for_inputs_with_events(
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+// Organize bindings for the UI, comparing against SC defaults when AllBinds is loaded
+fn organize_for_ui(bindings: &ActionMaps, all_binds: Option<&AllBinds>) -> OrganizedKeybindings {
+    match all_binds {
+        Some(all_binds) => bindings.organize_with_defaults(all_binds),
+        None => bindings.organize(),
+    }
+}
+
 #[tauri::command]
 fn load_keybindings(
     file_path: String,
@@ -168,7 +650,7 @@ fn load_keybindings(
         std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
 
     // Parse the XML
-    let action_maps = ActionMaps::from_xml(&xml_content)?;
+    let mut action_maps = ActionMaps::from_xml(&xml_content)?;
 
     // Extract filename from path
     let file_name = std::path::Path::new(&file_path)
@@ -177,242 +659,485 @@ fn load_keybindings(
         .unwrap_or("layout_exported.xml")
         .to_string();
 
+    // A blank profileName shows up as an empty entry in SC's list
+    let name_source = action_maps.fill_blank_profile_name(&file_name);
+
+    // Load the notes sidecar if there is one
+    let notes = load_notes_or_empty(std::path::Path::new(&file_path));
+
     // Store in state
     let mut app_state = state.lock().unwrap();
-    app_state.current_bindings = Some(action_maps.clone());
-    app_state.current_file_name = Some(file_name);
+    app_state.load_profile(action_maps.clone(), file_name, notes);
 
     // Organize the data for the UI
-    Ok(action_maps.organize())
+    let mut organized = organize_for_ui(&action_maps, app_state.all_binds.as_ref());
+    organized.profile_name_source = name_source;
+    Ok(organized)
 }
 
+// Save the loaded bindings in the app's binary format, which reloads much faster than
+// parsing XML for huge profiles. Not a file SC can import
 #[tauri::command]
-fn update_binding(
-    action_map_name: String,
-    action_name: String,
-    new_input: String,
-    multi_tap: Option<u32>,
-    activation_mode: Option<String>,
+fn save_binary_profile(
+    file_path: String,
     state: tauri::State<Mutex<AppState>>,
 ) -> Result<(), String> {
-    eprintln!("update_binding called with:");
-    eprintln!("  action_map_name: '{}'", action_map_name);
-    eprintln!("  action_name: '{}'", action_name);
-    eprintln!("  new_input: '{}'", new_input);
-    eprintln!("  multi_tap: {:?}", multi_tap);
-    eprintln!("  activation_mode: {:?}", activation_mode);
+    let app_state = state.lock().unwrap();
+
+    let bindings = app_state
+        .current_bindings
+        .as_ref()
+        .ok_or_else(|| "No keybindings loaded to export".to_string())?;
+
+    let bytes = bindings.to_binary()?;
+    std::fs::write(&file_path, &bytes)
+        .map_err(|e| format!("Failed to write binary profile: {}", e))?;
+
+    info!("Saved binary profile {} ({} bytes)", file_path, bytes.len());
+    Ok(())
+}
+
+// Load a profile saved by save_binary_profile, like load_keybindings does for XML
+#[tauri::command]
+fn load_binary_profile(
+    file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<OrganizedKeybindings, String> {
+    let bytes = std::fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let action_maps = ActionMaps::from_binary(&bytes)?;
+
+    let file_name = std::path::Path::new(&file_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("layout_exported.xml")
+        .to_string();
+
+    let notes = load_notes_or_empty(std::path::Path::new(&file_path));
 
     let mut app_state = state.lock().unwrap();
+    app_state.load_profile(action_maps.clone(), file_name, notes);
 
-    if let Some(ref mut bindings) = app_state.current_bindings {
-        eprintln!("Current bindings available, checking action maps...");
-        eprintln!(
-            "Available action maps: {:?}",
-            bindings
+    Ok(organize_for_ui(&action_maps, app_state.all_binds.as_ref()))
+}
+
+// Parse a profile and merge it into current_bindings, the incoming file winning per device
+// instance. Without loaded bindings the file is taken as is; the current file name is kept
+fn load_profile_additive(app_state: &mut AppState, file_path: &str) -> Result<usize, String> {
+    let xml_content =
+        std::fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let incoming = ActionMaps::from_xml(&xml_content)?;
+
+    let before = app_state.current_bindings.clone();
+    let merged = match app_state.current_bindings {
+        Some(ref mut bindings) => bindings.merge_profile(&incoming),
+        None => {
+            let file_name = std::path::Path::new(file_path)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("layout_exported.xml")
+                .to_string();
+            let mut incoming = incoming;
+            incoming.fill_blank_profile_name(&file_name);
+            let rebinds = incoming
                 .action_maps
                 .iter()
-                .map(|am| &am.name)
-                .collect::<Vec<_>>()
-        );
-
-        // Find the action map
-        if let Some(action_map) = bindings
-            .action_maps
-            .iter_mut()
-            .find(|am| am.name == action_map_name)
-        {
-            eprintln!("Found action map: '{}'", action_map_name);
-            eprintln!(
-                "Available actions: {:?}",
-                action_map
-                    .actions
-                    .iter()
-                    .map(|a| &a.name)
-                    .collect::<Vec<_>>()
-            );
+                .flat_map(|am| &am.actions)
+                .map(|a| a.rebinds.len())
+                .sum();
 
-            // Find the action
-            if let Some(action) = action_map
-                .actions
-                .iter_mut()
-                .find(|a| a.name == action_name)
-            {
-                eprintln!("Found action: '{}'", action_name);
-
-                // Create the new rebind
-                let new_rebind = keybindings::Rebind {
-                    input: new_input.clone(),
-                    multi_tap,
-                    activation_mode: activation_mode.unwrap_or_default(),
-                };
-                eprintln!(
-                    "New rebind: input='{}', multi_tap={:?}, activation_mode='{}'",
-                    new_rebind.input, new_rebind.multi_tap, new_rebind.activation_mode
-                );
+            app_state.current_bindings = Some(incoming);
+            app_state.current_file_name = Some(file_name);
+            rebinds
+        }
+    };
+    app_state.record_edit_since(before);
 
-                let new_input_type = new_rebind.get_input_type();
+    Ok(merged)
+}
 
-                // Extract device instance from the new input (e.g., "js1" from "js1_button3")
-                let new_device_instance = if let Some(underscore_pos) = new_input.find('_') {
-                    new_input[..underscore_pos].to_string()
-                } else {
-                    new_input.clone()
-                };
-
-                // Remove any existing binding from the same device instance
-                // This ensures we only have one binding per device (js1, js2, kb1, mouse1, etc.)
-                action.rebinds.retain(|r| {
-                    let existing_device_instance = if let Some(underscore_pos) = r.input.find('_') {
-                        r.input[..underscore_pos].to_string()
-                    } else {
-                        r.input.clone()
-                    };
-                    existing_device_instance != new_device_instance
-                });
+// Mapping files SC split one profile into: "{base}_keyboard.xml", "{base}_joystick.xml",
+// "{base}_joystick2.xml", ... (mouse and gamepad too), sorted by file name
+fn find_split_mapping_files(
+    mappings_dir: &std::path::Path,
+    base_name: &str,
+) -> Result<Vec<std::path::PathBuf>, String> {
+    let prefix = format!("{}_", base_name.to_lowercase());
+    let is_device_suffix = |suffix: &str| {
+        ["keyboard", "mouse", "joystick", "gamepad"]
+            .iter()
+            .any(|device| {
+                suffix
+                    .strip_prefix(device)
+                    .is_some_and(|instance| instance.chars().all(|c| c.is_ascii_digit()))
+            })
+    };
 
-                // Add the new binding
-                action.rebinds.push(new_rebind);
+    let entries = std::fs::read_dir(mappings_dir)
+        .map_err(|e| format!("Failed to read {}: {}", mappings_dir.display(), e))?;
+
+    let mut files: Vec<std::path::PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let is_xml = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"));
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            is_xml && stem.strip_prefix(&prefix).is_some_and(is_device_suffix)
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
 
-                eprintln!("Successfully updated binding");
-                return Ok(());
-            } else {
-                eprintln!("Action '{}' not found in action map", action_name);
+// Parse split mapping files and merge them, in order, into one profile named `base_name`
+fn merge_split_mappings(
+    files: &[std::path::PathBuf],
+    base_name: &str,
+) -> Result<ActionMaps, String> {
+    let mut merged: Option<ActionMaps> = None;
+    for path in files {
+        let xml_content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let profile = ActionMaps::from_xml(&xml_content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+        match merged {
+            Some(ref mut merged) => {
+                merged.merge_profile(&profile);
             }
-        } else {
-            eprintln!("Action map '{}' not found", action_map_name);
+            None => merged = Some(profile),
         }
-    } else {
-        eprintln!("No current bindings loaded in state");
     }
 
-    // If we couldn't find it in current_bindings, try to create the structure from all_binds
-    eprintln!("Attempting to use all_binds as template...");
-    if let Some(ref all_binds) = app_state.all_binds {
-        eprintln!("AllBinds available, looking for action...");
+    let mut merged = merged.ok_or_else(|| "No mapping files to merge".to_string())?;
+    merged.profile_name = base_name.to_string();
+    Ok(merged)
+}
 
-        // Find the action in all_binds to verify it exists
-        let found = all_binds.action_maps.iter().any(|am| {
-            am.name == action_map_name && am.actions.iter().any(|a| a.name == action_name)
-        });
+// Load the profile SC stored as separate per-device files in an installation's mappings
+// folder as one profile, e.g. "hosas" from hosas_keyboard.xml and hosas_joystick.xml
+#[tauri::command]
+fn import_split_mappings(
+    installation_path: String,
+    base_name: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<OrganizedKeybindings, String> {
+    let mappings_dir = install_mappings_dir(std::path::Path::new(&installation_path));
+    let files = find_split_mapping_files(&mappings_dir, &base_name)?;
+    if files.is_empty() {
+        return Err(format!(
+            "No split mapping files for '{}' in {}",
+            base_name,
+            mappings_dir.display()
+        ));
+    }
 
-        if found {
-            eprintln!("Action found in all_binds, creating user binding entry");
-
-            // Initialize or update current_bindings from all_binds structure
-            if app_state.current_bindings.is_none() {
-                eprintln!("Creating new current_bindings structure");
-                app_state.current_bindings = Some(ActionMaps {
-                    profile_name: "User Customizations".to_string(),
-                    action_maps: Vec::new(),
-                    categories: Vec::new(),
-                    devices: keybindings::DeviceInfo {
-                        keyboards: Vec::new(),
-                        mice: Vec::new(),
-                        joysticks: Vec::new(),
-                    },
-                });
-            }
+    let action_maps = merge_split_mappings(&files, &base_name)?;
+    info!(
+        "Merged {} split mapping files into {}",
+        files.len(),
+        base_name
+    );
 
-            if let Some(ref mut bindings) = app_state.current_bindings {
-                // Find or create the action map
-                if let Some(action_map) = bindings
-                    .action_maps
-                    .iter_mut()
-                    .find(|am| am.name == action_map_name)
-                {
-                    // Find or create the action
-                    if let Some(action) = action_map
-                        .actions
-                        .iter_mut()
-                        .find(|a| a.name == action_name)
-                    {
-                        // Update existing action
-                        let new_rebind = keybindings::Rebind {
-                            input: new_input.clone(),
-                            multi_tap,
-                            activation_mode: activation_mode.clone().unwrap_or_default(),
-                        };
-
-                        // Extract device instance from the new input (e.g., "js1" from "js1_button3")
-                        let new_device_instance = if let Some(underscore_pos) = new_input.find('_')
-                        {
-                            new_input[..underscore_pos].to_string()
-                        } else {
-                            new_input.clone()
-                        };
-
-                        // Remove any existing binding from the same device instance
-                        action.rebinds.retain(|r| {
-                            let existing_device_instance =
-                                if let Some(underscore_pos) = r.input.find('_') {
-                                    r.input[..underscore_pos].to_string()
-                                } else {
-                                    r.input.clone()
-                                };
-                            existing_device_instance != new_device_instance
-                        });
-
-                        // Add the new binding
-                        action.rebinds.push(new_rebind);
-                        eprintln!("Successfully updated binding (existing action, replaced same device instance)");
-                        return Ok(());
-                    } else {
-                        // Create new action
-                        let new_action = Action {
-                            name: action_name.clone(),
-                            rebinds: vec![keybindings::Rebind {
-                                input: new_input,
-                                multi_tap,
-                                activation_mode: activation_mode.clone().unwrap_or_default(),
-                            }],
-                        };
-                        action_map.actions.push(new_action);
-                        eprintln!("Successfully updated binding (new action)");
-                        return Ok(());
-                    }
-                } else {
-                    // Create new action map
-                    let new_action = Action {
-                        name: action_name.clone(),
-                        rebinds: vec![keybindings::Rebind {
-                            input: new_input,
-                            multi_tap,
-                            activation_mode: activation_mode.unwrap_or_default(),
-                        }],
-                    };
-                    let new_action_map =
-                        ActionMaps::new_empty_action_map(action_map_name.clone(), vec![new_action]);
-                    bindings.action_maps.push(new_action_map);
-                    eprintln!("Successfully updated binding (new action map)");
-                    return Ok(());
-                }
-            }
-        } else {
-            eprintln!("Action not found in all_binds either - invalid action");
-        }
-    } else {
-        eprintln!("AllBinds not available");
-    }
+    let mut app_state = state.lock().unwrap();
+    app_state.load_profile(
+        action_maps.clone(),
+        format!("{}.xml", base_name),
+        HashMap::new(),
+    );
 
-    Err("Action not found".to_string())
+    Ok(organize_for_ui(&action_maps, app_state.all_binds.as_ref()))
 }
 
+// Layer a profile on top of the loaded one instead of replacing it
 #[tauri::command]
-fn reset_binding(
-    action_map_name: String,
-    action_name: String,
+fn load_keybindings_additive(
+    file_path: String,
     state: tauri::State<Mutex<AppState>>,
-) -> Result<(), String> {
+) -> Result<OrganizedKeybindings, String> {
     let mut app_state = state.lock().unwrap();
+    let merged = load_profile_additive(&mut app_state, &file_path)?;
+    info!("Merged {} rebinds from {}", merged, file_path);
 
-    eprintln!(
-        "Resetting binding for action: {} in map: {}",
-        action_name, action_map_name
-    );
+    let bindings = app_state.current_bindings.as_ref().unwrap();
+    Ok(organize_for_ui(bindings, app_state.all_binds.as_ref()))
+}
 
-    // Remove the custom binding from current_bindings
+// Struct for describing what was loaded from a bundle
+#[derive(serde::Serialize)]
+struct BundleManifest {
+    profile_file: String,
+    notes_loaded: usize,
+    template_file: Option<String>,
+    template_json: Option<String>,
+    keybindings: OrganizedKeybindings,
+}
+
+#[tauri::command]
+fn load_bindings_bundle(
+    zip_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<BundleManifest, String> {
+    let file =
+        std::fs::File::open(&zip_path).map_err(|e| format!("Failed to open bundle: {}", e))?;
+    let bundle = bundle::read_bundle(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+
+    let action_maps = ActionMaps::from_xml(&bundle.profile_xml)?;
+    let notes = match bundle.notes_json {
+        Some(ref json) => parse_notes_json(json)?,
+        None => HashMap::new(),
+    };
+
+    // Store in state
+    let mut app_state = state.lock().unwrap();
+    let notes_loaded = notes.len();
+    app_state.load_profile(action_maps.clone(), bundle.profile_file_name.clone(), notes);
+
+    Ok(BundleManifest {
+        profile_file: bundle.profile_file_name,
+        notes_loaded,
+        template_file: bundle.template_file_name,
+        template_json: bundle.template_json,
+        keybindings: organize_for_ui(&action_maps, app_state.all_binds.as_ref()),
+    })
+}
+
+// A single binding change submitted by the UI
+#[derive(Debug, Clone, serde::Deserialize)]
+struct BindingEdit {
+    action_map_name: String,
+    action_name: String,
+    new_input: String,
+    #[serde(default)]
+    multi_tap: Option<u32>,
+    #[serde(default)]
+    activation_mode: Option<String>,
+    #[serde(default)]
+    multi_tap_block: Option<bool>,
+    #[serde(default)]
+    press_trigger_threshold: Option<u32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct BatchResult {
+    applied: usize,
+    errors: Vec<String>,
+}
+
+// Apply one binding edit to the current bindings, replacing any existing binding from the
+// same device instance (js1, js2, kb1, mouse1, ...). Actions missing from the current
+// bindings are created when AllBinds knows them
+fn apply_binding_edit(app_state: &mut AppState, edit: &BindingEdit) -> Result<(), String> {
+    // Validate and normalize the activation mode before touching any bindings
+    let activation_mode = edit
+        .activation_mode
+        .as_deref()
+        .map(keybindings::normalize_activation_mode)
+        .transpose()?;
+
+    let new_rebind = keybindings::Rebind {
+        input: edit.new_input.clone(),
+        multi_tap: edit.multi_tap,
+        multi_tap_block: edit.multi_tap_block,
+        press_trigger_threshold: edit.press_trigger_threshold,
+        activation_mode: activation_mode.unwrap_or_default(),
+    };
+
+    let in_current = app_state.current_bindings.as_ref().is_some_and(|bindings| {
+        bindings.action_maps.iter().any(|am| {
+            am.name == edit.action_map_name && am.actions.iter().any(|a| a.name == edit.action_name)
+        })
+    });
+
+    if !in_current {
+        // Fall back to all_binds as the template for actions the profile doesn't mention yet
+        let in_all_binds = app_state.all_binds.as_ref().is_some_and(|all_binds| {
+            all_binds.action_maps.iter().any(|am| {
+                am.name == edit.action_map_name
+                    && am.actions.iter().any(|a| a.name == edit.action_name)
+            })
+        });
+        if !in_all_binds {
+            eprintln!(
+                "Action '{}' not found in '{}' - invalid action",
+                edit.action_name, edit.action_map_name
+            );
+            return Err("Action not found".to_string());
+        }
+    }
+
+    let bindings = app_state
+        .current_bindings
+        .get_or_insert_with(ActionMaps::empty);
+
+    let action_map = match bindings
+        .action_maps
+        .iter()
+        .position(|am| am.name == edit.action_map_name)
+    {
+        Some(pos) => &mut bindings.action_maps[pos],
+        None => {
+            bindings.action_maps.push(ActionMaps::new_empty_action_map(
+                edit.action_map_name.clone(),
+                Vec::new(),
+            ));
+            bindings.action_maps.last_mut().unwrap()
+        }
+    };
+
+    let action = match action_map
+        .actions
+        .iter()
+        .position(|a| a.name == edit.action_name)
+    {
+        Some(pos) => &mut action_map.actions[pos],
+        None => {
+            action_map.actions.push(Action {
+                name: edit.action_name.clone(),
+                rebinds: Vec::new(),
+            });
+            action_map.actions.last_mut().unwrap()
+        }
+    };
+
+    // Device instance is everything before the first '_' (e.g., "js1" from "js1_button3")
+    let device_instance = |input: &str| input.split('_').next().unwrap_or("").to_string();
+    let new_device_instance = device_instance(&edit.new_input);
+    action
+        .rebinds
+        .retain(|r| device_instance(&r.input) != new_device_instance);
+    action.rebinds.push(new_rebind);
+
+    eprintln!(
+        "Updated binding {}/{} -> '{}'",
+        edit.action_map_name, edit.action_name, edit.new_input
+    );
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn update_binding(
+    action_map_name: String,
+    action_name: String,
+    new_input: String,
+    multi_tap: Option<u32>,
+    activation_mode: Option<String>,
+    multi_tap_block: Option<bool>,
+    press_trigger_threshold: Option<u32>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let edit = BindingEdit {
+        action_map_name,
+        action_name,
+        new_input,
+        multi_tap,
+        activation_mode,
+        multi_tap_block,
+        press_trigger_threshold,
+    };
+    eprintln!("update_binding called with: {:?}", edit);
+
+    let mut app_state = state.lock().unwrap();
+    app_state.edit(|app_state| apply_binding_edit(app_state, &edit))
+}
+
+// Apply many binding edits under one lock and one undo step; failed edits are reported
+// individually and don't stop the rest of the batch
+#[tauri::command]
+fn update_bindings_batch(
+    edits: Vec<BindingEdit>,
+    state: tauri::State<Mutex<AppState>>,
+) -> BatchResult {
+    let mut app_state = state.lock().unwrap();
+
+    let before = app_state.current_bindings.clone();
+    let result = apply_binding_edits(&mut app_state, &edits);
+    app_state.record_edit_since(before);
+    result
+}
+
+fn apply_binding_edits(app_state: &mut AppState, edits: &[BindingEdit]) -> BatchResult {
+    let mut result = BatchResult {
+        applied: 0,
+        errors: Vec::new(),
+    };
+
+    for edit in edits {
+        match apply_binding_edit(app_state, edit) {
+            Ok(()) => result.applied += 1,
+            Err(e) => result.errors.push(format!(
+                "{}/{} -> '{}': {}",
+                edit.action_map_name, edit.action_name, edit.new_input, e
+            )),
+        }
+    }
+
+    result
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct InputDisplay {
+    display_name: String,
+    input_type: keybindings::InputType,
+}
+
+// Format a raw SC input ("lalt+js1_button3") the same way bindings are shown, so the UI
+// doesn't need its own copy of the formatting rules. Cleared inputs come back as "Unbound"
+// with the device type they clear
+#[tauri::command]
+fn format_input_display(input: String) -> InputDisplay {
+    let rebind = keybindings::Rebind {
+        input,
+        multi_tap: None,
+        multi_tap_block: None,
+        press_trigger_threshold: None,
+        activation_mode: String::new(),
+    };
+
+    InputDisplay {
+        display_name: rebind.get_display_name(),
+        input_type: rebind.classify_input().input_type,
+    }
+}
+
+#[tauri::command]
+fn list_activation_modes() -> Vec<keybindings::ActivationModeInfo> {
+    keybindings::ActivationMode::ALL_MODES
+        .iter()
+        .map(|mode| mode.info())
+        .collect()
+}
+
+// Convert a key label submitted by the UI ("Numpad 1", ";") back to the SC token
+#[tauri::command]
+fn key_label_to_keyname(label: String) -> Result<String, String> {
+    keybindings::label_to_keyname(&label)
+        .map(|key| key.to_string())
+        .ok_or_else(|| format!("Unknown key label: {}", label))
+}
+
+#[tauri::command]
+fn reset_binding(
+    action_map_name: String,
+    action_name: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let mut app_state = state.lock().unwrap();
+
+    eprintln!(
+        "Resetting binding for action: {} in map: {}",
+        action_name, action_map_name
+    );
+
+    // Remove the custom binding from current_bindings
     // This will cause the merged view to show defaults from AllBinds again
-    if let Some(ref mut bindings) = app_state.current_bindings {
+    app_state.edit_bindings(|bindings| {
         if let Some(action_map) = bindings
             .action_maps
             .iter_mut()
@@ -426,9 +1151,102 @@ fn reset_binding(
             // (keeping empty action maps shouldn't cause issues)
         }
         Ok(())
-    } else {
-        Err("No bindings loaded".to_string())
+    })
+}
+
+#[tauri::command]
+fn remap_keyboard_layout(
+    from: KeyboardLayout,
+    to: KeyboardLayout,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+
+    app_state.edit_bindings(|bindings| {
+        let changed = bindings.remap_keyboard_layout(from, to);
+        info!(
+            "Remapped {} keyboard bindings from {:?} to {:?}",
+            changed, from, to
+        );
+        Ok(changed)
+    })
+}
+
+// Step the edit history back or forward and return the restored bindings
+// None when the restored state has no bindings loaded
+fn step_history(
+    state: tauri::State<Mutex<AppState>>,
+    undo: bool,
+) -> Result<Option<OrganizedKeybindings>, String> {
+    let mut app_state = state.lock().unwrap();
+
+    if !app_state.step_history(undo) {
+        return Err(if undo {
+            "Nothing to undo"
+        } else {
+            "Nothing to redo"
+        }
+        .to_string());
     }
+
+    Ok(app_state
+        .current_bindings
+        .as_ref()
+        .map(|bindings| organize_for_ui(bindings, app_state.all_binds.as_ref())))
+}
+
+#[tauri::command]
+fn undo_last_edit(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Option<OrganizedKeybindings>, String> {
+    step_history(state, true)
+}
+
+#[tauri::command]
+fn redo_last_edit(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Option<OrganizedKeybindings>, String> {
+    step_history(state, false)
+}
+
+// Move joystick bindings and options to the instances chosen in the device assignment UI,
+// old instance -> new instance; any permutation is applied in one step
+#[tauri::command]
+fn apply_device_assignment(
+    assignment: HashMap<u32, u32>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+
+    // A rejected assignment leaves the bindings and undo history untouched
+    let changed =
+        app_state.edit_bindings(|bindings| bindings.apply_joystick_assignment(&assignment))?;
+
+    info!("Reassigned {} joystick bindings", changed);
+    Ok(changed)
+}
+
+#[tauri::command]
+fn convert_device_type(
+    from_prefix: String,
+    to_prefix: String,
+    instance_map: HashMap<u32, u32>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::DeviceConversion, String> {
+    let mut app_state = state.lock().unwrap();
+
+    app_state.edit_bindings(|bindings| {
+        let conversion = bindings.convert_device_type(&from_prefix, &to_prefix, &instance_map)?;
+        info!(
+            "Converted {} bindings from {} to {}, {} unmappable, {} already bound on the target",
+            conversion.converted,
+            from_prefix,
+            to_prefix,
+            conversion.unmappable.len(),
+            conversion.collisions.len()
+        );
+        Ok(conversion)
+    })
 }
 
 #[tauri::command]
@@ -438,22 +1256,172 @@ fn get_current_bindings(
     let app_state = state.lock().unwrap();
 
     if let Some(ref bindings) = app_state.current_bindings {
-        Ok(bindings.organize())
+        Ok(organize_for_ui(bindings, app_state.all_binds.as_ref()))
     } else {
         Err("No bindings loaded".to_string())
     }
 }
 
+// Struct for a user note attached to an action (also the sidecar file entry format)
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+struct ActionNote {
+    action_map: String,
+    action: String,
+    note: String,
+}
+
+// Notes are stored next to the profile: "layout_foo.xml" -> "layout_foo.notes.json"
+fn notes_sidecar_path(xml_path: &std::path::Path) -> std::path::PathBuf {
+    xml_path.with_extension("notes.json")
+}
+
+fn notes_to_entries(notes: &HashMap<(String, String), String>) -> Vec<ActionNote> {
+    let mut entries: Vec<ActionNote> = notes
+        .iter()
+        .map(|((action_map, action), note)| ActionNote {
+            action_map: action_map.clone(),
+            action: action.clone(),
+            note: note.clone(),
+        })
+        .collect();
+    entries.sort_by(|a, b| (&a.action_map, &a.action).cmp(&(&b.action_map, &b.action)));
+    entries
+}
+
+// No notes removes the sidecar, so notes deleted since the last export don't come back on load
+fn write_notes_sidecar(
+    xml_path: &std::path::Path,
+    notes: &HashMap<(String, String), String>,
+) -> Result<(), String> {
+    let sidecar = notes_sidecar_path(xml_path);
+    if notes.is_empty() {
+        return match std::fs::remove_file(&sidecar) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove notes file: {}", e))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let json = serde_json::to_string_pretty(&notes_to_entries(notes))
+        .map_err(|e| format!("Failed to serialize notes: {}", e))?;
+    std::fs::write(sidecar, json).map_err(|e| format!("Failed to write notes file: {}", e))
+}
+
+// Missing sidecar means no notes
+fn read_notes_sidecar(
+    xml_path: &std::path::Path,
+) -> Result<HashMap<(String, String), String>, String> {
+    let sidecar = notes_sidecar_path(xml_path);
+    if !sidecar.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let json = std::fs::read_to_string(&sidecar)
+        .map_err(|e| format!("Failed to read notes file: {}", e))?;
+    parse_notes_json(&json)
+}
+
+// Notes for a profile being loaded; a broken sidecar is logged and skipped rather than
+// failing the load, since notes are only annotations
+fn load_notes_or_empty(xml_path: &std::path::Path) -> HashMap<(String, String), String> {
+    read_notes_sidecar(xml_path).unwrap_or_else(|e| {
+        error!("Ignoring notes for {:?}: {}", xml_path, e);
+        HashMap::new()
+    })
+}
+
+fn parse_notes_json(json: &str) -> Result<HashMap<(String, String), String>, String> {
+    let entries: Vec<ActionNote> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid notes file: {}", e))?;
+
+    Ok(entries
+        .into_iter()
+        .map(|n| ((n.action_map, n.action), n.note))
+        .collect())
+}
+
+#[tauri::command]
+fn set_action_note(
+    action_map_name: String,
+    action_name: String,
+    note: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let mut app_state = state.lock().unwrap();
+    let key = (action_map_name, action_name);
+
+    // An empty note removes it
+    if note.trim().is_empty() {
+        app_state.notes.remove(&key);
+    } else {
+        app_state.notes.insert(key, note);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_action_notes(state: tauri::State<Mutex<AppState>>) -> Result<Vec<ActionNote>, String> {
+    let app_state = state.lock().unwrap();
+    Ok(notes_to_entries(&app_state.notes))
+}
+
+// Profile file name SC expects: "layout_" prefix, no characters that are illegal in file names,
+// and a base name matching the profileName attribute
+// e.g. "My Profile: v2" -> "layout_My_Profile__v2.xml"
+fn canonical_profile_filename(profile_name: &str) -> String {
+    format!("{}.xml", canonical_profile_stem(profile_name))
+}
+
+// File name without ".xml", also used as the profileName attribute
+fn canonical_profile_stem(profile_name: &str) -> String {
+    let name = profile_name.trim();
+    let name = name
+        .strip_suffix(".xml")
+        .or_else(|| name.strip_suffix(".XML"))
+        .unwrap_or(name);
+
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || c.is_whitespace() || "<>:\"/\\|?*".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    // Windows rejects names ending in a dot
+    let sanitized = sanitized.trim_end_matches('.');
+
+    let sanitized = if sanitized.is_empty() {
+        "Profile"
+    } else {
+        sanitized
+    };
+
+    if sanitized.to_lowercase().starts_with("layout_") {
+        sanitized.to_string()
+    } else {
+        format!("layout_{}", sanitized)
+    }
+}
+
+// Returns the path that was written, which may differ from `file_path` in its file name
 #[tauri::command]
 fn export_keybindings(
     file_path: String,
+    write_notes: Option<bool>,
+    line_ending: Option<LineEnding>,
     state: tauri::State<Mutex<AppState>>,
-) -> Result<(), String> {
+) -> Result<String, String> {
     let mut app_state = state.lock().unwrap();
 
+    let mut file_path = std::path::PathBuf::from(file_path);
     if let Some(ref mut bindings) = app_state.current_bindings {
         // Extract filename from path (without extension)
-        let mut file_name = std::path::Path::new(&file_path)
+        let mut file_name = file_path
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("Profile")
@@ -464,7 +1432,9 @@ fn export_keybindings(
             file_name.truncate(file_name.len() - 9); // Remove "_exported" (9 chars)
         }
 
-        // Update profile name to match the filename
+        // Keep the written filename and the profile name in sync
+        let file_name = canonical_profile_stem(&file_name);
+        file_path.set_file_name(canonical_profile_filename(&file_name));
         bindings.profile_name = file_name;
     }
 
@@ -474,83 +1444,1295 @@ fn export_keybindings(
         let all_binds = app_state.all_binds.as_ref();
 
         // Serialize to XML with category information
-        let xml_content = bindings.to_xml_with_categories(all_binds);
+        let xml_content = bindings.to_xml_with_categories(all_binds, true);
+        let xml_content = line_ending.unwrap_or_default().apply(&xml_content);
 
         // Write to file
         std::fs::write(&file_path, xml_content)
             .map_err(|e| format!("Failed to write keybindings file: {}", e))?;
 
-        Ok(())
+        // Write the notes sidecar alongside the profile
+        if write_notes.unwrap_or(false) {
+            write_notes_sidecar(&file_path, &app_state.notes)?;
+        }
+
+        Ok(file_path.to_string_lossy().to_string())
     } else {
         Err("No keybindings loaded to export".to_string())
     }
 }
 
-// Template management commands
-#[tauri::command]
-fn save_template(file_path: String, template_json: String) -> Result<(), String> {
-    std::fs::write(&file_path, template_json)
-        .map_err(|e| format!("Failed to save template: {}", e))?;
-    Ok(())
+// Line endings for exported profiles. SC reads either; CRLF matches the game's own exports
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
 }
 
-#[tauri::command]
-fn load_template(file_path: String) -> Result<String, String> {
-    std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to load template: {}", e))
+impl LineEnding {
+    // Generated XML always uses "\n", so only CRLF needs a rewrite
+    fn apply(self, xml: &str) -> String {
+        match self {
+            LineEnding::Lf => xml.to_string(),
+            LineEnding::Crlf => xml.replace("\r\n", "\n").replace('\n', "\r\n"),
+        }
+    }
 }
 
-#[tauri::command]
-fn load_all_binds(
-    state: tauri::State<Mutex<AppState>>,
-    app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    // Load AllBinds.xml from resources
-    let all_binds_path = if cfg!(debug_assertions) {
-        // Development: look in project root
-        let exe_path =
-            std::env::current_exe().map_err(|e| format!("Failed to get exe path: {}", e))?;
-        let exe_dir = exe_path
-            .parent()
-            .ok_or_else(|| "Failed to get exe directory".to_string())?;
-        exe_dir
-            .parent()
-            .and_then(|p| p.parent())
-            .and_then(|p| p.parent())
-            .ok_or_else(|| "Failed to find project root".to_string())?
-            .join("AllBinds.xml")
+// Output format for tabular binding exports
+#[derive(serde::Deserialize, Debug, Clone, Copy)]
+enum TableFormat {
+    Csv,
+    Html,
+}
+
+// Column headers shared by the CSV and HTML exports
+const TABLE_HEADERS: [&str; 6] = [
+    "Category",
+    "Action Map",
+    "Action",
+    "Device",
+    "Input",
+    "Display Name",
+];
+
+// Quote a CSV field if it contains separators, quotes or newlines
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') || field.contains('\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-        // Production: use Tauri's resource resolver
-        // File is in the resources subfolder within resources
-        app_handle
-            .path()
-            .resource_dir()
-            .map_err(|e| format!("Failed to get resource dir: {}", e))?
-            .join(RESOURCES_SUBFOLDER)
-            .join("AllBinds.xml")
-    };
+        field.to_string()
+    }
+}
 
-    // Read the XML file
-    let xml_content = std::fs::read_to_string(&all_binds_path)
-        .map_err(|e| format!("Failed to read AllBinds.xml at {:?}: {}", all_binds_path, e))?;
+// Escape text for inclusion in HTML
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
 
-    // Parse the XML
-    let all_binds = AllBinds::from_xml(&xml_content)?;
+// Fields of a table row, in header order
+fn table_row_fields(row: &keybindings::BindingTableRow) -> [&str; 6] {
+    [
+        &row.category,
+        &row.action_map_label,
+        &row.action_label,
+        &row.device,
+        &row.input,
+        &row.display_name,
+    ]
+}
+
+fn render_bindings_csv(rows: &[keybindings::BindingTableRow]) -> String {
+    let mut csv = TABLE_HEADERS.join(",");
+    csv.push_str("\r\n");
+
+    for row in rows {
+        let fields: Vec<String> = table_row_fields(row)
+            .iter()
+            .map(|f| csv_escape(f))
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+// Column headers for the one-row-per-action analysis CSV
+const ANALYSIS_HEADERS: [&str; 10] = [
+    "category",
+    "action_map",
+    "action",
+    "label",
+    "keyboard",
+    "mouse",
+    "joystick1",
+    "gamepad",
+    "activation_mode",
+    "customized",
+];
+
+fn render_analysis_csv(rows: &[keybindings::AnalysisRow]) -> String {
+    let mut csv = ANALYSIS_HEADERS.join(",");
+    csv.push_str("\r\n");
+
+    for row in rows {
+        let customized = row.customized.to_string();
+        let fields: Vec<String> = [
+            &row.category,
+            &row.action_map,
+            &row.action,
+            &row.label,
+            &row.keyboard,
+            &row.mouse,
+            &row.joystick1,
+            &row.gamepad,
+            &row.activation_mode,
+            &customized,
+        ]
+        .iter()
+        .map(|f| csv_escape(f))
+        .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+fn render_bindings_html(rows: &[keybindings::BindingTableRow], title: &str) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"UTF-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", html_escape(title)));
+    html.push_str("</head>\n<body>\n<table>\n<thead>\n<tr>");
+    for header in TABLE_HEADERS {
+        html.push_str(&format!("<th>{}</th>", header));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for row in rows {
+        html.push_str("<tr>");
+        for field in table_row_fields(row) {
+            html.push_str(&format!("<td>{}</td>", html_escape(field)));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+    html
+}
+
+// Every action using one physical button (or hat/axis when `kind` is given) on a device
+// instance, e.g. ("js2", 12) -> [("spaceship_weapons", "v_attack1", "lalt+js2_button12")]
+#[tauri::command]
+fn usages_of_button(
+    device_instance: String,
+    button: u32,
+    kind: Option<String>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<(String, String, String)>, String> {
+    let kind = kind.unwrap_or_else(|| "button".to_string());
+    if !matches!(kind.as_str(), "button" | "hat" | "axis") {
+        return Err(format!(
+            "Unknown control kind '{}', expected button, hat or axis",
+            kind
+        ));
+    }
+
+    let app_state = state.lock().unwrap();
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    Ok(all_binds
+        .merge_with_user_bindings(app_state.current_bindings.as_ref())
+        .usages_of_control(&device_instance, &kind, button))
+}
+
+// Unbound buttons (and hats when `max_hat` is given) on a device instance, so the UI can
+// recommend one for a new binding, e.g. ("js1", 6) -> ["button3", "button4", "button6"]
+#[tauri::command]
+fn suggest_free_controls(
+    device_instance: String,
+    max_button: u32,
+    max_hat: Option<u32>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<String>, String> {
+    let app_state = state.lock().unwrap();
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    let merged = all_binds.merge_with_user_bindings(app_state.current_bindings.as_ref());
+    let mut free = merged.free_controls(&device_instance, "button", max_button);
+    if let Some(max_hat) = max_hat {
+        free.extend(merged.free_controls(&device_instance, "hat", max_hat));
+    }
+    Ok(free)
+}
+
+// Highest button/axis/hat numbers bound on a device instance (e.g. "js1"), so the
+// binding grid can render just enough rows for that device
+#[tauri::command]
+fn max_indices_for_device(
+    device_instance: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::MaxIndices, String> {
+    let app_state = state.lock().unwrap();
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    Ok(all_binds
+        .merge_with_user_bindings(app_state.current_bindings.as_ref())
+        .max_indices(&device_instance))
+}
+
+// Actions bound on several device types at once (keyboard and joystick, ...), defaults
+// included, where whichever device is used first wins
+#[tauri::command]
+fn find_multidevice_ambiguities(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::Ambiguity>, String> {
+    let app_state = state.lock().unwrap();
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    Ok(all_binds
+        .merge_with_user_bindings(app_state.current_bindings.as_ref())
+        .find_multidevice_ambiguities())
+}
+
+// Category -> action map tree with action counts for the navigation sidebar
+#[tauri::command]
+fn get_category_tree(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::CategoryNode>, String> {
+    let app_state = state.lock().unwrap();
+
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    Ok(all_binds
+        .merge_with_user_bindings(app_state.current_bindings.as_ref())
+        .category_tree())
+}
+
+// Wide CSV for spreadsheets: one row per action with a column per device, unbound included
+#[tauri::command]
+fn export_analysis_csv(
+    file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let app_state = state.lock().unwrap();
+
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    let rows = all_binds
+        .merge_with_user_bindings(app_state.current_bindings.as_ref())
+        .to_analysis_rows();
+    std::fs::write(&file_path, render_analysis_csv(&rows))
+        .map_err(|e| format!("Failed to write analysis CSV: {}", e))?;
+
+    info!(
+        "Exported {} actions for analysis to {}",
+        rows.len(),
+        file_path
+    );
+    Ok(())
+}
+
+#[tauri::command]
+fn export_bindings_table(
+    file_path: String,
+    format: TableFormat,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let app_state = state.lock().unwrap();
+
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    let merged = all_binds.merge_with_user_bindings(app_state.current_bindings.as_ref());
+    let rows = merged.to_table_rows();
+
+    let content = match format {
+        TableFormat::Csv => render_bindings_csv(&rows),
+        TableFormat::Html => {
+            let title = app_state
+                .current_bindings
+                .as_ref()
+                .map(|b| b.profile_name.clone())
+                .unwrap_or_else(|| "Star Citizen Keybindings".to_string());
+            render_bindings_html(&rows, &title)
+        }
+    };
+
+    std::fs::write(&file_path, content)
+        .map_err(|e| format!("Failed to write bindings table: {}", e))?;
+
+    info!(
+        "Exported {} binding rows as {:?} to {}",
+        rows.len(),
+        format,
+        file_path
+    );
+    Ok(())
+}
+
+#[tauri::command]
+fn export_keybindings_filtered(
+    file_path: String,
+    action_map_names: Vec<String>,
+    line_ending: Option<LineEnding>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let app_state = state.lock().unwrap();
+
+    let bindings = app_state
+        .current_bindings
+        .as_ref()
+        .ok_or_else(|| "No keybindings loaded to export".to_string())?;
+
+    if !bindings
+        .action_maps
+        .iter()
+        .any(|am| action_map_names.contains(&am.name))
+    {
+        return Err("None of the selected action maps have bindings to export".to_string());
+    }
+
+    // Serialize only the selected action maps with category information
+    let xml_content = bindings.to_xml_with_categories_filtered(
+        app_state.all_binds.as_ref(),
+        &action_map_names,
+        true,
+    );
+    let xml_content = line_ending.unwrap_or_default().apply(&xml_content);
+
+    std::fs::write(&file_path, xml_content)
+        .map_err(|e| format!("Failed to write keybindings file: {}", e))
+}
+
+// Write one profile per device instance next to `base_file_path`: "{name}_keyboard.xml",
+// "{name}_joystick1.xml", ... each with only that device's rebinds. Returns the written paths
+fn split_profile_files(
+    bindings: &ActionMaps,
+    all_binds: Option<&AllBinds>,
+    base_file_path: &std::path::Path,
+) -> Result<Vec<String>, String> {
+    let name = base_file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| format!("Invalid profile path: {}", base_file_path.display()))?;
+    let dir = base_file_path
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""));
+
+    let devices = bindings.device_instances();
+    if devices.is_empty() {
+        return Err("No bindings to split".to_string());
+    }
+
+    let mut written = Vec::new();
+    for device in devices {
+        let stem = format!("{}_{}", name, device.file_suffix());
+        let mut profile = bindings.clone();
+        profile.profile_name = stem.clone();
+
+        let xml_content = profile.to_xml_with_categories_for_device(all_binds, &device, true);
+        let path = dir.join(format!("{}.xml", stem));
+        std::fs::write(&path, xml_content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(written)
+}
+
+#[tauri::command]
+fn split_profile_by_device(
+    base_file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<String>, String> {
+    let app_state = state.lock().unwrap();
+
+    let bindings = app_state
+        .current_bindings
+        .as_ref()
+        .ok_or_else(|| "No keybindings loaded to export".to_string())?;
+
+    let written = split_profile_files(
+        bindings,
+        app_state.all_binds.as_ref(),
+        std::path::Path::new(&base_file_path),
+    )?;
+    info!("Split profile into {} device files", written.len());
+    Ok(written)
+}
+
+// Write a profile with only one action's rebinds, so a binding that misbehaves can be
+// tested (and attached to a bug report) on its own. Named after the file it's written to
+#[tauri::command]
+fn extract_single_binding_profile(
+    action_map: String,
+    action: String,
+    file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let app_state = state.lock().unwrap();
+
+    let bindings = app_state
+        .current_bindings
+        .as_ref()
+        .ok_or_else(|| "No keybindings loaded to export".to_string())?;
+
+    let mut profile = bindings.clone();
+    if let Some(stem) = std::path::Path::new(&file_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+    {
+        profile.profile_name = stem.to_string();
+    }
+
+    let xml_content =
+        profile.to_xml_single_action(app_state.all_binds.as_ref(), &action_map, &action, true)?;
+    std::fs::write(&file_path, xml_content).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    info!("Extracted {}/{} to {}", action_map, action, file_path);
+    Ok(())
+}
+
+// Export only the action maps in one AllBinds UI category, e.g. just the flight bindings
+#[tauri::command]
+fn export_category(
+    file_path: String,
+    category: String,
+    line_ending: Option<LineEnding>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let app_state = state.lock().unwrap();
+
+    let bindings = app_state
+        .current_bindings
+        .as_ref()
+        .ok_or_else(|| "No keybindings loaded to export".to_string())?;
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or("AllBinds not loaded. Please load the keybindings first.")?;
+
+    let action_map_names = bindings.action_maps_in_category(all_binds, &category);
+    if action_map_names.is_empty() {
+        return Err(format!("No bindings to export in category '{}'", category));
+    }
+
+    let xml_content =
+        bindings.to_xml_with_categories_filtered(Some(all_binds), &action_map_names, true);
+    let xml_content = line_ending.unwrap_or_default().apply(&xml_content);
+    info!(
+        "Exporting {} action maps in category '{}' to {}",
+        action_map_names.len(),
+        category,
+        file_path
+    );
+
+    std::fs::write(&file_path, xml_content)
+        .map_err(|e| format!("Failed to write keybindings file: {}", e))
+}
+
+// Template management commands
+#[tauri::command]
+fn save_template(file_path: String, template_json: String) -> Result<(), String> {
+    std::fs::write(&file_path, template_json)
+        .map_err(|e| format!("Failed to save template: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+fn load_template(file_path: String) -> Result<String, String> {
+    std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to load template: {}", e))
+}
+
+#[tauri::command]
+fn save_template_v2(
+    file_path: String,
+    roles: Vec<keybindings::TemplateRole>,
+    instance_roles: HashMap<String, String>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let app_state = state.lock().unwrap();
+
+    let bindings = app_state
+        .current_bindings
+        .as_ref()
+        .ok_or_else(|| "No keybindings loaded to save as a template".to_string())?;
+
+    let template = keybindings::TemplateV2::from_action_maps(bindings, roles, &instance_roles);
+    let json = serde_json::to_string_pretty(&template)
+        .map_err(|e| format!("Failed to serialize template: {}", e))?;
+
+    std::fs::write(&file_path, json).map_err(|e| format!("Failed to save template: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+fn apply_template_v2(
+    file_path: String,
+    role_to_instance: HashMap<String, String>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<usize, String> {
+    let json = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to load template: {}", e))?;
+    let template: keybindings::TemplateV2 =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid template file: {}", e))?;
+
+    let mut app_state = state.lock().unwrap();
+
+    app_state.edit(|app_state| {
+        // Initialize current_bindings if it doesn't exist
+        let bindings = app_state
+            .current_bindings
+            .get_or_insert_with(ActionMaps::empty);
+
+        let applied = template.apply_to(bindings, &role_to_instance)?;
+        info!("Applied {} template bindings from {}", applied, file_path);
+
+        Ok(applied)
+    })
+}
+
+// Replace the device options (keyboards, mice and joystick instances with their Product strings)
+// with another profile's, creating empty bindings when none are loaded. Bindings are untouched
+fn import_device_options<'a>(
+    current: &'a mut Option<ActionMaps>,
+    source: &ActionMaps,
+) -> &'a ActionMaps {
+    let bindings = current.get_or_insert_with(ActionMaps::empty);
+    bindings.devices = source.devices.clone();
+    bindings
+}
+
+#[tauri::command]
+fn import_device_options_from(
+    file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<OrganizedKeybindings, String> {
+    let xml_content =
+        std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let source = ActionMaps::from_xml(&xml_content)?;
+
+    let mut app_state = state.lock().unwrap();
+    let before = app_state.current_bindings.clone();
+    let joysticks = import_device_options(&mut app_state.current_bindings, &source)
+        .devices
+        .joysticks
+        .len();
+    // Re-importing the same options isn't an edit
+    app_state.record_edit_since(before);
+    info!(
+        "Imported device options from {}: {} joystick instance(s)",
+        file_path, joysticks
+    );
+
+    let bindings = app_state.current_bindings.as_ref().unwrap();
+    Ok(organize_for_ui(bindings, app_state.all_binds.as_ref()))
+}
+
+// Turn SC's defaults for one device type into editable rebinds in current_bindings
+#[tauri::command]
+fn materialize_defaults(
+    input_type: keybindings::InputType,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+    if app_state.all_binds.is_none() {
+        return Err("AllBinds not loaded. Please load the keybindings first.".to_string());
+    }
+
+    let before = app_state.current_bindings.clone();
+    let app_state = &mut *app_state;
+    let all_binds = app_state.all_binds.as_ref().unwrap();
+    let bindings = app_state
+        .current_bindings
+        .get_or_insert_with(ActionMaps::empty);
+
+    let added = bindings.materialize_defaults(all_binds, &input_type);
+    info!("Materialized {} {:?} default bindings", added, input_type);
+
+    // Nothing materialized isn't an edit, and doesn't create an empty profile either
+    if added == 0 {
+        app_state.current_bindings = before;
+    } else {
+        app_state.record_edit_since(before);
+    }
+
+    Ok(added)
+}
+
+// Resolve the location of the bundled AllBinds.xml
+fn all_binds_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    if cfg!(debug_assertions) {
+        // Development: look in project root
+        let exe_path =
+            std::env::current_exe().map_err(|e| format!("Failed to get exe path: {}", e))?;
+        let exe_dir = exe_path
+            .parent()
+            .ok_or_else(|| "Failed to get exe directory".to_string())?;
+        Ok(exe_dir
+            .parent()
+            .and_then(|p| p.parent())
+            .and_then(|p| p.parent())
+            .ok_or_else(|| "Failed to find project root".to_string())?
+            .join("AllBinds.xml"))
+    } else {
+        // Production: use Tauri's resource resolver
+        // File is in the resources subfolder within resources
+        Ok(app_handle
+            .path()
+            .resource_dir()
+            .map_err(|e| format!("Failed to get resource dir: {}", e))?
+            .join(RESOURCES_SUBFOLDER)
+            .join("AllBinds.xml"))
+    }
+}
+
+// Size and full-precision mtime of a file for change detection; a rewrite within the same
+// second still changes the sub-second part on filesystems that record it
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+}
+
+fn file_source_meta(path: &std::path::Path) -> Result<FileStamp, String> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read metadata for {:?}: {}", path, e))?;
+    Ok(FileStamp {
+        len: metadata.len(),
+        modified: metadata.modified().ok(),
+    })
+}
+
+// AllBinds only needs re-parsing when forced, never loaded, or the source file has changed
+// since the last load; an unknown mtime always reloads
+fn should_reload_all_binds(
+    loaded: bool,
+    cached_meta: Option<FileStamp>,
+    current_meta: FileStamp,
+    force: bool,
+) -> bool {
+    force || !loaded || current_meta.modified.is_none() || cached_meta != Some(current_meta)
+}
+
+// Stamp the AllBinds.xml at `path` for a load, or None when the loaded copy is still current
+fn all_binds_reload_stamp(
+    state: &Mutex<AppState>,
+    path: &std::path::Path,
+    force: bool,
+) -> Result<Option<FileStamp>, String> {
+    let source_meta = file_source_meta(path)?;
+    let app_state = state.lock().unwrap();
+
+    if should_reload_all_binds(
+        app_state.all_binds.is_some(),
+        app_state.all_binds_source_meta,
+        source_meta,
+        force,
+    ) {
+        Ok(Some(source_meta))
+    } else {
+        info!("AllBinds.xml unchanged since last load, skipping re-parse");
+        Ok(None)
+    }
+}
+
+// Read and parse AllBinds.xml; needs no app state so it can run on a blocking thread
+fn read_all_binds_file(path: &std::path::Path) -> Result<AllBinds, String> {
+    let xml_content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read AllBinds.xml at {:?}: {}", path, e))?;
+    AllBinds::from_xml(&xml_content)
+}
+
+// Compare an older AllBinds.xml against the loaded one: new, removed and re-defaulted actions
+#[tauri::command]
+fn diff_allbinds(
+    old_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::AllBindsDiff, String> {
+    // Parse before taking the lock; AllBinds.xml is large
+    let old = read_all_binds_file(std::path::Path::new(&old_path))?;
+
+    let app_state = state.lock().unwrap();
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    Ok(all_binds.diff_from(&old))
+}
+
+#[tauri::command]
+async fn load_all_binds(
+    force: Option<bool>,
+    state: tauri::State<'_, Mutex<AppState>>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    // Load AllBinds.xml from resources
+    let all_binds_path = all_binds_path(&app_handle)?;
+    let Some(source_meta) =
+        all_binds_reload_stamp(&state, &all_binds_path, force.unwrap_or(false))?
+    else {
+        return Ok(());
+    };
+
+    // Read and parse off the main thread without holding the state lock
+    let all_binds = tokio::task::spawn_blocking(move || read_all_binds_file(&all_binds_path))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+    // Lock only to store the result
+    let mut app_state = state.lock().unwrap();
+    store_all_binds(&mut app_state, all_binds, source_meta);
+
+    Ok(())
+}
+
+// Replace the loaded AllBinds, keeping a localization loaded for the previous one
+fn store_all_binds(app_state: &mut AppState, mut all_binds: AllBinds, source_meta: FileStamp) {
+    if let Some(previous) = app_state.all_binds.take() {
+        all_binds.localization = previous.localization;
+    }
+    app_state.input_index = None;
+    app_state.all_binds = Some(all_binds);
+    app_state.all_binds_source_meta = Some(source_meta);
+}
+
+// Load SC's global.ini so '@' UILabels show translated text; returns the entry count
+#[tauri::command]
+fn load_localization(
+    file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<usize, String> {
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read localization file: {}", e))?;
+    let localization = keybindings::parse_localization(&content);
 
-    // Store in state
     let mut app_state = state.lock().unwrap();
-    app_state.all_binds = Some(all_binds);
+    let all_binds = app_state
+        .all_binds
+        .as_mut()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    let count = localization.len();
+    all_binds.localization = localization;
+    info!("Loaded {} localization entries from {}", count, file_path);
+    Ok(count)
+}
+
+// Number of actionmaps parsed between progress events
+const ALL_BINDS_PROGRESS_INTERVAL: usize = 25;
+
+// Struct for AllBinds loading progress events
+#[derive(serde::Serialize, Clone)]
+struct AllBindsProgress {
+    action_maps_parsed: usize,
+    bytes_read: usize,
+    total_bytes: usize,
+}
+
+#[tauri::command]
+async fn load_all_binds_with_progress(
+    force: Option<bool>,
+    window: tauri::Window,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    use tauri::Emitter;
+
+    let all_binds_path = all_binds_path(window.app_handle())?;
+    let Some(source_meta) =
+        all_binds_reload_stamp(&state, &all_binds_path, force.unwrap_or(false))?
+    else {
+        return Ok(());
+    };
+
+    // Parse in a separate thread so progress events reach the UI while we work
+    let all_binds = tokio::task::spawn_blocking(move || {
+        let xml_content = std::fs::read_to_string(&all_binds_path)
+            .map_err(|e| format!("Failed to read AllBinds.xml at {:?}: {}", all_binds_path, e))?;
+        let total_bytes = xml_content.len();
+
+        AllBinds::from_xml_with_progress(
+            &xml_content,
+            ALL_BINDS_PROGRESS_INTERVAL,
+            |action_maps_parsed, bytes_read| {
+                let _ = window.emit(
+                    "all-binds-progress",
+                    AllBindsProgress {
+                        action_maps_parsed,
+                        bytes_read,
+                        total_bytes,
+                    },
+                );
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let mut app_state = state.lock().unwrap();
+    store_all_binds(&mut app_state, all_binds, source_meta);
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_merged_bindings(state: tauri::State<Mutex<AppState>>) -> Result<MergedBindings, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref all_binds) = app_state.all_binds {
+        // Merge with user bindings if they exist
+        let user_bindings = app_state.current_bindings.as_ref();
+        Ok(all_binds.merge_with_user_bindings(user_bindings))
+    } else {
+        Err("AllBinds.xml not loaded. Please restart the application.".to_string())
+    }
+}
+
+/// Build the input reverse-lookup index over the merged view and cache it for conflict checks
+/// Returns the number of distinct inputs indexed
+#[tauri::command]
+fn build_input_index(state: tauri::State<Mutex<AppState>>) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+    Ok(app_state.input_index()?.len())
+}
+
+#[tauri::command]
+fn get_merged_bindings_filtered(
+    input_types: Vec<String>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<MergedBindings, String> {
+    Ok(get_merged_bindings(state)?.filter_input_types(&input_types))
+}
+
+#[tauri::command]
+fn get_effective_binding(
+    action_map_name: String,
+    action_name: String,
+    connected_instances: Vec<String>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Option<keybindings::EffectiveBinding>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref all_binds) = app_state.all_binds {
+        let merged = all_binds.merge_with_user_bindings(app_state.current_bindings.as_ref());
+        Ok(merged.effective_binding(&action_map_name, &action_name, &connected_instances))
+    } else {
+        Err("AllBinds.xml not loaded. Please restart the application.".to_string())
+    }
+}
+
+#[tauri::command]
+fn binding_coverage(
+    device_prefix: String,
+    essential_actions: Option<Vec<(String, String)>>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::CoverageReport, String> {
+    let app_state = state.lock().unwrap();
+
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or_else(|| "AllBinds.xml not loaded. Please restart the application.".to_string())?;
+
+    // Fall back to the built-in list of essential flight actions
+    let essential_actions = essential_actions.unwrap_or_else(|| {
+        keybindings::ESSENTIAL_FLIGHT_ACTIONS
+            .iter()
+            .map(|(map, action)| (map.to_string(), action.to_string()))
+            .collect()
+    });
+
+    let merged = all_binds.merge_with_user_bindings(app_state.current_bindings.as_ref());
+    Ok(merged.binding_coverage(&device_prefix, &essential_actions))
+}
+
+// Inputs an action has under several activation modes (button3 as press and hold), so the
+// UI can confirm the user meant both; SC fires whichever mode's condition is met first
+#[tauri::command]
+fn find_same_input_different_mode(
+    action_map: String,
+    action: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        bindings.find_same_input_different_mode(&action_map, &action)
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+#[tauri::command]
+fn toggle_axis_inversion(
+    action_map: String,
+    action: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<String, String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| bindings.toggle_axis_inversion(&action_map, &action))
+}
+
+#[tauri::command]
+fn set_primary_binding(
+    action_map: String,
+    action: String,
+    input: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| bindings.set_primary_binding(&action_map, &action, &input))
+}
+
+#[tauri::command]
+fn find_overbound_actions(
+    max_per_action: Option<usize>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::OverboundAction>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_overbound_actions(
+            max_per_action.unwrap_or(keybindings::DEFAULT_MAX_BINDINGS_PER_ACTION),
+        ))
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Find rebinds using input tokens SC has removed, with the replacement where one is known
+#[tauri::command]
+fn find_deprecated_tokens(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::DeprecatedBinding>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_deprecated_tokens(keybindings::DEPRECATED_TOKENS))
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+#[tauri::command]
+fn apply_token_replacements(state: tauri::State<Mutex<AppState>>) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+
+    app_state.edit_bindings(|bindings| {
+        let changed = bindings.apply_token_replacements(keybindings::DEPRECATED_TOKENS);
+        info!("Replaced deprecated tokens in {} bindings", changed);
+        Ok(changed)
+    })
+}
+
+// Compare the loaded profile's optionsVersion/rebindVersion with what SC currently expects
+#[tauri::command]
+fn check_version_compatibility(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::VersionCheck, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.check_version_compatibility())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Round-trip bindings through the exporter so hand-edited profiles take the canonical
+// layout. Device options, categories and the versions as read are kept from the original,
+// since the export writes only what the game needs. Returns the reformatted bindings and XML
+fn reformat_bindings(
+    bindings: &ActionMaps,
+    all_binds: Option<&AllBinds>,
+) -> Result<(ActionMaps, String), String> {
+    let xml_content = bindings.to_xml_with_categories(all_binds, false);
+    let mut reformatted = ActionMaps::from_xml(&xml_content)?;
+    reformatted.devices = bindings.devices.clone();
+    reformatted.categories = bindings.categories.clone();
+    reformatted.options_version = bindings.options_version;
+    reformatted.rebind_version = bindings.rebind_version;
+    Ok((reformatted, xml_content))
+}
+
+// Reformat current_bindings so later saves only differ where bindings changed. Returns the XML
+#[tauri::command]
+fn reformat_profile(state: tauri::State<Mutex<AppState>>) -> Result<String, String> {
+    let mut app_state = state.lock().unwrap();
+
+    let (reformatted, xml_content) = reformat_bindings(
+        app_state
+            .current_bindings
+            .as_ref()
+            .ok_or_else(|| "No keybindings loaded".to_string())?,
+        app_state.all_binds.as_ref(),
+    )?;
+    app_state.edit_bindings(|bindings| {
+        *bindings = reformatted;
+        Ok(xml_content)
+    })
+}
+
+// Action maps and actions in the loaded profile that hold no rebinds
+#[tauri::command]
+fn find_empty_containers(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::EmptyContainers, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_empty_containers())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Cleared overrides the categories export writes but the plain to_xml export leaves out,
+// so users can see why the two exports of one profile differ
+#[tauri::command]
+fn find_export_discrepancies(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<(String, String, String)>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_export_discrepancies())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Keyboard bindings on punctuation or moved letters, which may not transfer to another layout
+#[tauri::command]
+fn find_layout_sensitive_bindings(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<(String, String, String)>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_layout_sensitive_bindings())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// List rebinds that are only modifier keys ("LALT+LCTRL"), usually a capture error
+#[tauri::command]
+fn find_modifier_only_bindings(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<(String, String, String)>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_modifier_only_bindings())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Rebinds on joystick/gamepad instances SC can't assign (above `max_instances`, default 8),
+// which usually come from a mangled import
+#[tauri::command]
+fn find_implausible_instances(
+    max_instances: Option<u32>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<(String, String, String)>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_implausible_instances(
+            max_instances.unwrap_or(keybindings::DEFAULT_MAX_DEVICE_INSTANCES),
+        ))
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Action maps with no real input that could be removed; `keep_clears` (on unless false)
+// keeps maps whose cleared placeholders intentionally unbind SC defaults
+#[tauri::command]
+fn find_noncontributing_actionmaps(
+    keep_clears: Option<bool>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<String>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_noncontributing_action_maps(keep_clears.unwrap_or(true)))
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Remove the action maps find_noncontributing_actionmaps lists, returning their names
+#[tauri::command]
+fn remove_noncontributing_actionmaps(
+    keep_clears: Option<bool>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<String>, String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| {
+        Ok(bindings.remove_noncontributing_action_maps(keep_clears.unwrap_or(true)))
+    })
+}
+
+// Drop empty action maps and actions from the loaded profile, returning how many were removed
+#[tauri::command]
+fn prune_empty_containers(state: tauri::State<Mutex<AppState>>) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| Ok(bindings.prune_empty_containers()))
+}
+
+// List actions the user has unbound on every device they bound, labelled from AllBinds
+#[tauri::command]
+fn list_fully_unbound_actions(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::UnboundAction>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_fully_unbound_actions(app_state.all_binds.as_ref()))
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+// Check joystick bindings against the button/axis/hat counts of the connected devices
+// Counts come from each device's HID descriptor; devices without one aren't checked, since
+// the gilrs device list only has estimates
+#[tauri::command]
+fn find_out_of_range_bindings(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::OutOfRangeBinding>, String> {
+    let capabilities: HashMap<u8, keybindings::DeviceCapabilities> =
+        directinput::list_device_identities()?
+            .into_iter()
+            .filter_map(|identity| {
+                let instance = u8::try_from(identity.sc_instance).ok()?;
+                let hid = directinput::hid_capabilities(identity.vendor_id?, identity.product_id?)?;
+                Some((
+                    instance,
+                    keybindings::DeviceCapabilities {
+                        button_count: hid.button_count,
+                        axis_count: hid.axis_count,
+                        hat_count: hid.hat_count,
+                    },
+                ))
+            })
+            .collect();
+
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_out_of_range_bindings(&capabilities))
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+#[tauri::command]
+fn normalize_cleared_bindings(state: tauri::State<Mutex<AppState>>) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| Ok(bindings.normalize_cleared_bindings()))
+}
+
+#[tauri::command]
+fn find_intra_action_duplicates(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<keybindings::DuplicateBinding>, String> {
+    let app_state = state.lock().unwrap();
+
+    if let Some(ref bindings) = app_state.current_bindings {
+        Ok(bindings.find_intra_action_duplicates())
+    } else {
+        Err("No keybindings loaded".to_string())
+    }
+}
+
+#[tauri::command]
+fn migrate_actionmap_name(
+    old: String,
+    new: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::RenameOutcome, String> {
+    let mut app_state = state.lock().unwrap();
+    app_state.edit_bindings(|bindings| Ok(bindings.rename_action_map(&old, &new)))
+}
+
+// Struct for an actionmap rename applied to the loaded profile
+#[derive(serde::Serialize, Debug, PartialEq)]
+struct AppliedMigration {
+    old_name: String,
+    new_name: String,
+    outcome: keybindings::RenameOutcome,
+}
+
+// Apply the actionmap renames inferred from the loaded AllBinds.xml (see
+// AllBinds::inferred_migrations), returning each one that changed the profile with the
+// rebinds it had to drop
+#[tauri::command]
+fn apply_known_migrations(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<AppliedMigration>, String> {
+    let mut app_state = state.lock().unwrap();
+
+    app_state.edit(|app_state| {
+        let all_binds = app_state.all_binds.as_ref().ok_or_else(|| {
+            "AllBinds.xml not loaded. Please restart the application.".to_string()
+        })?;
+        let bindings = app_state
+            .current_bindings
+            .as_mut()
+            .ok_or_else(|| "No keybindings loaded".to_string())?;
+
+        Ok(apply_migrations(all_binds, bindings))
+    })
+}
+
+fn apply_migrations(all_binds: &AllBinds, bindings: &mut ActionMaps) -> Vec<AppliedMigration> {
+    let migrations = all_binds.inferred_migrations(bindings);
+
+    let mut applied = Vec::new();
+    for (old_name, new_name) in migrations {
+        let outcome = bindings.rename_action_map(&old_name, &new_name);
+        if outcome.moved == 0 && outcome.dropped.is_empty() {
+            continue;
+        }
+
+        info!("Migrated actionmap {} -> {}", old_name, new_name);
+        for (action, input) in &outcome.dropped {
+            error!(
+                "Dropped {} on {}/{}: {} already has that device bound",
+                input, old_name, action, new_name
+            );
+        }
+        applied.push(AppliedMigration {
+            old_name,
+            new_name,
+            outcome,
+        });
+    }
 
-    Ok(())
+    applied
 }
 
 #[tauri::command]
-fn get_merged_bindings(state: tauri::State<Mutex<AppState>>) -> Result<MergedBindings, String> {
+fn binding_statistics(
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::BindingStats, String> {
     let app_state = state.lock().unwrap();
 
     if let Some(ref all_binds) = app_state.all_binds {
-        // Merge with user bindings if they exist
-        let user_bindings = app_state.current_bindings.as_ref();
-        Ok(all_binds.merge_with_user_bindings(user_bindings))
+        let merged = all_binds.merge_with_user_bindings(app_state.current_bindings.as_ref());
+        Ok(merged.binding_statistics())
     } else {
         Err("AllBinds.xml not loaded. Please restart the application.".to_string())
     }
@@ -593,7 +2775,8 @@ fn restore_user_customizations(
 
     // Restore the cached user customizations (delta) to backend state
     // This allows us to preserve unsaved work across app restarts
-    app_state.current_bindings = customizations;
+    // Undo steps belong to the bindings being replaced
+    app_state.replace_bindings(customizations);
 
     eprintln!("restore_user_customizations completed successfully");
     Ok(())
@@ -606,32 +2789,22 @@ fn find_conflicting_bindings(
     exclude_action: String,
     state: tauri::State<Mutex<AppState>>,
 ) -> Result<Vec<ConflictingBinding>, String> {
-    let app_state = state.lock().unwrap();
+    let mut app_state = state.lock().unwrap();
     let mut conflicts = Vec::new();
 
-    // Check in current bindings
-    if let Some(ref bindings) = app_state.current_bindings {
-        for action_map in &bindings.action_maps {
-            for action in &action_map.actions {
-                // Skip the action we're trying to bind
-                if action_map.name == exclude_action_map && action.name == exclude_action {
-                    continue;
-                }
-
-                // Check if this action has the same input bound
-                for rebind in &action.rebinds {
-                    if rebind.input == input {
-                        conflicts.push(ConflictingBinding {
-                            action_map_name: action_map.name.clone(),
-                            action_map_label: action_map.name.clone(), // Will be enhanced with UI label
-                            action_name: action.name.clone(),
-                            action_label: action.name.clone(), // Will be enhanced with UI label
-                        });
-                        break; // Only add once per action
-                    }
-                }
-            }
+    // Indexed lookup over the merged view (defaults included)
+    let index = app_state.input_index()?;
+    let matches = index.get(&keybindings::normalize_input(&input));
+    for (action_map_name, action_name) in matches.into_iter().flatten() {
+        if *action_map_name == exclude_action_map && *action_name == exclude_action {
+            continue;
         }
+        conflicts.push(ConflictingBinding {
+            action_map_name: action_map_name.clone(),
+            action_map_label: action_map_name.clone(), // Will be enhanced with UI label
+            action_name: action_name.clone(),
+            action_label: action_name.clone(), // Will be enhanced with UI label
+        });
     }
 
     // Enhance with UI labels from AllBinds
@@ -676,22 +2849,16 @@ fn clear_specific_binding(
     let clear_rebind = keybindings::Rebind {
         input: input_to_clear.clone(),
         multi_tap: None,
+        multi_tap_block: None,
+        press_trigger_threshold: None,
         activation_mode: String::new(),
     };
     let input_type = clear_rebind.get_input_type();
     eprintln!("Input type to clear: {:?}", input_type);
 
-    // Extract the joystick instance number if it's a joystick binding
+    // Extract the joystick instance number if it's a joystick binding (also handles hats and modifiers)
     let js_instance = if matches!(input_type, keybindings::InputType::Joystick) {
-        if let Some(js_part) = input_to_clear.split('_').next() {
-            if js_part.starts_with("js") {
-                js_part.get(2..).and_then(|s| s.parse::<u8>().ok())
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        clear_rebind.joystick_instance()
     } else {
         None
     };
@@ -750,6 +2917,7 @@ fn clear_specific_binding(
     };
 
     eprintln!("Cleared input string: '{}'", cleared_input);
+    app_state.record_edit();
 
     // If there's no default binding and we're just removing, we can delete the entire action if it becomes empty
     if cleared_input.is_empty() {
@@ -778,16 +2946,7 @@ fn clear_specific_binding(
     // Initialize current_bindings if it doesn't exist
     if app_state.current_bindings.is_none() {
         eprintln!("Creating new current_bindings structure");
-        app_state.current_bindings = Some(ActionMaps {
-            profile_name: "User Customizations".to_string(),
-            action_maps: Vec::new(),
-            categories: Vec::new(),
-            devices: keybindings::DeviceInfo {
-                keyboards: Vec::new(),
-                mice: Vec::new(),
-                joysticks: Vec::new(),
-            },
-        });
+        app_state.current_bindings = Some(ActionMaps::empty());
     }
 
     if let Some(ref mut bindings) = app_state.current_bindings {
@@ -830,6 +2989,8 @@ fn clear_specific_binding(
         action.rebinds.push(keybindings::Rebind {
             input: cleared_input,
             multi_tap: None,
+            multi_tap_block: None,
+            press_trigger_threshold: None,
             activation_mode: String::new(),
         });
 
@@ -840,11 +3001,35 @@ fn clear_specific_binding(
     }
 }
 
+#[tauri::command]
+fn purge_cleared_bindings(
+    keep_default_overrides: Option<bool>,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<usize, String> {
+    let mut app_state = state.lock().unwrap();
+
+    app_state.edit(|app_state| {
+        let bindings = app_state
+            .current_bindings
+            .as_mut()
+            .ok_or_else(|| "No bindings loaded".to_string())?;
+
+        let removed = bindings.purge_cleared_bindings(
+            app_state.all_binds.as_ref(),
+            keep_default_overrides.unwrap_or(true),
+        );
+        info!("Purged {} cleared bindings", removed);
+
+        Ok(removed)
+    })
+}
+
 #[tauri::command]
 fn clear_custom_bindings(state: tauri::State<Mutex<AppState>>) -> Result<(), String> {
     let mut app_state = state.lock().unwrap();
-    app_state.current_bindings = None;
+    app_state.replace_bindings(None);
     app_state.current_file_name = None;
+    app_state.notes.clear();
     Ok(())
 }
 
@@ -891,6 +3076,97 @@ fn scan_sc_installations(base_path: String) -> Result<Vec<ScInstallation>, Strin
     Ok(installations)
 }
 
+// Default Star Citizen install location used when the launcher config can't be found
+const DEFAULT_SC_BASE_PATH: &str = "C:\\Program Files\\Roberts Space Industries\\StarCitizen";
+
+// RSI Launcher config files (relative to %APPDATA%\rsilauncher) that may record the library folder
+const LAUNCHER_CONFIG_FILES: [&str; 3] = ["library.json", "settings.json", "launcher store.json"];
+
+// JSON keys the launcher has used for the library folder
+const LAUNCHER_LIBRARY_KEYS: [&str; 4] = [
+    "libraryFolder",
+    "defaultLibraryFolder",
+    "libraryPath",
+    "installDir",
+];
+
+// Extract the game library folder from an RSI Launcher config file
+fn parse_launcher_library_folder(json: &str) -> Option<String> {
+    fn find_key(value: &serde_json::Value) -> Option<String> {
+        match value {
+            serde_json::Value::Object(map) => {
+                for key in LAUNCHER_LIBRARY_KEYS {
+                    if let Some(serde_json::Value::String(path)) = map.get(key) {
+                        if !path.trim().is_empty() {
+                            return Some(path.trim().to_string());
+                        }
+                    }
+                }
+                map.values().find_map(find_key)
+            }
+            serde_json::Value::Array(items) => items.iter().find_map(find_key),
+            _ => None,
+        }
+    }
+
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    find_key(&value)
+}
+
+// Candidate base paths (folders containing LIVE/PTU/...) from the launcher config
+fn launcher_base_paths() -> Vec<std::path::PathBuf> {
+    let mut paths = Vec::new();
+
+    let Ok(app_data) = std::env::var("APPDATA") else {
+        return paths;
+    };
+    let launcher_dir = std::path::Path::new(&app_data).join("rsilauncher");
+
+    for config_file in LAUNCHER_CONFIG_FILES {
+        let Ok(content) = std::fs::read_to_string(launcher_dir.join(config_file)) else {
+            continue;
+        };
+
+        if let Some(library) = parse_launcher_library_folder(&content) {
+            let library = std::path::PathBuf::from(library);
+            // The library folder normally contains a StarCitizen folder, but may point at it directly
+            let sc_path = library.join("StarCitizen");
+            let base = if sc_path.is_dir() { sc_path } else { library };
+            if !paths.contains(&base) {
+                paths.push(base);
+            }
+        }
+    }
+
+    paths
+}
+
+#[tauri::command]
+fn autodetect_sc_installations() -> Vec<ScInstallation> {
+    let mut base_paths = launcher_base_paths();
+
+    if base_paths.is_empty() {
+        info!("No RSI Launcher library found, falling back to default install path");
+        base_paths.push(std::path::PathBuf::from(DEFAULT_SC_BASE_PATH));
+    }
+
+    let mut installations: Vec<ScInstallation> = Vec::new();
+    for base_path in base_paths {
+        match scan_sc_installations(base_path.to_string_lossy().to_string()) {
+            Ok(found) => {
+                for install in found {
+                    if !installations.iter().any(|i| i.path == install.path) {
+                        installations.push(install);
+                    }
+                }
+            }
+            Err(e) => info!("Skipping {:?}: {}", base_path, e),
+        }
+    }
+
+    installations
+}
+
 #[tauri::command]
 fn get_current_file_name(state: tauri::State<Mutex<AppState>>) -> Result<String, String> {
     let app_state = state.lock().unwrap();
@@ -902,14 +3178,90 @@ fn get_current_file_name(state: tauri::State<Mutex<AppState>>) -> Result<String,
     }
 }
 
+// Struct for the result of probing an installation folder for write access
+#[derive(serde::Serialize, Debug)]
+struct WriteCheck {
+    writable: bool,
+    reason: Option<String>,
+}
+
+// INSTALL\user\client\0\controls\mappings
+fn install_mappings_dir(installation_path: &std::path::Path) -> std::path::PathBuf {
+    installation_path
+        .join("user")
+        .join("client")
+        .join("0")
+        .join("controls")
+        .join("mappings")
+}
+
+// Turn an IO error from the write probe into something a user can act on
+fn describe_write_error(e: &std::io::Error, path: &std::path::Path) -> String {
+    use std::io::ErrorKind;
+
+    match e.kind() {
+        ErrorKind::NotFound => format!("Folder does not exist: {}", path.display()),
+        ErrorKind::ReadOnlyFilesystem => {
+            format!("Folder is on a read-only drive: {}", path.display())
+        }
+        ErrorKind::PermissionDenied => {
+            let read_only = std::fs::metadata(path)
+                .map(|m| m.permissions().readonly())
+                .unwrap_or(false);
+            if read_only {
+                format!("Folder is read-only: {}", path.display())
+            } else {
+                format!(
+                    "Permission denied writing to {} (try running as administrator)",
+                    path.display()
+                )
+            }
+        }
+        _ => format!("Cannot write to {}: {}", path.display(), e),
+    }
+}
+
+fn probe_install_writable(installation_path: &std::path::Path) -> WriteCheck {
+    let fail = |reason: String| WriteCheck {
+        writable: false,
+        reason: Some(reason),
+    };
+
+    if !installation_path.is_dir() {
+        return fail(format!(
+            "Installation folder not found: {}",
+            installation_path.display()
+        ));
+    }
+
+    let mappings_dir = install_mappings_dir(installation_path);
+    if let Err(e) = std::fs::create_dir_all(&mappings_dir) {
+        return fail(describe_write_error(&e, &mappings_dir));
+    }
+
+    // Write and remove a probe file to be sure files can actually be created
+    let probe = mappings_dir.join(".sc_binding_utility_write_probe");
+    if let Err(e) = std::fs::write(&probe, b"probe") {
+        return fail(describe_write_error(&e, &mappings_dir));
+    }
+    let _ = std::fs::remove_file(&probe);
+
+    WriteCheck {
+        writable: true,
+        reason: None,
+    }
+}
+
 #[tauri::command]
-fn save_bindings_to_install(
-    installation_path: String,
-    state: tauri::State<Mutex<AppState>>,
-) -> Result<(), String> {
-    use std::path::Path;
+fn check_install_writable(installation_path: String) -> WriteCheck {
+    probe_install_writable(std::path::Path::new(&installation_path))
+}
 
-    let app_state = state.lock().unwrap();
+// Name the profile for an install (the profile name, or the loaded file if it has none),
+// keeping the profileName attribute consistent with the file name
+// Returns (profile_name, file_name)
+fn install_profile_names(app_state: &AppState) -> Result<(String, String), String> {
+    use std::path::Path;
 
     // Get the current bindings
     let bindings = app_state
@@ -917,45 +3269,301 @@ fn save_bindings_to_install(
         .as_ref()
         .ok_or_else(|| "No keybindings loaded".to_string())?;
 
-    // Get the filename
-    let file_name = app_state
-        .current_file_name
-        .as_ref()
-        .ok_or_else(|| "No filename stored".to_string())?;
+    let profile_name = if bindings.profile_name.trim().is_empty() {
+        let current_file_name = app_state
+            .current_file_name
+            .as_ref()
+            .ok_or_else(|| "No filename stored".to_string())?;
+        Path::new(current_file_name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string()
+    } else {
+        bindings.profile_name.clone()
+    };
 
-    // Build the target path: INSTALL\user\client\0\controls\mappings
-    let target_dir = Path::new(&installation_path)
-        .join("user")
-        .join("client")
-        .join("0")
-        .join("controls")
-        .join("mappings");
+    let profile_name = canonical_profile_stem(&profile_name);
+    let file_name = canonical_profile_filename(&profile_name);
+    Ok((profile_name, file_name))
+}
 
-    // Create the directory structure if it doesn't exist
-    std::fs::create_dir_all(&target_dir)
-        .map_err(|e| format!("Failed to create directory structure: {}", e))?;
+// Bindings as they will be written to an install, renamed to the install profile name
+// Returns (profile_name, file_name, bindings)
+fn install_profile(app_state: &AppState) -> Result<(String, String, ActionMaps), String> {
+    let (profile_name, file_name) = install_profile_names(app_state)?;
 
-    // Full path to the target file
-    let target_file = target_dir.join(file_name);
+    let mut bindings = app_state
+        .current_bindings
+        .clone()
+        .ok_or_else(|| "No keybindings loaded".to_string())?;
+    bindings.profile_name = profile_name.clone();
 
-    // Get AllBinds for category mapping
-    let all_binds = app_state.all_binds.as_ref();
+    Ok((profile_name, file_name, bindings))
+}
+
+// Profile as it will be written to an install, the state is left untouched until it is
+// Returns (profile_name, file_name, xml)
+fn prepare_profile_for_install(app_state: &AppState) -> Result<(String, String, String), String> {
+    let (profile_name, file_name, bindings) = install_profile(app_state)?;
+
+    // Serialize to XML with category information from AllBinds
+    let xml_content = bindings.to_xml_with_categories(app_state.all_binds.as_ref(), true);
+
+    Ok((profile_name, file_name, xml_content))
+}
+
+// After a successful save the loaded profile takes the name it was saved under
+fn adopt_install_profile_name(app_state: &mut AppState, profile_name: String, file_name: String) {
+    if let Some(ref mut bindings) = app_state.current_bindings {
+        bindings.profile_name = profile_name;
+    }
+    app_state.current_file_name = Some(file_name);
+}
+
+// Copy the profile about to be overwritten to "<file>.bak", returning the backup path
+fn backup_existing_profile(
+    target_file: &std::path::Path,
+) -> Result<Option<std::path::PathBuf>, String> {
+    if !target_file.is_file() {
+        return Ok(None);
+    }
 
-    // Serialize to XML with category information
-    let xml_content = bindings.to_xml_with_categories(all_binds);
+    let mut backup = target_file.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = std::path::PathBuf::from(backup);
+    std::fs::copy(target_file, &backup)
+        .map_err(|e| format!("Failed to back up {}: {}", target_file.display(), e))?;
+    Ok(Some(backup))
+}
+
+// Where a profile was written, and the backup of the file it replaced
+struct WrittenProfile {
+    path: std::path::PathBuf,
+    backup: Option<std::path::PathBuf>,
+}
+
+// Write a profile into INSTALL\user\client\0\controls\mappings, backing up the file it replaces
+fn write_profile_to_install(
+    installation_path: &std::path::Path,
+    file_name: &str,
+    xml_content: &str,
+) -> Result<WrittenProfile, String> {
+    // Fail early with a readable reason rather than a raw OS error mid-write
+    // The probe also creates the mappings folder
+    let check = probe_install_writable(installation_path);
+    if !check.writable {
+        return Err(check
+            .reason
+            .unwrap_or_else(|| "Installation folder is not writable".to_string()));
+    }
 
-    // Write to the target location
+    let target_file = install_mappings_dir(installation_path).join(file_name);
+    let backup = backup_existing_profile(&target_file)?;
     std::fs::write(&target_file, xml_content)
         .map_err(|e| format!("Failed to write keybindings file: {}", e))?;
 
+    Ok(WrittenProfile {
+        path: target_file,
+        backup,
+    })
+}
+
+// Save the loaded profile to one install, renaming it only once the file is written
+fn save_profile_to_install(
+    app_state: &mut AppState,
+    installation_path: &std::path::Path,
+) -> Result<WrittenProfile, String> {
+    let (profile_name, file_name, xml_content) = prepare_profile_for_install(app_state)?;
+
+    let written = write_profile_to_install(installation_path, &file_name, &xml_content)?;
+    adopt_install_profile_name(app_state, profile_name, file_name);
+    Ok(written)
+}
+
+#[tauri::command]
+fn save_bindings_to_install(
+    installation_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<(), String> {
+    let mut app_state = state.lock().unwrap();
+    save_profile_to_install(&mut app_state, std::path::Path::new(&installation_path))?;
     Ok(())
 }
 
+// Struct for whether the loaded profile is saved in an installation
+#[derive(serde::Serialize, Debug, PartialEq)]
+struct SavedStatus {
+    present: bool,
+    up_to_date: bool,
+}
+
+// Compare the profile saved in an install with what saving now would write, under the
+// file name saving would use. Both sides are re-exported without the generated comment,
+// so only the bindings, header and device options count, not the save date or line endings
+fn profile_saved_status(
+    app_state: &AppState,
+    installation_path: &std::path::Path,
+) -> Result<SavedStatus, String> {
+    let (_, file_name, bindings) = install_profile(app_state)?;
+    let all_binds = app_state.all_binds.as_ref();
+
+    let saved_path = install_mappings_dir(installation_path).join(file_name);
+    if !saved_path.is_file() {
+        return Ok(SavedStatus {
+            present: false,
+            up_to_date: false,
+        });
+    }
+
+    let up_to_date = std::fs::read_to_string(&saved_path)
+        .ok()
+        .and_then(|xml| ActionMaps::from_xml(&xml).ok())
+        .is_some_and(|saved| {
+            saved.to_xml_with_categories(all_binds, false)
+                == bindings.to_xml_with_categories(all_binds, false)
+        });
+
+    Ok(SavedStatus {
+        present: true,
+        up_to_date,
+    })
+}
+
+// Saved/unsaved indicator for one installation: is the profile in its mappings folder under
+// the name saving would use, and does it match the current bindings
+#[tauri::command]
+fn is_profile_saved_to(
+    installation_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<SavedStatus, String> {
+    let app_state = state.lock().unwrap();
+    profile_saved_status(&app_state, std::path::Path::new(&installation_path))
+}
+
+// Struct for the outcome of saving to one installation
+#[derive(serde::Serialize, Debug)]
+struct SaveResult {
+    installation_name: String,
+    installation_path: String,
+    file_path: Option<String>,
+    backup_path: Option<String>, // Previous profile, when one was replaced
+    error: Option<String>,
+}
+
+// Write a profile to every installation found under base_path
+fn save_profile_to_installs(
+    base_path: &str,
+    file_name: &str,
+    xml_content: &str,
+) -> Result<Vec<SaveResult>, String> {
+    let installations = scan_sc_installations(base_path.to_string())?;
+
+    Ok(installations
+        .into_iter()
+        .map(|install| {
+            let written = write_profile_to_install(
+                std::path::Path::new(&install.path),
+                file_name,
+                xml_content,
+            );
+            let (file_path, backup_path, error) = match written {
+                Ok(written) => (
+                    Some(written.path.to_string_lossy().to_string()),
+                    written.backup.map(|p| p.to_string_lossy().to_string()),
+                    None,
+                ),
+                Err(e) => (None, None, Some(e)),
+            };
+            SaveResult {
+                installation_name: install.name,
+                installation_path: install.path,
+                file_path,
+                backup_path,
+                error,
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn save_bindings_to_all_installs(
+    base_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<Vec<SaveResult>, String> {
+    let (profile_name, file_name, xml_content) = {
+        let app_state = state.lock().unwrap();
+        prepare_profile_for_install(&app_state)?
+    };
+
+    let results = save_profile_to_installs(&base_path, &file_name, &xml_content)?;
+    if results.is_empty() {
+        return Err(format!(
+            "No Star Citizen installations found in {}",
+            base_path
+        ));
+    }
+    if results.iter().any(|r| r.error.is_none()) {
+        let mut app_state = state.lock().unwrap();
+        adopt_install_profile_name(&mut app_state, profile_name, file_name.clone());
+    }
+
+    for result in &results {
+        match &result.error {
+            None => info!("Saved {} to {}", file_name, result.installation_name),
+            Some(e) => error!("Failed to save to {}: {}", result.installation_name, e),
+        }
+    }
+    Ok(results)
+}
+
 #[tauri::command]
 fn write_binary_file(path: String, contents: Vec<u8>) -> Result<(), String> {
     std::fs::write(&path, contents).map_err(|e| format!("Failed to write file: {}", e))
 }
 
+#[tauri::command]
+fn begin_binary_write(
+    path: String,
+    writes: tauri::State<Mutex<BinaryWrites>>,
+) -> Result<u64, String> {
+    writes.lock().unwrap().begin(&path)
+}
+
+#[tauri::command]
+fn append_binary_chunk(
+    write_id: u64,
+    chunk: Vec<u8>,
+    writes: tauri::State<Mutex<BinaryWrites>>,
+) -> Result<(), String> {
+    writes.lock().unwrap().append(write_id, &chunk)
+}
+
+#[tauri::command]
+fn finish_binary_write(
+    write_id: u64,
+    writes: tauri::State<Mutex<BinaryWrites>>,
+) -> Result<(), String> {
+    writes.lock().unwrap().finish(write_id)
+}
+
+// Abandon a chunked write, or every open one when no id is given (e.g. after a page reload)
+// Returns the number of writes aborted
+#[tauri::command]
+fn abort_binary_write(write_id: Option<u64>, writes: tauri::State<Mutex<BinaryWrites>>) -> usize {
+    let mut writes = writes.lock().unwrap();
+
+    let ids: Vec<u64> = match write_id {
+        Some(id) => vec![id],
+        None => writes.open.keys().copied().collect(),
+    };
+    let aborted = ids.into_iter().filter(|id| writes.abort(*id)).count();
+    if aborted > 0 {
+        info!("Aborted {} chunked file writes", aborted);
+    }
+    aborted
+}
+
 #[tauri::command]
 fn log_error(message: String, stack: Option<String>) -> Result<(), String> {
     if let Some(stack_trace) = stack {
@@ -1053,19 +3661,108 @@ fn setup_logging(app_handle: &tauri::AppHandle) -> Result<(), Box<dyn std::error
     info!("Version: {}", env!("CARGO_PKG_VERSION"));
     info!("Log file: {:?}", log_file);
 
-    Ok(())
+    Ok(())
+}
+
+// Struct for unbind profile generation result
+#[derive(serde::Serialize)]
+struct UnbindProfileResult {
+    saved_locations: Vec<String>,
+    failed: Vec<FailedWrite>,
+}
+
+// Struct for an unbind profile location that could not be written
+#[derive(serde::Serialize, Debug)]
+struct FailedWrite {
+    path: String,
+    reason: String,
+}
+
+// Struct for unbind profile removal result
+#[derive(serde::Serialize)]
+struct RemoveUnbindResult {
+    removed_count: usize,
+}
+
+// Unbind profile file name written into each installation's mappings folder
+const UNBIND_PROFILE_FILE_NAME: &str = "UNBIND_ALL.xml";
+
+// Where the unbind profile goes: every installation found under base_path,
+// or the current directory when there are none
+fn unbind_target_paths(base_path: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    match scan_sc_installations(base_path.to_string()) {
+        Ok(installations) => {
+            info!("Found {} SC installations", installations.len());
+            for install in installations {
+                info!(
+                    "Processing installation: {} at {}",
+                    install.name, install.path
+                );
+                let file_path = install_mappings_dir(std::path::Path::new(&install.path))
+                    .join(UNBIND_PROFILE_FILE_NAME);
+                paths.push(file_path.to_string_lossy().to_string());
+            }
+        }
+        Err(e) => {
+            error!(
+                "Failed to scan SC installations from base path '{}': {}",
+                base_path, e
+            );
+        }
+    }
+
+    // If no installations found, save to current directory as fallback
+    if paths.is_empty() {
+        paths.push(UNBIND_PROFILE_FILE_NAME.to_string());
+    }
+
+    paths
+}
+
+// Struct for previewing an unbind profile without writing it
+#[derive(serde::Serialize)]
+struct UnbindProfilePreview {
+    xml: String,
+    target_paths: Vec<String>,
+}
+
+#[tauri::command]
+fn preview_unbind_profile(
+    devices: keybindings::DeviceSelection,
+    base_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<UnbindProfilePreview, String> {
+    let app_state = state
+        .lock()
+        .map_err(|e| format!("Failed to lock state: {}", e))?;
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or("AllBinds not loaded. Please load the keybindings first.")?;
+
+    Ok(UnbindProfilePreview {
+        xml: keybindings::generate_unbind_xml(all_binds, &devices)?,
+        target_paths: unbind_target_paths(&base_path),
+    })
 }
 
-// Struct for unbind profile generation result
-#[derive(serde::Serialize)]
-struct UnbindProfileResult {
-    saved_locations: Vec<String>,
-}
+// Count what an unbind profile would disable so the UI can confirm before writing it
+#[tauri::command]
+fn summarize_unbind_profile(
+    devices: keybindings::DeviceSelection,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<keybindings::UnbindSummary, String> {
+    let app_state = state
+        .lock()
+        .map_err(|e| format!("Failed to lock state: {}", e))?;
+    let all_binds = app_state
+        .all_binds
+        .as_ref()
+        .ok_or("AllBinds not loaded. Please load the keybindings first.")?;
 
-// Struct for unbind profile removal result
-#[derive(serde::Serialize)]
-struct RemoveUnbindResult {
-    removed_count: usize,
+    Ok(keybindings::summarize_unbind(all_binds, &devices))
 }
 
 #[tauri::command]
@@ -1097,60 +3794,71 @@ fn generate_unbind_profile(
     info!("Generated unbind XML, length: {} bytes", unbind_xml.len());
 
     // Try to save to SC installation directories
-    let mut saved_locations = Vec::new();
+    let mut result = write_unbind_profile(&unbind_target_paths(&base_path), &unbind_xml);
 
-    // Get SC installations
-    match scan_sc_installations(base_path.clone()) {
-        Ok(installations) => {
-            info!("Found {} SC installations", installations.len());
-            for install in installations {
-                info!(
-                    "Processing installation: {} at {}",
-                    install.name, install.path
-                );
-                let mappings_dir = format!("{}\\user\\client\\0\\controls\\mappings", install.path);
+    // If nothing could be written, save to current directory as fallback
+    if result.saved_locations.is_empty() {
+        fs::write(UNBIND_PROFILE_FILE_NAME, &unbind_xml)
+            .map_err(|e| format!("Failed to write unbind profile: {}", e))?;
+        result
+            .saved_locations
+            .push(UNBIND_PROFILE_FILE_NAME.to_string());
+        info!(
+            "Saved unbind profile to current directory: {}",
+            UNBIND_PROFILE_FILE_NAME
+        );
+    }
 
-                // Create directory if it doesn't exist
-                if let Err(e) = fs::create_dir_all(&mappings_dir) {
-                    error!(
-                        "Failed to create mappings directory {}: {}",
-                        mappings_dir, e
-                    );
-                    continue;
-                }
+    Ok(result)
+}
 
-                let file_path = format!("{}\\UNBIND_ALL.xml", mappings_dir);
-                info!("Attempting to write to: {}", file_path);
-                match fs::write(&file_path, &unbind_xml) {
-                    Ok(_) => {
-                        info!("Successfully saved unbind profile to: {}", file_path);
-                        saved_locations.push(file_path);
-                    }
-                    Err(e) => error!("Failed to write to {}: {}", file_path, e),
-                }
+// Write the unbind profile to each target path, collecting the ones that failed and why
+fn write_unbind_profile(target_paths: &[String], unbind_xml: &str) -> UnbindProfileResult {
+    use std::fs;
+
+    let mut saved_locations = Vec::new();
+    let mut failed = Vec::new();
+
+    for file_path in target_paths {
+        let file_path_buf = std::path::PathBuf::from(file_path);
+
+        // Create directory if it doesn't exist
+        if let Some(mappings_dir) = file_path_buf.parent().filter(|d| !d.as_os_str().is_empty()) {
+            if let Err(e) = fs::create_dir_all(mappings_dir) {
+                error!(
+                    "Failed to create mappings directory {}: {}",
+                    mappings_dir.display(),
+                    e
+                );
+                failed.push(FailedWrite {
+                    path: file_path.clone(),
+                    reason: describe_write_error(&e, mappings_dir),
+                });
+                continue;
             }
         }
-        Err(e) => {
-            error!(
-                "Failed to scan SC installations from base path '{}': {}",
-                base_path, e
-            );
+
+        info!("Attempting to write to: {}", file_path);
+        match fs::write(&file_path_buf, unbind_xml) {
+            Ok(_) => {
+                info!("Successfully saved unbind profile to: {}", file_path);
+                saved_locations.push(file_path.clone());
+            }
+            Err(e) => {
+                error!("Failed to write to {}: {}", file_path, e);
+                let folder = file_path_buf.parent().unwrap_or(&file_path_buf);
+                failed.push(FailedWrite {
+                    path: file_path.clone(),
+                    reason: describe_write_error(&e, folder),
+                });
+            }
         }
     }
 
-    // If no installations found, save to current directory as fallback
-    if saved_locations.is_empty() {
-        let fallback_path = "UNBIND_ALL.xml";
-        fs::write(fallback_path, &unbind_xml)
-            .map_err(|e| format!("Failed to write unbind profile: {}", e))?;
-        saved_locations.push(fallback_path.to_string());
-        info!(
-            "Saved unbind profile to current directory: {}",
-            fallback_path
-        );
+    UnbindProfileResult {
+        saved_locations,
+        failed,
     }
-
-    Ok(UnbindProfileResult { saved_locations })
 }
 
 #[tauri::command]
@@ -1162,7 +3870,7 @@ fn remove_unbind_profile() -> Result<RemoveUnbindResult, String> {
     let mut removed_count = 0;
 
     // Get base path for SC installations
-    let base_path = "C:\\Program Files\\Roberts Space Industries\\StarCitizen".to_string();
+    let base_path = DEFAULT_SC_BASE_PATH.to_string();
 
     // Get SC installations
     match scan_sc_installations(base_path) {
@@ -1204,13 +3912,15 @@ fn remove_unbind_profile() -> Result<RemoveUnbindResult, String> {
     Ok(RemoveUnbindResult { removed_count })
 }
 
-#[tauri::command]
-fn scan_character_files(directory_path: String) -> Result<Vec<CharacterFile>, String> {
+// List the files with the given extension in a directory, with size and modified time
+// A missing directory gives an empty list
+fn list_files_with_metadata(
+    dir_path: &std::path::Path,
+    extension: &str,
+) -> Result<Vec<CharacterFile>, String> {
     use std::fs;
     use std::time::UNIX_EPOCH;
 
-    let dir_path = std::path::Path::new(&directory_path);
-
     // Check if directory exists
     if !dir_path.exists() {
         // Return empty list instead of error if directory doesn't exist
@@ -1221,7 +3931,7 @@ fn scan_character_files(directory_path: String) -> Result<Vec<CharacterFile>, St
         return Err("Path is not a directory".to_string());
     }
 
-    let mut characters = Vec::new();
+    let mut files = Vec::new();
 
     // Read directory entries
     let entries = fs::read_dir(dir_path)
@@ -1231,43 +3941,333 @@ fn scan_character_files(directory_path: String) -> Result<Vec<CharacterFile>, St
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
         let path = entry.path();
 
-        // Only process .chf files
-        if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext == "chf" {
-                    if let Some(file_name) = path.file_name() {
-                        if let Some(name_str) = file_name.to_str() {
-                            // Get file metadata
-                            let metadata = fs::metadata(&path)
-                                .map_err(|e| format!("Failed to read metadata: {}", e))?;
-
-                            let size = metadata.len();
-                            let modified = metadata
-                                .modified()
-                                .map_err(|e| format!("Failed to get modified time: {}", e))?
-                                .duration_since(UNIX_EPOCH)
-                                .map_err(|e| format!("Time error: {}", e))?
-                                .as_secs();
-
-                            characters.push(CharacterFile {
-                                name: name_str.to_string(),
-                                path: path.to_string_lossy().to_string(),
-                                size,
-                                modified,
-                            });
-                        }
-                    }
-                }
-            }
+        let matches_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(extension));
+        if !path.is_file() || !matches_extension {
+            continue;
+        }
+
+        if let Some(name_str) = path.file_name().and_then(|n| n.to_str()) {
+            // Get file metadata
+            let metadata =
+                fs::metadata(&path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+
+            let size = metadata.len();
+            let modified = metadata
+                .modified()
+                .map_err(|e| format!("Failed to get modified time: {}", e))?
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| format!("Time error: {}", e))?
+                .as_secs();
+
+            files.push(CharacterFile {
+                name: name_str.to_string(),
+                path: path.to_string_lossy().to_string(),
+                size,
+                modified,
+            });
         }
     }
 
+    Ok(files)
+}
+
+#[tauri::command]
+fn scan_character_files(directory_path: String) -> Result<Vec<CharacterFile>, String> {
+    let mut characters = list_files_with_metadata(std::path::Path::new(&directory_path), "chf")?;
+
     // Sort by name
     characters.sort_by(|a, b| a.name.cmp(&b.name));
 
     Ok(characters)
 }
 
+// Mappings files in an install, newest first
+fn list_active_mappings(installation_path: &std::path::Path) -> Result<Vec<CharacterFile>, String> {
+    let mut mappings = list_files_with_metadata(&install_mappings_dir(installation_path), "xml")?;
+    mappings.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(mappings)
+}
+
+// List the profiles SC has in INSTALL\user\client\0\controls\mappings, most recently modified first
+#[tauri::command]
+fn find_active_mappings(installation_path: String) -> Result<Vec<CharacterFile>, String> {
+    list_active_mappings(std::path::Path::new(&installation_path))
+}
+
+#[tauri::command]
+fn import_active_mapping(
+    file_path: String,
+    state: tauri::State<Mutex<AppState>>,
+) -> Result<OrganizedKeybindings, String> {
+    load_keybindings(file_path, state)
+}
+
+// Struct for a profile name used by more than one file
+#[derive(serde::Serialize, Debug)]
+struct ProfileCollision {
+    profile_name: String,
+    files: Vec<ProfileLocation>,
+}
+
+// Struct for where a profile file lives: an installation name or "Library"
+#[derive(serde::Serialize, Debug)]
+struct ProfileLocation {
+    location: String,
+    path: String,
+}
+
+// Find profile names shared by several files across installations' mappings folders and the library
+// Names are compared case-insensitively; files without a profileName are skipped
+fn find_profile_name_collisions(
+    base_path: &str,
+    library_path: Option<&str>,
+) -> Result<Vec<ProfileCollision>, String> {
+    let mut folders: Vec<(String, std::path::PathBuf)> =
+        scan_sc_installations(base_path.to_string())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|install| {
+                let dir = install_mappings_dir(std::path::Path::new(&install.path));
+                (install.name, dir)
+            })
+            .collect();
+    if let Some(library) = library_path {
+        folders.push(("Library".to_string(), std::path::PathBuf::from(library)));
+    }
+
+    // Keyed by lowercase name, keeping the first spelling seen
+    let mut by_name: Vec<(String, ProfileCollision)> = Vec::new();
+
+    for (location, dir) in folders {
+        for file in list_files_with_metadata(&dir, "xml")? {
+            let Ok(xml) = std::fs::read_to_string(&file.path) else {
+                error!("Failed to read profile {}", file.path);
+                continue;
+            };
+            let Some(profile_name) = ActionMaps::read_profile_name(&xml) else {
+                continue;
+            };
+
+            let key = profile_name.trim().to_lowercase();
+            let entry = match by_name.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, collision)) => collision,
+                None => {
+                    by_name.push((
+                        key,
+                        ProfileCollision {
+                            profile_name,
+                            files: Vec::new(),
+                        },
+                    ));
+                    &mut by_name.last_mut().unwrap().1
+                }
+            };
+            entry.files.push(ProfileLocation {
+                location: location.clone(),
+                path: file.path,
+            });
+        }
+    }
+
+    let mut collisions: Vec<ProfileCollision> = by_name
+        .into_iter()
+        .map(|(_, collision)| collision)
+        .filter(|collision| collision.files.len() > 1)
+        .collect();
+    collisions.sort_by(|a, b| a.profile_name.cmp(&b.profile_name));
+    Ok(collisions)
+}
+
+#[tauri::command]
+fn scan_profile_name_collisions(
+    base_path: String,
+    library_path: Option<String>,
+) -> Result<Vec<ProfileCollision>, String> {
+    let collisions = find_profile_name_collisions(&base_path, library_path.as_deref())?;
+    info!("Found {} duplicated profile names", collisions.len());
+    Ok(collisions)
+}
+
+// Folder SC keeps saved characters in: INSTALL\user\client\0\customcharacters
+fn install_characters_dir(installation_path: &std::path::Path) -> std::path::PathBuf {
+    installation_path
+        .join("user")
+        .join("client")
+        .join("0")
+        .join("customcharacters")
+}
+
+// FNV-1a 64-bit hash of file contents as hex; stable across runs and platforms
+fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+// Struct for one copy of a character: an installation name or "Library", and the file
+#[derive(serde::Serialize, Debug)]
+struct CharacterLocation {
+    location: String,
+    name: String,
+    path: String,
+}
+
+// Struct for a unique character (by content) and every place a copy of it lives
+#[derive(serde::Serialize, Debug)]
+struct CharacterEntry {
+    hash: String,
+    size: u64,
+    locations: Vec<CharacterLocation>,
+}
+
+// Collect the .chf files in every installation's customcharacters folder and the library,
+// grouping copies with identical contents whatever their file names
+fn find_all_characters(
+    base_path: &str,
+    library_path: Option<&str>,
+) -> Result<Vec<CharacterEntry>, String> {
+    let mut folders: Vec<(String, std::path::PathBuf)> =
+        scan_sc_installations(base_path.to_string())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|install| {
+                let dir = install_characters_dir(std::path::Path::new(&install.path));
+                (install.name, dir)
+            })
+            .collect();
+    if let Some(library) = library_path {
+        folders.push(("Library".to_string(), std::path::PathBuf::from(library)));
+    }
+
+    let mut characters: Vec<CharacterEntry> = Vec::new();
+
+    for (location, dir) in folders {
+        for file in list_files_with_metadata(&dir, "chf")? {
+            let bytes = match std::fs::read(&file.path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    error!("Failed to read character {}: {}", file.path, e);
+                    continue;
+                }
+            };
+            let hash = content_hash(&bytes);
+
+            let entry = match characters.iter_mut().position(|c| c.hash == hash) {
+                Some(pos) => &mut characters[pos],
+                None => {
+                    characters.push(CharacterEntry {
+                        hash,
+                        size: file.size,
+                        locations: Vec::new(),
+                    });
+                    characters.last_mut().unwrap()
+                }
+            };
+            entry.locations.push(CharacterLocation {
+                location: location.clone(),
+                name: file.name,
+                path: file.path,
+            });
+        }
+    }
+
+    characters.sort_by(|a, b| a.locations[0].name.cmp(&b.locations[0].name));
+    Ok(characters)
+}
+
+#[tauri::command]
+fn scan_all_characters(
+    base_path: String,
+    library_path: Option<String>,
+) -> Result<Vec<CharacterEntry>, String> {
+    let characters = find_all_characters(&base_path, library_path.as_deref())?;
+    info!("Found {} unique characters", characters.len());
+    Ok(characters)
+}
+
+// Expected first two bytes of a saved character (.chf) file. The format has no published
+// spec to cite and this value hasn't been checked against files from current game builds,
+// so a different header is only reported as a warning and never blocks a deploy on its own
+const CHF_MAGIC: [u8; 2] = [0x42, 0x42];
+// .chf files are a few KB: much smaller is truncated, much larger isn't a character file
+const CHF_MIN_SIZE: u64 = 64;
+const CHF_MAX_SIZE: u64 = 1024 * 1024;
+
+// Struct for the result of checking a character file before deploying it
+#[derive(serde::Serialize, Debug)]
+struct CharacterValidation {
+    valid: bool,
+    size: u64,
+    reason: Option<String>,
+    warning: Option<String>,
+}
+
+// Check a .chf file exists and has a plausible size, warning when the header isn't the usual one
+#[tauri::command]
+fn validate_character_file(path: String) -> CharacterValidation {
+    use std::io::Read;
+
+    let invalid = |size: u64, reason: String| CharacterValidation {
+        valid: false,
+        size,
+        reason: Some(reason),
+        warning: None,
+    };
+
+    let size = match std::fs::metadata(&path) {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        Ok(_) => return invalid(0, format!("{} is not a file", path)),
+        Err(e) => return invalid(0, format!("Cannot read {}: {}", path, e)),
+    };
+
+    if size == 0 {
+        return invalid(size, "File is empty".to_string());
+    }
+    if size < CHF_MIN_SIZE {
+        return invalid(
+            size,
+            format!("File is too small ({} bytes), it may be truncated", size),
+        );
+    }
+    if size > CHF_MAX_SIZE {
+        return invalid(
+            size,
+            format!("File is too large ({} bytes) for a character", size),
+        );
+    }
+
+    let mut header = [0u8; 2];
+    let read = std::fs::File::open(&path).and_then(|mut file| file.read_exact(&mut header));
+    if let Err(e) = read {
+        return invalid(size, format!("Cannot read {}: {}", path, e));
+    }
+    let warning = (header != CHF_MAGIC).then(|| {
+        warn!(
+            "Character file {} has an unexpected header {:02X?}",
+            path, header
+        );
+        format!(
+            "Unexpected header {:02X?}, the file may not be a character",
+            header
+        )
+    });
+
+    CharacterValidation {
+        valid: true,
+        size,
+        reason: None,
+        warning,
+    }
+}
+
 #[tauri::command]
 fn deploy_character_to_installation(
     character_name: String,
@@ -1278,11 +4278,17 @@ fn deploy_character_to_installation(
     use std::path::Path;
 
     let source_path = Path::new(&library_path).join(&character_name);
-    let target_dir = Path::new(&installation_path)
-        .join("user")
-        .join("client")
-        .join("0")
-        .join("customcharacters");
+    let target_dir = install_characters_dir(Path::new(&installation_path));
+
+    // Don't replace a working character with a corrupt one
+    let validation = validate_character_file(source_path.to_string_lossy().to_string());
+    if !validation.valid {
+        return Err(format!(
+            "Character {} can't be deployed: {}",
+            character_name,
+            validation.reason.unwrap_or_default()
+        ));
+    }
 
     // Create target directory if it doesn't exist
     fs::create_dir_all(&target_dir)
@@ -1313,12 +4319,7 @@ fn import_character_to_library(
     use std::fs;
     use std::path::Path;
 
-    let source_path = Path::new(&installation_path)
-        .join("user")
-        .join("client")
-        .join("0")
-        .join("customcharacters")
-        .join(&character_name);
+    let source_path = install_characters_dir(Path::new(&installation_path)).join(&character_name);
 
     let target_dir = Path::new(&library_path);
 
@@ -1363,64 +4364,63 @@ fn parse_hid_report(report: Vec<u8>) -> Result<hid_reader::HidAxisReport, String
     hid_reader::parse_hid_axes(&report, &empty_names)
 }
 
+#[tauri::command]
+fn get_hid_button_layout(
+    device_path: String,
+) -> Result<Option<hid_reader::HidButtonLayout>, String> {
+    hid_reader::get_button_layout_from_descriptor(&device_path)
+}
+
+#[tauri::command]
+fn parse_hid_report_full(
+    report: Vec<u8>,
+    button_layout: Option<hid_reader::HidButtonLayout>,
+) -> Result<hid_reader::HidFullReport, String> {
+    // Like parse_hid_report, the descriptor-derived button layout is fetched once
+    // via get_hid_button_layout and passed in with each report
+    let empty_names = std::collections::HashMap::new();
+    let axes = hid_reader::parse_hid_axes(&report, &empty_names)?;
+    let buttons = button_layout
+        .map(|layout| hid_reader::parse_hid_buttons(&report, &layout))
+        .unwrap_or_default();
+
+    Ok(hid_reader::HidFullReport { axes, buttons })
+}
+
 #[tauri::command]
 fn get_hid_axis_names(device_path: String) -> Result<std::collections::HashMap<u32, String>, String> {
     hid_reader::get_axis_names_from_descriptor(&device_path)
 }
 
-fn find_matching_hid_device(device_name: &str, hid_devices: &[hid_reader::HidDeviceListItem]) -> Option<hid_reader::HidDeviceListItem> {
-    hid_devices.iter().find(|dev| {
-        let product = dev.product.as_deref().unwrap_or("").to_lowercase();
-        let manufacturer = dev.manufacturer.as_deref().unwrap_or("").to_lowercase();
-        let combined = format!("{} {}", manufacturer, product).trim().to_string();
-        let search_name = device_name.to_lowercase();
-        
-        // Clean search name: remove (...) at the end which might be added by Gilrs/OS
-        // e.g. "VKB Gladiator NXT (Left)" -> "vkb gladiator nxt"
-        let clean_search_name = if let Some(idx) = search_name.find('(') {
-            search_name[..idx].trim().to_string()
-        } else {
-            search_name.clone()
-        };
-        
-        // 1. Product contains search name OR Search name contains product
-        if !product.is_empty() && (product.contains(&search_name) || search_name.contains(&product)) {
-            return true;
-        }
-        
-        // 2. Combined (Manuf + Prod) contains search name OR Search name contains Combined
-        if !combined.is_empty() && (combined.contains(&search_name) || search_name.contains(&combined)) {
-            return true;
-        }
-
-        // 3. Try with cleaned search name (removed parentheses)
-        if !clean_search_name.is_empty() {
-            if !product.is_empty() && (product.contains(&clean_search_name) || clean_search_name.contains(&product)) {
-                return true;
-            }
-            if !combined.is_empty() && (combined.contains(&clean_search_name) || clean_search_name.contains(&combined)) {
-                return true;
-            }
-        }
+// Minimum score for a HID device to be taken as the device behind a gilrs/OS name
+const MIN_HID_MATCH_SCORE: f32 = 0.5;
+
+// Best-scoring HID device for a device name, with its score in 0.0..=1.0
+// Names are compared against both the product string and "manufacturer product", since OS
+// names sometimes include the vendor and sometimes don't; ties keep the first device listed
+fn match_hid_device_scored(
+    device_name: &str,
+    hid_devices: &[hid_reader::HidDeviceListItem],
+) -> Option<(hid_reader::HidDeviceListItem, f32)> {
+    hid_devices
+        .iter()
+        .map(|dev| {
+            let product = dev.product.as_deref().unwrap_or("");
+            let combined = format!("{} {}", dev.manufacturer.as_deref().unwrap_or(""), product);
+            let score = device_matching::name_similarity(device_name, product)
+                .max(device_matching::name_similarity(device_name, &combined));
+            (dev, score)
+        })
+        .filter(|(_, score)| *score >= MIN_HID_MATCH_SCORE)
+        .min_by(|a, b| b.1.total_cmp(&a.1))
+        .map(|(dev, score)| (dev.clone(), score))
+}
 
-        // 4. Token based matching (fuzzy)
-        // Split cleaned search name into tokens and check if they exist in the product/combined name
-        let search_tokens: Vec<&str> = clean_search_name.split_whitespace().collect();
-        if search_tokens.len() >= 2 {
-            let matches = search_tokens.iter().filter(|&t| {
-                // Skip very short words
-                if t.len() < 2 { return false; }
-                combined.contains(t)
-            }).count();
-            
-            // If most tokens match, assume it's the same device
-            if matches >= search_tokens.len() - 1 {
-                return true;
-            }
-        }
-        
-        false
-    }).cloned()
+fn find_matching_hid_device(
+    device_name: &str,
+    hid_devices: &[hid_reader::HidDeviceListItem],
+) -> Option<hid_reader::HidDeviceListItem> {
+    match_hid_device_scored(device_name, hid_devices).map(|(device, _)| device)
 }
 
 #[tauri::command]
@@ -1435,6 +4435,169 @@ fn get_hid_device_path(device_name: String) -> Result<Option<String>, String> {
     }
 }
 
+// The user's own additions to the device database; kept in app data so updates don't overwrite it
+fn user_device_database_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("user-device-database.json"))
+}
+
+// App-wide preferences persisted across runs; unknown or missing fields fall back to defaults
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+struct AppSettings {
+    #[serde(default)]
+    device_aliases: HashMap<String, String>, // Device UUID -> name the user gave it
+}
+
+fn settings_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("settings.json"))
+}
+
+// Missing settings file means defaults
+fn read_settings(path: &std::path::Path) -> Result<AppSettings, String> {
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid settings file: {}", e))
+}
+
+fn write_settings(path: &std::path::Path, settings: &AppSettings) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+// Set or (with a blank alias) remove the alias of a device in the settings file
+fn store_device_alias(
+    path: &std::path::Path,
+    device_uuid: &str,
+    alias: &str,
+) -> Result<(), String> {
+    let mut settings = read_settings(path)?;
+    let alias = alias.trim();
+    if alias.is_empty() {
+        settings.device_aliases.remove(device_uuid);
+    } else {
+        settings
+            .device_aliases
+            .insert(device_uuid.to_string(), alias.to_string());
+    }
+    write_settings(path, &settings)
+}
+
+// Aliases are only display metadata, so a broken settings file shouldn't stop device listing
+fn stored_device_aliases(app_handle: &tauri::AppHandle) -> HashMap<String, String> {
+    settings_path(app_handle)
+        .and_then(|path| read_settings(&path))
+        .map(|settings| settings.device_aliases)
+        .unwrap_or_else(|e| {
+            error!("Failed to load device aliases: {}", e);
+            HashMap::new()
+        })
+}
+
+fn apply_device_alias(device: &mut directinput::DeviceInfo, aliases: &HashMap<String, String>) {
+    device.alias = aliases.get(&device.uuid).cloned();
+}
+
+// Give a device a friendly name shown instead of the OS name; a blank alias removes it
+#[tauri::command]
+fn set_device_alias(
+    device_uuid: String,
+    alias: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let path = settings_path(&app_handle)?;
+    store_device_alias(&path, &device_uuid, &alias)?;
+    info!("Set alias of device {} to '{}'", device_uuid, alias.trim());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_device_aliases(app_handle: tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    let path = settings_path(&app_handle)?;
+    Ok(read_settings(&path)?.device_aliases)
+}
+
+#[tauri::command]
+fn export_unknown_device_entry(
+    device_uuid: String,
+) -> Result<device_database::DeviceDbEntry, String> {
+    let names = directinput::reported_device_names(&device_uuid)?
+        .ok_or_else(|| format!("Device {} is not connected", device_uuid))?;
+    let (Some(vendor_id), Some(product_id)) = (names.vendor_id, names.product_id) else {
+        return Err(format!(
+            "'{}' does not report a VID/PID, so it can't be added to the device database",
+            names.os_name
+        ));
+    };
+
+    // HID strings are usually the most descriptive names a device has
+    let hid_device = match hid_reader::list_hid_game_controllers() {
+        Ok(devices) => devices
+            .into_iter()
+            .find(|d| d.vendor_id == vendor_id && d.product_id == product_id),
+        Err(e) => {
+            error!("Failed to list HID devices: {}", e);
+            None
+        }
+    };
+    let hid_manufacturer = hid_device.as_ref().and_then(|d| d.manufacturer.clone());
+    let hid_product = hid_device.and_then(|d| d.product);
+
+    Ok(device_database::DeviceDbEntry {
+        entry: device_database::DeviceEntry {
+            vendor_id: format!("0x{:04x}", vendor_id),
+            product_id: format!("0x{:04x}", product_id),
+            name: device_database::suggested_device_name(
+                hid_manufacturer.as_deref(),
+                hid_product.as_deref(),
+                &names.os_name,
+            ),
+            device_type: names.device_type,
+            axis_profile: "default".to_string(),
+        },
+        os_name: Some(names.os_name),
+        gilrs_name: Some(names.gilrs_name),
+        hid_manufacturer,
+        hid_product,
+    })
+}
+
+#[tauri::command]
+fn save_user_device_entry(
+    entry: device_database::DeviceDbEntry,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let path = user_device_database_path(&app_handle)?;
+    device_database::append_user_entry(&path, &entry)?;
+    info!(
+        "Added {} (VID={}, PID={}) to {:?}",
+        entry.entry.name, entry.entry.vendor_id, entry.entry.product_id, path
+    );
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Check a device database file (the bundled one or a contributor's edit) and list every
+// problem with its line or device entry, so a bad entry doesn't just disable the database
+#[tauri::command]
+fn validate_device_database(path: String) -> Vec<device_database::DbValidationError> {
+    device_database::validate_device_database(&path)
+}
+
 #[tauri::command]
 fn get_axis_names_for_device(device_name: String) -> Result<std::collections::HashMap<u32, String>, String> {
     // Try to find a matching HID device by name
@@ -1489,12 +4652,8 @@ fn delete_character_from_installation(
 
     // Build path to character file in installation
     // Path format: {install}\user\client\0\customcharacters\{character_name}
-    let char_file_path = Path::new(&installation_path)
-        .join("user")
-        .join("client")
-        .join("0")
-        .join("customcharacters")
-        .join(&character_name);
+    let char_file_path =
+        install_characters_dir(Path::new(&installation_path)).join(&character_name);
 
     // Delete the file
     fs::remove_file(&char_file_path).map_err(|e| format!("Failed to delete character file: {}", e))?;
@@ -1510,43 +4669,134 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(Mutex::new(AppState::new()))
+        .manage(Mutex::new(BinaryWrites::default()))
         .invoke_handler(tauri::generate_handler![
             get_app_version,
             greet,
             detect_joysticks,
             get_connected_devices,
+            poll_active_modifiers,
+            get_connected_devices_detailed,
+            set_device_alias,
+            get_device_aliases,
+            suggest_device_assignment,
+            suggest_full_device_assignment,
+            build_device_options,
+            ensure_device_options_complete,
+            autofill_device_options,
+            get_device_product_string,
             get_device_axis_mapping,
             detect_axis_movement,
             get_axis_profiles,
             wait_for_input_binding,
+            start_recording_session,
+            stop_recording_session,
             wait_for_multiple_inputs,
+            rumble_device,
             wait_for_inputs_with_events,
             load_keybindings,
+            save_binary_profile,
+            load_binary_profile,
+            load_keybindings_additive,
+            import_split_mappings,
+            load_bindings_bundle,
             update_binding,
+            update_bindings_batch,
+            format_input_display,
+            get_category_tree,
+            find_multidevice_ambiguities,
+            diff_allbinds,
+            usages_of_button,
+            suggest_free_controls,
+            max_indices_for_device,
+            list_activation_modes,
+            key_label_to_keyname,
             reset_binding,
+            remap_keyboard_layout,
+            convert_device_type,
+            apply_device_assignment,
             get_current_bindings,
+            undo_last_edit,
+            redo_last_edit,
             export_keybindings,
+            export_keybindings_filtered,
+            export_category,
+            split_profile_by_device,
+            extract_single_binding_profile,
+            set_action_note,
+            get_action_notes,
+            export_bindings_table,
+            export_analysis_csv,
             save_template,
             load_template,
+            save_template_v2,
+            apply_template_v2,
+            import_device_options_from,
+            materialize_defaults,
             load_all_binds,
+            load_localization,
+            load_all_binds_with_progress,
             get_merged_bindings,
+            get_merged_bindings_filtered,
+            build_input_index,
+            get_effective_binding,
+            binding_coverage,
+            binding_statistics,
+            find_intra_action_duplicates,
+            normalize_cleared_bindings,
+            find_overbound_actions,
+            list_fully_unbound_actions,
+            reformat_profile,
+            find_empty_containers,
+            find_modifier_only_bindings,
+            find_implausible_instances,
+            find_layout_sensitive_bindings,
+            find_export_discrepancies,
+            prune_empty_containers,
+            find_noncontributing_actionmaps,
+            remove_noncontributing_actionmaps,
+            check_version_compatibility,
+            find_deprecated_tokens,
+            apply_token_replacements,
+            find_out_of_range_bindings,
+            toggle_axis_inversion,
+            find_same_input_different_mode,
+            set_primary_binding,
+            migrate_actionmap_name,
+            apply_known_migrations,
             get_user_customizations,
             restore_user_customizations,
             find_conflicting_bindings,
             clear_specific_binding,
             clear_custom_bindings,
+            purge_cleared_bindings,
             scan_sc_installations,
+            autodetect_sc_installations,
             get_current_file_name,
             save_bindings_to_install,
+            is_profile_saved_to,
+            save_bindings_to_all_installs,
+            check_install_writable,
             write_binary_file,
+            begin_binary_write,
+            append_binary_chunk,
+            finish_binary_write,
+            abort_binary_write,
             log_error,
             log_info,
             get_log_file_path,
             get_resource_dir,
             open_url,
             generate_unbind_profile,
+            preview_unbind_profile,
+            summarize_unbind_profile,
             remove_unbind_profile,
             scan_character_files,
+            find_active_mappings,
+            import_active_mapping,
+            scan_profile_name_collisions,
+            validate_character_file,
+            scan_all_characters,
             deploy_character_to_installation,
             import_character_to_library,
             delete_character_from_library,
@@ -1554,8 +4804,13 @@ pub fn run() {
             list_hid_devices,
             read_hid_device_report,
             parse_hid_report,
+            parse_hid_report_full,
+            get_hid_button_layout,
             get_hid_axis_names,
             get_axis_names_for_device,
+            export_unknown_device_entry,
+            save_user_device_entry,
+            validate_device_database,
             get_hid_device_path
         ])
         .setup(|app| {
@@ -1592,9 +4847,20 @@ pub fn run() {
 
             eprintln!("Attempting to load device database from: {:?}", db_path);
             eprintln!("Database exists: {}", db_path.exists());
-            
-            if let Err(e) = device_database::DeviceDatabase::init(&db_path) {
+
+            // Without an app data dir only the built-in database is loaded
+            let user_db_path = user_device_database_path(app.handle()).unwrap_or_default();
+
+            if let Err(e) = device_database::DeviceDatabase::init(&db_path, &user_db_path) {
                 eprintln!("Warning: Failed to initialize device database: {}", e);
+                for path in [&db_path, &user_db_path] {
+                    if !path.exists() {
+                        continue;
+                    }
+                    for error in device_database::validate_device_database(path) {
+                        eprintln!("  {}: {}", path.display(), error);
+                    }
+                }
                 eprintln!("Device lookup will fall back to OS device names");
                 // Don't fail startup if database fails to load
             } else {
@@ -1612,3 +4878,1344 @@ pub fn run() {
             }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("Fire Group 1"), "Fire Group 1");
+        assert_eq!(csv_escape("Lights, Camera"), "\"Lights, Camera\"");
+        assert_eq!(csv_escape("Say \"Hi\""), "\"Say \"\"Hi\"\"\"");
+    }
+
+    #[test]
+    fn test_parse_launcher_library_folder() {
+        let config = r#"{
+            "version": 2,
+            "library": {
+                "available": [],
+                "settings": [
+                    { "id": "SC", "libraryFolder": "D:\\Games\\Roberts Space Industries" }
+                ]
+            }
+        }"#;
+        assert_eq!(
+            parse_launcher_library_folder(config),
+            Some("D:\\Games\\Roberts Space Industries".to_string())
+        );
+
+        assert_eq!(parse_launcher_library_folder(r#"{ "library": {} }"#), None);
+        assert_eq!(parse_launcher_library_folder("not json"), None);
+    }
+
+    #[test]
+    fn test_all_binds_reload_guard() {
+        let path = std::env::temp_dir().join(format!("allbinds_guard_{}.xml", std::process::id()));
+        std::fs::write(&path, "<ActionMaps></ActionMaps>").unwrap();
+
+        let first = file_source_meta(&path).unwrap();
+        assert!(should_reload_all_binds(false, None, first, false));
+
+        // Unchanged file: skip unless forced or nothing is loaded
+        let second = file_source_meta(&path).unwrap();
+        assert!(!should_reload_all_binds(true, Some(first), second, false));
+        assert!(should_reload_all_binds(true, Some(first), second, true));
+        assert!(should_reload_all_binds(false, Some(first), second, false));
+
+        // Same size rewritten within the same second: only the sub-second mtime differs
+        let modified = first.modified.unwrap();
+        let same_second = FileStamp {
+            len: first.len,
+            modified: Some(modified + std::time::Duration::from_millis(1)),
+        };
+        assert!(should_reload_all_binds(
+            true,
+            Some(first),
+            same_second,
+            false
+        ));
+
+        // Touched file with new content and mtime: reload
+        std::fs::write(&path, "<ActionMaps><actionmap name=\"a\"/></ActionMaps>").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified + std::time::Duration::from_secs(10))
+            .unwrap();
+        let touched = file_source_meta(&path).unwrap();
+        assert!(should_reload_all_binds(true, Some(first), touched, false));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_all_binds_reload_stamp() {
+        let path = std::env::temp_dir().join(format!("allbinds_stamp_{}.xml", std::process::id()));
+        std::fs::write(&path, "<ActionMaps></ActionMaps>").unwrap();
+        let state = Mutex::new(AppState::new());
+
+        // Nothing loaded yet: load, and store the stamp like the commands do
+        let stamp = all_binds_reload_stamp(&state, &path, false)
+            .unwrap()
+            .unwrap();
+        store_all_binds(
+            &mut state.lock().unwrap(),
+            read_all_binds_file(&path).unwrap(),
+            stamp,
+        );
+
+        assert_eq!(all_binds_reload_stamp(&state, &path, false).unwrap(), None);
+        assert_eq!(
+            all_binds_reload_stamp(&state, &path, true).unwrap(),
+            Some(stamp)
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_all_binds_off_thread() {
+        let path = std::env::temp_dir().join(format!("allbinds_thread_{}.xml", std::process::id()));
+        std::fs::write(
+            &path,
+            "<ActionMaps>\n <actionmap name=\"seat_general\" version=\"1\" UILabel=\"Seats\" UICategory=\"Vehicle\">\n  <action name=\"v_eject\" keyboard=\"ralt+y\"/>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+
+        // Parsing needs no access to app state, even while another caller holds the lock
+        let state = Mutex::new(AppState::new());
+        let guard = state.lock().unwrap();
+        let parse_path = path.clone();
+        let all_binds = std::thread::spawn(move || read_all_binds_file(&parse_path))
+            .join()
+            .unwrap()
+            .unwrap();
+        drop(guard);
+
+        assert_eq!(all_binds.action_maps.len(), 1);
+        assert!(read_all_binds_file(&path.with_extension("missing")).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_build_device_suggestions() {
+        let connected = vec![
+            directinput::DeviceIdentity {
+                uuid: "uuid-vkb".to_string(),
+                name: "VKB-Sim Gladiator NXT R".to_string(),
+                vendor_id: None,
+                product_id: None,
+                sc_instance: 1,
+            },
+            directinput::DeviceIdentity {
+                uuid: "uuid-tm".to_string(),
+                name: "Thrustmaster T.16000M".to_string(),
+                vendor_id: None,
+                product_id: None,
+                sc_instance: 2,
+            },
+        ];
+        let declared = vec![
+            " T.16000M  {B10A044F-0000-0000-0000-504944564944}".to_string(),
+            " Saitek X56 Throttle  {A2210738-0000-0000-0000-504944564944}".to_string(),
+        ];
+
+        let suggestions = build_device_suggestions(&declared, &connected);
+        assert_eq!(suggestions[0].instance, 1);
+        assert_eq!(suggestions[0].device_uuid.as_deref(), Some("uuid-tm"));
+        assert_eq!(suggestions[0].current_instance, Some(2));
+        assert!(suggestions[0].confidence > 0.7);
+        assert_eq!(suggestions[1].device_uuid, None);
+    }
+
+    #[test]
+    fn test_build_full_device_assignment() {
+        let identity =
+            |uuid: &str, name: &str, ids: Option<(u16, u16)>| directinput::DeviceIdentity {
+                uuid: uuid.to_string(),
+                name: name.to_string(),
+                vendor_id: ids.map(|(vid, _)| vid),
+                product_id: ids.map(|(_, pid)| pid),
+                sc_instance: 1,
+            };
+        let connected = vec![
+            identity(
+                "uuid-vkb",
+                "VKB-Sim Gladiator NXT R",
+                Some((0x231D, 0x0200)),
+            ),
+            identity(
+                "uuid-tm-left",
+                "Thrustmaster T.16000M",
+                Some((0x044F, 0xB10A)),
+            ),
+            identity(
+                "uuid-tm-right",
+                "Thrustmaster T.16000M",
+                Some((0x044F, 0xB10A)),
+            ),
+        ];
+        // A dual T.16000M setup: both instances declare the same product
+        let declared = vec![
+            " T.16000M  {B10A044F-0000-0000-0000-504944564944}".to_string(),
+            " T.16000M  {B10A044F-0000-0000-0000-504944564944}".to_string(),
+        ];
+
+        let assignments = build_full_device_assignment(&declared, &connected);
+        assert_eq!(assignments.len(), 2);
+        assert_eq!(assignments[0].instance, 1);
+        assert_eq!(
+            assignments[0].suggested_device_uuid.as_deref(),
+            Some("uuid-tm-left")
+        );
+        assert_eq!(assignments[1].instance, 2);
+        assert_eq!(
+            assignments[1].suggested_device_uuid.as_deref(),
+            Some("uuid-tm-right")
+        );
+        assert!(assignments.iter().all(|a| a.confidence == 1.0));
+
+        // Only one device fits: the second instance gets nothing rather than a reused device
+        let assignments = build_full_device_assignment(&declared, &connected[..2]);
+        assert_eq!(
+            assignments[0].suggested_device_uuid.as_deref(),
+            Some("uuid-tm-left")
+        );
+        assert_eq!(assignments[1].suggested_device_uuid, None);
+        assert_eq!(assignments[1].confidence, 0.0);
+    }
+
+    #[test]
+    fn test_notes_sidecar_round_trip() {
+        let dir = std::env::temp_dir().join(format!("sc_notes_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let xml_path = dir.join("layout_mining.xml");
+
+        let mut notes = HashMap::new();
+        notes.insert(
+            (
+                "spaceship_mining".to_string(),
+                "v_toggle_mining_mode".to_string(),
+            ),
+            "this is for mining mode, \"hold\" for laser".to_string(),
+        );
+
+        write_notes_sidecar(&xml_path, &notes).unwrap();
+        assert!(dir.join("layout_mining.notes.json").exists());
+
+        let loaded = read_notes_sidecar(&xml_path).unwrap();
+        assert_eq!(loaded, notes);
+
+        // No sidecar: no notes
+        assert!(read_notes_sidecar(&dir.join("other.xml"))
+            .unwrap()
+            .is_empty());
+
+        // All notes deleted: the stale sidecar goes away instead of restoring them
+        write_notes_sidecar(&xml_path, &HashMap::new()).unwrap();
+        assert!(!dir.join("layout_mining.notes.json").exists());
+        write_notes_sidecar(&xml_path, &HashMap::new()).unwrap();
+
+        // A corrupt sidecar is an error to read, but doesn't stop a profile load
+        std::fs::write(dir.join("layout_mining.notes.json"), "{ not notes").unwrap();
+        assert!(read_notes_sidecar(&xml_path).is_err());
+        assert!(load_notes_or_empty(&xml_path).is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_check_install_writable() {
+        let dir = std::env::temp_dir().join(format!("sc_write_check_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let check = probe_install_writable(&dir);
+        assert!(check.writable, "{:?}", check.reason);
+        assert!(install_mappings_dir(&dir).is_dir());
+        // Probe file is cleaned up
+        assert_eq!(
+            std::fs::read_dir(install_mappings_dir(&dir))
+                .unwrap()
+                .count(),
+            0
+        );
+
+        let missing = probe_install_writable(&dir.join("does_not_exist"));
+        assert!(!missing.writable);
+        assert!(missing.reason.unwrap().contains("not found"));
+
+        // A file where the "user" folder should be: the mappings folder can never be
+        // created, whatever the permissions of the account running the test
+        let blocked = dir.join("blocked");
+        std::fs::create_dir_all(&blocked).unwrap();
+        std::fs::write(blocked.join("user"), b"").unwrap();
+
+        let check = probe_install_writable(&blocked);
+        assert!(!check.writable);
+        assert!(check.reason.unwrap().contains("Cannot write to"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_check_install_read_only() {
+        let dir = std::env::temp_dir().join(format!("sc_read_only_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut perms = std::fs::metadata(&dir).unwrap().permissions();
+        perms.set_readonly(true);
+        std::fs::set_permissions(&dir, perms.clone()).unwrap();
+
+        // Elevated accounts (root, administrators) and some filesystems ignore the flag
+        let ignored = std::fs::create_dir(dir.join("probe")).is_ok();
+        let check = probe_install_writable(&dir);
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        std::fs::set_permissions(&dir, perms).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        if ignored {
+            eprintln!("skipping test_check_install_read_only: read-only flag not enforced here");
+            return;
+        }
+        assert!(!check.writable);
+        let reason = check.reason.unwrap();
+        assert!(
+            reason.contains("read-only") || reason.contains("Permission denied"),
+            "{}",
+            reason
+        );
+    }
+
+    #[test]
+    fn test_unbind_target_paths_preview() {
+        let base = std::env::temp_dir().join(format!("sc_unbind_preview_{}", std::process::id()));
+        for folder in ["LIVE", "PTU"] {
+            std::fs::create_dir_all(base.join(folder)).unwrap();
+            std::fs::write(base.join(folder).join("data.p4k"), b"").unwrap();
+        }
+        // Not an installation: no data.p4k
+        std::fs::create_dir_all(base.join("EPTU")).unwrap();
+
+        let paths = unbind_target_paths(&base.to_string_lossy());
+        let expected: Vec<String> = ["LIVE", "PTU"]
+            .iter()
+            .map(|folder| {
+                install_mappings_dir(&base.join(folder))
+                    .join(UNBIND_PROFILE_FILE_NAME)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(paths, expected);
+
+        // Nothing was created
+        assert!(!base.join("LIVE").join("user").exists());
+        assert!(!base.join("PTU").join("user").exists());
+
+        // No installations: falls back to the current directory
+        assert_eq!(
+            unbind_target_paths(&base.join("missing").to_string_lossy()),
+            vec![UNBIND_PROFILE_FILE_NAME.to_string()]
+        );
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_canonical_profile_filename() {
+        // Prefix rule
+        assert_eq!(canonical_profile_filename("mining"), "layout_mining.xml");
+        assert_eq!(
+            canonical_profile_filename("layout_mining"),
+            "layout_mining.xml"
+        );
+        assert_eq!(
+            canonical_profile_filename("Layout_Mining.xml"),
+            "Layout_Mining.xml"
+        );
+
+        // Sanitization
+        assert_eq!(
+            canonical_profile_filename("My Profile: v2/HOSAS?"),
+            "layout_My_Profile__v2_HOSAS_.xml"
+        );
+        assert_eq!(canonical_profile_filename("pilot..."), "layout_pilot.xml");
+        assert_eq!(canonical_profile_filename("   "), "layout_Profile.xml");
+
+        // Stem matches the file name so profileName and the file agree
+        assert_eq!(
+            format!("{}.xml", canonical_profile_stem("My Profile")),
+            canonical_profile_filename("My Profile")
+        );
+    }
+
+    #[test]
+    fn test_save_profile_to_all_installs() {
+        let base = std::env::temp_dir().join(format!("sc_all_installs_{}", std::process::id()));
+        for install in ["LIVE", "PTU"] {
+            std::fs::create_dir_all(base.join(install)).unwrap();
+            std::fs::write(base.join(install).join("data.p4k"), b"").unwrap();
+        }
+        // Not an install: no data.p4k
+        std::fs::create_dir_all(base.join("EPTU")).unwrap();
+        // LIVE already has this profile, it gets backed up before being replaced
+        let live_mappings = install_mappings_dir(&base.join("LIVE"));
+        std::fs::create_dir_all(&live_mappings).unwrap();
+        std::fs::write(live_mappings.join("layout_hosas.xml"), "old").unwrap();
+
+        let xml = "<ActionMaps profileName=\"layout_hosas\"/>\n";
+        let results =
+            save_profile_to_installs(&base.to_string_lossy(), "layout_hosas.xml", xml).unwrap();
+
+        let names: Vec<&str> = results
+            .iter()
+            .map(|r| r.installation_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["LIVE", "PTU"]);
+        for install in ["LIVE", "PTU"] {
+            let written = install_mappings_dir(&base.join(install)).join("layout_hosas.xml");
+            assert_eq!(std::fs::read_to_string(written).unwrap(), xml);
+        }
+        assert!(results
+            .iter()
+            .all(|r| r.error.is_none() && r.file_path.is_some()));
+        assert!(!install_mappings_dir(&base.join("EPTU")).exists());
+
+        let live_backup = live_mappings.join("layout_hosas.xml.bak");
+        assert_eq!(
+            results[0].backup_path.as_deref(),
+            Some(live_backup.to_string_lossy().as_ref())
+        );
+        assert_eq!(std::fs::read_to_string(live_backup).unwrap(), "old");
+        assert_eq!(results[1].backup_path, None);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_failed_install_save_keeps_profile_name() {
+        let mut app_state = AppState::new();
+        app_state.current_bindings =
+            Some(ActionMaps::from_xml("<ActionMaps profileName=\"My Profile\"/>\n").unwrap());
+        let missing = std::env::temp_dir().join(format!("sc_no_install_{}", std::process::id()));
+
+        assert!(save_profile_to_install(&mut app_state, &missing).is_err());
+        assert_eq!(
+            app_state.current_bindings.as_ref().unwrap().profile_name,
+            "My Profile"
+        );
+        assert_eq!(app_state.current_file_name, None);
+
+        let install = std::env::temp_dir().join(format!("sc_one_install_{}", std::process::id()));
+        std::fs::create_dir_all(&install).unwrap();
+        let written = save_profile_to_install(&mut app_state, &install).unwrap();
+        assert_eq!(written.path.file_name().unwrap(), "layout_My_Profile.xml");
+        assert_eq!(
+            app_state.current_bindings.as_ref().unwrap().profile_name,
+            "layout_My_Profile"
+        );
+        assert_eq!(
+            app_state.current_file_name.as_deref(),
+            Some("layout_My_Profile.xml")
+        );
+
+        let _ = std::fs::remove_dir_all(&install);
+    }
+
+    #[test]
+    fn test_active_mappings_newest_first() {
+        let install =
+            std::env::temp_dir().join(format!("sc_active_mappings_{}", std::process::id()));
+        let mappings = install_mappings_dir(&install);
+        std::fs::create_dir_all(&mappings).unwrap();
+
+        let now = std::time::SystemTime::now();
+        for (name, age_secs) in [("layout_old.xml", 600), ("layout_new.xml", 5)] {
+            let path = mappings.join(name);
+            std::fs::write(&path, "<ActionMaps/>").unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - std::time::Duration::from_secs(age_secs))
+                .unwrap();
+        }
+        std::fs::write(mappings.join("notes.txt"), "not a profile").unwrap();
+
+        let found = list_active_mappings(&install).unwrap();
+        let names: Vec<&str> = found.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["layout_new.xml", "layout_old.xml"]);
+
+        let _ = std::fs::remove_dir_all(&install);
+    }
+
+    #[test]
+    fn test_undo_restores_original_bindings() {
+        let original = ActionMaps::from_xml(
+            r#"<ActionMaps version="1" optionsVersion="2" rebindVersion="2" profileName="hosas">
+ <actionmap name="spaceship_weapons">
+  <action name="v_attack1">
+   <rebind input="js1_button1"/>
+  </action>
+ </actionmap>
+</ActionMaps>"#,
+        )
+        .unwrap();
+        let mut current = Some(original.clone());
+        let mut history = EditHistory::default();
+
+        fn rebinds(maps: &mut Option<ActionMaps>) -> &mut Vec<keybindings::Rebind> {
+            &mut maps.as_mut().unwrap().action_maps[0].actions[0].rebinds
+        }
+
+        // Bind
+        history.record(&current);
+        rebinds(&mut current).push(keybindings::Rebind {
+            input: "kb1_space".to_string(),
+            multi_tap: None,
+            multi_tap_block: None,
+            press_trigger_threshold: None,
+            activation_mode: String::new(),
+        });
+
+        // Clear
+        history.record(&current);
+        rebinds(&mut current).retain(|r| r.input != "js1_button1");
+        assert_eq!(rebinds(&mut current).len(), 1);
+
+        assert!(history.undo(&mut current));
+        assert!(history.undo(&mut current));
+        assert!(!history.undo(&mut current));
+        assert_eq!(
+            current.as_ref().unwrap().to_xml(false),
+            original.to_xml(false)
+        );
+
+        // Redo re-applies the bind, and a new edit drops the rest of the redo history
+        assert!(history.redo(&mut current));
+        assert_eq!(rebinds(&mut current).len(), 2);
+        history.record(&current);
+        assert!(!history.redo(&mut current));
+    }
+
+    #[test]
+    fn test_undo_history_is_capped() {
+        let mut history = EditHistory::default();
+        for _ in 0..UNDO_LIMIT + 10 {
+            history.record(&None);
+        }
+        assert_eq!(history.undo.len(), UNDO_LIMIT);
+    }
+
+    #[test]
+    fn test_only_successful_edits_are_recorded() {
+        let mut app_state = AppState::new();
+        app_state.current_bindings = Some(
+            ActionMaps::from_xml(
+                "<ActionMaps profileName=\"undo\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js1_button1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+            )
+            .unwrap(),
+        );
+        let original = app_state.current_bindings.clone();
+
+        // Fails part way: the change made before the error is rolled back, nothing is recorded
+        let failed = app_state.edit_bindings(|bindings| {
+            bindings.action_maps[0].actions[0].rebinds.clear();
+            bindings.set_primary_binding("spaceship_weapons", "v_attack1", "js1_button9")
+        });
+        assert!(failed.is_err());
+        assert_eq!(app_state.current_bindings, original);
+        assert!(app_state.history.undo.is_empty());
+
+        // Succeeds without changing anything: no undo step
+        app_state
+            .edit_bindings(|bindings| Ok(bindings.normalize_cleared_bindings()))
+            .unwrap();
+        assert!(app_state.history.undo.is_empty());
+
+        app_state
+            .edit(|app_state| {
+                apply_binding_edit(
+                    app_state,
+                    &BindingEdit {
+                        action_map_name: "spaceship_weapons".to_string(),
+                        action_name: "v_attack1".to_string(),
+                        new_input: "kb1_space".to_string(),
+                        multi_tap: None,
+                        activation_mode: None,
+                        multi_tap_block: None,
+                        press_trigger_threshold: None,
+                    },
+                )
+            })
+            .unwrap();
+        assert_eq!(app_state.history.undo.len(), 1);
+        assert_eq!(app_state.history.undo[0], original);
+    }
+
+    #[test]
+    fn test_match_hid_device_scored() {
+        let hid = |manufacturer: &str, product: &str| hid_reader::HidDeviceListItem {
+            vendor_id: 0,
+            product_id: 0,
+            serial_number: None,
+            manufacturer: Some(manufacturer.to_string()),
+            product: Some(product.to_string()),
+            path: format!("{} {}", manufacturer, product),
+            interface_number: 0,
+        };
+        let devices = vec![
+            hid("Thrustmaster", "TWCS Throttle"),
+            hid("Thrustmaster", "T.16000M"),
+            hid("VIRPIL Controls 20220720", "VPC Constellation ALPHA-R"),
+            hid("VKB-Sim © Alex Oz 2021", "VKBsim Gladiator EVO L"),
+            hid("VKB-Sim © Alex Oz 2021", "VKBsim Gladiator EVO R"),
+        ];
+        let best = |name: &str| match_hid_device_scored(name, &devices).map(|(d, _)| d.path);
+
+        assert_eq!(
+            best("T.16000M (Left)").as_deref(),
+            Some("Thrustmaster T.16000M")
+        );
+        assert_eq!(
+            best("VIRPIL Controls 20220720").as_deref(),
+            Some("VIRPIL Controls 20220720 VPC Constellation ALPHA-R")
+        );
+        assert_eq!(
+            best("VPC Constellation ALPHA-R").as_deref(),
+            Some("VIRPIL Controls 20220720 VPC Constellation ALPHA-R")
+        );
+        assert_eq!(
+            best("VKBsim Gladiator EVO R").as_deref(),
+            Some("VKB-Sim © Alex Oz 2021 VKBsim Gladiator EVO R")
+        );
+
+        // Exact product names score highest
+        let (_, score) = match_hid_device_scored("TWCS Throttle", &devices).unwrap();
+        assert_eq!(score, 1.0);
+
+        // Unrelated devices are rejected rather than matched on a shared vendor word
+        assert_eq!(best("Logitech Extreme 3D Pro"), None);
+        assert_eq!(best("Thrustmaster TFRP Rudder"), None);
+    }
+
+    #[test]
+    fn test_unbind_profile_reports_failed_writes() {
+        let dir = std::env::temp_dir().join(format!("sc_unbind_failed_{}", std::process::id()));
+        let writable = dir.join("LIVE").join(UNBIND_PROFILE_FILE_NAME);
+        // A file where the mappings folder should be: creating the folder always fails
+        std::fs::create_dir_all(dir.join("PTU")).unwrap();
+        std::fs::write(dir.join("PTU").join("mappings"), b"").unwrap();
+        let blocked = dir
+            .join("PTU")
+            .join("mappings")
+            .join(UNBIND_PROFILE_FILE_NAME);
+
+        let targets = vec![
+            writable.to_string_lossy().to_string(),
+            blocked.to_string_lossy().to_string(),
+        ];
+        let result = write_unbind_profile(&targets, "<ActionMaps/>");
+        assert_eq!(result.saved_locations, vec![targets[0].clone()]);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].path, targets[1]);
+        assert!(!result.failed[0].reason.is_empty());
+
+        // Read-only folder (permissions may be ignored when running elevated)
+        let locked = dir.join("locked");
+        std::fs::create_dir_all(&locked).unwrap();
+        let mut perms = std::fs::metadata(&locked).unwrap().permissions();
+        perms.set_readonly(true);
+        std::fs::set_permissions(&locked, perms.clone()).unwrap();
+
+        let target = locked
+            .join(UNBIND_PROFILE_FILE_NAME)
+            .to_string_lossy()
+            .to_string();
+        let result = write_unbind_profile(std::slice::from_ref(&target), "<ActionMaps/>");
+        if result.saved_locations.is_empty() {
+            assert_eq!(result.failed[0].path, target);
+            assert!(
+                result.failed[0].reason.contains("read-only"),
+                "{}",
+                result.failed[0].reason
+            );
+        }
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        std::fs::set_permissions(&locked, perms).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_profile_name_collisions() {
+        let base = std::env::temp_dir().join(format!("sc_name_collisions_{}", std::process::id()));
+        let profile = |name: &str| {
+            format!(
+                "<ActionMaps version=\"1\" profileName=\"{}\">\n</ActionMaps>\n",
+                name
+            )
+        };
+        for install in ["LIVE", "PTU"] {
+            std::fs::create_dir_all(install_mappings_dir(&base.join(install))).unwrap();
+            std::fs::write(base.join(install).join("data.p4k"), b"").unwrap();
+        }
+        let live = install_mappings_dir(&base.join("LIVE"));
+        let ptu = install_mappings_dir(&base.join("PTU"));
+        let library = base.join("library");
+        std::fs::create_dir_all(&library).unwrap();
+
+        std::fs::write(live.join("layout_combat.xml"), profile("layout_combat")).unwrap();
+        std::fs::write(live.join("layout_mining.xml"), profile("layout_mining")).unwrap();
+        std::fs::write(ptu.join("combat_copy.xml"), profile("Layout_Combat")).unwrap();
+        std::fs::write(ptu.join("blank.xml"), profile("")).unwrap();
+        std::fs::write(library.join("combat.xml"), profile("layout_combat")).unwrap();
+
+        let collisions =
+            find_profile_name_collisions(&base.to_string_lossy(), Some(&library.to_string_lossy()))
+                .unwrap();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].profile_name, "layout_combat");
+        let locations: Vec<&str> = collisions[0]
+            .files
+            .iter()
+            .map(|f| f.location.as_str())
+            .collect();
+        assert_eq!(locations, vec!["LIVE", "PTU", "Library"]);
+
+        // Installs alone still collide
+        let collisions = find_profile_name_collisions(&base.to_string_lossy(), None).unwrap();
+        assert_eq!(collisions[0].files.len(), 2);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_import_device_options_keeps_bindings() {
+        let source = ActionMaps::from_xml(
+            "<ActionMaps profileName=\"layout_hosas\">\n <options type=\"keyboard\" instance=\"1\" Product=\"Keyboard  {6F1D2B61-D5A0-11CF-BFC7-444553540000}\"/>\n <options type=\"joystick\" instance=\"1\" Product=\" VKB-Sim Gladiator NXT L  {0201231D-0000-0000-0000-504944564944}\"/>\n <options type=\"joystick\" instance=\"2\" Product=\" VKB-Sim Gladiator NXT R  {0200231D-0000-0000-0000-504944564944}\"/>\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1_group2\">\n   <rebind input=\"js2_button2\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+        let target = ActionMaps::from_xml(
+            "<ActionMaps profileName=\"layout_fresh\">\n <options type=\"joystick\" instance=\"1\" Product=\"Old Stick\"/>\n <actionmap name=\"spaceship_movement\">\n  <action name=\"v_roll\">\n   <rebind input=\"js1_x\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+
+        let mut current = Some(target.clone());
+        let imported = import_device_options(&mut current, &source);
+        assert_eq!(imported.devices.joysticks, source.devices.joysticks);
+        assert_eq!(imported.devices.keyboards, source.devices.keyboards);
+        assert_eq!(imported.profile_name, "layout_fresh");
+        // Bindings are the target's, not the source's
+        assert_eq!(
+            serde_json::to_value(&imported.action_maps).unwrap(),
+            serde_json::to_value(&target.action_maps).unwrap()
+        );
+
+        // Nothing loaded: devices only
+        let mut current = None;
+        let imported = import_device_options(&mut current, &source);
+        assert!(imported.action_maps.is_empty());
+        assert_eq!(imported.devices.joysticks.len(), 2);
+    }
+
+    #[test]
+    fn test_reformat_keeps_devices_and_versions() {
+        let original = ActionMaps::from_xml(
+            "<ActionMaps version=\"1\" optionsVersion=\"1\" rebindVersion=\"1\" profileName=\"layout_hosas\">\n <CustomisationUIHeader label=\"layout_hosas\" description=\"\" image=\"\">\n  <devices>\n   <keyboard instance=\"1\"/>\n   <mouse instance=\"1\"/>\n   <joystick instance=\"1\"/>\n  </devices>\n  <categories>\n   <category label=\"@ui_CCSpaceFlight\"/>\n  </categories>\n </CustomisationUIHeader>\n <options type=\"keyboard\" instance=\"1\" Product=\"Keyboard  {6F1D2B61-D5A0-11CF-BFC7-444553540000}\"/>\n <options type=\"mouse\" instance=\"1\" Product=\"Mouse  {6F1D2B60-D5A0-11CF-BFC7-444553540000}\"/>\n <options type=\"joystick\" instance=\"1\" Product=\" VKB-Sim Gladiator NXT L  {0201231D-0000-0000-0000-504944564944}\"/>\n <actionmap name=\"spaceship_movement\">\n  <action name=\"v_roll\">\n   <rebind input=\"js1_x\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+        assert!(!original.devices.keyboards.is_empty());
+        assert!(!original.devices.mice.is_empty());
+        assert!(!original.categories.is_empty());
+
+        let mut app_state = AppState::new();
+        app_state.current_bindings = Some(original.clone());
+        let (reformatted, xml) = reformat_bindings(&original, None).unwrap();
+        assert!(xml.contains("js1_x"));
+        app_state
+            .edit_bindings(|bindings| {
+                *bindings = reformatted;
+                Ok(())
+            })
+            .unwrap();
+
+        let bindings = app_state.current_bindings.as_ref().unwrap();
+        assert_eq!(bindings.devices, original.devices);
+        assert_eq!(bindings.categories, original.categories);
+        assert_eq!(bindings.options_version, Some(1));
+        assert_eq!(bindings.rebind_version, Some(1));
+        assert_eq!(
+            serde_json::to_value(&bindings.action_maps).unwrap(),
+            serde_json::to_value(&original.action_maps).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_chunked_binary_write() {
+        let dir = std::env::temp_dir().join(format!("sc_chunked_write_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let contents: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+
+        let single = dir.join("single.chf");
+        std::fs::write(&single, &contents).unwrap();
+
+        let mut writes = BinaryWrites::default();
+        let chunked = dir.join("chunked.chf");
+        let id = writes.begin(&chunked.to_string_lossy()).unwrap();
+        for chunk in [&contents[..1000], &contents[1000..7000], &contents[7000..]] {
+            writes.append(id, chunk).unwrap();
+        }
+        // Nothing at the target until the write is finished
+        assert!(!chunked.exists());
+        writes.finish(id).unwrap();
+
+        assert_eq!(
+            std::fs::read(&chunked).unwrap(),
+            std::fs::read(&single).unwrap()
+        );
+        assert!(!BinaryWrites::partial_path(&chunked, id).exists());
+        assert!(writes.append(id, b"late").is_err());
+
+        // Two writes to the same target don't share a partial file
+        let first = writes.begin(&single.to_string_lossy()).unwrap();
+        let second = writes.begin(&single.to_string_lossy()).unwrap();
+        writes.append(first, b"first").unwrap();
+        writes.append(second, b"second").unwrap();
+
+        // Aborting leaves an existing target untouched and removes the partial file
+        assert!(writes.abort(second));
+        assert!(!writes.abort(second));
+        assert_eq!(std::fs::read(&single).unwrap(), contents);
+        assert!(!BinaryWrites::partial_path(&single, second).exists());
+
+        // A finish that can't move the file into place removes the partial file
+        std::fs::remove_file(&single).unwrap();
+        std::fs::create_dir_all(single.join("blocker")).unwrap();
+        assert!(writes.finish(first).is_err());
+        assert!(!BinaryWrites::partial_path(&single, first).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_update_bindings_batch_reports_failed_edits() {
+        let edit = |map: &str, action: &str, input: &str| BindingEdit {
+            action_map_name: map.to_string(),
+            action_name: action.to_string(),
+            new_input: input.to_string(),
+            multi_tap: None,
+            activation_mode: None,
+            multi_tap_block: None,
+            press_trigger_threshold: None,
+        };
+
+        let mut app_state = AppState::new();
+        app_state.current_bindings = Some(
+            ActionMaps::from_xml(
+                "<ActionMaps profileName=\"layout_batch\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js1_button1\"/>\n   <rebind input=\"kb1_space\"/>\n  </action>\n  <action name=\"v_attack2\">\n   <rebind input=\"js1_button2\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+            )
+            .unwrap(),
+        );
+
+        let result = apply_binding_edits(
+            &mut app_state,
+            &[
+                edit("spaceship_weapons", "v_attack1", "js1_button5"),
+                edit("spaceship_weapons", "v_no_such_action", "js1_button6"),
+                edit("spaceship_weapons", "v_attack2", "js2_button2"),
+            ],
+        );
+
+        assert_eq!(result.applied, 2);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].contains("v_no_such_action"));
+
+        let bindings = app_state.current_bindings.as_ref().unwrap();
+        let inputs = |action: &str| -> Vec<String> {
+            bindings.action_maps[0]
+                .actions
+                .iter()
+                .find(|a| a.name == action)
+                .unwrap()
+                .rebinds
+                .iter()
+                .map(|r| r.input.clone())
+                .collect()
+        };
+        // Same device instance is replaced, other devices are kept
+        assert_eq!(inputs("v_attack1"), vec!["kb1_space", "js1_button5"]);
+        assert_eq!(inputs("v_attack2"), vec!["js1_button2", "js2_button2"]);
+        assert_eq!(bindings.action_maps[0].actions.len(), 2);
+    }
+
+    #[test]
+    fn test_format_input_display() {
+        let modified = format_input_display("lalt+js2_hat2_down_left".to_string());
+        assert_eq!(
+            modified.display_name,
+            "Left Alt + Joystick 2 - Hat 2 Down-Left"
+        );
+        assert_eq!(modified.input_type, keybindings::InputType::Joystick);
+
+        let cleared = format_input_display("js1_ ".to_string());
+        assert_eq!(cleared.display_name, "Unbound");
+        assert_eq!(cleared.input_type, keybindings::InputType::Joystick);
+    }
+
+    #[test]
+    fn test_export_line_endings() {
+        let bindings = ActionMaps::from_xml(
+            "<ActionMaps profileName=\"layout_crlf\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js1_button1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+        let xml = bindings.to_xml_with_categories(None, false);
+
+        assert_eq!(LineEnding::default(), LineEnding::Lf);
+        assert_eq!(LineEnding::Lf.apply(&xml), xml);
+
+        let crlf = LineEnding::Crlf.apply(&xml);
+        assert!(crlf.ends_with("\r\n"));
+        assert_eq!(crlf.matches("\r\n").count(), xml.matches('\n').count());
+        assert!(crlf.split("\r\n").all(|line| !line.contains('\n')));
+        // Applying twice must not produce "\r\r\n"
+        assert_eq!(LineEnding::Crlf.apply(&crlf), crlf);
+    }
+
+    #[test]
+    fn test_load_keybindings_additive() {
+        let dir = std::env::temp_dir().join(format!("additive_load_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let flight = dir.join("layout_flight_exported.xml");
+        let fps = dir.join("layout_fps_exported.xml");
+        std::fs::write(
+            &flight,
+            "<ActionMaps profileName=\"flight\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js1_button1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &fps,
+            "<ActionMaps profileName=\"fps\">\n <actionmap name=\"player\">\n  <action name=\"attack1\">\n   <rebind input=\"mouse1_mouse1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+
+        let mut app_state = AppState::new();
+        assert_eq!(
+            load_profile_additive(&mut app_state, flight.to_str().unwrap()).unwrap(),
+            1
+        );
+        assert_eq!(
+            load_profile_additive(&mut app_state, fps.to_str().unwrap()).unwrap(),
+            1
+        );
+
+        let bindings = app_state.current_bindings.as_ref().unwrap();
+        let inputs: Vec<&str> = bindings
+            .action_maps
+            .iter()
+            .flat_map(|am| &am.actions)
+            .flat_map(|a| &a.rebinds)
+            .map(|r| r.input.as_str())
+            .collect();
+        assert_eq!(inputs, vec!["js1_button1", "mouse1_mouse1"]);
+        // The first file stays the one being edited
+        assert_eq!(bindings.profile_name, "flight");
+        assert_eq!(
+            app_state.current_file_name.as_deref(),
+            Some("layout_flight_exported.xml")
+        );
+
+        // Merging the same bindings again changes nothing and leaves no undo step
+        assert_eq!(app_state.history.undo.len(), 2);
+        assert_eq!(
+            load_profile_additive(&mut app_state, flight.to_str().unwrap()).unwrap(),
+            1
+        );
+        assert_eq!(app_state.history.undo.len(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validate_character_file() {
+        let dir = std::env::temp_dir().join(format!("chf_validate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+
+        // No real character ships with the repo: a synthetic file with the expected header
+        // passes cleanly, and one with any other header is still deployable but warns
+        let mut character = vec![0xAB; 4096];
+        character[..CHF_MAGIC.len()].copy_from_slice(&CHF_MAGIC);
+        std::fs::write(path("valid.chf"), &character).unwrap();
+        std::fs::write(path("odd_header.chf"), vec![0xAB; 4096]).unwrap();
+        std::fs::write(path("truncated.chf"), &character[..10]).unwrap();
+        std::fs::write(path("oversized.chf"), vec![0xAB; 2 * 1024 * 1024]).unwrap();
+        std::fs::write(path("empty.chf"), b"").unwrap();
+
+        let valid = validate_character_file(path("valid.chf"));
+        assert!(valid.valid);
+        assert_eq!(valid.size, 4096);
+        assert_eq!(valid.reason, None);
+        assert_eq!(valid.warning, None);
+
+        let odd_header = validate_character_file(path("odd_header.chf"));
+        assert!(odd_header.valid);
+        assert!(odd_header.warning.unwrap().contains("Unexpected header"));
+
+        for name in ["truncated.chf", "oversized.chf", "empty.chf", "missing.chf"] {
+            let result = validate_character_file(path(name));
+            assert!(!result.valid, "{} should be invalid", name);
+            assert!(result.reason.is_some());
+        }
+        assert!(validate_character_file(path("truncated.chf"))
+            .reason
+            .unwrap()
+            .contains("too small"));
+
+        let installation = dir.join("LIVE");
+        deploy_character_to_installation(
+            "valid.chf".to_string(),
+            dir.to_string_lossy().to_string(),
+            installation.to_string_lossy().to_string(),
+        )
+        .unwrap();
+        assert!(deploy_character_to_installation(
+            "truncated.chf".to_string(),
+            dir.to_string_lossy().to_string(),
+            installation.to_string_lossy().to_string(),
+        )
+        .is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_scan_all_characters_dedupes_by_content() {
+        let base = std::env::temp_dir().join(format!("sc_all_characters_{}", std::process::id()));
+        for install in ["LIVE", "PTU"] {
+            std::fs::create_dir_all(install_characters_dir(&base.join(install))).unwrap();
+            std::fs::write(base.join(install).join("data.p4k"), b"").unwrap();
+        }
+        let live = install_characters_dir(&base.join("LIVE"));
+        let ptu = install_characters_dir(&base.join("PTU"));
+        let library = base.join("library");
+        std::fs::create_dir_all(&library).unwrap();
+
+        std::fs::write(live.join("pilot.chf"), b"BB pilot").unwrap();
+        std::fs::write(ptu.join("pilot_copy.chf"), b"BB pilot").unwrap();
+        std::fs::write(library.join("miner.chf"), b"BB miner").unwrap();
+
+        let characters =
+            find_all_characters(&base.to_string_lossy(), Some(&library.to_string_lossy())).unwrap();
+        assert_eq!(characters.len(), 2);
+
+        assert_eq!(characters[0].locations.len(), 1);
+        assert_eq!(characters[0].locations[0].location, "Library");
+
+        let pilot = &characters[1];
+        assert_eq!(pilot.hash, content_hash(b"BB pilot"));
+        assert_eq!(pilot.size, 8);
+        let copies: Vec<(&str, &str)> = pilot
+            .locations
+            .iter()
+            .map(|l| (l.location.as_str(), l.name.as_str()))
+            .collect();
+        assert_eq!(
+            copies,
+            vec![("LIVE", "pilot.chf"), ("PTU", "pilot_copy.chf")]
+        );
+        assert_ne!(content_hash(b"BB pilot"), content_hash(b"BB miner"));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_split_profile_by_device() {
+        let dir = std::env::temp_dir().join(format!("split_profile_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bindings = ActionMaps::from_xml(
+            "<ActionMaps profileName=\"hosas\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js1_button1\"/>\n   <rebind input=\"kb1_space\"/>\n  </action>\n  <action name=\"v_attack2\">\n   <rebind input=\"kb1_f\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+        )
+        .unwrap();
+
+        let written = split_profile_files(&bindings, None, &dir.join("hosas.xml")).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                dir.join("hosas_keyboard.xml").to_string_lossy().to_string(),
+                dir.join("hosas_joystick1.xml")
+                    .to_string_lossy()
+                    .to_string(),
+            ]
+        );
+
+        let inputs = |path: &str| -> Vec<String> {
+            let profile = ActionMaps::from_xml(&std::fs::read_to_string(path).unwrap()).unwrap();
+            profile
+                .action_maps
+                .iter()
+                .flat_map(|am| &am.actions)
+                .flat_map(|a| &a.rebinds)
+                .map(|r| r.input.clone())
+                .collect()
+        };
+        assert_eq!(inputs(&written[0]), vec!["kb1_space", "kb1_f"]);
+        assert_eq!(inputs(&written[1]), vec!["js1_button1"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_split_mappings() {
+        let install = std::env::temp_dir().join(format!("split_mappings_{}", std::process::id()));
+        let mappings = install_mappings_dir(&install);
+        std::fs::create_dir_all(&mappings).unwrap();
+
+        let profile = |name: &str, input: &str| {
+            format!(
+                "<ActionMaps profileName=\"{}\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"{}\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+                name, input
+            )
+        };
+        std::fs::write(
+            mappings.join("hosas_keyboard.xml"),
+            profile("hosas_keyboard", "kb1_space"),
+        )
+        .unwrap();
+        std::fs::write(
+            mappings.join("hosas_joystick.xml"),
+            profile("hosas_joystick", "js1_button1"),
+        )
+        .unwrap();
+        // Neither is part of the split "hosas" profile
+        std::fs::write(mappings.join("hosas.xml"), profile("hosas", "kb1_f")).unwrap();
+        std::fs::write(
+            mappings.join("other_keyboard.xml"),
+            profile("other", "kb1_g"),
+        )
+        .unwrap();
+
+        let files = find_split_mapping_files(&mappings, "hosas").unwrap();
+        assert_eq!(
+            files,
+            vec![
+                mappings.join("hosas_joystick.xml"),
+                mappings.join("hosas_keyboard.xml")
+            ]
+        );
+
+        let merged = merge_split_mappings(&files, "hosas").unwrap();
+        assert_eq!(merged.profile_name, "hosas");
+        let inputs: Vec<&str> = merged.action_maps[0].actions[0]
+            .rebinds
+            .iter()
+            .map(|r| r.input.as_str())
+            .collect();
+        assert_eq!(inputs, vec!["js1_button1", "kb1_space"]);
+
+        let _ = std::fs::remove_dir_all(&install);
+    }
+
+    #[test]
+    fn test_profile_saved_status() {
+        let install = std::env::temp_dir().join(format!("saved_status_{}", std::process::id()));
+        std::fs::create_dir_all(&install).unwrap();
+
+        // A blank profileName: saving names the file after the loaded one, not "my hosas.xml"
+        let mut app_state = AppState::new();
+        app_state.current_bindings = Some(
+            ActionMaps::from_xml(
+                "<ActionMaps profileName=\"\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack1\">\n   <rebind input=\"js1_button1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+            )
+            .unwrap(),
+        );
+        app_state.current_file_name = Some("my hosas.xml".to_string());
+
+        let status = profile_saved_status(&app_state, &install).unwrap();
+        assert_eq!(
+            status,
+            SavedStatus {
+                present: false,
+                up_to_date: false
+            }
+        );
+
+        let (_, file_name, xml_content) = prepare_profile_for_install(&app_state).unwrap();
+        assert_eq!(file_name, "layout_my_hosas.xml");
+        write_profile_to_install(&install, &file_name, &xml_content).unwrap();
+        let status = profile_saved_status(&app_state, &install).unwrap();
+        assert_eq!(
+            status,
+            SavedStatus {
+                present: true,
+                up_to_date: true
+            }
+        );
+
+        app_state.current_bindings.as_mut().unwrap().action_maps[0].actions[0].rebinds[0].input =
+            "js1_button2".to_string();
+        let status = profile_saved_status(&app_state, &install).unwrap();
+        assert_eq!(
+            status,
+            SavedStatus {
+                present: true,
+                up_to_date: false
+            }
+        );
+
+        app_state.current_bindings = None;
+        assert!(profile_saved_status(&app_state, &install).is_err());
+
+        let _ = std::fs::remove_dir_all(&install);
+    }
+
+    #[test]
+    fn test_device_alias_round_trip() {
+        let dir = std::env::temp_dir().join(format!("sc_device_alias_{}", std::process::id()));
+        let path = dir.join("settings.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        store_device_alias(&path, "uuid-stick", "  My Stick ").unwrap();
+        store_device_alias(&path, "uuid-throttle", "Throttle").unwrap();
+        store_device_alias(&path, "uuid-throttle", "").unwrap();
+
+        let aliases = read_settings(&path).unwrap().device_aliases;
+        assert_eq!(aliases.len(), 1);
+
+        let device = |uuid: &str| directinput::DeviceInfo {
+            uuid: uuid.to_string(),
+            name: "VKB-Sim Gladiator NXT R".to_string(),
+            axis_count: 7,
+            button_count: 32,
+            hat_count: 1,
+            device_type: "joystick".to_string(),
+            is_connected: true,
+            alias: None,
+        };
+        let mut devices = vec![device("uuid-stick"), device("uuid-throttle")];
+        for device in &mut devices {
+            apply_device_alias(device, &aliases);
+        }
+        assert_eq!(devices[0].alias.as_deref(), Some("My Stick"));
+        assert_eq!(devices[1].alias, None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_input_index_follows_binding_edits() {
+        let mut app_state = AppState::new();
+        app_state.all_binds = Some(
+            AllBinds::from_xml(
+                "<ActionMaps>\n <actionmap name=\"spaceship_weapons\" version=\"1\" UILabel=\"Weapons\" UICategory=\"Ship\">\n  <action name=\"v_attack1\" keyboard=\"mouse1\"/>\n  <action name=\"v_attack2\" keyboard=\"f2\"/>\n </actionmap>\n</ActionMaps>\n",
+            )
+            .unwrap(),
+        );
+        assert!(app_state
+            .input_index()
+            .unwrap()
+            .get("js1_button1")
+            .is_none());
+
+        app_state.load_profile(
+            ActionMaps::from_xml(
+                "<ActionMaps profileName=\"index\">\n <actionmap name=\"spaceship_weapons\">\n  <action name=\"v_attack2\">\n   <rebind input=\"js1_button1\"/>\n  </action>\n </actionmap>\n</ActionMaps>\n",
+            )
+            .unwrap(),
+            "index.xml".to_string(),
+            HashMap::new(),
+        );
+        assert_eq!(
+            app_state.input_index().unwrap().get("js1_button1"),
+            Some(&vec![(
+                "spaceship_weapons".to_string(),
+                "v_attack2".to_string()
+            )])
+        );
+
+        // A lookup without changes reuses the index
+        let generation = app_state.input_index.as_ref().unwrap().generation;
+        app_state.input_index().unwrap();
+        assert_eq!(
+            app_state.input_index.as_ref().unwrap().generation,
+            generation
+        );
+
+        app_state
+            .edit_bindings(|bindings| {
+                bindings.action_maps[0].actions[0].rebinds[0].input = "js1_button2".to_string();
+                Ok(())
+            })
+            .unwrap();
+        assert!(app_state
+            .input_index()
+            .unwrap()
+            .get("js1_button1")
+            .is_none());
+        assert!(app_state
+            .input_index()
+            .unwrap()
+            .get("js1_button2")
+            .is_some());
+
+        assert!(app_state.step_history(true));
+        assert!(app_state
+            .input_index()
+            .unwrap()
+            .get("js1_button1")
+            .is_some());
+    }
+
+    #[test]
+    fn test_render_bindings_csv() {
+        let rows = vec![keybindings::BindingTableRow {
+            category: "Flight".to_string(),
+            action_map_label: "Weapons, Guns".to_string(),
+            action_label: "Fire".to_string(),
+            device: "Joystick".to_string(),
+            input: "js1_button1".to_string(),
+            display_name: "Joystick 1 - Button 1".to_string(),
+        }];
+
+        let csv = render_bindings_csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Category,Action Map,Action,Device,Input,Display Name"
+        );
+        assert_eq!(
+            lines[1],
+            "Flight,\"Weapons, Guns\",Fire,Joystick,js1_button1,Joystick 1 - Button 1"
+        );
+    }
+
+    #[test]
+    fn test_render_analysis_csv() {
+        let rows = vec![keybindings::AnalysisRow {
+            category: "Flight".to_string(),
+            action_map: "spaceship_weapons".to_string(),
+            action: "v_attack1".to_string(),
+            label: "Fire, Group 1".to_string(),
+            keyboard: String::new(),
+            mouse: String::new(),
+            joystick1: "js1_button1; lalt+js1_button2".to_string(),
+            gamepad: String::new(),
+            activation_mode: "tap".to_string(),
+            customized: true,
+        }];
+
+        let csv = render_analysis_csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "category,action_map,action,label,keyboard,mouse,joystick1,gamepad,activation_mode,customized"
+        );
+        assert_eq!(
+            lines[1],
+            "Flight,spaceship_weapons,v_attack1,\"Fire, Group 1\",,,js1_button1; lalt+js1_button2,,tap,true"
+        );
+    }
+}